@@ -25,6 +25,9 @@ class SsspResultInfo(ctypes.Structure):
     ("heavy_relaxations", ctypes.c_uint64),
         ("settled", ctypes.c_uint32),
         ("error_code", ctypes.c_int32),
+        ("overflow", ctypes.c_uint32),
+        ("reachable", ctypes.c_uint32),
+        ("edges", ctypes.c_uint64),
     ]
 
 _lib.sssp_run_baseline.restype = ctypes.c_int32
@@ -50,7 +53,7 @@ _lib.sssp_version.restype = ctypes.c_uint32
 
 # Optional bucket stats FFI
 class _BucketStats(ctypes.Structure):
-    _fields_=[('buckets_visited',ctypes.c_uint32),('light_pass_repeats',ctypes.c_uint32),('max_bucket_index',ctypes.c_uint32),('restarts',ctypes.c_uint32),('delta_x1000',ctypes.c_uint32),('heavy_ratio_x1000',ctypes.c_uint32)]
+    _fields_=[('buckets_visited',ctypes.c_uint32),('light_pass_repeats',ctypes.c_uint32),('max_bucket_index',ctypes.c_uint32),('restarts',ctypes.c_uint32),('delta_x1000',ctypes.c_uint32),('heavy_ratio_x1000',ctypes.c_uint32),('peak_live_buckets',ctypes.c_uint32)]
 _HAS_BUCKET_STATS = hasattr(_lib, 'sssp_get_bucket_stats') and hasattr(_lib,'sssp_get_last_delta')
 if _HAS_BUCKET_STATS:
     _lib.sssp_get_bucket_stats.argtypes=[ctypes.POINTER(_BucketStats)]
@@ -144,6 +147,9 @@ def _run(offsets, targets, weights, source: int, mode):
             'light_relaxations': info.light_relaxations,
             'heavy_relaxations': info.heavy_relaxations,
             'settled': info.settled,
+            'overflow': info.overflow,
+            'reachable': info.reachable,
+            'edges': info.edges,
             'version': _lib.sssp_version(),
             'variant': variant
         }
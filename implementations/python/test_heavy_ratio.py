@@ -5,7 +5,7 @@ import rust_sssp
 from ctypes import Structure, c_uint32, byref
 
 class BucketStats(Structure):
-    _fields_=[('buckets_visited',c_uint32),('light_pass_repeats',c_uint32),('max_bucket_index',c_uint32),('restarts',c_uint32),('delta_x1000',c_uint32),('heavy_ratio_x1000',c_uint32)]
+    _fields_=[('buckets_visited',c_uint32),('light_pass_repeats',c_uint32),('max_bucket_index',c_uint32),('restarts',c_uint32),('delta_x1000',c_uint32),('heavy_ratio_x1000',c_uint32),('peak_live_buckets',c_uint32)]
 
 HAS_STATS = hasattr(rust_sssp._lib,'sssp_get_bucket_stats')
 if HAS_STATS:
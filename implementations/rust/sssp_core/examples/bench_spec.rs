@@ -1,34 +1,33 @@
 use std::time::Instant;
 use sssp_core::{sssp_run_baseline, sssp_run_spec_phase3, sssp_run_spec_boundary_chain, sssp_run_spec_recursive, sssp_run_spec_recursive_ml, sssp_get_spec_recursion_stats, sssp_get_spec_recursion_frame_count, sssp_get_spec_recursion_frame, SpecRecursionStats};
-use rand::{SeedableRng, rngs::SmallRng, Rng};
 use std::fs::File; use std::io::Write;
 
-fn make_random_graph(n: usize, avg_degree: f32, seed: u64) -> (Vec<u32>, Vec<u32>, Vec<f32>) {
-    let mut rng = SmallRng::seed_from_u64(seed);
-    let m_est = (n as f32 * avg_degree) as usize;
-    let mut adj: Vec<Vec<(u32,f32)>> = vec![Vec::new(); n];
-    for _ in 0..m_est { let u = rng.gen_range(0..n as u32); let v = rng.gen_range(0..n as u32); if u==v { continue; } let w = rng.gen_range(1.0..5.0); adj[u as usize].push((v,w)); }
-    // Build CSR
-    let mut offsets = Vec::with_capacity(n+1); offsets.push(0u32); let mut targets = Vec::new(); let mut weights = Vec::new();
-    for u in 0..n { for &(v,w) in &adj[u] { targets.push(v); weights.push(w); } offsets.push(targets.len() as u32); }
-    (offsets, targets, weights)
+// `scale_free` swaps in preferential attachment with `avg_degree` links per node
+// (symmetric, so the mean out-degree is about twice that).
+fn make_random_graph(n: usize, avg_degree: f32, seed: u64, scale_free: bool) -> (Vec<u32>, Vec<u32>, Vec<f32>) {
+    let g = if scale_free { sssp_core::gen::scale_free(n as u32, (avg_degree as u32).max(1), seed) } else { sssp_core::gen::random_graph(n as u32, avg_degree, seed) };
+    (g.offsets, g.targets, g.weights)
 }
 
 type SsspResultInfo = sssp_core::SsspResultInfo;
 
-fn run_one(n: usize, avg_degree: f32, seed: u64, check_boundary: bool, do_recursion: bool, do_recursion_ml: bool) -> serde_json::Value {
-    let (off, tgt, wt) = make_random_graph(n, avg_degree, seed);
-    let m = wt.len();
-    let mut dist_b = vec![f32::INFINITY; n]; let mut pred_b = vec![-1i32; n]; let mut info_b = SsspResultInfo{ relaxations:0, light_relaxations:0, heavy_relaxations:0, settled:0, error_code:0 };
-    let mut dist_p3 = vec![f32::INFINITY; n]; let mut pred_p3 = vec![-1i32; n]; let mut info_p3 = SsspResultInfo{ relaxations:0, light_relaxations:0, heavy_relaxations:0, settled:0, error_code:0 };
-    let mut dist_bc = vec![f32::INFINITY; n]; let mut pred_bc = vec![-1i32; n]; let mut info_bc = SsspResultInfo{ relaxations:0, light_relaxations:0, heavy_relaxations:0, settled:0, error_code:0 };
+// `baseline_repeat` reruns the baseline and reports the fastest run (prefetch A/B comparisons).
+fn run_one(graph: (Vec<u32>, Vec<u32>, Vec<f32>), check_boundary: bool, do_recursion: bool, do_recursion_ml: bool, baseline_repeat: u32) -> serde_json::Value {
+    let (off, tgt, wt) = graph;
+    let (n, m) = (off.len() - 1, wt.len());
+    let avg_degree = m as f32 / n.max(1) as f32;
+    let mut dist_b = vec![f32::INFINITY; n]; let mut pred_b = vec![-1i32; n]; let mut info_b = SsspResultInfo{ relaxations:0, light_relaxations:0, heavy_relaxations:0, settled:0, error_code:0, overflow:0, reachable:0, edges:0 };
+    let mut dist_p3 = vec![f32::INFINITY; n]; let mut pred_p3 = vec![-1i32; n]; let mut info_p3 = SsspResultInfo{ relaxations:0, light_relaxations:0, heavy_relaxations:0, settled:0, error_code:0, overflow:0, reachable:0, edges:0 };
+    let mut dist_bc = vec![f32::INFINITY; n]; let mut pred_bc = vec![-1i32; n]; let mut info_bc = SsspResultInfo{ relaxations:0, light_relaxations:0, heavy_relaxations:0, settled:0, error_code:0, overflow:0, reachable:0, edges:0 };
     unsafe {
-        let t0=Instant::now(); sssp_run_baseline(n as u32, off.as_ptr(), tgt.as_ptr(), wt.as_ptr(), 0, dist_b.as_mut_ptr(), pred_b.as_mut_ptr(), &mut info_b as *mut _); let dt_base = t0.elapsed().as_secs_f64()*1000.0;
-        let t1=Instant::now(); sssp_run_spec_phase3(n as u32, off.as_ptr(), tgt.as_ptr(), wt.as_ptr(), 0, dist_p3.as_mut_ptr(), pred_p3.as_mut_ptr(), &mut info_p3 as *mut _); let dt_p3 = t1.elapsed().as_secs_f64()*1000.0;
+        let mut dt_base = f64::INFINITY;
+        for _ in 0..baseline_repeat.max(1) { let t0=Instant::now(); sssp_run_baseline(n as u32, off.as_ptr(), tgt.as_ptr(), wt.as_ptr(), 0, dist_b.as_mut_ptr(), pred_b.as_mut_ptr(), &mut info_b as *mut _); dt_base = dt_base.min(t0.elapsed().as_secs_f64()*1000.0); }
+        let t1=Instant::now(); sssp_run_spec_phase3(n as u32, off.as_ptr(), tgt.as_ptr(), wt.as_ptr(), 0, dist_p3.as_mut_ptr(), pred_p3.as_mut_ptr(), &mut info_p3 as *mut _, f32::NAN); let dt_p3 = t1.elapsed().as_secs_f64()*1000.0;
         let t2=Instant::now(); sssp_run_spec_boundary_chain(n as u32, off.as_ptr(), tgt.as_ptr(), wt.as_ptr(), 0, dist_bc.as_mut_ptr(), pred_bc.as_mut_ptr(), &mut info_bc as *mut _); let dt_bc = t2.elapsed().as_secs_f64()*1000.0;
     let (_dt_rec, rec_obj) = if do_recursion {
-            let mut dist_r = vec![f32::INFINITY; n]; let mut pred_r = vec![-1i32; n]; let mut info_r = SsspResultInfo{ relaxations:0, light_relaxations:0, heavy_relaxations:0, settled:0, error_code:0 };
+            let mut dist_r = vec![f32::INFINITY; n]; let mut pred_r = vec![-1i32; n]; let mut info_r = SsspResultInfo{ relaxations:0, light_relaxations:0, heavy_relaxations:0, settled:0, error_code:0, overflow:0, reachable:0, edges:0 };
             let tr=Instant::now(); sssp_run_spec_recursive(n as u32, off.as_ptr(), tgt.as_ptr(), wt.as_ptr(), 0, dist_r.as_mut_ptr(), pred_r.as_mut_ptr(), &mut info_r as *mut _); let dt_rec = tr.elapsed().as_secs_f64()*1000.0;
+            for i in 0..n { let (db, dr) = (dist_b[i], dist_r[i]); if !(db.is_infinite() && dr.is_infinite()) { assert!((db-dr).abs() < 1e-5, "recursion parity fail at {}", i); } }
             // Collect stats & frame details
             let mut stats = SpecRecursionStats{frames:0,total_relaxations:0,baseline_relaxations:0,seed_k:0,chain_segments:0,chain_total_collected:0,inv_checks:0,inv_failures:0};
             sssp_get_spec_recursion_stats(&mut stats as *mut _);
@@ -65,7 +64,7 @@ fn run_one(n: usize, avg_degree: f32, seed: u64, check_boundary: bool, do_recurs
             }
         }
         let mut obj = serde_json::json!({
-            "n": n, "m": m, "avg_degree": avg_degree,
+            "n": n, "m": m, "avg_degree": avg_degree, "prefetch": cfg!(feature = "prefetch"),
             "baseline_ms": dt_base, "phase3_ms": dt_p3, "boundary_chain_ms": dt_bc,
             "phase3_speedup": dt_base/dt_p3.max(1e-9),
             "boundary_chain_speedup": dt_base/dt_bc.max(1e-9),
@@ -76,8 +75,9 @@ fn run_one(n: usize, avg_degree: f32, seed: u64, check_boundary: bool, do_recurs
         if let Some(rj) = rec_obj { if let serde_json::Value::Object(ref mut map) = obj { map.insert("recursion".to_string(), rj); } }
         if do_recursion_ml {
             // Run multi-level skeleton
-            let mut dist_r = vec![f32::INFINITY; n]; let mut pred_r = vec![-1i32; n]; let mut info_r = SsspResultInfo{ relaxations:0, light_relaxations:0, heavy_relaxations:0, settled:0, error_code:0 };
+            let mut dist_r = vec![f32::INFINITY; n]; let mut pred_r = vec![-1i32; n]; let mut info_r = SsspResultInfo{ relaxations:0, light_relaxations:0, heavy_relaxations:0, settled:0, error_code:0, overflow:0, reachable:0, edges:0 };
             let tr=Instant::now(); sssp_run_spec_recursive_ml(n as u32, off.as_ptr(), tgt.as_ptr(), wt.as_ptr(), 0, dist_r.as_mut_ptr(), pred_r.as_mut_ptr(), &mut info_r as *mut _); let dt_rml = tr.elapsed().as_secs_f64()*1000.0;
+            for i in 0..n { let (db, dr) = (dist_b[i], dist_r[i]); if !(db.is_infinite() && dr.is_infinite()) { assert!((db-dr).abs() < 1e-5, "recursion_ml parity fail at {}", i); } }
             let mut stats = SpecRecursionStats{frames:0,total_relaxations:0,baseline_relaxations:0,seed_k:0,chain_segments:0,chain_total_collected:0,inv_checks:0,inv_failures:0};
             sssp_get_spec_recursion_stats(&mut stats as *mut _);
             let frame_count = sssp_get_spec_recursion_frame_count();
@@ -110,9 +110,12 @@ fn run_one(n: usize, avg_degree: f32, seed: u64, check_boundary: bool, do_recurs
 
 fn main(){
     let args: Vec<String> = std::env::args().collect();
-    if args.iter().any(|a| a=="--help") { eprintln!("Usage: bench_spec --sizes 10000,20000 --degrees 2,4,8 --seed 42 --out benchmarks/native_sample.json [--no-boundary-parity] [--full-parity] [--recursion] [--recursion-ml]"); return; }
+    if args.iter().any(|a| a=="--help") { eprintln!("Usage: bench_spec (--sizes 10000,20000 --degrees 2,4,8 --seed 42 | --dimacs graph.gr) --out benchmarks/native_sample.json [--no-boundary-parity] [--full-parity] [--recursion] [--recursion-ml] [--baseline-repeat 3] [--scale-free]"); return; }
+    let dimacs = args.iter().position(|a| a=="--dimacs").and_then(|i| args.get(i+1)).cloned();
     let do_recursion = args.iter().any(|a| a=="--recursion");
+    let scale_free = args.iter().any(|a| a=="--scale-free");
     let do_recursion_ml = args.iter().any(|a| a=="--recursion-ml");
+    let baseline_repeat: u32 = args.iter().position(|a| a=="--baseline-repeat").and_then(|i| args.get(i+1)).and_then(|v| v.parse().ok()).unwrap_or(1);
     let sizes_arg = args.iter().position(|a| a=="--sizes").and_then(|i| args.get(i+1)).cloned().unwrap_or("10000,20000".into());
     let degrees_arg = args.iter().position(|a| a=="--degrees").and_then(|i| args.get(i+1)).cloned();
     let single_degree: f32 = args.iter().position(|a| a=="--avg-degree").and_then(|i| args.get(i+1)).and_then(|v| v.parse().ok()).unwrap_or(4.0);
@@ -129,7 +132,16 @@ fn main(){
     let sizes: Vec<usize> = sizes_arg.split(',').filter_map(|s| s.parse().ok()).collect();
     let degrees: Vec<f32> = if let Some(darg) = degrees_arg { darg.split(',').filter_map(|s| s.parse().ok()).collect() } else { vec![single_degree] };
     let mut results = Vec::new();
-    for s in &sizes { for &deg in &degrees { results.push(run_one(*s, deg, seed, full_parity && !skip_boundary_parity, do_recursion, do_recursion_ml)); } }
+    let check_boundary = full_parity && !skip_boundary_parity;
+    if let Some(path) = dimacs {
+        // A DIMACS graph replaces the random size/degree sweep (source 0 = DIMACS node 1).
+        let g = sssp_core::load_dimacs_gr(std::path::Path::new(&path), false).unwrap_or_else(|e| panic!("{}: {}", path, e));
+        let mut obj = run_one((g.offsets, g.targets, g.weights), check_boundary, do_recursion, do_recursion_ml, baseline_repeat);
+        if let serde_json::Value::Object(ref mut map) = obj { map.insert("dimacs".to_string(), serde_json::json!(path)); }
+        results.push(obj);
+    } else {
+        for s in &sizes { for &deg in &degrees { results.push(run_one(make_random_graph(*s, deg, seed, scale_free), check_boundary, do_recursion, do_recursion_ml, baseline_repeat)); } }
+    }
     let json = serde_json::Value::Array(results);
     if let Some(dir) = std::path::Path::new(&out_path).parent() { std::fs::create_dir_all(dir).ok(); }
     let mut f=File::create(&out_path).expect("create out"); f.write_all(serde_json::to_string_pretty(&json).unwrap().as_bytes()).unwrap();
@@ -0,0 +1,153 @@
+//! Deterministic graph generators shared by the examples, benchmarks and tests
+//! (uniform random, path, star, lattice and scale-free).
+//! Every generator draws from `XorShift64Star` rather than `rand`, so a given seed
+//! produces the same graph across dependency bumps and platforms. Outputs are
+//! `CsrGraph`s in the layout every runner takes; out-edges of a node keep their
+//! generation order.
+
+use crate::CsrGraph;
+
+// xorshift64* (Vigna): 64-bit state, upper 32 bits of the scrambled state as output.
+#[derive(Clone, Debug)]
+pub struct XorShift64Star(u64);
+
+impl XorShift64Star {
+    // A zero state would be a fixed point; seed 0 maps to a fixed odd constant.
+    pub fn new(seed: u64) -> Self { XorShift64Star(if seed == 0 { 0x9E37_79B9_7F4A_7C15 } else { seed }) }
+    pub fn next_u32(&mut self) -> u32 {
+        let s = &mut self.0;
+        *s ^= *s >> 12; *s ^= *s << 25; *s ^= *s >> 27;
+        (s.wrapping_mul(2685821657736338717) >> 32) as u32
+    }
+    // Uniform in [0, bound) by multiply-shift (no modulo bias beyond 2^-32).
+    pub fn below(&mut self, bound: u32) -> u32 { ((self.next_u32() as u64 * bound as u64) >> 32) as u32 }
+    // Uniform in [lo, hi) from 24 random mantissa bits.
+    pub fn range_f32(&mut self, lo: f32, hi: f32) -> f32 { lo + (hi - lo) * ((self.next_u32() >> 8) as f32 / (1u32 << 24) as f32) }
+}
+
+// Builds CSR from an edge list, grouping by source and keeping list order per source.
+fn from_edges(n: u32, edges: &[(u32, u32, f32)]) -> CsrGraph {
+    let mut offsets = vec![0u32; n as usize + 1];
+    for &(u, _, _) in edges { offsets[u as usize + 1] += 1; }
+    for i in 0..n as usize { offsets[i + 1] += offsets[i]; }
+    let mut fill = offsets.clone();
+    let (mut targets, mut weights) = (vec![0u32; edges.len()], vec![0f32; edges.len()]);
+    for &(u, v, w) in edges { let k = fill[u as usize] as usize; targets[k] = v; weights[k] = w; fill[u as usize] += 1; }
+    CsrGraph { n, offsets, targets, weights }
+}
+
+// Directed random graph with floor(n * avg_degree) edges, weights uniform in [1, 5):
+// sources and targets are uniform, self-loops are redrawn, parallel edges allowed.
+pub fn random_graph(n: u32, avg_degree: f32, seed: u64) -> CsrGraph { random_graph_in(n, avg_degree, seed, 1.0, 5.0) }
+
+// `random_graph` with weights uniform in [w_min, w_max). Needs n >= 2.
+pub fn random_graph_in(n: u32, avg_degree: f32, seed: u64, w_min: f32, w_max: f32) -> CsrGraph {
+    assert!(n >= 2, "random_graph needs n >= 2");
+    let mut rng = XorShift64Star::new(seed);
+    let m = (n as f64 * avg_degree.max(0.0) as f64) as usize;
+    let mut edges = Vec::with_capacity(m);
+    while edges.len() < m {
+        let u = rng.below(n); let v = rng.below(n);
+        if u == v { continue; }
+        edges.push((u, v, rng.range_f32(w_min, w_max)));
+    }
+    from_edges(n, &edges)
+}
+
+// 0 -> 1 -> ... -> n-1, every edge weight `w`.
+pub fn path_graph(n: u32, w: f32) -> CsrGraph {
+    let edges: Vec<(u32, u32, f32)> = (1..n).map(|v| (v - 1, v, w)).collect();
+    from_edges(n, &edges)
+}
+
+// Center 0 with edges to leaves 1..=k, every edge weight `w`.
+pub fn star_graph(k: u32, w: f32) -> CsrGraph {
+    let edges: Vec<(u32, u32, f32)> = (1..=k).map(|v| (0, v, w)).collect();
+    from_edges(k + 1, &edges)
+}
+
+// side x side 4-neighbour lattice, edges in both directions with weight `w`; node
+// r*side + c lists its neighbours up, left, right, down. Diameter 2*(side-1) hops:
+// the large-diameter case where delta-stepping visits many buckets.
+pub fn grid_graph(side: u32, w: f32) -> CsrGraph {
+    let n = side * side;
+    let mut edges = Vec::with_capacity(4 * n as usize);
+    for u in 0..n {
+        let (r, c) = (u / side, u % side);
+        if r > 0 { edges.push((u, u - side, w)); }
+        if c > 0 { edges.push((u, u - 1, w)); }
+        if c + 1 < side { edges.push((u, u + 1, w)); }
+        if r + 1 < side { edges.push((u, u + side, w)); }
+    }
+    from_edges(n, &edges)
+}
+
+// Barabasi-Albert preferential attachment, as a symmetric directed graph (each
+// undirected edge stored both ways, weights uniform in [1, 5)). Starts from a clique
+// on m_per_node + 1 nodes; every later node links to m_per_node distinct earlier
+// nodes chosen in proportion to their degree, so early nodes grow into hubs with
+// out-degree far above the mean. Degree-proportional draws are O(1) by picking a
+// uniform entry of the list of all edge endpoints (a node of degree d appears d
+// times), which keeps generation O(n + m). Needs n > m_per_node >= 1.
+pub fn scale_free(n: u32, m_per_node: u32, seed: u64) -> CsrGraph {
+    assert!(m_per_node >= 1 && n > m_per_node, "scale_free needs n > m_per_node >= 1");
+    let mut rng = XorShift64Star::new(seed);
+    let k = m_per_node;
+    let mut ends: Vec<u32> = Vec::with_capacity(2 * (k as usize) * n as usize);
+    let mut edges = Vec::with_capacity(ends.capacity());
+    let mut link = |u: u32, v: u32, rng: &mut XorShift64Star, ends: &mut Vec<u32>| {
+        let w = rng.range_f32(1.0, 5.0);
+        edges.push((u, v, w)); edges.push((v, u, w)); ends.push(u); ends.push(v);
+    };
+    for u in 0..=k { for v in 0..u { link(u, v, &mut rng, &mut ends); } }
+    let mut picked: Vec<u32> = Vec::with_capacity(k as usize);
+    for u in k + 1..n {
+        picked.clear();
+        while picked.len() < k as usize {
+            let v = ends[rng.below(ends.len() as u32) as usize];
+            if !picked.contains(&v) { picked.push(v); }
+        }
+        for &v in &picked { link(u, v, &mut rng, &mut ends); }
+    }
+    from_edges(n, &edges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generators_are_deterministic_and_well_formed(){
+        let a = random_graph(500, 4.0, 7);
+        assert_eq!(a, random_graph(500, 4.0, 7));
+        assert_ne!(a.targets, random_graph(500, 4.0, 8).targets);
+        assert_eq!(a.targets.len(), 2000);
+        assert_eq!(crate::sssp_validate_csr(a.n, a.offsets.as_ptr(), a.targets.as_ptr(), a.weights.as_ptr(), core::ptr::null_mut()), 0);
+        for u in 0..a.n as usize { assert!(a.targets[a.offsets[u] as usize..a.offsets[u + 1] as usize].iter().all(|&v| v as usize != u)); }
+        assert!(a.weights.iter().all(|&w| (1.0..5.0).contains(&w)));
+        let g = grid_graph(4, 1.0);
+        assert_eq!((g.n, g.targets.len()), (16, 48));
+        assert_eq!(&g.targets[g.offsets[5] as usize..g.offsets[6] as usize], &[1, 4, 6, 9]);
+        let p = path_graph(3, 2.0);
+        assert_eq!((p.offsets, p.targets), (vec![0, 1, 2, 2], vec![1, 2]));
+        let s = star_graph(3, 1.0);
+        assert_eq!((s.n, s.offsets), (4, vec![0, 3, 3, 3, 3]));
+    }
+
+    #[test]
+    fn scale_free_grows_hubs_deterministically(){
+        let (n, k) = (20_000u32, 2u32);
+        let g = scale_free(n, k, 11);
+        assert_eq!(g, scale_free(n, k, 11));
+        // Clique on k+1 nodes plus k links per later node, each stored both ways.
+        assert_eq!(g.targets.len() as u32, 2 * (k * (k + 1) / 2 + (n - k - 1) * k));
+        assert_eq!(crate::sssp_validate_csr(g.n, g.offsets.as_ptr(), g.targets.as_ptr(), g.weights.as_ptr(), core::ptr::null_mut()), 0);
+        let deg: Vec<u32> = g.offsets.windows(2).map(|w| w[1] - w[0]).collect();
+        let max = *deg.iter().max().unwrap();
+        let mean = g.targets.len() as f64 / n as f64;
+        assert!(max as f64 > 25.0 * mean, "max out-degree {max} vs mean {mean}");
+        assert!(deg.iter().all(|&d| d >= k));
+        // Hubs are the early nodes.
+        assert!(deg.iter().position(|&d| d == max).unwrap() < 100);
+    }
+}
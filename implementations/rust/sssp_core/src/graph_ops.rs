@@ -0,0 +1,741 @@
+//! CSR graph transforms that complement the runners (sparsification, reachable
+//! extraction, union, zero-weight SCCs, reduced costs, coarsening, MST, ...).
+//! Outputs are written into caller-owned CSR buffers using the same layout as
+//! the inputs: `offsets` len n+1, `targets`/`weights` len m.
+
+use crate::{as_mut_slice, as_slice, csr_slices, BinaryHeapSimple, HeapItem};
+
+// ---------------- Greedy multiplicative spanner ----------------
+// Edges are scanned in non-decreasing weight order (ties by CSR index) and an edge
+// (u,v,w) is kept only if the spanner built so far has no u->v path of length
+// <= stretch * w (checked with a Dijkstra from u bounded at stretch * w).
+// Guarantee: for every pair, d_G(s,v) <= d_H(s,v) <= stretch * d_G(s,v), because
+// each dropped edge is replaced by a spanner path at most `stretch` times longer.
+// Output buffers must hold m edges (the spanner never has more); `out_m` receives
+// the kept edge count and edges keep their original per-node CSR order.
+// Returns -7 if `stretch` is NaN or < 1, and -6/-7 for negative/non-finite
+// weights (the stretch guarantee needs non-negative weights).
+#[no_mangle]
+pub extern "C" fn sssp_build_spanner(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    stretch: f32,
+    out_offsets: *mut u32, // len n+1
+    out_targets: *mut u32, // len >= m
+    out_weights: *mut f32, // len >= m
+    out_m: *mut u32,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if out_offsets.is_null() || out_targets.is_null() || out_weights.is_null() || out_m.is_null() { return -3; }
+        if stretch.is_nan() || stretch < 1.0 { return -7; }
+        let rc = crate::precheck_weights(wts, core::ptr::null_mut()); if rc != 0 { return rc; }
+        let n_usize = n as usize; let m = tgt.len();
+        let mut src = vec![0u32; m];
+        for u in 0..n_usize { for s in &mut src[off[u] as usize..off[u + 1] as usize] { *s = u as u32; } }
+        let mut order: Vec<usize> = (0..m).collect();
+        order.sort_by(|&a, &b| wts[a].total_cmp(&wts[b]).then(a.cmp(&b)));
+
+        let mut adj: Vec<Vec<(u32, f32)>> = vec![Vec::new(); n_usize];
+        let mut kept = vec![false; m];
+        let mut dist = vec![f32::INFINITY; n_usize];
+        let mut touched: Vec<u32> = Vec::new();
+        let mut heap = BinaryHeapSimple::new(64);
+        let (mut pushes, mut pops) = (0u64, 0u64);
+        for &e in &order {
+            let (u, v, w) = (src[e], tgt[e], wts[e]);
+            if u == v { continue; }
+            let bound = stretch * w;
+            // Bounded Dijkstra in the current spanner; scratch reset via the touched list.
+            dist[u as usize] = 0.0; touched.push(u);
+            heap.push(HeapItem { node: u, dist: 0.0 }, &mut pushes);
+            let mut reached = false;
+            while let Some(item) = heap.pop(&mut pops) {
+                if item.dist > dist[item.node as usize] { continue; }
+                if item.node == v { reached = true; break; }
+                for &(x, wx) in &adj[item.node as usize] {
+                    let nd = item.dist + wx;
+                    if nd <= bound && nd < dist[x as usize] {
+                        if !dist[x as usize].is_finite() { touched.push(x); }
+                        dist[x as usize] = nd;
+                        heap.push(HeapItem { node: x, dist: nd }, &mut pushes);
+                    }
+                }
+            }
+            heap.data.clear();
+            for &t in &touched { dist[t as usize] = f32::INFINITY; }
+            touched.clear();
+            if !reached { adj[u as usize].push((v, w)); kept[e] = true; }
+        }
+
+        let o_off = as_mut_slice(out_offsets, n_usize + 1);
+        let o_tgt = as_mut_slice(out_targets, m);
+        let o_wts = as_mut_slice(out_weights, m);
+        let mut k = 0usize; o_off[0] = 0;
+        for u in 0..n_usize {
+            for e in off[u] as usize..off[u + 1] as usize { if kept[e] { o_tgt[k] = tgt[e]; o_wts[k] = wts[e]; k += 1; } }
+            o_off[u + 1] = k as u32;
+        }
+        unsafe { *out_m = k as u32; }
+        0
+    })
+}
+
+// ---------------- Reachable induced subgraph ----------------
+// Extracts the subgraph induced by the nodes reachable from `source` (weights are
+// ignored for reachability). Reachable nodes are renumbered compactly in ascending
+// original id order; `out_map[v]` is the new id of v or -1 if unreachable. Every
+// out-edge of a reachable node stays inside the set, so the output keeps all of
+// them in original order. Buffers are sized for the worst case (n+1 offsets, m
+// edges); `out_n` / `out_m` receive the actual sizes.
+#[no_mangle]
+pub extern "C" fn sssp_reachable_subgraph(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    out_map: *mut i32,     // len n
+    out_n: *mut u32,
+    out_offsets: *mut u32, // len >= out_n+1 (n+1 is always enough)
+    out_targets: *mut u32, // len >= out_m (m is always enough)
+    out_weights: *mut f32,
+    out_m: *mut u32,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if source >= n { return -2; }
+        if out_map.is_null() || out_n.is_null() || out_offsets.is_null() || out_targets.is_null() || out_weights.is_null() || out_m.is_null() { return -3; }
+        let n_usize = n as usize;
+        let map = as_mut_slice(out_map, n_usize);
+        for x in map.iter_mut() { *x = -1; }
+        // Explicit-stack DFS marks reachability (map = -2 as "seen" before renumbering).
+        let mut stack = vec![source]; map[source as usize] = -2;
+        while let Some(u) = stack.pop() {
+            for &v in &tgt[off[u as usize] as usize..off[u as usize + 1] as usize] { if map[v as usize] == -1 { map[v as usize] = -2; stack.push(v); } }
+        }
+        let mut sub_n = 0u32; let mut sub_m = 0usize;
+        for (u, x) in map.iter_mut().enumerate() { if *x == -2 { *x = sub_n as i32; sub_n += 1; sub_m += (off[u + 1] - off[u]) as usize; } }
+        let o_off = as_mut_slice(out_offsets, sub_n as usize + 1);
+        let o_tgt = as_mut_slice(out_targets, sub_m);
+        let o_wts = as_mut_slice(out_weights, sub_m);
+        let mut k = 0usize; o_off[0] = 0;
+        for u in 0..n_usize {
+            if map[u] < 0 { continue; }
+            for e in off[u] as usize..off[u + 1] as usize { o_tgt[k] = map[tgt[e] as usize] as u32; o_wts[k] = wts[e]; k += 1; }
+            o_off[map[u] as usize + 1] = k as u32;
+        }
+        unsafe { *out_n = sub_n; *out_m = sub_m as u32; }
+        0
+    })
+}
+
+// ---------------- Reachability mask ----------------
+// Marks the nodes reachable from `source` (weights ignored): out_mask[v] = 1 if
+// reachable, else 0. Returns the reachable count, which is at least 1 (the source)
+// on valid input; 0 means n == 0, a null pointer or source >= n. Explicit-stack
+// DFS with the mask itself as the visited set, so deep graphs cannot overflow the
+// call stack and the only allocation is the stack. Much cheaper than a full SSSP
+// when only "which nodes can get a finite distance" is needed.
+#[no_mangle]
+pub extern "C" fn sssp_reachable_mask(n: u32, offsets: *const u32, targets: *const u32, source: u32, out_mask: *mut u8) -> u32 {
+    crate::ffi_guard(0, || {
+        if n == 0 || offsets.is_null() || targets.is_null() || out_mask.is_null() || source >= n { return 0; }
+        let off = as_slice(offsets, n as usize + 1);
+        let tgt = as_slice(targets, off[n as usize] as usize);
+        let mask = as_mut_slice(out_mask, n as usize);
+        for x in mask.iter_mut() { *x = 0; }
+        let mut stack = vec![source]; mask[source as usize] = 1;
+        let mut count = 1u32;
+        while let Some(u) = stack.pop() {
+            for &v in &tgt[off[u as usize] as usize..off[u as usize + 1] as usize] { if mask[v as usize] == 0 { mask[v as usize] = 1; count += 1; stack.push(v); } }
+        }
+        count
+    })
+}
+
+// ---------------- CSR union ----------------
+// Merges two graphs over the same node set (e.g. base network + overlay). Each
+// node's out-edges are its `a` edges followed by its `b` edges. With `dedup`,
+// parallel edges u->v are collapsed to the minimum weight, kept at the position
+// of their first occurrence. Output buffers must hold m_a + m_b edges.
+#[no_mangle]
+pub extern "C" fn sssp_merge_csr(
+    n: u32,
+    a_offsets: *const u32,
+    a_targets: *const u32,
+    a_weights: *const f32,
+    b_offsets: *const u32,
+    b_targets: *const u32,
+    b_weights: *const f32,
+    out_offsets: *mut u32, // len n+1
+    out_targets: *mut u32, // len >= m_a + m_b
+    out_weights: *mut f32, // len >= m_a + m_b
+    out_m: *mut u32,
+    dedup: bool,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (a_off, a_tgt, a_wts) = match csr_slices(n, a_offsets, a_targets, a_weights) { Ok(s) => s, Err(rc) => return rc };
+        let (b_off, b_tgt, b_wts) = match csr_slices(n, b_offsets, b_targets, b_weights) { Ok(s) => s, Err(rc) => return rc };
+        if out_offsets.is_null() || out_targets.is_null() || out_weights.is_null() || out_m.is_null() { return -3; }
+        let n_usize = n as usize; let cap = a_tgt.len() + b_tgt.len();
+        let o_off = as_mut_slice(out_offsets, n_usize + 1);
+        let o_tgt = as_mut_slice(out_targets, cap);
+        let o_wts = as_mut_slice(out_weights, cap);
+        // slot[v] = output index of u->v for the node being merged (u32::MAX = none yet).
+        let mut slot = vec![u32::MAX; if dedup { n_usize } else { 0 }];
+        let mut k = 0usize; o_off[0] = 0;
+        for u in 0..n_usize {
+            let start = k;
+            let a_edges = (a_off[u] as usize..a_off[u + 1] as usize).map(|e| (a_tgt[e], a_wts[e]));
+            let b_edges = (b_off[u] as usize..b_off[u + 1] as usize).map(|e| (b_tgt[e], b_wts[e]));
+            for (v, w) in a_edges.chain(b_edges) {
+                if dedup {
+                    let sl = slot[v as usize];
+                    if sl != u32::MAX { if w < o_wts[sl as usize] { o_wts[sl as usize] = w; } continue; }
+                    slot[v as usize] = k as u32;
+                }
+                o_tgt[k] = v; o_wts[k] = w; k += 1;
+            }
+            if dedup { for &v in &o_tgt[start..k] { slot[v as usize] = u32::MAX; } }
+            o_off[u + 1] = k as u32;
+        }
+        unsafe { *out_m = k as u32; }
+        0
+    })
+}
+
+// ---------------- Zero-weight SCC contraction map ----------------
+// Strongly connected components of the subgraph of zero-weight edges: all nodes
+// in one component are at the same distance from any source, so contracting them
+// (out_super[v] = component id) yields a graph without zero-weight cycles.
+// Components are numbered 0.. in order of their smallest node id; singleton nodes
+// get their own id. Iterative Tarjan, O(n + m).
+#[no_mangle]
+pub extern "C" fn sssp_contract_zero_sccs(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    out_super: *mut i32, // len n
+    out_num_super: *mut u32,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if out_super.is_null() || out_num_super.is_null() { return -3; }
+        let n_usize = n as usize;
+        const UNSEEN: u32 = u32::MAX;
+        let mut index = vec![UNSEEN; n_usize]; let mut low = vec![0u32; n_usize];
+        let mut on_stack = vec![false; n_usize]; let mut stack: Vec<u32> = Vec::new();
+        let mut comp = vec![u32::MAX; n_usize]; let mut num_comp = 0u32; let mut next_index = 0u32;
+        // Call stack frames: (node, next edge position).
+        let mut frames: Vec<(u32, usize)> = Vec::new();
+        for root in 0..n_usize {
+            if index[root] != UNSEEN { continue; }
+            frames.push((root as u32, off[root] as usize));
+            index[root] = next_index; low[root] = next_index; next_index += 1; stack.push(root as u32); on_stack[root] = true;
+            while let Some(&mut (u, ref mut pos)) = frames.last_mut() {
+                let u = u as usize;
+                if *pos < off[u + 1] as usize {
+                    let e = *pos; *pos += 1;
+                    if wts[e] != 0.0 { continue; }
+                    let v = tgt[e] as usize;
+                    if index[v] == UNSEEN {
+                        index[v] = next_index; low[v] = next_index; next_index += 1; stack.push(v as u32); on_stack[v] = true;
+                        frames.push((v as u32, off[v] as usize));
+                    } else if on_stack[v] { low[u] = low[u].min(index[v]); }
+                    continue;
+                }
+                frames.pop();
+                if let Some(&(p, _)) = frames.last() { let p = p as usize; low[p] = low[p].min(low[u]); }
+                if low[u] == index[u] {
+                    while let Some(x) = stack.pop() { on_stack[x as usize] = false; comp[x as usize] = num_comp; if x as usize == u { break; } }
+                    num_comp += 1;
+                }
+            }
+        }
+        // Renumber by smallest member id.
+        let mut remap = vec![u32::MAX; num_comp as usize]; let mut next = 0u32;
+        let sup = as_mut_slice(out_super, n_usize);
+        for v in 0..n_usize {
+            let c = comp[v] as usize;
+            if remap[c] == u32::MAX { remap[c] = next; next += 1; }
+            sup[v] = remap[c] as i32;
+        }
+        unsafe { *out_num_super = num_comp; }
+        0
+    })
+}
+
+// ---------------- Coarsening by edge matching ----------------
+// One level of a multilevel scheme: nodes are visited in id order and each
+// unmatched u is merged with the unmatched out-neighbour v over the heaviest
+// (`matching` = 0, classic heavy-edge matching) or lightest (`matching` = 1,
+// better at preserving short distances) edge; ties go to the lower edge index.
+// Coarse ids follow visit order; `out_map[v]` is v's coarse node. Coarse edges
+// keep the minimum weight between two coarse nodes and intra-pair edges vanish,
+// so coarse distances are lower bounds on the original ones. Output buffers must
+// hold n+1 offsets and m edges. Returns -7 for an unknown `matching`.
+#[no_mangle]
+pub extern "C" fn sssp_coarsen(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    out_offsets: *mut u32, // len >= out_n+1 (n+1 is always enough)
+    out_targets: *mut u32, // len >= out_m (m is always enough)
+    out_weights: *mut f32,
+    out_m: *mut u32,
+    out_n: *mut u32,
+    out_map: *mut u32,     // len n
+    matching: i32,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if out_offsets.is_null() || out_targets.is_null() || out_weights.is_null() || out_m.is_null() || out_n.is_null() || out_map.is_null() { return -3; }
+        let heavy = match matching { 0 => true, 1 => false, _ => return -7 };
+        let n_usize = n as usize;
+        let map = as_mut_slice(out_map, n_usize);
+        for x in map.iter_mut() { *x = u32::MAX; }
+        let mut nc = 0u32;
+        for u in 0..n_usize {
+            if map[u] != u32::MAX { continue; }
+            let mut best: Option<(usize, f32)> = None;
+            for e in off[u] as usize..off[u + 1] as usize {
+                let v = tgt[e] as usize;
+                if v == u || map[v] != u32::MAX { continue; }
+                let better = match best { None => true, Some((_, bw)) => if heavy { wts[e] > bw } else { wts[e] < bw } };
+                if better { best = Some((v, wts[e])); }
+            }
+            map[u] = nc;
+            if let Some((v, _)) = best { map[v] = nc; }
+            nc += 1;
+        }
+        // Coarse adjacency: group original edges by coarse source.
+        let mut members_off = vec![0u32; nc as usize + 1];
+        for &c in map.iter() { members_off[c as usize + 1] += 1; }
+        for i in 0..nc as usize { members_off[i + 1] += members_off[i]; }
+        let mut fill = members_off.clone(); let mut members = vec![0u32; n_usize];
+        for (v, &c) in map.iter().enumerate() { members[fill[c as usize] as usize] = v as u32; fill[c as usize] += 1; }
+        let o_off = as_mut_slice(out_offsets, nc as usize + 1);
+        let o_tgt = as_mut_slice(out_targets, tgt.len());
+        let o_wts = as_mut_slice(out_weights, tgt.len());
+        let mut slot = vec![u32::MAX; nc as usize];
+        let mut k = 0usize; o_off[0] = 0;
+        for c in 0..nc as usize {
+            let start = k;
+            for &u in &members[members_off[c] as usize..members_off[c + 1] as usize] {
+                for e in off[u as usize] as usize..off[u as usize + 1] as usize {
+                    let cv = map[tgt[e] as usize];
+                    if cv as usize == c { continue; }
+                    let sl = slot[cv as usize];
+                    if sl != u32::MAX { if wts[e] < o_wts[sl as usize] { o_wts[sl as usize] = wts[e]; } continue; }
+                    slot[cv as usize] = k as u32; o_tgt[k] = cv; o_wts[k] = wts[e]; k += 1;
+                }
+            }
+            for &cv in &o_tgt[start..k] { slot[cv as usize] = u32::MAX; }
+            o_off[c + 1] = k as u32;
+        }
+        unsafe { *out_n = nc; *out_m = k as u32; }
+        0
+    })
+}
+
+// ---------------- Minimum spanning tree (Prim) ----------------
+// Prim's algorithm on the baseline min-heap keyed by attaching-edge weight.
+// Requires an undirected graph stored symmetrically (u->v and v->u with equal
+// weight); on a directed CSR the result is not a meaningful spanning tree.
+// Disconnected inputs yield a spanning forest: each component is grown from its
+// lowest node id, whose `out_parent` is -1. `out_total` is the forest weight.
+#[no_mangle]
+pub extern "C" fn sssp_mst_prim(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    out_parent: *mut i32, // len n
+    out_total: *mut f64,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if out_parent.is_null() || out_total.is_null() { return -3; }
+        let n_usize = n as usize;
+        let parent = as_mut_slice(out_parent, n_usize);
+        for p in parent.iter_mut() { *p = -1; }
+        let mut key = vec![f32::INFINITY; n_usize]; let mut in_tree = vec![false; n_usize];
+        let mut heap = BinaryHeapSimple::new(n_usize.min(1024));
+        let (mut pushes, mut pops) = (0u64, 0u64);
+        let mut total = 0f64;
+        for root in 0..n_usize {
+            if in_tree[root] { continue; }
+            key[root] = 0.0; heap.push(HeapItem { node: root as u32, dist: 0.0 }, &mut pushes);
+            while let Some(item) = heap.pop(&mut pops) {
+                let u = item.node as usize;
+                if in_tree[u] || item.dist > key[u] { continue; }
+                in_tree[u] = true; total += item.dist as f64;
+                for e in off[u] as usize..off[u + 1] as usize {
+                    let v = tgt[e] as usize;
+                    if !in_tree[v] && wts[e] < key[v] { key[v] = wts[e]; parent[v] = u as i32; heap.push(HeapItem { node: v as u32, dist: wts[e] }, &mut pushes); }
+                }
+            }
+        }
+        unsafe { *out_total = total; }
+        0
+    })
+}
+
+// ---------------- Reduced costs ----------------
+// out_reduced[e] = (pot[u] + w[e]) - pot[v] for edge e = u->v, evaluated in this
+// order so that with pot = Dijkstra distances (the same f32 sums the relaxation
+// computed) the result is exactly >= 0. Feasible potentials come from
+// `sssp_run_baseline_potentials`.
+#[no_mangle]
+pub extern "C" fn sssp_reduced_cost(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    pot: *const f32,         // len n
+    out_reduced: *mut f32,   // len m
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if pot.is_null() || out_reduced.is_null() { return -3; }
+        let p = crate::as_slice(pot, n as usize);
+        let out = as_mut_slice(out_reduced, tgt.len());
+        for u in 0..n as usize { for e in off[u] as usize..off[u + 1] as usize { out[e] = (p[u] + wts[e]) - p[tgt[e] as usize]; } }
+        0
+    })
+}
+
+// ---------------- Symmetrization ----------------
+// Undirected closure: every edge u->v (w) gets a matching v->u (w). All edges
+// between u and v in either direction collapse to one edge each way carrying
+// their minimum weight, so exact duplicates disappear. Each node's out-edges are
+// its original targets (first-occurrence order) followed by new reverse ones.
+// Returns the output edge count. Passing null output buffers with out_cap = 0
+// only sizes the result; otherwise -2 if out_cap is below the count.
+#[no_mangle]
+pub extern "C" fn sssp_symmetrize_csr(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    out_offsets: *mut u32, // len n+1
+    out_targets: *mut u32, // len out_cap
+    out_weights: *mut f32, // len out_cap
+    out_cap: u32,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        let sizing = out_cap == 0 && out_offsets.is_null() && out_targets.is_null() && out_weights.is_null();
+        if !sizing && (out_offsets.is_null() || out_targets.is_null() || out_weights.is_null()) { return -3; }
+        let n_usize = n as usize;
+        let (r_off, r_tgt, r_wts) = transpose_csr(off, tgt, wts);
+        let mut s_off = Vec::with_capacity(n_usize + 1); s_off.push(0u32);
+        let mut s_tgt: Vec<u32> = Vec::with_capacity(2 * tgt.len()); let mut s_wts: Vec<f32> = Vec::with_capacity(2 * tgt.len());
+        // slot[v] = output index of u->v for the node being built (u32::MAX = none yet).
+        let mut slot = vec![u32::MAX; n_usize];
+        for u in 0..n_usize {
+            let start = s_tgt.len();
+            let fwd = (off[u] as usize..off[u + 1] as usize).map(|e| (tgt[e], wts[e]));
+            let rev = (r_off[u] as usize..r_off[u + 1] as usize).map(|e| (r_tgt[e], r_wts[e]));
+            for (v, w) in fwd.chain(rev) {
+                let sl = slot[v as usize];
+                if sl != u32::MAX { if w < s_wts[sl as usize] { s_wts[sl as usize] = w; } continue; }
+                slot[v as usize] = s_tgt.len() as u32;
+                s_tgt.push(v); s_wts.push(w);
+            }
+            for &v in &s_tgt[start..] { slot[v as usize] = u32::MAX; }
+            s_off.push(s_tgt.len() as u32);
+        }
+        let m = s_tgt.len();
+        if m > i32::MAX as usize { return -4; }
+        if sizing { return m as i32; }
+        if (out_cap as usize) < m { return -2; }
+        as_mut_slice(out_offsets, n_usize + 1).copy_from_slice(&s_off);
+        as_mut_slice(out_targets, m).copy_from_slice(&s_tgt);
+        as_mut_slice(out_weights, m).copy_from_slice(&s_wts);
+        m as i32
+    })
+}
+
+// ---------------- Transpose ----------------
+// Reverse graph for backward searches: every edge u->v (w) becomes v->u (w), built
+// by counting sort over targets (in-degree count, prefix sum, scatter) with edges
+// into each node in ascending source order. The output has the same m, so the
+// caller sizes it from offsets[n]. The input is checked with sssp_validate_csr
+// first and its code returned on failure; -3 if an output buffer is null.
+#[no_mangle]
+pub extern "C" fn sssp_transpose_csr(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    out_offsets: *mut u32, // len n+1
+    out_targets: *mut u32, // len m
+    out_weights: *mut f32, // len m
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        let rc = crate::sssp_validate_csr(n, offsets, targets, weights, core::ptr::null_mut()); if rc != 0 { return rc; }
+        if out_offsets.is_null() || out_targets.is_null() || out_weights.is_null() { return -3; }
+        let (r_off, r_tgt, r_wts) = transpose_csr(off, tgt, wts);
+        as_mut_slice(out_offsets, n as usize + 1).copy_from_slice(&r_off);
+        as_mut_slice(out_targets, tgt.len()).copy_from_slice(&r_tgt);
+        as_mut_slice(out_weights, tgt.len()).copy_from_slice(&r_wts);
+        0
+    })
+}
+
+// ---------------- BFS relabeling ----------------
+// Renumbers nodes in BFS order from `source` (which becomes node 0) so nodes settled
+// close together in time also sit close together in `dist`/`offsets`; nodes BFS
+// cannot reach follow, in further BFS sweeps from the lowest unvisited id.
+// `perm[old] = new`, `inv_perm[new] = old`; each row keeps its original edge order
+// with relabeled targets. Distances are identical on the reordered graph: baseline
+// from `perm[source]` (= 0) gives `dist_new`, and `dist[inv_perm[i]] = dist_new[i]`
+// recovers the original labelling. On randomly numbered graphs the reordered run is
+// usually faster, since most relaxations then touch nearby cache lines. Outputs: perm
+// and inv_perm len n, out_offsets n+1, out_targets/out_weights m. The input is checked
+// with sssp_validate_csr first; -2 if source >= n, -3 if an output is null.
+#[no_mangle]
+pub extern "C" fn sssp_reorder_bfs(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    perm: *mut u32,
+    inv_perm: *mut u32,
+    out_offsets: *mut u32,
+    out_targets: *mut u32,
+    out_weights: *mut f32,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        let rc = crate::sssp_validate_csr(n, offsets, targets, weights, core::ptr::null_mut()); if rc != 0 { return rc; }
+        if source >= n { return -2; }
+        if perm.is_null() || inv_perm.is_null() || out_offsets.is_null() || out_targets.is_null() || out_weights.is_null() { return -3; }
+        let n_usize = n as usize;
+        let (perm, inv) = (as_mut_slice(perm, n_usize), as_mut_slice(inv_perm, n_usize));
+        perm.fill(u32::MAX);
+        // `inv[head..next]` is the BFS queue: labels are handed out in enqueue order.
+        let (mut head, mut next) = (0usize, 0usize);
+        for start in core::iter::once(source).chain(0..n) {
+            if perm[start as usize] != u32::MAX { continue; }
+            perm[start as usize] = next as u32; inv[next] = start; next += 1;
+            while head < next {
+                let u = inv[head] as usize; head += 1;
+                for &v in &tgt[off[u] as usize..off[u + 1] as usize] {
+                    if perm[v as usize] == u32::MAX { perm[v as usize] = next as u32; inv[next] = v; next += 1; }
+                }
+            }
+        }
+        let (o_off, o_tgt, o_wts) = (as_mut_slice(out_offsets, n_usize + 1), as_mut_slice(out_targets, tgt.len()), as_mut_slice(out_weights, tgt.len()));
+        o_off[0] = 0;
+        let mut k = 0usize;
+        for i in 0..n_usize {
+            let u = inv[i] as usize;
+            for e in off[u] as usize..off[u + 1] as usize { o_tgt[k] = perm[tgt[e] as usize]; o_wts[k] = wts[e]; k += 1; }
+            o_off[i + 1] = k as u32;
+        }
+        0
+    })
+}
+
+// Reverse-edge CSR (u->v,w becomes v->u,w); edges into each node keep ascending
+// source order. Shared by runners that need backward distances.
+pub(crate) fn transpose_csr(off: &[u32], tgt: &[u32], wts: &[f32]) -> (Vec<u32>, Vec<u32>, Vec<f32>) {
+    let n = off.len() - 1; let m = tgt.len();
+    let mut r_off = vec![0u32; n + 1];
+    for &v in tgt { r_off[v as usize + 1] += 1; }
+    for i in 0..n { r_off[i + 1] += r_off[i]; }
+    let mut fill = r_off.clone();
+    let mut r_tgt = vec![0u32; m]; let mut r_wts = vec![0f32; m];
+    for u in 0..n {
+        for e in off[u] as usize..off[u + 1] as usize { let v = tgt[e] as usize; let k = fill[v] as usize; r_tgt[k] = u as u32; r_wts[k] = wts[e]; fill[v] += 1; }
+    }
+    (r_off, r_tgt, r_wts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bfs_reorder_round_trips_and_keeps_distances(){
+        let n = 400u32;
+        let g = crate::gen::random_graph_in(n, 1.5, 0x510E527FADE682D1, 0.0, 14.0); let (off, tgt, wts) = (g.offsets, g.targets, g.weights);
+        let (m, nu, source) = (tgt.len(), n as usize, 17u32);
+        let (mut perm, mut inv) = (vec![0u32; nu], vec![0u32; nu]);
+        let (mut r_off, mut r_tgt, mut r_wts) = (vec![0u32; nu + 1], vec![0u32; m], vec![0f32; m]);
+        assert_eq!(sssp_reorder_bfs(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), source, perm.as_mut_ptr(), inv.as_mut_ptr(), r_off.as_mut_ptr(), r_tgt.as_mut_ptr(), r_wts.as_mut_ptr()), 0);
+        assert_eq!(perm[source as usize], 0);
+        for v in 0..nu { assert_eq!(inv[perm[v] as usize] as usize, v); }
+        for u in 0..nu { assert_eq!(r_off[perm[u] as usize + 1] - r_off[perm[u] as usize], off[u + 1] - off[u]); }
+        let (mut d, mut p, mut rd, mut rp) = (vec![0f32; nu], vec![0i32; nu], vec![0f32; nu], vec![0i32; nu]);
+        crate::sssp_run_baseline(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), source, d.as_mut_ptr(), p.as_mut_ptr(), core::ptr::null_mut());
+        crate::sssp_run_baseline(n, r_off.as_ptr(), r_tgt.as_ptr(), r_wts.as_ptr(), 0, rd.as_mut_ptr(), rp.as_mut_ptr(), core::ptr::null_mut());
+        for i in 0..nu { assert_eq!(d[inv[i] as usize].to_bits(), rd[i].to_bits()); }
+        // Reachable nodes are numbered before unreachable ones.
+        let reach = d.iter().filter(|x| x.is_finite()).count();
+        assert!(rd[..reach].iter().all(|x| x.is_finite()) && rd[reach..].iter().all(|x| x.is_infinite()));
+        assert_eq!(sssp_reorder_bfs(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), n, perm.as_mut_ptr(), inv.as_mut_ptr(), r_off.as_mut_ptr(), r_tgt.as_mut_ptr(), r_wts.as_mut_ptr()), -2);
+    }
+
+    #[test]
+    fn spanner_within_stretch_of_exact(){
+        // Complete directed graph on 12 nodes with varied weights: many edges are redundant.
+        let n = 12u32; let mut off = vec![0u32]; let mut tgt = Vec::new(); let mut wts = Vec::new();
+        for u in 0..n { for v in 0..n { if u != v { tgt.push(v); wts.push(1.0 + ((u * 7 + v * 13) % 10) as f32); } } off.push(tgt.len() as u32); }
+        let m = tgt.len();
+        let stretch = 2.0f32;
+        let mut s_off = vec![0u32; n as usize + 1]; let mut s_tgt = vec![0u32; m]; let mut s_wts = vec![0f32; m]; let mut s_m = 0u32;
+        let rc = sssp_build_spanner(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), stretch, s_off.as_mut_ptr(), s_tgt.as_mut_ptr(), s_wts.as_mut_ptr(), &mut s_m);
+        assert_eq!(rc, 0);
+        assert!((s_m as usize) < m);
+        assert_eq!(s_off[n as usize], s_m);
+        for s in 0..n {
+            let mut exact = vec![0f32; n as usize]; let mut sp = vec![0f32; n as usize]; let mut pred = vec![-1i32; n as usize];
+            crate::sssp_run_baseline(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), s, exact.as_mut_ptr(), pred.as_mut_ptr(), core::ptr::null_mut());
+            crate::sssp_run_baseline(n, s_off.as_ptr(), s_tgt.as_ptr(), s_wts.as_ptr(), s, sp.as_mut_ptr(), pred.as_mut_ptr(), core::ptr::null_mut());
+            for v in 0..n as usize { assert!(sp[v] >= exact[v] - 1e-5 && sp[v] <= stretch * exact[v] + 1e-4, "s={} v={} exact={} spanner={}", s, v, exact[v], sp[v]); }
+        }
+    }
+
+    #[test]
+    fn reachable_subgraph_drops_isolated_component(){
+        // Reachable from 1: {1,3,4} (1->3, 3->4, 4->1); component {0,2} (0->2, 2->0) is unreachable.
+        let off = [0u32,1,2,3,4,5]; let tgt = [2u32,3,0,4,1]; let wts = [5.0f32,1.0,5.0,2.0,3.0];
+        let mut map = [0i32;5]; let mut sub_n = 0u32; let mut sub_m = 0u32;
+        let mut o_off = [0u32;6]; let mut o_tgt = [0u32;5]; let mut o_wts = [0f32;5];
+        let rc = sssp_reachable_subgraph(5, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 1, map.as_mut_ptr(), &mut sub_n, o_off.as_mut_ptr(), o_tgt.as_mut_ptr(), o_wts.as_mut_ptr(), &mut sub_m);
+        assert_eq!(rc, 0);
+        assert_eq!(map, [-1, 0, -1, 1, 2]);
+        assert_eq!((sub_n, sub_m), (3, 3));
+        assert_eq!(&o_off[..4], &[0, 1, 2, 3]);
+        assert_eq!(&o_tgt[..3], &[1, 2, 0]);
+        assert_eq!(&o_wts[..3], &[1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn reachable_mask_counts_source_component(){
+        let off = [0u32,1,2,3,4,5]; let tgt = [2u32,3,0,4,1];
+        let mut mask = [7u8;5];
+        assert_eq!(sssp_reachable_mask(5, off.as_ptr(), tgt.as_ptr(), 1, mask.as_mut_ptr()), 3);
+        assert_eq!(mask, [0, 1, 0, 1, 1]);
+        // A 200k-node path is deeper than any recursive walk could go.
+        let n = 200_000u32; let off: Vec<u32> = (0..=n).map(|i| i.min(n - 1)).collect(); let tgt: Vec<u32> = (1..n).collect();
+        let mut mask = vec![0u8; n as usize];
+        assert_eq!(sssp_reachable_mask(n, off.as_ptr(), tgt.as_ptr(), 0, mask.as_mut_ptr()), n);
+        assert_eq!(sssp_reachable_mask(n, off.as_ptr(), tgt.as_ptr(), n, mask.as_mut_ptr()), 0);
+    }
+
+    #[test]
+    fn spanner_rejects_bad_stretch_and_weights(){
+        let off = [0u32,1,1]; let tgt = [1u32]; let wts = [1.0f32];
+        let mut o_off = [0u32;3]; let mut o_tgt = [0u32;1]; let mut o_wts = [0f32;1]; let mut o_m = 0u32;
+        assert_eq!(sssp_build_spanner(2, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0.5, o_off.as_mut_ptr(), o_tgt.as_mut_ptr(), o_wts.as_mut_ptr(), &mut o_m), -7);
+        for (w, want) in [(-1.0f32, -6), (f32::NAN, -7), (f32::INFINITY, -7)] {
+            assert_eq!(sssp_build_spanner(2, off.as_ptr(), tgt.as_ptr(), [w].as_ptr(), 2.0, o_off.as_mut_ptr(), o_tgt.as_mut_ptr(), o_wts.as_mut_ptr(), &mut o_m), want);
+        }
+    }
+
+    #[test]
+    fn merge_adds_shortcut_and_dedups_parallel_edges(){
+        // a: path 0->1->2->3 (weight 2 each); b: shortcut 0->3 (1) and a cheaper 1->2 (1).
+        let a_off = [0u32,1,2,3,3]; let a_tgt = [1u32,2,3]; let a_wts = [2.0f32,2.0,2.0];
+        let b_off = [0u32,1,2,2,2]; let b_tgt = [3u32,2]; let b_wts = [1.0f32,1.0];
+        let mut o_off = [0u32;5]; let mut o_tgt = [0u32;5]; let mut o_wts = [0f32;5]; let mut o_m = 0u32;
+        let merge = |dedup, o_off: &mut [u32;5], o_tgt: &mut [u32;5], o_wts: &mut [f32;5], o_m: &mut u32| sssp_merge_csr(4, a_off.as_ptr(), a_tgt.as_ptr(), a_wts.as_ptr(), b_off.as_ptr(), b_tgt.as_ptr(), b_wts.as_ptr(), o_off.as_mut_ptr(), o_tgt.as_mut_ptr(), o_wts.as_mut_ptr(), o_m, dedup);
+        assert_eq!(merge(false, &mut o_off, &mut o_tgt, &mut o_wts, &mut o_m), 0);
+        assert_eq!(o_m, 5);
+        assert_eq!(merge(true, &mut o_off, &mut o_tgt, &mut o_wts, &mut o_m), 0);
+        assert_eq!(o_m, 4);
+        assert_eq!(&o_off, &[0, 2, 3, 4, 4]);
+        assert_eq!(&o_tgt[..4], &[1, 3, 2, 3]);
+        assert_eq!(&o_wts[..4], &[2.0, 1.0, 1.0, 2.0]);
+        let mut dist = [0f32;4]; let mut pred = [0i32;4];
+        crate::sssp_run_baseline(4, o_off.as_ptr(), o_tgt.as_ptr(), o_wts.as_ptr(), 0, dist.as_mut_ptr(), pred.as_mut_ptr(), core::ptr::null_mut());
+        assert_eq!(dist, [0.0, 2.0, 3.0, 1.0]);
+    }
+
+    #[test]
+    fn zero_weight_cycle_becomes_one_super_node(){
+        // 0 -> 1 (2); zero-weight cycle 1->2->3->1; 3 -> 4 (0) one-way; 4 -> 1 (1) is not zero.
+        let off = [0u32,1,2,3,5,6]; let tgt = [1u32,2,3,1,4,1]; let wts = [2.0f32,0.0,0.0,0.0,0.0,1.0];
+        let mut sup = [0i32;5]; let mut num = 0u32;
+        assert_eq!(sssp_contract_zero_sccs(5, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), sup.as_mut_ptr(), &mut num), 0);
+        assert_eq!(num, 3);
+        assert_eq!(sup, [0, 1, 1, 1, 2]);
+    }
+
+    #[test]
+    fn coarsen_halves_grid_and_paths_lift(){
+        // Symmetric 6x6 grid with varied weights.
+        let mut g = crate::gen::grid_graph(6, 1.0); let n = g.n;
+        for u in 0..n { for e in g.offsets[u as usize] as usize..g.offsets[u as usize + 1] as usize { let v = g.targets[e]; g.weights[e] = 1.0 + ((u.min(v) * 7 + u.max(v) * 3) % 5) as f32; } }
+        let (off, tgt, wts) = (g.offsets, g.targets, g.weights);
+        let m = tgt.len();
+        let mut c_off = vec![0u32; n as usize + 1]; let mut c_tgt = vec![0u32; m]; let mut c_wts = vec![0f32; m];
+        let (mut cm, mut cn) = (0u32, 0u32); let mut map = vec![0u32; n as usize];
+        assert_eq!(sssp_coarsen(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), c_off.as_mut_ptr(), c_tgt.as_mut_ptr(), c_wts.as_mut_ptr(), &mut cm, &mut cn, map.as_mut_ptr(), 0), 0);
+        assert!(cn >= n / 2 && cn <= n * 3 / 4, "coarse n = {}", cn);
+        // Coarse distances lower-bound the originals.
+        let mut dist = vec![0f32; n as usize]; let mut pred = vec![0i32; n as usize];
+        let mut cdist = vec![0f32; cn as usize]; let mut cpred = vec![0i32; cn as usize];
+        crate::sssp_run_baseline(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, dist.as_mut_ptr(), pred.as_mut_ptr(), core::ptr::null_mut());
+        crate::sssp_run_baseline(cn, c_off.as_ptr(), c_tgt.as_ptr(), c_wts.as_ptr(), map[0], cdist.as_mut_ptr(), cpred.as_mut_ptr(), core::ptr::null_mut());
+        for v in 0..n as usize { assert!(cdist[map[v] as usize] <= dist[v] + 1e-5); }
+        // Lift the coarse path to the far corner: hop along original edges, crossing
+        // inside a merged pair via its (symmetric) matching edge when needed.
+        let goal = n - 1;
+        let mut chain = vec![map[goal as usize]]; while cpred[*chain.last().unwrap() as usize] >= 0 { chain.push(cpred[*chain.last().unwrap() as usize] as u32); } chain.reverse();
+        let edge = |a: u32, b: u32| (off[a as usize]..off[a as usize + 1]).any(|e| tgt[e as usize] == b);
+        let mut path = vec![0u32];
+        for w in chain.windows(2) {
+            let x = *path.last().unwrap();
+            let hop = |x: u32| (off[x as usize]..off[x as usize + 1]).map(|e| tgt[e as usize]).find(|&y| map[y as usize] == w[1]);
+            match hop(x) {
+                Some(y) => path.push(y),
+                None => { let p = (0..n).find(|&p| p != x && map[p as usize] == w[0]).unwrap(); path.push(p); path.push(hop(p).expect("coarse edge has an original witness")); }
+            }
+        }
+        if *path.last().unwrap() != goal { path.push(goal); }
+        for p in path.windows(2) { assert!(edge(p[0], p[1]), "lifted hop {}->{} is not an edge", p[0], p[1]); }
+    }
+
+    #[test]
+    fn prim_matches_hand_computed_total(){
+        // Undirected: 0-1 (4), 0-2 (1), 1-2 (2), 1-3 (5), 2-3 (8), 3-4 (3); isolated 5.
+        // MST: 0-2 (1) + 2-1 (2) + 1-3 (5) + 3-4 (3) = 11.
+        let und = [(0u32,1u32,4.0f32),(0,2,1.0),(1,2,2.0),(1,3,5.0),(2,3,8.0),(3,4,3.0)];
+        let n = 6usize; let mut adj: Vec<Vec<(u32,f32)>> = vec![Vec::new(); n];
+        for &(u,v,w) in &und { adj[u as usize].push((v,w)); adj[v as usize].push((u,w)); }
+        let mut off = vec![0u32]; let mut tgt = Vec::new(); let mut wts = Vec::new();
+        for a in &adj { for &(v,w) in a { tgt.push(v); wts.push(w); } off.push(tgt.len() as u32); }
+        let mut parent = [0i32;6]; let mut total = 0f64;
+        assert_eq!(sssp_mst_prim(6, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), parent.as_mut_ptr(), &mut total), 0);
+        assert_eq!(total, 11.0);
+        assert_eq!(parent, [-1, 2, 0, 1, 3, -1]);
+    }
+
+    #[test]
+    fn symmetrize_adds_reverse_edges_and_keeps_min_weight(){
+        // 0->1 (3) and 1->0 (2) collapse to 2 both ways; 1->2 (1) twice is deduplicated;
+        // 2->3 (4) gains 3->2; self-loop 3->3 stays a single edge.
+        let off = [0u32,1,4,5,6]; let tgt = [1u32,0,2,2,3,3]; let wts = [3.0f32,2.0,1.0,1.0,4.0,5.0];
+        let none = core::ptr::null_mut();
+        let m = sssp_symmetrize_csr(4, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), none, none, core::ptr::null_mut(), 0);
+        assert_eq!(m, 7);
+        let mut o_off = [0u32;5]; let mut o_tgt = [0u32;7]; let mut o_wts = [0f32;7];
+        assert_eq!(sssp_symmetrize_csr(4, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), o_off.as_mut_ptr(), o_tgt.as_mut_ptr(), o_wts.as_mut_ptr(), 6), -2);
+        assert_eq!(sssp_symmetrize_csr(4, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), o_off.as_mut_ptr(), o_tgt.as_mut_ptr(), o_wts.as_mut_ptr(), 7), 7);
+        assert_eq!(o_off, [0, 1, 3, 5, 7]);
+        assert_eq!(o_tgt, [1, 0, 2, 3, 1, 3, 2]);
+        assert_eq!(o_wts, [2.0, 2.0, 1.0, 4.0, 1.0, 5.0, 4.0]);
+    }
+}
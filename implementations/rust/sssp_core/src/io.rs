@@ -0,0 +1,295 @@
+//! Graph files: a DIMACS `.gr` reader for published datasets, plus binary CSR files
+//! and a baseline runner that reads them in place. Binary layout
+//! (little-endian, 4-byte words):
+//!   magic "SSSPCSR1" | n: u32 | m: u32 | offsets[n+1]: u32 | targets[m]: u32 | weights[m]: f32
+//! The 16-byte header keeps every array word-aligned relative to the start of the
+//! file, and mappings start on a page boundary, so mapped words are borrowed as-is.
+
+use std::ffi::CStr;
+use std::io::{self, BufRead};
+use std::os::raw::c_char;
+use std::path::Path;
+
+use crate::{csr_slices, SsspResultInfo};
+
+const CSR_MAGIC: &[u8; 8] = b"SSSPCSR1";
+const CSR_HEADER_WORDS: usize = 4;
+
+fn path_arg<'a>(path: *const c_char) -> Result<&'a str, i32> {
+    if path.is_null() { return Err(-3); }
+    unsafe { CStr::from_ptr(path) }.to_str().map_err(|_| -7)
+}
+
+// Writes a CSR graph in the layout above. Returns -8 on an I/O failure.
+#[no_mangle]
+pub extern "C" fn sssp_write_csr(
+    path: *const c_char,
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        let path = match path_arg(path) { Ok(p) => p, Err(rc) => return rc };
+        let mut buf: Vec<u8> = Vec::with_capacity(4 * (CSR_HEADER_WORDS + off.len() + 2 * tgt.len()));
+        buf.extend_from_slice(CSR_MAGIC);
+        buf.extend_from_slice(&n.to_le_bytes()); buf.extend_from_slice(&(tgt.len() as u32).to_le_bytes());
+        for &o in off { buf.extend_from_slice(&o.to_le_bytes()); }
+        for &t in tgt { buf.extend_from_slice(&t.to_le_bytes()); }
+        for &w in wts { buf.extend_from_slice(&w.to_le_bytes()); }
+        match std::fs::write(path, &buf) { Ok(()) => 0, Err(_) => -8 }
+    })
+}
+
+// `sssp_write_csr` under the name that pairs with `sssp_load_csr`.
+#[no_mangle]
+pub extern "C" fn sssp_save_csr(path: *const c_char, n: u32, offsets: *const u32, targets: *const u32, weights: *const f32) -> i32 {
+    sssp_write_csr(path, n, offsets, targets, weights)
+}
+
+// Loads a file written by `sssp_save_csr` into caller buffers: `out_offsets` holds
+// offsets_cap words (>= n+1), `out_targets`/`out_weights` edges_cap entries (>= m).
+// `out_n`/`out_m` always receive the header sizes, so null output arrays with zero
+// caps make a sizing query. Errors: -8 unreadable file, -4 wrong magic or malformed
+// contents, -10 truncated (shorter than its header declares), -2 buffers too small.
+#[no_mangle]
+pub extern "C" fn sssp_load_csr(
+    path: *const c_char,
+    out_n: *mut u32,
+    out_m: *mut u32,
+    out_offsets: *mut u32,
+    offsets_cap: u32,
+    out_targets: *mut u32,
+    out_weights: *mut f32,
+    edges_cap: u32,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let path = match path_arg(path) { Ok(p) => p, Err(rc) => return rc };
+        if out_n.is_null() || out_m.is_null() { return -3; }
+        let bytes = match std::fs::read(path) { Ok(b) => b, Err(_) => return -8 };
+        let magic_len = bytes.len().min(CSR_MAGIC.len());
+        if bytes[..magic_len] != CSR_MAGIC[..magic_len] { return -4; }
+        if bytes.len() < 4 * CSR_HEADER_WORDS { return -10; }
+        let word = |i: usize| u32::from_le_bytes([bytes[4 * i], bytes[4 * i + 1], bytes[4 * i + 2], bytes[4 * i + 3]]);
+        let (n, m) = (word(2), word(3));
+        let want = 4 * (CSR_HEADER_WORDS as u64 + n as u64 + 1 + 2 * m as u64);
+        if (bytes.len() as u64) < want { return -10; }
+        if bytes.len() as u64 != want { return -4; }
+        let words: Vec<u32> = (0..bytes.len() / 4).map(word).collect();
+        let (_, off, tgt, wts) = match csr_parts(&words) { Ok(p) => p, Err(rc) => return rc };
+        unsafe { *out_n = n; *out_m = m; }
+        if out_offsets.is_null() && out_targets.is_null() && out_weights.is_null() && offsets_cap == 0 && edges_cap == 0 { return 0; }
+        if out_offsets.is_null() || out_targets.is_null() || out_weights.is_null() { return -3; }
+        if (offsets_cap as u64) < n as u64 + 1 || edges_cap < m { return -2; }
+        crate::as_mut_slice(out_offsets, off.len()).copy_from_slice(off);
+        crate::as_mut_slice(out_targets, tgt.len()).copy_from_slice(tgt);
+        crate::as_mut_slice(out_weights, wts.len()).copy_from_slice(wts);
+        0
+    })
+}
+
+// Read-only words of a CSR file. On little-endian unix this is a private PROT_READ
+// mapping: pages fault in on demand and nothing is copied; the mapping outlives the
+// closed fd and is unmapped on drop, so borrowed slices cannot dangle. Elsewhere the
+// file is decoded into memory instead.
+#[cfg(all(unix, target_endian = "little"))]
+struct CsrWords { ptr: *mut libc::c_void, len: usize }
+
+#[cfg(all(unix, target_endian = "little"))]
+impl CsrWords {
+    fn open(path: &str) -> Result<Self, i32> {
+        use std::os::unix::io::AsRawFd;
+        let file = std::fs::File::open(path).map_err(|_| -8)?;
+        let len = file.metadata().map_err(|_| -8)?.len() as usize;
+        if len < 4 * CSR_HEADER_WORDS || !len.is_multiple_of(4) { return Err(-4); }
+        let ptr = unsafe { libc::mmap(core::ptr::null_mut(), len, libc::PROT_READ, libc::MAP_PRIVATE, file.as_raw_fd(), 0) };
+        if ptr == libc::MAP_FAILED { return Err(-8); }
+        Ok(CsrWords { ptr, len })
+    }
+    fn words(&self) -> &[u32] { unsafe { core::slice::from_raw_parts(self.ptr as *const u32, self.len / 4) } }
+}
+
+#[cfg(all(unix, target_endian = "little"))]
+impl Drop for CsrWords {
+    fn drop(&mut self) { unsafe { libc::munmap(self.ptr, self.len); } }
+}
+
+#[cfg(not(all(unix, target_endian = "little")))]
+struct CsrWords(Vec<u32>);
+
+#[cfg(not(all(unix, target_endian = "little")))]
+impl CsrWords {
+    fn open(path: &str) -> Result<Self, i32> {
+        let bytes = std::fs::read(path).map_err(|_| -8)?;
+        if bytes.len() < 4 * CSR_HEADER_WORDS || !bytes.len().is_multiple_of(4) { return Err(-4); }
+        Ok(CsrWords(bytes.chunks_exact(4).map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]])).collect()))
+    }
+    fn words(&self) -> &[u32] { &self.0 }
+}
+
+// (n, offsets, targets, weights) borrowed from a file's words.
+type CsrParts<'a> = (u32, &'a [u32], &'a [u32], &'a [f32]);
+
+// Splits file words into CSR arrays, checking magic, sizes, monotone offsets and
+// in-range targets: file contents are untrusted, so anything off returns -4 up front
+// rather than indexing out of bounds mid-run.
+fn csr_parts(w: &[u32]) -> Result<CsrParts<'_>, i32> {
+    if w[0].to_le_bytes() != CSR_MAGIC[..4] || w[1].to_le_bytes() != CSR_MAGIC[4..] { return Err(-4); }
+    let (n, m) = (w[2] as usize, w[3] as usize);
+    if (w.len() - CSR_HEADER_WORDS) as u64 != n as u64 + 1 + 2 * m as u64 { return Err(-4); }
+    let off = &w[CSR_HEADER_WORDS..CSR_HEADER_WORDS + n + 1];
+    let tgt = &w[CSR_HEADER_WORDS + n + 1..CSR_HEADER_WORDS + n + 1 + m];
+    let raw_w = &w[CSR_HEADER_WORDS + n + 1 + m..];
+    if off[0] != 0 || off[n] as usize != m || off.windows(2).any(|p| p[0] > p[1]) { return Err(-4); }
+    if tgt.iter().any(|&t| t as usize >= n) { return Err(-4); }
+    let wts = unsafe { core::slice::from_raw_parts(raw_w.as_ptr() as *const f32, m) };
+    Ok((n as u32, off, tgt, wts))
+}
+
+// Baseline Dijkstra over a CSR file written by `sssp_write_csr`, run directly on the
+// read-only mapped arrays (no copy of the graph; only dist/pred/heap are allocated).
+// Returns -8 if the file cannot be opened or mapped and -4 if it is malformed.
+#[no_mangle]
+pub extern "C" fn sssp_run_baseline_mmap(
+    path: *const c_char,
+    source: u32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let path = match path_arg(path) { Ok(p) => p, Err(rc) => return rc };
+        let file = match CsrWords::open(path) { Ok(f) => f, Err(rc) => return rc };
+        let (n, off, tgt, wts) = match csr_parts(file.words()) { Ok(p) => p, Err(rc) => return rc };
+        crate::baseline_run(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), source, out_dist, out_pred, info, core::ptr::null_mut(), core::ptr::null_mut(), crate::RunOpts::checked())
+    })
+}
+
+// Owned CSR arrays in the layout every runner takes (`offsets` len n+1).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CsrGraph { pub n: u32, pub offsets: Vec<u32>, pub targets: Vec<u32>, pub weights: Vec<f32> }
+
+fn dimacs_err(line: usize, msg: &str) -> io::Error { io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", line, msg)) }
+
+// Reads a DIMACS shortest-path file: `c` comment lines, one `p sp n m` header, then
+// m `a u v w` arcs with 1-based node ids (converted to 0-based). Arcs keep their file
+// order within each source node. Missing or repeated headers, ids outside 1..=n, an
+// arc count other than m, non-finite weights, and negative weights (unless
+// `allow_negative`) are InvalidData errors. The header is untrusted: m must fit
+// the u32 offsets and the file's size (an arc line takes at least 8 bytes), and a
+// node count whose arrays cannot be allocated is rejected rather than aborting.
+pub fn load_dimacs_gr(path: &Path, allow_negative: bool) -> io::Result<CsrGraph> {
+    let file = std::fs::File::open(path)?;
+    let max_arcs = (file.metadata()?.len().saturating_add(1) / 8) as usize;
+    let reader = io::BufReader::new(file);
+    let mut header: Option<(u32, usize)> = None;
+    let mut arcs: Vec<(u32, u32, f32)> = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?; let ln = i + 1;
+        let mut it = line.split_whitespace();
+        match it.next() {
+            None | Some("c") => {}
+            Some("p") => {
+                if header.is_some() { return Err(dimacs_err(ln, "repeated problem line")); }
+                if it.next() != Some("sp") { return Err(dimacs_err(ln, "expected `p sp n m`")); }
+                let n = it.next().and_then(|t| t.parse::<u32>().ok()).ok_or_else(|| dimacs_err(ln, "bad node count"))?;
+                let m = it.next().and_then(|t| t.parse::<u32>().ok()).ok_or_else(|| dimacs_err(ln, "bad arc count"))? as usize;
+                if m > max_arcs { return Err(dimacs_err(ln, &format!("header declares {} arcs, more than the file can hold", m))); }
+                arcs.reserve(m); header = Some((n, m));
+            }
+            Some("a") => {
+                let (n, _) = header.ok_or_else(|| dimacs_err(ln, "arc before problem line"))?;
+                let mut id = || it.next().and_then(|t| t.parse::<u32>().ok()).filter(|&v| v >= 1 && v <= n).ok_or_else(|| dimacs_err(ln, "node id out of range"));
+                let (u, v) = (id()?, id()?);
+                let w = it.next().and_then(|t| t.parse::<f32>().ok()).filter(|w| w.is_finite()).ok_or_else(|| dimacs_err(ln, "bad weight"))?;
+                if w < 0.0 && !allow_negative { return Err(dimacs_err(ln, "negative weight")); }
+                arcs.push((u - 1, v - 1, w));
+            }
+            Some(_) => return Err(dimacs_err(ln, "unknown line type")),
+        }
+    }
+    let (n, m) = header.ok_or_else(|| dimacs_err(0, "missing problem line"))?;
+    if arcs.len() != m { return Err(dimacs_err(0, &format!("header declares {} arcs, found {}", m, arcs.len()))); }
+    // Counting sort by source keeps per-node file order.
+    let mut offsets: Vec<u32> = Vec::new();
+    if offsets.try_reserve_exact(n as usize + 1).is_err() { return Err(dimacs_err(0, &format!("node count {} too large", n))); }
+    offsets.resize(n as usize + 1, 0);
+    for &(u, _, _) in &arcs { offsets[u as usize + 1] += 1; }
+    for i in 0..n as usize { offsets[i + 1] += offsets[i]; }
+    let mut fill = offsets.clone();
+    let (mut targets, mut weights) = (vec![0u32; m], vec![0f32; m]);
+    for &(u, v, w) in &arcs { let slot = fill[u as usize] as usize; targets[slot] = v; weights[slot] = w; fill[u as usize] += 1; }
+    Ok(CsrGraph { n, offsets, targets, weights })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+
+    #[test]
+    fn mmap_run_matches_in_memory_baseline(){
+        let off = [0u32,2,4,5,6,6]; let tgt = [1u32,2,3,2,4,4]; let wts = [0.5f32,4.0,1.5,2.0,1.0,3.0];
+        let file = std::env::temp_dir().join(format!("sssp_csr_{}.bin", std::process::id()));
+        let path = CString::new(file.to_str().unwrap()).unwrap();
+        assert_eq!(sssp_write_csr(path.as_ptr(), 5, off.as_ptr(), tgt.as_ptr(), wts.as_ptr()), 0);
+        let (mut d1, mut p1) = ([0f32;5], [0i32;5]); let (mut d2, mut p2) = ([0f32;5], [0i32;5]);
+        let empty = || SsspResultInfo { relaxations:0, light_relaxations:0, heavy_relaxations:0, settled:0, error_code:0, overflow:0, reachable:0, edges:0 };
+        let (mut i1, mut i2) = (empty(), empty());
+        assert_eq!(sssp_run_baseline_mmap(path.as_ptr(), 0, d1.as_mut_ptr(), p1.as_mut_ptr(), &mut i1), 0);
+        assert_eq!(crate::sssp_run_baseline(5, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d2.as_mut_ptr(), p2.as_mut_ptr(), &mut i2), 0);
+        assert_eq!((d1, p1, i1.relaxations, i1.settled), (d2, p2, i2.relaxations, i2.settled));
+        assert_eq!(sssp_run_baseline_mmap(path.as_ptr(), 5, d1.as_mut_ptr(), p1.as_mut_ptr(), &mut i1), -2);
+        // A truncated file is rejected, not read past its end.
+        let bytes = std::fs::read(&file).unwrap();
+        std::fs::write(&file, &bytes[..bytes.len() - 4]).unwrap();
+        assert_eq!(sssp_run_baseline_mmap(path.as_ptr(), 0, d1.as_mut_ptr(), p1.as_mut_ptr(), &mut i1), -4);
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(sssp_run_baseline_mmap(path.as_ptr(), 0, d1.as_mut_ptr(), p1.as_mut_ptr(), &mut i1), -8);
+    }
+
+    #[test]
+    fn dimacs_reader_builds_zero_based_csr(){
+        let file = std::env::temp_dir().join(format!("sssp_dimacs_{}.gr", std::process::id()));
+        let write = |body: &str| std::fs::write(&file, body).unwrap();
+        write("c tiny example\np sp 3 4\na 2 3 1.5\na 1 2 2\nc mid-file comment\na 1 3 7\n\na 3 1 4\n");
+        let g = load_dimacs_gr(&file, false).unwrap();
+        assert_eq!(g, CsrGraph { n: 3, offsets: vec![0, 2, 3, 4], targets: vec![1, 2, 2, 0], weights: vec![2.0, 7.0, 1.5, 4.0] });
+        write("p sp 2 1\na 1 2 -3\n");
+        assert_eq!(load_dimacs_gr(&file, false).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert_eq!(load_dimacs_gr(&file, true).unwrap().weights, vec![-3.0]);
+        // Arc counts past u32 offsets or the file size are rejected before allocating.
+        for bad in ["p sp 2 1\na 1 3 1\n", "a 1 2 1\np sp 2 1\n", "p sp 2 2\na 1 2 1\n", "p sp 2 4294967296\n", "p sp 2 4000000000\na 1 2 1\n", "p sp 2 1\na 1 2 nan\n", "p sp 2 1\na 1 2 inf\n", "p sp 2 1\na 1 2 -inf\n"] {
+            write(bad);
+            assert_eq!(load_dimacs_gr(&file, false).unwrap_err().kind(), io::ErrorKind::InvalidData, "{:?}", bad);
+        }
+        std::fs::remove_file(&file).unwrap();
+    }
+
+    #[test]
+    fn save_load_round_trip_and_truncation(){
+        let off = [0u32,2,3,3]; let tgt = [1u32,2,0]; let wts = [0.25f32,9.5,1.0];
+        let file = std::env::temp_dir().join(format!("sssp_csr_rt_{}.bin", std::process::id()));
+        let path = CString::new(file.to_str().unwrap()).unwrap();
+        assert_eq!(sssp_save_csr(path.as_ptr(), 3, off.as_ptr(), tgt.as_ptr(), wts.as_ptr()), 0);
+        let (mut n, mut m) = (0u32, 0u32);
+        let null = (core::ptr::null_mut(), core::ptr::null_mut(), core::ptr::null_mut());
+        assert_eq!(sssp_load_csr(path.as_ptr(), &mut n, &mut m, null.0, 0, null.1, null.2, 0), 0);
+        assert_eq!((n, m), (3, 3));
+        let (mut o, mut t, mut w) = ([0u32; 4], [0u32; 3], [0f32; 3]);
+        assert_eq!(sssp_load_csr(path.as_ptr(), &mut n, &mut m, o.as_mut_ptr(), 3, t.as_mut_ptr(), w.as_mut_ptr(), 3), -2);
+        assert_eq!(sssp_load_csr(path.as_ptr(), &mut n, &mut m, o.as_mut_ptr(), 4, t.as_mut_ptr(), w.as_mut_ptr(), 3), 0);
+        assert_eq!((o, t, w), (off, tgt, wts));
+        // Truncated anywhere (mid-header or mid-arrays) is -10; a foreign file is -4.
+        let bytes = std::fs::read(&file).unwrap();
+        for cut in [12, bytes.len() - 4] {
+            std::fs::write(&file, &bytes[..cut]).unwrap();
+            assert_eq!(sssp_load_csr(path.as_ptr(), &mut n, &mut m, o.as_mut_ptr(), 4, t.as_mut_ptr(), w.as_mut_ptr(), 3), -10);
+        }
+        std::fs::write(&file, b"not a csr file at all").unwrap();
+        assert_eq!(sssp_load_csr(path.as_ptr(), &mut n, &mut m, o.as_mut_ptr(), 4, t.as_mut_ptr(), w.as_mut_ptr(), 3), -4);
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(sssp_load_csr(path.as_ptr(), &mut n, &mut m, o.as_mut_ptr(), 4, t.as_mut_ptr(), w.as_mut_ptr(), 3), -8);
+    }
+}
@@ -5,7 +5,10 @@
 //! All other experimental variants have been removed per simplification.
 
 use core::slice;
+use std::collections::BTreeMap;
 
+// Mirrored field for field by the bindings (wrappers/csharp/RustSssp.cs,
+// wrappers/go/sssp.go, implementations/python/rust_sssp.py): change them together.
 #[repr(C)]
 pub struct SsspResultInfo {
     pub relaxations: u64,          // total relax operations
@@ -13,6 +16,17 @@ pub struct SsspResultInfo {
     pub heavy_relaxations: u64,     // heavy-edge relaxations (delta-stepping)
     pub settled: u32,               // nodes settled (visited)
     pub error_code: i32,            // 0 == success
+    pub overflow: u32,              // 1 if any u32 counter behind this run saturated (values are lower bounds)
+    pub reachable: u32,             // nodes left with a finite distance (0 when the run fails)
+    pub edges: u64,                 // input edge count m = offsets[n]
+}
+
+// Saturating u32 counter bump: pins at u32::MAX and raises `overflow` instead of wrapping.
+// Relaxation totals are u64 and `settled` is bounded by n; this covers the per-run u32 tallies
+// (bucket visits, light-pass repeats, phase-3 pulls/pushes/batches) that can exceed 2^32 on huge inputs.
+#[inline(always)]
+pub(crate) fn sat_bump(counter: &mut u32, overflow: &mut bool) {
+    match counter.checked_add(1) { Some(v) => *counter = v, None => *overflow = true }
 }
 
 // Baseline heap instrumentation
@@ -20,7 +34,7 @@ pub struct SsspResultInfo {
 pub struct BaselineHeapStats { pub pushes: u64, pub pops: u64, pub max_size: u64 }
 impl Copy for BaselineHeapStats {}
 impl Clone for BaselineHeapStats { fn clone(&self) -> Self { *self } }
-static mut LAST_BASELINE_HEAP_STATS: BaselineHeapStats = BaselineHeapStats { pushes:0, pops:0, max_size:0 };
+thread_local! { static LAST_BASELINE_HEAP_STATS: core::cell::Cell<BaselineHeapStats> = const { core::cell::Cell::new(BaselineHeapStats { pushes:0, pops:0, max_size:0 }) }; }
 
 // Additional global instrumentation for delta-stepping (light/heavy) to correlate scaling behavior.
 // Updated on each STOC / autotune final full run.
@@ -32,29 +46,118 @@ pub struct SsspBucketStats {
     pub restarts: u32,              // adaptive restarts performed (delta adjustments)
     pub delta_x1000: u32,           // final delta * 1000 (for quick inspection)
     pub heavy_ratio_x1000: u32,     // (heavy_relax / total_relax) * 1000
+    pub peak_live_buckets: u32,     // most non-empty buckets held at once (ring + far map)
 }
 
 impl Copy for SsspBucketStats {}
 impl Clone for SsspBucketStats { fn clone(&self) -> Self { *self } }
 
-static mut LAST_BUCKET_STATS: SsspBucketStats = SsspBucketStats { buckets_visited: 0, light_pass_repeats: 0, max_bucket_index: 0, restarts: 0, delta_x1000: 0, heavy_ratio_x1000: 0 };
-static mut LAST_DELTA: f32 = 0.0;
+thread_local! {
+    static LAST_BUCKET_STATS: core::cell::Cell<SsspBucketStats> = const { core::cell::Cell::new(SsspBucketStats { buckets_visited: 0, light_pass_repeats: 0, max_bucket_index: 0, restarts: 0, delta_x1000: 0, heavy_ratio_x1000: 0, peak_live_buckets: 0 }) };
+    static LAST_DELTA: core::cell::Cell<f32> = const { core::cell::Cell::new(0.0) };
+}
+// Largest priority-queue occupancy of the last heap or spec run on this thread (see
+// sssp_get_last_peak_queue).
+thread_local! { static LAST_PEAK_QUEUE: core::cell::Cell<u64> = const { core::cell::Cell::new(0) }; }
+
+// Wall-clock breakdown filled by the `_timed` runners: array initialization, the
+// main relaxation loop (including any adaptive restarts), and output/statistics.
+// Clock reads need the `std` feature; without it every phase reports 0.
+#[repr(C)]
+#[derive(Clone, Copy, Default, Debug)]
+pub struct SsspTiming { pub init_ns: u64, pub loop_ns: u64, pub finalize_ns: u64 }
+
+#[cfg(feature = "std")]
+type Tick = std::time::Instant;
+// Stand-in clock without `std`: every lap is zero.
+#[cfg(not(feature = "std"))]
+#[derive(Clone, Copy)]
+struct Tick;
+#[cfg(not(feature = "std"))]
+impl Tick {
+    fn now() -> Self { Tick }
+    fn duration_since(self, _earlier: Tick) -> core::time::Duration { core::time::Duration::ZERO }
+}
+
+// Phase clock for SsspTiming; inert (no clock reads) when `out` is null.
+struct PhaseClock { out: *mut SsspTiming, last: Option<Tick> }
+impl PhaseClock {
+    fn start(out: *mut SsspTiming) -> Self { PhaseClock { out, last: if out.is_null() { None } else { Some(Tick::now()) } } }
+    fn lap(&mut self) -> u64 { match self.last { Some(t) => { let now = Tick::now(); self.last = Some(now); now.duration_since(t).as_nanos() as u64 } None => 0 } }
+    fn init_done(&mut self) { let ns = self.lap(); if !self.out.is_null() { unsafe { (*self.out).init_ns = ns; } } }
+    fn loop_done(&mut self) { let ns = self.lap(); if !self.out.is_null() { unsafe { (*self.out).loop_ns = ns; } } }
+    fn finalize_done(&mut self) { let ns = self.lap(); if !self.out.is_null() { unsafe { (*self.out).finalize_ns = ns; } } }
+}
 
+// Deprecated compatibility getters for the thread-locals above. They report the last
+// run on the calling thread (last call wins), so a caller that runs on one thread and
+// reads on another sees stale values. Prefer the `_stats` runner variants, which
+// write into caller-owned structs.
 #[no_mangle]
 pub extern "C" fn sssp_get_bucket_stats(out: *mut SsspBucketStats) {
     if out.is_null() { return; }
-    unsafe { *out = LAST_BUCKET_STATS; }
+    unsafe { *out = LAST_BUCKET_STATS.with(|s| s.get()); }
 }
 
 #[no_mangle]
-pub extern "C" fn sssp_get_last_delta() -> f32 { unsafe { LAST_DELTA } }
+pub extern "C" fn sssp_get_last_delta() -> f32 { LAST_DELTA.with(|d| d.get()) }
 
 #[no_mangle]
 pub extern "C" fn sssp_get_baseline_heap_stats(out: *mut BaselineHeapStats) {
     if out.is_null() { return; }
-    unsafe { *out = LAST_BASELINE_HEAP_STATS; }
+    unsafe { *out = LAST_BASELINE_HEAP_STATS.with(|s| s.get()); }
 }
 
+// Records a baseline run's heap counters: into `out` when non-null, and always into
+// the deprecated thread-local behind `sssp_get_baseline_heap_stats` (plus its peak queue).
+pub(crate) fn publish_heap_stats(stats: BaselineHeapStats, out: *mut BaselineHeapStats) {
+    LAST_BASELINE_HEAP_STATS.with(|s| s.set(stats));
+    if !out.is_null() { unsafe { *out = stats; } }
+    publish_peak_queue(stats.max_size);
+}
+
+pub(crate) fn publish_peak_queue(peak: u64) { LAST_PEAK_QUEUE.with(|p| p.set(peak)); }
+
+// Peak queue size of the last run that reports one, in entries: the heap's max_size
+// for the baseline family (binary, d-ary, indexed, f64, ...), the largest
+// DataStructureD occupancy for sssp_run_spec_phase3 and the largest heap across
+// segments for sssp_run_spec_boundary_chain. Per thread like the getters above, so it
+// reports the caller's own last run.
+#[no_mangle]
+pub extern "C" fn sssp_get_last_peak_queue() -> u64 { LAST_PEAK_QUEUE.with(|p| p.get()) }
+
+// SSSP_BASELINE_CHECK=1 (debug, like SSSP_SPEC_CHECK; read once per process): the
+// baseline heap loop checks that accepted pops come out in non-decreasing distance
+// order, Dijkstra's core invariant. Any failure points at a heap bug or a negative
+// weight slipping past the pre-scan. The counters cover this thread's last checked
+// baseline run.
+thread_local! { static LAST_BASELINE_INV_STATS: core::cell::Cell<SpecInvariantStats> = const { core::cell::Cell::new(SpecInvariantStats { checks: 0, failures: 0 }) }; }
+
+fn baseline_check_enabled() -> bool {
+    static ON: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+    *ON.get_or_init(|| std::env::var("SSSP_BASELINE_CHECK").map(|v| v == "1" || v.eq_ignore_ascii_case("true")).unwrap_or(false))
+}
+
+#[no_mangle]
+pub extern "C" fn sssp_get_baseline_invariant_stats(out: *mut SpecInvariantStats) {
+    if out.is_null() { return; }
+    unsafe { *out = LAST_BASELINE_INV_STATS.with(|s| s.get()); }
+}
+
+// Panic firewall for the C ABI: unwinding out of an `extern "C"` fn is undefined
+// behavior, so every entry returning a status runs its body here and maps a panic
+// (e.g. an out-of-bounds index on malformed CSR) to `on_panic` (-99 for i32
+// entries). Needs the `std` feature (default) and an unwinding panic strategy;
+// without `std` the body runs unguarded.
+#[cfg(feature = "std")]
+#[inline(always)]
+fn ffi_guard<R, F: FnOnce() -> R>(on_panic: R, body: F) -> R {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(body)).unwrap_or(on_panic)
+}
+#[cfg(not(feature = "std"))]
+#[inline(always)]
+fn ffi_guard<R, F: FnOnce() -> R>(_on_panic: R, body: F) -> R { body() }
+
 #[inline(always)]
 fn as_slice<'a, T>(ptr: *const T, len: usize) -> &'a [T] {
     unsafe { slice::from_raw_parts(ptr, len) }
@@ -64,15 +167,146 @@ fn as_mut_slice<'a, T>(ptr: *mut T, len: usize) -> &'a mut [T] {
     unsafe { slice::from_raw_parts_mut(ptr, len) }
 }
 
+// CSR offset element: u32 for the standard entry points, u64 for the `*64` ones
+// (graphs past u32::MAX edges). Edge indices are always handled as usize.
+trait CsrOffset: Copy { fn idx(self) -> usize; }
+impl CsrOffset for u32 { #[inline(always)] fn idx(self) -> usize { self as usize } }
+// On 32-bit targets u64 offsets can exceed usize; the `*64` runners reject that (-4).
+impl CsrOffset for u64 { #[inline(always)] fn idx(self) -> usize { self as usize } }
+
+// Edge weight / distance scalar for code shared between precisions: f32 is the
+// native type of the C ABI, f64 backs the `*_f64` runners and i64 the exact
+// integer runner (i32 weights summed in i64, unreached = i64::MAX).
+trait Weight: Copy + PartialOrd + core::ops::Add<Output = Self> + core::ops::Div<Output = Self> {
+    const ZERO: Self;
+    const INFINITY: Self;
+    fn is_finite(self) -> bool;
+    fn to_f64(self) -> f64;
+    // Delta-stepping bucket width from a real-valued estimate, clamped to what the
+    // type can bucket: [1e-4, 1e6] for f32, [1e-4, 1e12] for f64, rounded and >= 1 for i64.
+    fn delta_from(x: f64) -> Self;
+    // Bucket index of distance `self` for width `delta`; `inv_delta` is 1 / delta,
+    // computed once per attempt. Exact `self / delta` for i64.
+    fn bucket(self, delta: Self, inv_delta: f64) -> usize;
+}
+impl Weight for f32 {
+    const ZERO: Self = 0.0; const INFINITY: Self = f32::INFINITY;
+    #[inline(always)] fn is_finite(self) -> bool { f32::is_finite(self) }
+    #[inline(always)] fn to_f64(self) -> f64 { self as f64 }
+    #[inline(always)] fn delta_from(x: f64) -> Self { (x as f32).clamp(1e-4, 1e6) }
+    #[inline(always)] fn bucket(self, _delta: Self, inv_delta: f64) -> usize { (self * inv_delta as f32) as usize }
+}
+impl Weight for f64 {
+    const ZERO: Self = 0.0; const INFINITY: Self = f64::INFINITY;
+    #[inline(always)] fn is_finite(self) -> bool { f64::is_finite(self) }
+    #[inline(always)] fn to_f64(self) -> f64 { self }
+    #[inline(always)] fn delta_from(x: f64) -> Self { x.clamp(1e-4, 1e12) }
+    #[inline(always)] fn bucket(self, _delta: Self, inv_delta: f64) -> usize { (self * inv_delta) as usize }
+}
+impl Weight for i64 {
+    const ZERO: Self = 0; const INFINITY: Self = i64::MAX;
+    #[inline(always)] fn is_finite(self) -> bool { self != i64::MAX }
+    #[inline(always)] fn to_f64(self) -> f64 { self as f64 }
+    #[inline(always)] fn delta_from(x: f64) -> Self { (x.round() as i64).max(1) }
+    #[inline(always)] fn bucket(self, delta: Self, _inv_delta: f64) -> usize { (self / delta) as usize }
+}
+
+// Shared CSR entry checks for the auxiliary runners (same codes as the main runners:
+// -1 empty graph, -3 null pointer, -4 malformed offsets). Source range is checked by callers.
+#[inline(always)]
+fn csr_slices<'a, W>(n: u32, offsets: *const u32, targets: *const u32, weights: *const W) -> Result<(&'a [u32], &'a [u32], &'a [W]), i32> {
+    if n == 0 { return Err(-1); }
+    if offsets.is_null() || targets.is_null() || weights.is_null() { return Err(-3); }
+    let off = as_slice(offsets, n as usize + 1);
+    let m = match off.last() { Some(v) => *v as usize, None => return Err(-4) };
+    Ok((off, as_slice(targets, m), as_slice(weights, m)))
+}
+
+// Structural CSR check for callers, run before handing arrays to a runner (the
+// runners trust offsets[n] as m and index without bounds checks). Returns 0 if
+// valid, else the first problem found: -3 offsets[0] != 0, -1 offsets decrease,
+// -2 a target >= n; `detail` (optional) receives the offending offsets/targets
+// index. -4 if offsets or (for m > 0) targets is null. `weights` is not read and
+// may be null: weight values are checked by the runners' own pre-scan.
+#[no_mangle]
+pub extern "C" fn sssp_validate_csr(n: u32, offsets: *const u32, targets: *const u32, _weights: *const f32, detail: *mut u32) -> i32 {
+    ffi_guard(-99, || {
+        let fail = |rc: i32, idx: usize| { if !detail.is_null() { unsafe { *detail = idx as u32; } } rc };
+        if offsets.is_null() { return -4; }
+        let off = as_slice(offsets, n as usize + 1);
+        if off[0] != 0 { return fail(-3, 0); }
+        if let Some(i) = off.windows(2).position(|p| p[1] < p[0]) { return fail(-1, i + 1); }
+        let m = off[n as usize] as usize;
+        if m > 0 && targets.is_null() { return -4; }
+        if m > 0 { if let Some(e) = as_slice(targets, m).iter().position(|&t| t >= n) { return fail(-2, e); } }
+        0
+    })
+}
+
+// Weight pre-scan shared by the baseline and STOC runners. Both assume non-negative
+// weights; the scan is O(m) but stops at the first offender. Always on, except in
+// `sssp_run_baseline_unchecked` / `sssp_run_stoc_unchecked`, which skip it per call
+// for trusted inputs in hot benchmark loops.
+// Stops at the first offender: -6 for a negative weight (incl. -inf; -0.0 compares
+// equal to 0.0 and passes), -7 for NaN or +inf (NaN never relaxes, so its target
+// would look disconnected, and both break the bucket index `(dist * inv_delta) as
+// usize`), else 0. Finite weights are all accepted, but near f32::MAX a sum
+// `dist[u] + w` can round to +inf mid-run and leave that node unreached.
+fn scan_weights<W: Weight>(wts: impl IntoIterator<Item = W>) -> i32 {
+    match wts.into_iter().find(|w| !(w.is_finite() && *w >= W::ZERO)) { None => 0, Some(w) if w < W::ZERO => -6, Some(_) => -7 }
+}
+
+// Standalone `scan_weights` for callers that validate once and then run many
+// queries through the `_unchecked` runners.
+#[no_mangle]
+pub extern "C" fn sssp_validate_weights(weights: *const f32, m: u32) -> i32 {
+    ffi_guard(-99, || {
+        if m == 0 { return 0; }
+        if weights.is_null() { return -3; }
+        scan_weights(as_slice(weights, m as usize).iter().copied())
+    })
+}
+
+// Per-call switches of the baseline and STOC cores, set by the entry point instead of
+// process-wide state so concurrent callers cannot change each other's runs.
+// `check_order` is the SSSP_BASELINE_CHECK invariant (baseline heap loop only) and
+// `canonical` the SSSP_CANONICAL_PRED tie rule.
+#[derive(Clone, Copy)]
+pub(crate) struct RunOpts { pub(crate) check_weights: bool, pub(crate) check_order: bool, pub(crate) canonical: bool }
+
+impl RunOpts {
+    pub(crate) fn checked() -> Self { RunOpts { check_weights: true, check_order: baseline_check_enabled(), canonical: canonical_pred_enabled() } }
+    pub(crate) fn unchecked() -> Self { RunOpts { check_weights: false, ..Self::checked() } }
+}
+
+// Finite entries of a distance array: the `reachable` field of SsspResultInfo.
+pub(crate) fn count_reachable<W: Weight>(dist: &[W]) -> u32 { dist.iter().filter(|d| d.is_finite()).count() as u32 }
+
+// Runs `scan_weights`, mirroring a failure into `info.error_code`.
+pub(crate) fn precheck_weights<W: Weight>(wts: &[W], info: *mut SsspResultInfo) -> i32 {
+    precheck_weights_by(wts, |w| w, info)
+}
+
+// `precheck_weights` on weights decoded by `weight_of` (e.g. i32 widened to i64).
+fn precheck_weights_by<E: Copy, W: Weight, G: Fn(E) -> W>(wts: &[E], weight_of: G, info: *mut SsspResultInfo) -> i32 {
+    let rc = scan_weights(wts.iter().map(|&w| weight_of(w)));
+    if rc != 0 && !info.is_null() { unsafe { *info = SsspResultInfo { relaxations: 0, light_relaxations: 0, heavy_relaxations: 0, settled: 0, error_code: rc, overflow: 0, reachable: 0, edges: wts.len() as u64 }; } }
+    rc
+}
+
 #[derive(Copy, Clone)]
-struct HeapItem { node: u32, dist: f32 }
+struct HeapItem<W = f32> { node: u32, dist: W }
 
-// ---------------- Baseline binary heap ----------------
-struct BinaryHeapSimple { data: Vec<HeapItem> }
-impl BinaryHeapSimple {
+// ---------------- Baseline d-ary heap ----------------
+// Min-heap with D children per node (parent (i-1)/D, children D*i+1..=D*i+D). A
+// wider node makes the tree shallower, trading fewer sift_up levels for more
+// compares per sift_down level. D = 2 is the classic binary heap used by default.
+struct DaryHeap<const D: usize, W = f32> { data: Vec<HeapItem<W>> }
+type BinaryHeapSimple = DaryHeap<2>;
+impl<const D: usize, W: Weight> DaryHeap<D, W> {
     #[inline] fn new(cap: usize) -> Self { Self { data: Vec::with_capacity(cap) } }
-    #[inline] fn push(&mut self, item: HeapItem, pushes: &mut u64) { self.data.push(item); *pushes += 1; self.sift_up(self.data.len()-1); }
-    #[inline] fn pop(&mut self, pops: &mut u64) -> Option<HeapItem> {
+    #[inline] fn push(&mut self, item: HeapItem<W>, pushes: &mut u64) { self.data.push(item); *pushes += 1; self.sift_up(self.data.len()-1); }
+    #[inline] fn pop(&mut self, pops: &mut u64) -> Option<HeapItem<W>> {
         let len = self.data.len();
         if len == 0 { return None; }
         self.data.swap(0, len-1);
@@ -83,10 +317,60 @@ impl BinaryHeapSimple {
     }
     #[inline] fn sift_up(&mut self, mut idx: usize) {
         while idx > 0 {
-            let parent = (idx - 1) / 2;
+            let parent = (idx - 1) / D;
             if self.data[idx].dist < self.data[parent].dist { self.data.swap(idx, parent); idx = parent; } else { break; }
         }
     }
+    #[inline] fn sift_down(&mut self, mut idx: usize) {
+        let n = self.data.len();
+        loop {
+            let first = idx * D + 1;
+            if first >= n { break; }
+            // First minimum among the children, so D = 2 breaks ties towards the left child.
+            let mut best = first;
+            for c in first + 1..(first + D).min(n) { if self.data[c].dist < self.data[best].dist { best = c; } }
+            if self.data[best].dist < self.data[idx].dist { self.data.swap(idx, best); idx = best; } else { break; }
+        }
+    }
+}
+
+// ---------------- Indexed binary heap (decrease-key) ----------------
+// Binary min-heap holding at most one entry per node: `pos[node]` is the node's
+// slot, or -1 while it is not in the heap. An improved distance moves the
+// existing entry up instead of pushing a second one, so pops are never stale.
+struct IndexedHeap { data: Vec<HeapItem>, pos: Vec<i32> }
+impl IndexedHeap {
+    fn new(n: usize) -> Self { Self { data: Vec::with_capacity(n.min(1024)), pos: vec![-1; n] } }
+    // Inserts `item` or lowers its node's key; only insertions count as pushes.
+    #[inline] fn push_or_decrease(&mut self, item: HeapItem, pushes: &mut u64) {
+        let slot = self.pos[item.node as usize];
+        if slot >= 0 { self.data[slot as usize].dist = item.dist; self.sift_up(slot as usize); return; }
+        self.data.push(item); *pushes += 1;
+        let idx = self.data.len() - 1;
+        self.pos[item.node as usize] = idx as i32;
+        self.sift_up(idx);
+    }
+    #[inline] fn pop(&mut self, pops: &mut u64) -> Option<HeapItem> {
+        let len = self.data.len();
+        if len == 0 { return None; }
+        self.swap(0, len - 1);
+        let out = self.data.pop()?;
+        self.pos[out.node as usize] = -1;
+        *pops += 1;
+        if !self.data.is_empty() { self.sift_down(0); }
+        Some(out)
+    }
+    #[inline] fn swap(&mut self, a: usize, b: usize) {
+        self.data.swap(a, b);
+        self.pos[self.data[a].node as usize] = a as i32;
+        self.pos[self.data[b].node as usize] = b as i32;
+    }
+    #[inline] fn sift_up(&mut self, mut idx: usize) {
+        while idx > 0 {
+            let parent = (idx - 1) / 2;
+            if self.data[idx].dist < self.data[parent].dist { self.swap(idx, parent); idx = parent; } else { break; }
+        }
+    }
     #[inline] fn sift_down(&mut self, mut idx: usize) {
         let n = self.data.len();
         loop {
@@ -95,94 +379,828 @@ impl BinaryHeapSimple {
             let right = left + 1;
             let mut best = left;
             if right < n && self.data[right].dist < self.data[left].dist { best = right; }
-            if self.data[best].dist < self.data[idx].dist { self.data.swap(idx, best); idx = best; } else { break; }
+            if self.data[best].dist < self.data[idx].dist { self.swap(idx, best); idx = best; } else { break; }
         }
     }
 }
 
 
 #[no_mangle]
-pub extern "C" fn sssp_run_baseline(
+pub extern "C" fn sssp_run_baseline(
+    n: u32,
+    offsets: *const u32, // len n+1
+    targets: *const u32, // len m
+    weights: *const f32, // len m
+    source: u32,
+    out_dist: *mut f32,  // len n
+    out_pred: *mut i32,  // len n
+    info: *mut SsspResultInfo,
+) -> i32 {
+    ffi_guard(-99, || {
+        baseline_run(n, offsets, targets, weights, source, out_dist, out_pred, info, core::ptr::null_mut(), core::ptr::null_mut(), RunOpts::checked())
+    })
+}
+
+// `sssp_run_baseline` without the weight pre-scan, for trusted inputs already checked
+// once with `sssp_validate_weights`. A negative or NaN weight gives wrong distances.
+#[no_mangle]
+pub extern "C" fn sssp_run_baseline_unchecked(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+) -> i32 {
+    ffi_guard(-99, || {
+        baseline_run(n, offsets, targets, weights, source, out_dist, out_pred, info, core::ptr::null_mut(), core::ptr::null_mut(), RunOpts::unchecked())
+    })
+}
+
+// u64 offsets must fit the platform's usize (always true on 64-bit targets).
+fn offsets64_fit(n: u32, offsets: *const u64) -> bool {
+    offsets.is_null() || usize::try_from(unsafe { *offsets.add(n as usize) }).is_ok()
+}
+
+// `sssp_run_baseline` over u64 offsets, for graphs with more than u32::MAX edges.
+// Node ids stay u32; edge indices are usize throughout. Returns -4 if offsets[n]
+// does not fit in usize.
+#[no_mangle]
+pub extern "C" fn sssp_run_baseline64(
+    n: u32,
+    offsets: *const u64, // len n+1
+    targets: *const u32, // len m
+    weights: *const f32, // len m
+    source: u32,
+    out_dist: *mut f32,  // len n
+    out_pred: *mut i32,  // len n
+    info: *mut SsspResultInfo,
+) -> i32 {
+    ffi_guard(-99, || {
+        if n > 0 && !offsets64_fit(n, offsets) { return -4; }
+        baseline_run(n, offsets, targets, weights, source, out_dist, out_pred, info, core::ptr::null_mut(), core::ptr::null_mut(), RunOpts::checked())
+    })
+}
+
+// `sssp_run_baseline` / `sssp_run_baseline64` that also write the heap counters into
+// `heap_stats` (may be null), so concurrent callers each get their own.
+#[no_mangle]
+pub extern "C" fn sssp_run_baseline_stats(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+    heap_stats: *mut BaselineHeapStats,
+) -> i32 {
+    ffi_guard(-99, || {
+        baseline_run(n, offsets, targets, weights, source, out_dist, out_pred, info, core::ptr::null_mut(), heap_stats, RunOpts::checked())
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn sssp_run_baseline64_stats(
+    n: u32,
+    offsets: *const u64,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+    heap_stats: *mut BaselineHeapStats,
+) -> i32 {
+    ffi_guard(-99, || {
+        if n > 0 && !offsets64_fit(n, offsets) { return -4; }
+        baseline_run(n, offsets, targets, weights, source, out_dist, out_pred, info, core::ptr::null_mut(), heap_stats, RunOpts::checked())
+    })
+}
+
+// `sssp_run_baseline` plus an SsspTiming breakdown (`timing` must be non-null).
+#[no_mangle]
+pub extern "C" fn sssp_run_baseline_timed(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+    timing: *mut SsspTiming,
+) -> i32 {
+    ffi_guard(-99, || {
+        if timing.is_null() { return -3; }
+        baseline_run(n, offsets, targets, weights, source, out_dist, out_pred, info, timing, core::ptr::null_mut(), RunOpts::checked())
+    })
+}
+
+// `sssp_run_baseline_stats` on a d-ary heap. `arity` is clamped to [2, 8] (0 picks
+// the default of 4). Distances match the binary heap; pred and the push count can
+// only differ through the pop order of equal-distance nodes, so `heap_stats`
+// compares arities on equal terms.
+#[no_mangle]
+pub extern "C" fn sssp_run_baseline_dary(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+    heap_stats: *mut BaselineHeapStats,
+    arity: u32,
+) -> i32 {
+    ffi_guard(-99, || {
+        let t = core::ptr::null_mut();
+        match if arity == 0 { 4 } else { arity.clamp(2, 8) } {
+            2 => baseline_run_dary::<u32, 2>(n, offsets, targets, weights, source, out_dist, out_pred, info, t, heap_stats, RunOpts::checked()),
+            3 => baseline_run_dary::<u32, 3>(n, offsets, targets, weights, source, out_dist, out_pred, info, t, heap_stats, RunOpts::checked()),
+            4 => baseline_run_dary::<u32, 4>(n, offsets, targets, weights, source, out_dist, out_pred, info, t, heap_stats, RunOpts::checked()),
+            5 => baseline_run_dary::<u32, 5>(n, offsets, targets, weights, source, out_dist, out_pred, info, t, heap_stats, RunOpts::checked()),
+            6 => baseline_run_dary::<u32, 6>(n, offsets, targets, weights, source, out_dist, out_pred, info, t, heap_stats, RunOpts::checked()),
+            7 => baseline_run_dary::<u32, 7>(n, offsets, targets, weights, source, out_dist, out_pred, info, t, heap_stats, RunOpts::checked()),
+            _ => baseline_run_dary::<u32, 8>(n, offsets, targets, weights, source, out_dist, out_pred, info, t, heap_stats, RunOpts::checked()),
+        }
+    })
+}
+
+// `sssp_run_baseline_stats` on an indexed heap with true decrease-key: the heap
+// never holds more than one entry per node, so `max_size <= n` and pushes equal
+// pops (decrease-keys are not counted as pushes). Same distances as the baseline.
+#[no_mangle]
+pub extern "C" fn sssp_run_baseline_indexed(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+    heap_stats: *mut BaselineHeapStats,
+) -> i32 {
+    ffi_guard(-99, || baseline_run_indexed(n, offsets, targets, weights, source, out_dist, out_pred, info, heap_stats, RunOpts::checked()))
+}
+
+fn baseline_run_indexed(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+    heap_stats: *mut BaselineHeapStats,
+    opts: RunOpts,
+) -> i32 {
+    let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+    if source >= n { return -2; }
+    if out_dist.is_null() || out_pred.is_null() { return -3; }
+    if opts.check_weights { let rc = precheck_weights(wts, info); if rc != 0 { return rc; } }
+    let n_usize = n as usize;
+    let dist = as_mut_slice(out_dist, n_usize);
+    let pred = as_mut_slice(out_pred, n_usize);
+    for d in dist.iter_mut() { *d = f32::INFINITY; }
+    for p in pred.iter_mut() { *p = -1; }
+    dist[source as usize] = 0.0;
+    let canonical = opts.canonical;
+    let mut heap = IndexedHeap::new(n_usize);
+    let (mut relaxations, mut heap_pushes, mut heap_pops, mut heap_max) = (0u64, 0u64, 0u64, 0u64);
+    heap.push_or_decrease(HeapItem { node: source, dist: 0.0 }, &mut heap_pushes);
+    heap_max = heap_max.max(heap.data.len() as u64);
+    while let Some(item) = heap.pop(&mut heap_pops) {
+        for e in off[item.node as usize] as usize..off[item.node as usize + 1] as usize {
+            let v = tgt[e] as usize;
+            let nd = item.dist + wts[e];
+            if nd < dist[v] {
+                dist[v] = nd;
+                pred[v] = item.node as i32;
+                heap.push_or_decrease(HeapItem { node: v as u32, dist: nd }, &mut heap_pushes);
+                if heap.data.len() as u64 > heap_max { heap_max = heap.data.len() as u64; }
+                relaxations += 1;
+            } else if canonical && canonical_tie(item.dist, nd, dist[v], item.node, pred[v]) {
+                pred[v] = item.node as i32;
+            }
+        }
+    }
+    if !info.is_null() { unsafe { *info = SsspResultInfo { relaxations, light_relaxations: 0, heavy_relaxations: 0, settled: n, error_code: 0, overflow: 0, reachable: count_reachable(dist), edges: tgt.len() as u64 }; } }
+    publish_heap_stats(BaselineHeapStats { pushes: heap_pushes, pops: heap_pops, max_size: heap_max }, heap_stats);
+    0
+}
+
+fn baseline_run<O: CsrOffset>(
+    n: u32,
+    offsets: *const O,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+    timing: *mut SsspTiming,
+    heap_stats: *mut BaselineHeapStats,
+    opts: RunOpts,
+) -> i32 {
+    baseline_run_dary::<O, 2>(n, offsets, targets, weights, source, out_dist, out_pred, info, timing, heap_stats, opts)
+}
+
+fn baseline_run_dary<O: CsrOffset, const D: usize>(
+    n: u32,
+    offsets: *const O,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+    timing: *mut SsspTiming,
+    heap_stats: *mut BaselineHeapStats,
+    opts: RunOpts,
+) -> i32 {
+    if n == 0 { return -1; }
+    if source >= n { return -2; }
+    if offsets.is_null() || targets.is_null() || weights.is_null() || out_dist.is_null() || out_pred.is_null() { return -3; }
+
+    // Safety: caller promises valid lengths. Derive m from offsets[n].
+    let n_usize = n as usize;
+    let off = as_slice(offsets, n_usize + 1);
+    let m = match off.last() { Some(v) => v.idx(), None => return -4 };
+    let tgt = as_slice(targets, m);
+    let wts = as_slice(weights, m);
+    if opts.check_weights { let rc = precheck_weights(wts, info); if rc != 0 { return rc; } }
+    let dist = as_mut_slice(out_dist, n_usize);
+    let pred = as_mut_slice(out_pred, n_usize);
+    let mut clock = PhaseClock::start(timing);
+
+    // Init
+    for d in dist.iter_mut() { *d = f32::INFINITY; }
+    for p in pred.iter_mut() { *p = -1; }
+    dist[source as usize] = 0.0;
+
+    let canonical = opts.canonical;
+    let mut heap = DaryHeap::<D>::new( (n as usize).min(1024) );
+    let mut relaxations: u64 = 0;
+    let light_relaxations: u64 = 0; // unused in baseline
+    let heavy_relaxations: u64 = 0; // unused in baseline
+    let mut heap_pushes: u64 = 0;
+    let mut heap_pops: u64 = 0;
+    let mut heap_max: u64 = 0;
+    heap.push(HeapItem { node: source, dist: 0.0 }, &mut heap_pushes);
+    heap_max = heap_max.max(heap.data.len() as u64);
+    clock.init_done();
+
+    let check = opts.check_order;
+    let mut inv = SpecInvariantStats::default();
+    let mut last_settled_dist = f32::NEG_INFINITY;
+    while let Some(item) = heap.pop(&mut heap_pops) {
+        if item.dist > dist[item.node as usize] { continue; }
+        if check {
+            inv.checks += 1;
+            if item.dist < last_settled_dist { inv.failures += 1; eprintln!("[baseline-invariant] FAIL: pop {} at {} after {}", item.node, item.dist, last_settled_dist); }
+            last_settled_dist = item.dist;
+        }
+        prefetch::prefetch_next_pops(&heap.data, off, tgt, wts);
+        let start = off[item.node as usize].idx();
+        let end = off[item.node as usize + 1].idx();
+        macro_rules! relax_edge { ($e:expr) => {{
+            let e = $e;
+            let v = tgt[e] as usize;
+            let w = wts[e];
+            let nd = item.dist + w;
+            let cur = dist[v];
+            // Self-loops never shorten a path; skip them outright (not a `continue`,
+            // which would bypass the caller's `e += 1`).
+            if v != item.node as usize && nd < cur {
+                dist[v] = nd;
+                pred[v] = item.node as i32;
+                heap.push(HeapItem { node: v as u32, dist: nd }, &mut heap_pushes);
+                if heap.data.len() as u64 > heap_max { heap_max = heap.data.len() as u64; }
+                relaxations += 1;
+            } else if canonical && v != item.node as usize && canonical_tie(item.dist, nd, cur, item.node, pred[v]) {
+                pred[v] = item.node as i32;
+            }
+        }}; }
+        // Blocks of simd::LANES edges are filtered by a vector compare; surviving
+        // lanes go through the scalar rule in edge order (bit-identical results).
+        // The compare drops ties, so canonical pred takes the scalar path.
+        let mut e = start;
+        while !canonical && e + simd::LANES <= end {
+            for k in 0..simd::LANES { prefetch::prefetch_dist_ahead(dist, tgt, e + k, end); }
+            let mask = simd::relax_mask4(item.dist, &wts[e..e + simd::LANES], &tgt[e..e + simd::LANES], dist);
+            if mask != 0 { for k in 0..simd::LANES { if mask & (1 << k) != 0 { relax_edge!(e + k); } } }
+            e += simd::LANES;
+        }
+        while e < end { prefetch::prefetch_dist_ahead(dist, tgt, e, end); relax_edge!(e); e += 1; }
+    }
+    clock.loop_done();
+
+    if !info.is_null() { unsafe { *info = SsspResultInfo { relaxations, light_relaxations, heavy_relaxations, settled: n, error_code: 0, overflow: 0, reachable: count_reachable(dist), edges: tgt.len() as u64 }; } }
+    publish_heap_stats(BaselineHeapStats { pushes: heap_pushes, pops: heap_pops, max_size: heap_max }, heap_stats);
+    if check { LAST_BASELINE_INV_STATS.with(|s| s.set(inv)); }
+    clock.finalize_done();
+    0
+}
+
+// SSSP_CANONICAL_PRED=1: among equally short predecessors keep the smallest node id,
+// so pred stops depending on heap/bucket insertion order (the baseline on a permuted
+// adjacency, or baseline vs STOC, agree whenever their distances do). Off by default;
+// read once per process. A tie is exact f32 equality: with an epsilon the first of two
+// near-equal labels would still decide. Ties only count on edges that strictly
+// increase the distance, which keeps pred acyclic across zero-weight edges.
+fn canonical_pred_enabled() -> bool {
+    static ON: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+    *ON.get_or_init(|| std::env::var("SSSP_CANONICAL_PRED").map(|v| v == "1" || v.eq_ignore_ascii_case("true")).unwrap_or(false))
+}
+
+#[inline(always)]
+fn canonical_tie<D: Weight>(du: D, nd: D, cur: D, u: u32, pred_v: i32) -> bool { nd == cur && du < nd && (u as i32) < pred_v }
+
+// Slice-level Dijkstra shared by the derived queries (same relaxation rule and
+// heap as `sssp_run_baseline`, which keeps its own inlined loop). `on_settle(node,
+// dist)` fires once per accepted pop in non-decreasing distance order; returning
+// false stops the search, leaving unsettled distances partial.
+struct DijkstraCounts { relaxations: u64, settled: u32, pushes: u64, pops: u64, max_size: u64 }
+
+fn dijkstra_with<O: CsrOffset, F: FnMut(u32, f32) -> bool>(off: &[O], tgt: &[u32], wts: &[f32], source: u32, dist: &mut [f32], pred: &mut [i32], on_settle: F) -> DijkstraCounts {
+    dijkstra_with_weights(off, tgt, wts, |w| w, source, dist, pred, on_settle)
+}
+
+// Same search over a compact weight encoding decoded per edge by `weight_of`.
+fn dijkstra_with_weights<O: CsrOffset, W: Copy, D: Weight, G: Fn(W) -> D, F: FnMut(u32, D) -> bool>(off: &[O], tgt: &[u32], wts: &[W], weight_of: G, source: u32, dist: &mut [D], pred: &mut [i32], on_settle: F) -> DijkstraCounts {
+    dijkstra_within_weights(off, tgt, wts, weight_of, source, D::INFINITY, dist, pred, on_settle)
+}
+
+// `dijkstra_with` that never labels a node beyond `cutoff` (those stay +inf), so
+// the heap drains once every node within it is settled.
+fn dijkstra_within<O: CsrOffset, F: FnMut(u32, f32) -> bool>(off: &[O], tgt: &[u32], wts: &[f32], source: u32, cutoff: f32, dist: &mut [f32], pred: &mut [i32], on_settle: F) -> DijkstraCounts {
+    dijkstra_within_weights(off, tgt, wts, |w| w, source, cutoff, dist, pred, on_settle)
+}
+
+fn dijkstra_within_weights<O: CsrOffset, W: Copy, D: Weight, G: Fn(W) -> D, F: FnMut(u32, D) -> bool>(off: &[O], tgt: &[u32], wts: &[W], weight_of: G, source: u32, cutoff: D, dist: &mut [D], pred: &mut [i32], mut on_settle: F) -> DijkstraCounts {
+    for d in dist.iter_mut() { *d = D::INFINITY; }
+    for p in pred.iter_mut() { *p = -1; }
+    dist[source as usize] = D::ZERO;
+    let canonical = canonical_pred_enabled();
+    let mut c = DijkstraCounts { relaxations: 0, settled: 0, pushes: 0, pops: 0, max_size: 0 };
+    let mut heap = DaryHeap::<2, D>::new(dist.len().min(1024));
+    heap.push(HeapItem { node: source, dist: D::ZERO }, &mut c.pushes);
+    c.max_size = 1;
+    while let Some(item) = heap.pop(&mut c.pops) {
+        let u = item.node as usize;
+        if item.dist > dist[u] { continue; }
+        c.settled += 1;
+        if !on_settle(item.node, item.dist) { break; }
+        for e in off[u].idx()..off[u + 1].idx() {
+            let v = tgt[e] as usize;
+            let nd = item.dist + weight_of(wts[e]);
+            if nd < dist[v] && nd <= cutoff {
+                dist[v] = nd; pred[v] = u as i32;
+                heap.push(HeapItem { node: v as u32, dist: nd }, &mut c.pushes);
+                if heap.data.len() as u64 > c.max_size { c.max_size = heap.data.len() as u64; }
+                c.relaxations += 1;
+            } else if canonical && canonical_tie(item.dist, nd, dist[v], item.node, pred[v]) {
+                pred[v] = u as i32;
+            }
+        }
+    }
+    c
+}
+
+#[no_mangle]
+pub extern "C" fn sssp_version() -> u32 { 8 } // incremented due to SsspResultInfo breaking change (edges)
+
+// Dynamic adaptive-restart trigger used when SSSP_STOC_ADAPT_TRIGGER is unset:
+// ln(n)/2 (ln floored at 1) bounded to [3,40] buckets.
+#[inline]
+fn stoc_default_adapt_trigger(n: u32) -> u32 {
+    let logn = (n as f32).ln().max(1.0);
+    ((logn / 2.0) as u32).clamp(3, 40)
+}
+
+// Exposes the default trigger (buckets visited before the adaptive delta check in
+// `sssp_run_stoc`) so callers can predict it or pin it via SSSP_STOC_ADAPT_TRIGGER.
+#[no_mangle]
+pub extern "C" fn sssp_stoc_default_adapt_trigger(n: u32) -> u32 { stoc_default_adapt_trigger(n) }
+
+// Light/heavy split used by the STOC loop for a given delta: `out_class[e]` = 0 if
+// `weights[e] <= delta` (relaxed inside the bucket's light passes), else 1. Lets
+// callers cache the classification across queries sharing a delta. NaN weights
+// compare false and land in the heavy class. Returns -7 if `delta` is NaN or <= 0.
+#[no_mangle]
+pub extern "C" fn sssp_classify_edges(weights: *const f32, m: u32, delta: f32, out_class: *mut u8) -> i32 {
+    ffi_guard(-99, || {
+        if delta.is_nan() || delta <= 0.0 { return -7; }
+        if m == 0 { return 0; }
+        if weights.is_null() || out_class.is_null() { return -3; }
+        let wts = as_slice(weights, m as usize);
+        for (c, &w) in as_mut_slice(out_class, m as usize).iter_mut().zip(wts) { *c = if w <= delta { 0 } else { 1 }; }
+        0
+    })
+}
+
+// ---------------- STOC-inspired (delta-stepping style) variant ----------------
+// This implements a simplified delta-stepping algorithm (Meyer & Sanders) often
+// used as a practical foundation for layering / bucket approaches referenced in
+// later theoretical STOC-style improvements. We expose it under the name
+// `sssp_run_stoc` per user request, though it is the classical delta-stepping
+// core (single-threaded here).
+// Key idea: partition edges into light (w <= delta) and heavy (w > delta).
+// Process buckets i in increasing order of floor(dist/delta). For each bucket:
+//  1. Repeatedly settle nodes reachable via light edges within the bucket.
+//  2. Afterwards relax heavy edges from those settled nodes, inserting targets
+//     into future buckets. This reduces priority queue operations to simple
+//     bucket insertions and batches many light-edge relaxations.
+// Expected benefit appears on graphs with many small weights creating clusters
+// per distance band; on random sparse graphs overhead may still dominate.
+#[no_mangle]
+pub extern "C" fn sssp_run_stoc(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+) -> i32 {
+    ffi_guard(-99, || {
+        stoc_run_adaptive(n, offsets, targets, weights, source, out_dist, out_pred, info, core::ptr::null_mut(), core::ptr::null_mut(), &StocConfig::from_env(), &mut StocScratch::default(), core::ptr::null_mut(), RunOpts::checked())
+    })
+}
+
+// `sssp_run_stoc` without the weight pre-scan (see `sssp_run_baseline_unchecked`).
+#[no_mangle]
+pub extern "C" fn sssp_run_stoc_unchecked(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+) -> i32 {
+    ffi_guard(-99, || {
+        stoc_run_adaptive(n, offsets, targets, weights, source, out_dist, out_pred, info, core::ptr::null_mut(), core::ptr::null_mut(), &StocConfig::from_env(), &mut StocScratch::default(), core::ptr::null_mut(), RunOpts::unchecked())
+    })
+}
+
+// Explicit STOC tuning, replacing the SSSP_STOC_* env knobs read by `sssp_run_stoc`
+// (process-global, so concurrent callers wanting different settings race on them).
+// Fields mirror the env vars and are clamped the same way:
+//   delta_mode         STOC_DELTA_AVG (0, avg weight x mult) | STOC_DELTA_QUANTILE (1)
+//   heavy_target       heavy-edge fraction targeted by quantile mode (0.15)
+//   delta_mult         delta multiplier; <= 0 picks the mode default (avg 3, quantile 1)
+//   adapt_max_restarts adaptive restart budget (4)
+//   adapt_trigger      buckets before the adaptive check; 0 = stoc_default_adapt_trigger(n)
+//   heavy_min_ratio / heavy_max_ratio  band that triggers shrink / expand (0.05 / 0.25)
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct StocConfig {
+    pub delta_mode: u32,
+    pub heavy_target: f32,
+    pub delta_mult: f32,
+    pub adapt_max_restarts: u32,
+    pub adapt_trigger: u32,
+    pub heavy_min_ratio: f32,
+    pub heavy_max_ratio: f32,
+}
+
+pub const STOC_DELTA_AVG: u32 = 0;
+pub const STOC_DELTA_QUANTILE: u32 = 1;
+
+impl Default for StocConfig {
+    fn default() -> Self {
+        StocConfig { delta_mode: STOC_DELTA_AVG, heavy_target: 0.15, delta_mult: 0.0, adapt_max_restarts: 4, adapt_trigger: 0, heavy_min_ratio: 0.05, heavy_max_ratio: 0.25 }
+    }
+}
+
+impl StocConfig {
+    // The settings `sssp_run_stoc` has always read from the environment; unset or
+    // unparsable vars keep the defaults. An explicit trigger of 0 behaves like 1.
+    pub(crate) fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(key: &str) -> Option<T> { std::env::var(key).ok().and_then(|v| v.parse().ok()) }
+        let d = StocConfig::default();
+        StocConfig {
+            delta_mode: if std::env::var("SSSP_STOC_DELTA_MODE").map(|v| v == "quantile").unwrap_or(false) { STOC_DELTA_QUANTILE } else { STOC_DELTA_AVG },
+            heavy_target: var("SSSP_STOC_HEAVY_TARGET").unwrap_or(d.heavy_target),
+            delta_mult: var("SSSP_STOC_DELTA_MULT").unwrap_or(d.delta_mult),
+            adapt_max_restarts: var("SSSP_STOC_ADAPT_MAX_RESTARTS").unwrap_or(d.adapt_max_restarts),
+            adapt_trigger: var::<u32>("SSSP_STOC_ADAPT_TRIGGER").map(|t| t.max(1)).unwrap_or(d.adapt_trigger),
+            heavy_min_ratio: var("SSSP_STOC_HEAVY_MIN_RATIO").unwrap_or(d.heavy_min_ratio),
+            heavy_max_ratio: var("SSSP_STOC_HEAVY_MAX_RATIO").unwrap_or(d.heavy_max_ratio),
+        }
+    }
+}
+
+// `sssp_run_stoc` configured by `cfg` instead of the environment (SSSP_STOC_ADAPT_TRACE
+// still applies). A null `cfg` reads the env vars exactly as `sssp_run_stoc` does.
+#[no_mangle]
+pub extern "C" fn sssp_run_stoc_cfg(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+    cfg: *const StocConfig,
+) -> i32 {
+    ffi_guard(-99, || {
+        let cfg = if cfg.is_null() { StocConfig::from_env() } else { unsafe { *cfg } };
+        stoc_run_adaptive(n, offsets, targets, weights, source, out_dist, out_pred, info, core::ptr::null_mut(), core::ptr::null_mut(), &cfg, &mut StocScratch::default(), core::ptr::null_mut(), RunOpts::checked())
+    })
+}
+
+// `sssp_run_stoc_stats` plus a histogram of the final attempt's work per bucket:
+// bucket_hist[i] = nodes settled in bucket i (saturating). Work concentrated in a
+// few buckets means delta fits the weights; a long flat tail means it is too small.
+// Exactly `hist_len` entries are written (0 past the last processed bucket);
+// bucket_stats->max_bucket_index is the true highest index, so a caller with
+// hist_len <= max_bucket_index can resize and re-run. A null `bucket_hist`
+// requires hist_len 0 (-3 otherwise).
+#[no_mangle]
+pub extern "C" fn sssp_run_stoc_detailed(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+    bucket_stats: *mut SsspBucketStats,
+    bucket_hist: *mut u32,
+    hist_len: u32,
+) -> i32 {
+    ffi_guard(-99, || {
+        if bucket_hist.is_null() && hist_len > 0 { return -3; }
+        let mut scratch = StocScratch { hist: Some(Vec::new()), ..StocScratch::default() };
+        let rc = stoc_run_adaptive(n, offsets, targets, weights, source, out_dist, out_pred, info, core::ptr::null_mut(), core::ptr::null_mut(), &StocConfig::from_env(), &mut scratch, bucket_stats, RunOpts::checked());
+        if rc != 0 { return rc; }
+        if hist_len > 0 {
+            let out = as_mut_slice(bucket_hist, hist_len as usize);
+            let hist = scratch.hist.unwrap_or_default();
+            for (i, x) in out.iter_mut().enumerate() { *x = hist.get(i).copied().unwrap_or(0); }
+        }
+        0
+    })
+}
+
+// `sssp_run_stoc_cfg` with per-call bucket stats (see `sssp_run_stoc_stats`).
+#[no_mangle]
+pub extern "C" fn sssp_run_stoc_cfg_stats(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+    cfg: *const StocConfig,
+    bucket_stats: *mut SsspBucketStats,
+) -> i32 {
+    ffi_guard(-99, || {
+        let cfg = if cfg.is_null() { StocConfig::from_env() } else { unsafe { *cfg } };
+        stoc_run_adaptive(n, offsets, targets, weights, source, out_dist, out_pred, info, core::ptr::null_mut(), core::ptr::null_mut(), &cfg, &mut StocScratch::default(), bucket_stats, RunOpts::checked())
+    })
+}
+
+// `sssp_run_stoc` over u64 offsets (see `sssp_run_baseline64`).
+#[no_mangle]
+pub extern "C" fn sssp_run_stoc64(
+    n: u32,
+    offsets: *const u64,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+) -> i32 {
+    ffi_guard(-99, || {
+        if n > 0 && !offsets64_fit(n, offsets) { return -4; }
+        stoc_run_adaptive(n, offsets, targets, weights, source, out_dist, out_pred, info, core::ptr::null_mut(), core::ptr::null_mut(), &StocConfig::from_env(), &mut StocScratch::default(), core::ptr::null_mut(), RunOpts::checked())
+    })
+}
+
+// STOC runners that also write the final attempt's SsspBucketStats (delta included,
+// as delta_x1000) into `bucket_stats` (may be null), replacing the process-wide
+// `sssp_get_bucket_stats` / `sssp_get_last_delta` for concurrent callers.
+#[no_mangle]
+pub extern "C" fn sssp_run_stoc_stats(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+    bucket_stats: *mut SsspBucketStats,
+) -> i32 {
+    ffi_guard(-99, || {
+        stoc_run_adaptive(n, offsets, targets, weights, source, out_dist, out_pred, info, core::ptr::null_mut(), core::ptr::null_mut(), &StocConfig::from_env(), &mut StocScratch::default(), bucket_stats, RunOpts::checked())
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn sssp_run_stoc64_stats(
+    n: u32,
+    offsets: *const u64,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+    bucket_stats: *mut SsspBucketStats,
+) -> i32 {
+    ffi_guard(-99, || {
+        if n > 0 && !offsets64_fit(n, offsets) { return -4; }
+        stoc_run_adaptive(n, offsets, targets, weights, source, out_dist, out_pred, info, core::ptr::null_mut(), core::ptr::null_mut(), &StocConfig::from_env(), &mut StocScratch::default(), bucket_stats, RunOpts::checked())
+    })
+}
+
+// `sssp_run_stoc` plus an SsspTiming breakdown (`timing` must be non-null). Init
+// covers the first array fill and delta selection; per-restart refills count
+// toward the loop.
+#[no_mangle]
+pub extern "C" fn sssp_run_stoc_timed(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+    timing: *mut SsspTiming,
+) -> i32 {
+    ffi_guard(-99, || {
+        if timing.is_null() { return -3; }
+        stoc_run_adaptive(n, offsets, targets, weights, source, out_dist, out_pred, info, core::ptr::null_mut(), timing, &StocConfig::from_env(), &mut StocScratch::default(), core::ptr::null_mut(), RunOpts::checked())
+    })
+}
+
+// Per-node phase tags written by `sssp_run_stoc_phased`: which relaxation kind
+// produced the node's final distance in the last (accepted) adaptive pass.
+const PHASE_LIGHT: u8 = 0;    // light-edge pass (the source is tagged light: bucket 0)
+const PHASE_HEAVY: u8 = 1;    // heavy-edge pass
+const PHASE_FALLBACK: u8 = 2; // never finalized by the bucket loop (unreachable)
+
+// Same as `sssp_run_stoc`, additionally filling `out_phase` (len n) with the
+// PHASE_* tag per node for attributing work in hybrid runners.
+#[no_mangle]
+pub extern "C" fn sssp_run_stoc_phased(
     n: u32,
-    offsets: *const u32, // len n+1
-    targets: *const u32, // len m
-    weights: *const f32, // len m
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
     source: u32,
-    out_dist: *mut f32,  // len n
-    out_pred: *mut i32,  // len n
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    out_phase: *mut u8,
     info: *mut SsspResultInfo,
 ) -> i32 {
-    if n == 0 { return -1; }
-    if source >= n { return -2; }
-    if offsets.is_null() || targets.is_null() || weights.is_null() || out_dist.is_null() || out_pred.is_null() { return -3; }
-
-    // Safety: caller promises valid lengths. Derive m from offsets[n].
-    let n_usize = n as usize;
-    let off = as_slice(offsets, n_usize + 1);
-    let m = match off.last() { Some(v) => *v as usize, None => return -4 };
-    let tgt = as_slice(targets, m);
-    let wts = as_slice(weights, m);
-    let dist = as_mut_slice(out_dist, n_usize);
-    let pred = as_mut_slice(out_pred, n_usize);
+    ffi_guard(-99, || {
+        if out_phase.is_null() { return -3; }
+        stoc_run_adaptive(n, offsets, targets, weights, source, out_dist, out_pred, info, out_phase, core::ptr::null_mut(), &StocConfig::from_env(), &mut StocScratch::default(), core::ptr::null_mut(), RunOpts::checked())
+    })
+}
 
-    // Init
-    for d in dist.iter_mut() { *d = f32::INFINITY; }
-    for p in pred.iter_mut() { *p = -1; }
-    dist[source as usize] = 0.0;
+// Bucket store for delta-stepping with memory bounded by the live buckets rather
+// than the diameter: a ring of BUCKET_RING_WIDTH slots holds indices
+// [base, base + width) and farther buckets wait in an ordered map until the window
+// reaches them. Advancing past `base` clears and recycles its slot. Pushes below
+// `base` target buckets the scan has already passed and would never be read, so
+// they are dropped.
+const BUCKET_RING_WIDTH: usize = 64;
 
-    let mut heap = BinaryHeapSimple::new( (n as usize).min(1024) );
-    let mut relaxations: u64 = 0;
-    let light_relaxations: u64 = 0; // unused in baseline
-    let heavy_relaxations: u64 = 0; // unused in baseline
-    let mut heap_pushes: u64 = 0;
-    let mut heap_pops: u64 = 0;
-    let mut heap_max: u64 = 0;
-    heap.push(HeapItem { node: source, dist: 0.0 }, &mut heap_pushes);
-    heap_max = heap_max.max(heap.data.len() as u64);
+#[derive(Default)]
+struct BucketRing {
+    ring: Vec<Vec<u32>>,
+    far: BTreeMap<usize, Vec<u32>>,
+    spare: Vec<Vec<u32>>,
+    base: usize,
+    live: u32,        // non-empty buckets in ring + far
+    peak_live: u32,
+    max_index: usize, // highest bucket index addressed this run
+}
 
-    while let Some(item) = heap.pop(&mut heap_pops) {
-        if item.dist > dist[item.node as usize] { continue; }
-        let start = off[item.node as usize] as usize;
-        let end = off[item.node as usize + 1] as usize;
-        for e in start..end {
-            let v = tgt[e] as usize;
-            let w = wts[e];
-            let nd = item.dist + w;
-            let cur = dist[v];
-            if nd < cur {
-                dist[v] = nd;
-                pred[v] = item.node as i32;
-                heap.push(HeapItem { node: v as u32, dist: nd }, &mut heap_pushes);
-                if heap.data.len() as u64 > heap_max { heap_max = heap.data.len() as u64; }
-                relaxations += 1;
-            }
+impl BucketRing {
+    fn reset(&mut self) {
+        if self.ring.len() != BUCKET_RING_WIDTH { self.ring.resize_with(BUCKET_RING_WIDTH, Vec::new); }
+        for b in self.ring.iter_mut() { b.clear(); }
+        while let Some((_, mut b)) = self.far.pop_first() { b.clear(); self.spare.push(b); }
+        self.base = 0; self.live = 0; self.peak_live = 0; self.max_index = 0;
+    }
+    #[inline(always)] fn note(&mut self, idx: usize) { if idx > self.max_index { self.max_index = idx; } }
+    #[inline(always)] fn push(&mut self, idx: usize, v: u32) {
+        if idx < self.base { return; }
+        let b = if idx < self.base + BUCKET_RING_WIDTH { &mut self.ring[idx % BUCKET_RING_WIDTH] } else { let spare = &mut self.spare; self.far.entry(idx).or_insert_with(|| spare.pop().unwrap_or_default()) };
+        if b.is_empty() { self.live += 1; if self.live > self.peak_live { self.peak_live = self.live; } }
+        b.push(v);
+    }
+    #[inline(always)] fn current_is_empty(&self) -> bool { self.ring[self.base % BUCKET_RING_WIDTH].is_empty() }
+    // Swaps the current bucket's contents into `out` (`out` must be empty; its
+    // capacity stays with the slot).
+    #[inline(always)] fn take_current(&mut self, out: &mut Vec<u32>) {
+        let slot = &mut self.ring[self.base % BUCKET_RING_WIDTH];
+        if !slot.is_empty() { self.live -= 1; }
+        core::mem::swap(out, slot);
+    }
+    // Moves the window one bucket on, pulling the newly covered far bucket into the
+    // recycled slot.
+    fn advance(&mut self) {
+        let slot = self.base % BUCKET_RING_WIDTH;
+        if !self.ring[slot].is_empty() { self.live -= 1; self.ring[slot].clear(); }
+        self.base += 1;
+        if let Some(mut b) = self.far.remove(&(self.base + BUCKET_RING_WIDTH - 1)) {
+            core::mem::swap(&mut b, &mut self.ring[slot]);
+            self.spare.push(b);
         }
     }
+}
 
-    if !info.is_null() { unsafe { *info = SsspResultInfo { relaxations, light_relaxations, heavy_relaxations, settled: n, error_code: 0 }; } }
-    unsafe { LAST_BASELINE_HEAP_STATS = BaselineHeapStats { pushes: heap_pushes, pops: heap_pops, max_size: heap_max }; }
-    0
+// Per-run buffers of the STOC loop. Fresh for one-shot entry points; an
+// SsspContext keeps one alive so repeated queries reuse the allocations. `reset`
+// clears without freeing: far buckets return to the ring's spare pool.
+#[derive(Default)]
+struct StocScratch {
+    buckets: BucketRing,
+    in_bucket: Vec<bool>,
+    settled: Vec<bool>,
+    frontier: Vec<u32>,
+    light_set: Vec<u32>,
+    // Nodes settled per bucket index in the current attempt; None = not tracked.
+    hist: Option<Vec<u32>>,
+}
+
+impl StocScratch {
+    fn reset(&mut self, n: usize) {
+        self.buckets.reset();
+        self.in_bucket.clear(); self.in_bucket.resize(n, false);
+        self.settled.clear(); self.settled.resize(n, false);
+        if let Some(h) = self.hist.as_mut() { h.clear(); }
+    }
 }
 
+// Reusable STOC solver state for many queries on graphs of one size `n`: the
+// scratch buffers survive between `sssp_run_stoc_ctx` calls, so only the first
+// query pays for their allocation. A context is not thread-safe; use one per thread.
+pub struct SsspContext { n: u32, scratch: StocScratch }
+
 #[no_mangle]
-pub extern "C" fn sssp_version() -> u32 { 4 } // incremented due to SsspResultInfo breaking change
+pub extern "C" fn sssp_context_new(n: u32) -> *mut SsspContext {
+    ffi_guard(core::ptr::null_mut(), || {
+        let mut scratch = StocScratch::default();
+        scratch.reset(n as usize);
+        Box::into_raw(Box::new(SsspContext { n, scratch }))
+    })
+}
 
-// ---------------- STOC-inspired (delta-stepping style) variant ----------------
-// This implements a simplified delta-stepping algorithm (Meyer & Sanders) often
-// used as a practical foundation for layering / bucket approaches referenced in
-// later theoretical STOC-style improvements. We expose it under the name
-// `sssp_run_stoc` per user request, though it is the classical delta-stepping
-// core (single-threaded here).
-// Key idea: partition edges into light (w <= delta) and heavy (w > delta).
-// Process buckets i in increasing order of floor(dist/delta). For each bucket:
-//  1. Repeatedly settle nodes reachable via light edges within the bucket.
-//  2. Afterwards relax heavy edges from those settled nodes, inserting targets
-//     into future buckets. This reduces priority queue operations to simple
-//     bucket insertions and batches many light-edge relaxations.
-// Expected benefit appears on graphs with many small weights creating clusters
-// per distance band; on random sparse graphs overhead may still dominate.
 #[no_mangle]
-pub extern "C" fn sssp_run_stoc(
+pub extern "C" fn sssp_context_free(ctx: *mut SsspContext) {
+    if !ctx.is_null() { unsafe { drop(Box::from_raw(ctx)); } }
+}
+
+// `sssp_run_stoc` reusing `ctx`'s buffers (env-configured like `sssp_run_stoc`).
+// Returns -3 for a null context and -8 if `n` differs from the context's size.
+#[no_mangle]
+pub extern "C" fn sssp_run_stoc_ctx(
+    ctx: *mut SsspContext,
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+) -> i32 {
+    ffi_guard(-99, || {
+        if ctx.is_null() { return -3; }
+        let ctx = unsafe { &mut *ctx };
+        if n != ctx.n { return -8; }
+        stoc_run_adaptive(n, offsets, targets, weights, source, out_dist, out_pred, info, core::ptr::null_mut(), core::ptr::null_mut(), &StocConfig::from_env(), &mut ctx.scratch, core::ptr::null_mut(), RunOpts::checked())
+    })
+}
+
+// `sssp_run_stoc_ctx` with per-call bucket stats (see `sssp_run_stoc_stats`).
+#[no_mangle]
+pub extern "C" fn sssp_run_stoc_ctx_stats(
+    ctx: *mut SsspContext,
     n: u32,
     offsets: *const u32,
     targets: *const u32,
@@ -191,6 +1209,57 @@ pub extern "C" fn sssp_run_stoc(
     out_dist: *mut f32,
     out_pred: *mut i32,
     info: *mut SsspResultInfo,
+    bucket_stats: *mut SsspBucketStats,
+) -> i32 {
+    ffi_guard(-99, || {
+        if ctx.is_null() { return -3; }
+        let ctx = unsafe { &mut *ctx };
+        if n != ctx.n { return -8; }
+        stoc_run_adaptive(n, offsets, targets, weights, source, out_dist, out_pred, info, core::ptr::null_mut(), core::ptr::null_mut(), &StocConfig::from_env(), &mut ctx.scratch, bucket_stats, RunOpts::checked())
+    })
+}
+
+// Adaptive delta-stepping core shared by the f32 STOC entry points; `out_phase` and
+// `timing` may be null.
+fn stoc_run_adaptive<O: CsrOffset>(
+    n: u32,
+    offsets: *const O,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+    out_phase: *mut u8,
+    timing: *mut SsspTiming,
+    cfg: &StocConfig,
+    scratch: &mut StocScratch,
+    bucket_stats: *mut SsspBucketStats,
+    opts: RunOpts,
+) -> i32 {
+    stoc_run_adaptive_weights(n, offsets, targets, weights, |w| w, source, out_dist, out_pred, info, out_phase, timing, cfg, scratch, bucket_stats, opts)
+}
+
+// Same loop over a weight encoding decoded per edge by `weight_of` into the distance
+// type `W` (f64 and the i32 -> i64 runner in variants.rs). Label-correcting: a node
+// whose distance drops is queued again in its new bucket, including the bucket being
+// drained, and copies left behind in higher buckets are skipped as stale when popped.
+pub(crate) fn stoc_run_adaptive_weights<O: CsrOffset, E: Copy, W: Weight, G: Fn(E) -> W>(
+    n: u32,
+    offsets: *const O,
+    targets: *const u32,
+    weights: *const E,
+    weight_of: G,
+    source: u32,
+    out_dist: *mut W,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+    out_phase: *mut u8,
+    timing: *mut SsspTiming,
+    cfg: &StocConfig,
+    scratch: &mut StocScratch,
+    bucket_stats: *mut SsspBucketStats,
+    opts: RunOpts,
 ) -> i32 {
     if n == 0 { return -1; }
     if source >= n { return -2; }
@@ -198,187 +1267,204 @@ pub extern "C" fn sssp_run_stoc(
 
     let n_usize = n as usize;
     let off = as_slice(offsets, n_usize + 1);
-    let m = match off.last() { Some(v) => *v as usize, None => return -4 };
+    let m = match off.last() { Some(v) => v.idx(), None => return -4 };
     let tgt = as_slice(targets, m);
     let wts = as_slice(weights, m);
+    if opts.check_weights { let rc = precheck_weights_by(wts, &weight_of, info); if rc != 0 { return rc; } }
     let dist = as_mut_slice(out_dist, n_usize);
     let pred = as_mut_slice(out_pred, n_usize);
+    let mut phase: Option<&mut [u8]> = if out_phase.is_null() { None } else { Some(as_mut_slice(out_phase, n_usize)) };
+    let mut clock = PhaseClock::start(timing);
+    let canonical = opts.canonical;
 
-    for d in dist.iter_mut() { *d = f32::INFINITY; }
+    for d in dist.iter_mut() { *d = W::INFINITY; }
     for p in pred.iter_mut() { *p = -1; }
-    dist[source as usize] = 0.0;
+    dist[source as usize] = W::ZERO;
 
     // Delta selection strategies: "avg" (default) or "quantile".
-    fn sample_weights(wts: &[f32], cap: usize) -> Vec<f32> {
-        let m = wts.len();
-        let take = cap.min(m);
-        let mut out = Vec::with_capacity(take);
-        for i in 0..take { out.push(unsafe { *wts.get_unchecked(i) }); }
-        out
-    }
-    let mode = std::env::var("SSSP_STOC_DELTA_MODE").unwrap_or_else(|_| "avg".to_string());
-    let heavy_target_raw: f32 = std::env::var("SSSP_STOC_HEAVY_TARGET").ok().and_then(|v| v.parse().ok()).unwrap_or(0.15);
-    let heavy_target: f32 = heavy_target_raw.max(0.01).min(0.9);
-    let mult_env: Option<f32> = std::env::var("SSSP_STOC_DELTA_MULT").ok().and_then(|v| v.parse().ok());
-    let choose_delta = || -> f32 {
-        if mode == "quantile" {
-            let mut samp = sample_weights(wts, 5000);
-            if samp.is_empty() { return 1.0; }
+    let heavy_target: f32 = cfg.heavy_target.max(0.01).min(0.9);
+    let mult_cfg: Option<f32> = if cfg.delta_mult > 0.0 { Some(cfg.delta_mult) } else { None };
+    let choose_delta = || -> W {
+        if cfg.delta_mode == STOC_DELTA_QUANTILE {
+            let mut samp: Vec<f64> = weight_sample(wts, 5000).map(|w| weight_of(w).to_f64()).collect();
+            if samp.is_empty() { return W::delta_from(1.0); }
             samp.sort_by(|a,b| a.partial_cmp(b).unwrap());
             let q_index = ((samp.len()-1) as f32 * (1.0 - heavy_target)).round() as usize;
             let base = samp[q_index].max(1e-4);
-            let mult = mult_env.unwrap_or(1.0);
-            (base * mult).clamp(1e-4, 1e6)
+            let mult = mult_cfg.unwrap_or(1.0) as f64;
+            W::delta_from(base * mult)
         } else {
-            // avg mode
-            let sample = core::cmp::min(1000, m);
-            let mut avg = 1.0f32;
-            if sample > 0 { let mut s = 0.0; for i in 0..sample { s += unsafe { *wts.get_unchecked(i) }; } avg = s / sample as f32; if avg <= 0.0 { avg = 1.0; } }
-            let mult = mult_env.unwrap_or(3.0);
-            (avg * mult).clamp(1e-4, 1e6)
+            // avg mode: mean of up to 1000 sampled weights, 1.0 if empty or non-positive
+            let (mut s, mut c) = (0.0f64, 0u32);
+            for w in weight_sample(wts, core::cmp::min(1000, m)) { s += weight_of(w).to_f64(); c += 1; }
+            let avg = if c == 0 || s <= 0.0 { 1.0 } else { s / c as f64 };
+            let mult = mult_cfg.unwrap_or(3.0) as f64;
+            W::delta_from(avg * mult)
         }
     };
 
-    let adaptive_max: u32 = std::env::var("SSSP_STOC_ADAPT_MAX_RESTARTS").ok().and_then(|v| v.parse().ok()).unwrap_or(4);
-    // Dynamic trigger ~ log2(n)/2 bounded [3,40]
-    let logn = (n as f32).ln().max(1.0);
-    let adapt_trigger_buckets: u32 = std::env::var("SSSP_STOC_ADAPT_TRIGGER")
-        .ok().and_then(|v| v.parse().ok())
-        .unwrap_or_else(|| {
-            let est = (logn / 2.0) as u32;
-            est.clamp(3,40)
-        });
-    let heavy_min_raw: f32 = std::env::var("SSSP_STOC_HEAVY_MIN_RATIO").ok().and_then(|v| v.parse().ok()).unwrap_or(0.05);
+    let adaptive_max: u32 = cfg.adapt_max_restarts;
+    let adapt_trigger_buckets: u32 = if cfg.adapt_trigger == 0 { stoc_default_adapt_trigger(n) } else { cfg.adapt_trigger };
+    let heavy_min_raw: f32 = cfg.heavy_min_ratio;
     let heavy_min: f32 = if heavy_min_raw < 0.0 {0.0} else if heavy_min_raw > 0.9 {0.9} else { heavy_min_raw };
-    let heavy_max_raw: f32 = std::env::var("SSSP_STOC_HEAVY_MAX_RATIO").ok().and_then(|v| v.parse().ok()).unwrap_or(0.25);
+    let heavy_max_raw: f32 = cfg.heavy_max_ratio;
     let mut heavy_max: f32 = if heavy_max_raw < heavy_min + 0.01 { heavy_min + 0.01 } else { heavy_max_raw };
     if heavy_max > 0.95 { heavy_max = 0.95; }
     let mut restarts: u32 = 0;
     let adapt_trace = std::env::var("SSSP_STOC_ADAPT_TRACE").ok().map(|v| v=="1" || v.to_lowercase()=="true").unwrap_or(false);
-    // Will hold (relax, light, heavy, settled, buckets_visited, light_repeat_total, bucket_cap)
-    let final_stats: Option<(u64,u64,u64,u32,u32,u32,usize)>; // will be set before break
+    let final_stats: Option<StocRunStats>;
+    let mut overflow: bool; // any saturated u32 tally in the final attempt
     let mut delta = choose_delta();
+    clock.init_done();
     loop {
         // Run with current delta
-        let inv_delta = 1.0f32 / delta;
-    let mut buckets: Vec<Vec<u32>> = Vec::new();
-    // Heuristic reserve to reduce reallocs on early growth (light clustering typical)
-    buckets.reserve((n_usize/64).max(32));
-        let mut in_bucket: Vec<bool> = vec![false; n_usize];
-        let mut settled: Vec<bool> = vec![false; n_usize];
+        let inv_delta = 1.0 / delta.to_f64();
+        scratch.reset(n_usize);
+        let StocScratch { buckets, in_bucket, settled, frontier, light_set, hist } = &mut *scratch;
         let mut relaxations: u64 = 0;
         let mut light_relax: u64 = 0;
         let mut heavy_relax: u64 = 0;
         let mut settled_count: u32 = 0;
-        #[inline(always)] fn ensure_bucket(buckets: &mut Vec<Vec<u32>>, idx: usize) { if idx >= buckets.len() { buckets.resize_with(idx + 1, Vec::new); } }
-        #[inline(always)] fn bucket_of(dist: f32, inv_delta: f32) -> usize { (dist * inv_delta) as usize }
-        ensure_bucket(&mut buckets, 0);
-        buckets[0].push(source);
+        buckets.push(0, source);
         in_bucket[source as usize] = true;
         let mut current_bucket = 0usize;
         let max_bucket_cap = 4 * n_usize + 1024;
         let mut buckets_visited: u32 = 0;
         let mut light_repeat_total: u32 = 0;
-        for d in dist.iter_mut() { *d = f32::INFINITY; }
+        overflow = false;
+        let mut restarted = false; // attempt abandoned mid-run for a delta change
+        for d in dist.iter_mut() { *d = W::INFINITY; }
         for p in pred.iter_mut() { *p = -1; }
-        dist[source as usize] = 0.0;
-        while current_bucket < buckets.len() {
-            if buckets[current_bucket].is_empty() { current_bucket += 1; continue; }
-            buckets_visited += 1;
+        dist[source as usize] = W::ZERO;
+        if let Some(ph) = phase.as_deref_mut() { for t in ph.iter_mut() { *t = PHASE_FALLBACK; } ph[source as usize] = PHASE_LIGHT; }
+        while buckets.live > 0 {
+            if buckets.current_is_empty() { buckets.advance(); current_bucket += 1; continue; }
+            sat_bump(&mut buckets_visited, &mut overflow);
             let mut request_light_repeat = true;
-            let mut light_set: Vec<u32> = Vec::new();
+            light_set.clear();
             while request_light_repeat {
-                light_repeat_total += 1;
+                sat_bump(&mut light_repeat_total, &mut overflow);
                 request_light_repeat = false;
-                let frontier: Vec<u32> = core::mem::take(&mut buckets[current_bucket]);
-                for &u_raw in &frontier { in_bucket[u_raw as usize] = false; }
+                // Swap the bucket out (it keeps the previous frontier's capacity).
+                frontier.clear(); buckets.take_current(frontier);
+                for &u_raw in frontier.iter() { in_bucket[u_raw as usize] = false; }
                 if frontier.is_empty() { break; }
-                for &u_raw in &frontier {
+                for &u_raw in frontier.iter() {
                     let u = u_raw as usize;
-                    if settled[u] { continue; }
-                    settled[u] = true; settled_count += 1;
-                    light_set.push(u_raw);
-                    let start = off[u] as usize; let end = off[u+1] as usize;
                     let base = dist[u];
+                    // Stale copy: the node has since moved to a lower bucket.
+                    if base.bucket(delta, inv_delta) != current_bucket { continue; }
+                    if !settled[u] {
+                        settled[u] = true; settled_count += 1;
+                        if let Some(h) = hist.as_mut() { if h.len() <= current_bucket { h.resize(current_bucket + 1, 0); } h[current_bucket] = h[current_bucket].saturating_add(1); }
+                        light_set.push(u_raw);
+                    }
+                    let start = off[u].idx(); let end = off[u+1].idx();
                     for e in start..end {
                         let v = unsafe { *tgt.get_unchecked(e) } as usize;
-                        let w = unsafe { *wts.get_unchecked(e) };
+                        if v == u { continue; } // self-loop: zero progress
+                        let w = weight_of(unsafe { *wts.get_unchecked(e) });
                         if w <= delta { // light edge
                             let nd = base + w;
                             let cur = unsafe { *dist.get_unchecked(v) };
                             if nd < cur {
                                 unsafe { *dist.get_unchecked_mut(v) = nd; *pred.get_unchecked_mut(v) = u as i32; }
-                                let b = bucket_of(nd, inv_delta);
+                                if let Some(ph) = phase.as_deref_mut() { ph[v] = PHASE_LIGHT; }
+                                let b = nd.bucket(delta, inv_delta);
                                 if b > max_bucket_cap { return -5; }
-                                ensure_bucket(&mut buckets, b);
-                                if !in_bucket[v] && !settled[v] { buckets[b].push(v as u32); in_bucket[v] = true; request_light_repeat |= b == current_bucket; }
+                                buckets.note(b);
+                                // Queue unless v already waits in bucket b.
+                                if !in_bucket[v] || cur.bucket(delta, inv_delta) != b { buckets.push(b, v as u32); in_bucket[v] = true; request_light_repeat |= b == current_bucket; }
                                 relaxations += 1; light_relax += 1;
+                            } else if canonical && canonical_tie(base, nd, cur, u as u32, pred[v]) {
+                                pred[v] = u as i32;
                             }
                         }
                     }
                 }
             }
             // Phase 2 heavy
-            for &u_raw in &light_set {
+            for &u_raw in light_set.iter() {
                 let u = u_raw as usize;
-                let start = off[u] as usize; let end = off[u+1] as usize; let base = dist[u];
+                let start = off[u].idx(); let end = off[u+1].idx(); let base = dist[u];
                 for e in start..end {
                     let v = unsafe { *tgt.get_unchecked(e) } as usize;
-                    let w = unsafe { *wts.get_unchecked(e) };
+                    if v == u { continue; }
+                    let w = weight_of(unsafe { *wts.get_unchecked(e) });
                     if w > delta {
                         let nd = base + w; let cur = unsafe { *dist.get_unchecked(v) };
                         if nd < cur {
                             unsafe { *dist.get_unchecked_mut(v) = nd; *pred.get_unchecked_mut(v) = u as i32; }
-                            let b = bucket_of(nd, inv_delta);
+                            if let Some(ph) = phase.as_deref_mut() { ph[v] = PHASE_HEAVY; }
+                            let b = nd.bucket(delta, inv_delta);
                             if b > max_bucket_cap { return -5; }
-                            ensure_bucket(&mut buckets, b);
-                            if !in_bucket[v] && !settled[v] { buckets[b].push(v as u32); in_bucket[v] = true; }
+                            buckets.note(b);
+                            if !in_bucket[v] || cur.bucket(delta, inv_delta) != b { buckets.push(b, v as u32); in_bucket[v] = true; }
                             relaxations += 1; heavy_relax += 1;
+                        } else if canonical && canonical_tie(base, nd, cur, u as u32, pred[v]) {
+                            pred[v] = u as i32;
                         }
                     }
                 }
             }
-            current_bucket += 1;
+            buckets.advance(); current_bucket += 1;
             // Adaptive restart / adjust conditions
             if buckets_visited >= adapt_trigger_buckets {
                 let heavy_ratio = if relaxations==0 {0.0} else { heavy_relax as f32 / relaxations as f32 };
                 if heavy_relax == 0 && restarts < adaptive_max {
                     // shrink delta to create heavy edges
-                    let old = delta; delta *= 0.5;
+                    let old = delta.to_f64(); delta = W::delta_from(old * 0.5);
                     restarts += 1;
-                    if adapt_trace { eprintln!("[stoc-adapt] restart={} action=shrink_zero heavy_relax=0 old_delta={:.6} new_delta={:.6}", restarts, old, delta); }
-                    break; // restart
+                    if adapt_trace { eprintln!("[stoc-adapt] restart={} action=shrink_zero heavy_relax=0 old_delta={:.6} new_delta={:.6}", restarts, old, delta.to_f64()); }
+                    restarted = true; break; // restart
                 } else if heavy_ratio < heavy_min && restarts < adaptive_max {
-                    let old = delta; delta *= 0.7; // small shrink
+                    let old = delta.to_f64(); delta = W::delta_from(old * 0.7); // small shrink
                     restarts += 1;
-                    if adapt_trace { eprintln!("[stoc-adapt] restart={} action=shrink heavy_ratio={:.4} min={} old_delta={:.6} new_delta={:.6}", restarts, heavy_ratio, heavy_min, old, delta); }
-                    break;
+                    if adapt_trace { eprintln!("[stoc-adapt] restart={} action=shrink heavy_ratio={:.4} min={} old_delta={:.6} new_delta={:.6}", restarts, heavy_ratio, heavy_min, old, delta.to_f64()); }
+                    restarted = true; break;
                 } else if heavy_ratio > heavy_max && restarts < adaptive_max {
-                    let old = delta; delta *= 1.5; // expand to reduce heavy churn
+                    let old = delta.to_f64(); delta = W::delta_from(old * 1.5); // expand to reduce heavy churn
                     restarts += 1;
-                    if adapt_trace { eprintln!("[stoc-adapt] restart={} action=expand heavy_ratio={:.4} max={} old_delta={:.6} new_delta={:.6}", restarts, heavy_ratio, heavy_max, old, delta); }
-                    break;
+                    if adapt_trace { eprintln!("[stoc-adapt] restart={} action=expand heavy_ratio={:.4} max={} old_delta={:.6} new_delta={:.6}", restarts, heavy_ratio, heavy_max, old, delta.to_f64()); }
+                    restarted = true; break;
                 }
             }
         }
-        // If we broke due to adjustment (restarts incremented) continue loop
-        if restarts > 0 && (relaxations == 0 || (buckets_visited >= adapt_trigger_buckets && restarts <= adaptive_max && (heavy_relax == 0 || {
-            let r = heavy_relax as f32 / relaxations.max(1) as f32; r < heavy_min || r > heavy_max
-        }))) {
-            if restarts <= adaptive_max { continue; }
-        }
-        final_stats = Some((relaxations, light_relax, heavy_relax, settled_count, buckets_visited, light_repeat_total, buckets.len()));
-        unsafe { LAST_DELTA = delta; }
+        // If we broke due to adjustment (restarts incremented) continue loop. Keyed on the
+        // abandoned attempt itself: re-deriving it from the counters could loop forever
+        // once the budget was spent, or accept an abandoned attempt's partial tree.
+        if restarted { continue; }
+        final_stats = Some((relaxations, light_relax, heavy_relax, settled_count, buckets_visited, light_repeat_total, buckets.max_index + 1, buckets.peak_live));
+        LAST_DELTA.with(|d| d.set(delta.to_f64() as f32));
         break;
     }
 
-    let (relaxations, light_relax, heavy_relax, settled_count, buckets_visited, light_repeat_total, bucket_len) = final_stats.expect("final_stats must be set before loop break");
-    if !info.is_null() { unsafe { *info = SsspResultInfo { relaxations, light_relaxations: light_relax, heavy_relaxations: heavy_relax, settled: settled_count, error_code: 0 }; } }
+    clock.loop_done();
+    let (relaxations, light_relax, heavy_relax, settled_count, buckets_visited, light_repeat_total, bucket_len, peak_live) = match final_stats {
+        Some(s) => s,
+        None => { overflow = false; stoc_fallback_stats(off, tgt, wts, &weight_of, source, dist, pred) }
+    };
+    if !info.is_null() { unsafe { *info = SsspResultInfo { relaxations, light_relaxations: light_relax, heavy_relaxations: heavy_relax, settled: settled_count, error_code: 0, overflow: overflow as u32, reachable: count_reachable(dist), edges: tgt.len() as u64 }; } }
     let heavy_ratio_x1000 = if relaxations==0 {0} else { ((heavy_relax as f64 / relaxations as f64)*1000.0) as u32 };
-    unsafe { LAST_BUCKET_STATS = SsspBucketStats { buckets_visited, light_pass_repeats: light_repeat_total, max_bucket_index: (bucket_len.saturating_sub(1)) as u32, restarts, delta_x1000: (LAST_DELTA * 1000.0) as u32, heavy_ratio_x1000 }; }
+    let stats = SsspBucketStats { buckets_visited, light_pass_repeats: light_repeat_total, max_bucket_index: (bucket_len.saturating_sub(1)) as u32, restarts, delta_x1000: (delta.to_f64() * 1000.0) as u32, heavy_ratio_x1000, peak_live_buckets: peak_live };
+    LAST_BUCKET_STATS.with(|s| s.set(stats));
+    if !bucket_stats.is_null() { unsafe { *bucket_stats = stats; } }
+    clock.finalize_done();
     0
 }
 
+// Totals of one completed adaptive attempt: (relax, light, heavy, settled,
+// buckets_visited, light_repeat_total, bucket_cap, peak_live).
+type StocRunStats = (u64, u64, u64, u32, u32, u32, usize, u32);
+
+// Defensive exit for the adaptive loop: if it ever leaves without a completed attempt
+// (dist/pred would be partial), recompute exactly with the baseline heap instead of
+// panicking at the ABI boundary. All edges count as light; bucket tallies are zero.
+fn stoc_fallback_stats<O: CsrOffset, E: Copy, W: Weight, G: Fn(E) -> W>(off: &[O], tgt: &[u32], wts: &[E], weight_of: G, source: u32, dist: &mut [W], pred: &mut [i32]) -> StocRunStats {
+    let c = dijkstra_with_weights(off, tgt, wts, weight_of, source, dist, pred, |_, _| true);
+    (c.relaxations, c.relaxations, 0, c.settled, 0, 0, 0, 0)
+}
+
 // ------------------- Light / Heavy getter helpers (C ABI) -------------------
 #[no_mangle]
 pub extern "C" fn sssp_info_light_relaxations(info: *const SsspResultInfo) -> u64 {
@@ -390,24 +1476,151 @@ pub extern "C" fn sssp_info_heavy_relaxations(info: *const SsspResultInfo) -> u6
     if info.is_null() { return 0; }
     unsafe { (*info).heavy_relaxations }
 }
+#[no_mangle]
+pub extern "C" fn sssp_info_overflow(info: *const SsspResultInfo) -> u32 {
+    if info.is_null() { return 0; }
+    unsafe { (*info).overflow }
+}
+
+// Self-check for runner instrumentation. Runner-independent invariants only:
+// STOC has light + heavy == relaxations and the baseline reports both as zero, so
+// the common bound is light + heavy <= relaxations.
+//   0  consistent
+//  -3  null info
+// -30  light_relaxations + heavy_relaxations > relaxations (or overflows u64)
+// -31  settled > n
+// -32  relaxations > 0 but nothing settled (the source is always settled first)
+#[no_mangle]
+pub extern "C" fn sssp_check_info_consistency(info: *const SsspResultInfo, n: u32) -> i32 {
+    ffi_guard(-99, || {
+        if info.is_null() { return -3; }
+        let i = unsafe { &*info };
+        match i.light_relaxations.checked_add(i.heavy_relaxations) { Some(lh) if lh <= i.relaxations => {}, _ => return -30 }
+        if i.settled > n { return -31; }
+        if i.relaxations > 0 && i.settled == 0 { return -32; }
+        0
+    })
+}
 
 // ------------------- Autotuned STOC (delta-stepping) -----------------------
 // Tries a set of delta multipliers on a truncated run (settling up to a limit
 // of nodes) and then executes the fastest multiplier on the full graph.
 // Candidate set can be overridden via env: SSSP_STOC_AUTOTUNE_SET="1.5,2,3,4,6".
 // Truncation limit (nodes) via env: SSSP_STOC_AUTOTUNE_LIMIT (default 2048).
+// Stopping condition via env: SSSP_STOC_AUTOTUNE_TRUNC=settled|relax|edges (default settled).
+//  - settled: stop after LIMIT nodes are settled.
+//  - relax / edges: stop after LIMIT * ceil(m/n) successful relaxations / scanned
+//    out-edges, the work of settling about LIMIT average-degree nodes. A probe
+//    that starts in a huge early bucket spends that budget on the bucket and
+//    settles far fewer nodes than `settled` would let it.
+//  Which predicts the full run best: on random (avg degree 4, 8), 550^2 grid and
+//  scale-free (3, 8 links per node) graphs of 200k-300k nodes, full-run times of
+//  the multipliers 2..6 were within ~25% of each other and 1.5 was the slowest.
+//  With METRIC=relax, `relax` picked 2 or 3 on every non-lattice graph (within
+//  ~10% of the fastest; unit-weight grid runs tie, so 1.5 wins the tie-break),
+//  while `settled` and `edges` picked 1.5 on scale-free graphs, where a hub drains
+//  a settle or edge budget far faster than the useful relaxations. With
+//  METRIC=time, timer noise dominated every mode. `relax` is therefore the better
+//  predictor; `settled` stays the default so existing tunings do not move.
+// Probe placement via env: SSSP_STOC_AUTOTUNE_PROBE=source|spread (default source).
+//  - source: one truncated run from the query source (its neighborhood only).
+//  - spread: the budget is split across the source plus
+//    SSSP_STOC_AUTOTUNE_PROBES-1 (default 8 total) seeds drawn uniformly over the
+//    whole id range (xorshift64*, SSSP_STOC_AUTOTUNE_SEED), so the timing reflects
+//    the global structure when low-id or source-local regions are atypical.
+// Scoring via env: SSSP_STOC_AUTOTUNE_METRIC=time|relax (default time).
+//  - time: wall-clock time of the truncated runs (machine- and load-dependent).
+//  - relax: their total relaxation count, fully deterministic for a given graph,
+//    source and candidate set, for reproducible benchmarks.
+// Under either metric ties break toward the smaller multiplier.
 use std::time::Instant;
 
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum AutotuneMetric { Time, Relax }
+
+// Where a truncated calibration run stops; budgets are totals across its probes.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum StocStop { Settled(u64), Relaxations(u64), EdgesScanned(u64) }
+
+impl StocStop {
+    fn budget(self) -> u64 { match self { StocStop::Settled(b) | StocStop::Relaxations(b) | StocStop::EdgesScanned(b) => b } }
+    fn with_budget(self, b: u64) -> Self { match self { StocStop::Settled(_) => StocStop::Settled(b), StocStop::Relaxations(_) => StocStop::Relaxations(b), StocStop::EdgesScanned(_) => StocStop::EdgesScanned(b) } }
+    // One probe's share when the budget is split across `probes` seeds (at least 1).
+    fn per_probe(self, probes: usize) -> Self { self.with_budget((self.budget() / probes.max(1) as u64).max(1)) }
+    #[inline(always)]
+    fn reached(self, settled: u32, relaxations: u64, scanned: u64) -> bool {
+        match self { StocStop::Settled(b) => settled as u64 >= b, StocStop::Relaxations(b) => relaxations >= b, StocStop::EdgesScanned(b) => scanned >= b }
+    }
+}
+
+// SSSP_STOC_AUTOTUNE_TRUNC with the `limit`-node budget scaled to edge work for
+// relax / edges (see the section comment).
+fn autotune_stop_from_env(limit: u32, n: u32, m: usize) -> StocStop {
+    let per_node = (m as u64).div_ceil(n.max(1) as u64).max(1);
+    match std::env::var("SSSP_STOC_AUTOTUNE_TRUNC").as_deref() {
+        Ok("relax") => StocStop::Relaxations(limit as u64 * per_node),
+        Ok("edges") => StocStop::EdgesScanned(limit as u64 * per_node),
+        _ => StocStop::Settled(limit as u64),
+    }
+}
+
+fn autotune_metric_from_env() -> AutotuneMetric {
+    match std::env::var("SSSP_STOC_AUTOTUNE_METRIC").as_deref() { Ok("relax") => AutotuneMetric::Relax, _ => AutotuneMetric::Time }
+}
+
 fn parse_autotune_set() -> Vec<f32> {
     if let Ok(v) = std::env::var("SSSP_STOC_AUTOTUNE_SET") { return v.split(',').filter_map(|s| s.trim().parse().ok()).filter(|x:&f32| *x>0.0).collect(); }
     vec![1.5, 2.0, 3.0, 4.0, 6.0]
 }
 
+// Probe start nodes: `source` first, then `probes-1` deterministic uniform draws.
+fn autotune_probe_seeds(n: u32, source: u32, probes: u32, seed: u64) -> Vec<u32> {
+    let mut seeds = Vec::with_capacity(probes.max(1) as usize);
+    seeds.push(source);
+    let mut rng = gen::XorShift64Star::new(seed | 1);
+    for _ in 1..probes.max(1) { seeds.push(rng.next_u32() % n); }
+    seeds
+}
+
+fn autotune_probe_seeds_from_env(n: u32, source: u32) -> Vec<u32> {
+    let spread = std::env::var("SSSP_STOC_AUTOTUNE_PROBE").map(|v| v == "spread").unwrap_or(false);
+    if !spread { return vec![source]; }
+    let probes: u32 = std::env::var("SSSP_STOC_AUTOTUNE_PROBES").ok().and_then(|v| v.parse().ok()).unwrap_or(8);
+    let seed: u64 = std::env::var("SSSP_STOC_AUTOTUNE_SEED").ok().and_then(|v| v.parse().ok()).unwrap_or(0x9E37_79B9_7F4A_7C15);
+    autotune_probe_seeds(n, source, probes, seed)
+}
+
+// Scores each candidate multiplier over truncated runs from every probe seed (the
+// budget of `stop` is split evenly) and returns the best-scoring multiplier.
+fn autotune_best_mult(n: u32, off: &[u32], tgt: &[u32], wts: &[f32], seeds: &[u32], candidates: &[f32], stop: StocStop, metric: AutotuneMetric, delta_for: impl Fn(f32) -> f32) -> f32 {
+    let per_seed = stop.per_probe(seeds.len());
+    let mut best_mult = candidates[0]; let mut best_score = f64::INFINITY;
+    let mut tmp_dist = vec![0f32; n as usize]; let mut tmp_pred = vec![0i32; n as usize];
+    'cand: for &mult in candidates {
+        let delta = delta_for(mult);
+        let start = Instant::now();
+        let mut relax_total = 0u64;
+        for &s in seeds { let (r,_l,_h,_s,err) = stoc_run_internal(n, off, tgt, wts, s, delta, &mut tmp_dist, &mut tmp_pred, Some(per_seed)); if err != 0 { continue 'cand; } relax_total += r; }
+        let score = match metric { AutotuneMetric::Time => start.elapsed().as_secs_f64(), AutotuneMetric::Relax => relax_total as f64 };
+        if score < best_score || (score == best_score && mult < best_mult) { best_score = score; best_mult = mult; }
+    }
+    best_mult
+}
+
+// Deterministic sample of at most `cap` weights at a uniform stride over the whole
+// edge array (every ceil(m/cap)-th edge from 0; all of them when m <= cap). A prefix
+// sample only sees the lowest ids' edges, which is biased when adjacency is grouped
+// by source node.
+pub(crate) fn weight_sample<W: Copy>(wts: &[W], cap: usize) -> impl Iterator<Item = W> + '_ {
+    wts.iter().step_by(wts.len().div_ceil(cap.max(1)).max(1)).copied()
+}
+
+// Mean of `weight_sample(wts, sample)`; 1.0 for an empty sample or a non-positive mean.
 #[inline(always)]
-fn derive_avg_weight(sample: usize, wts: &[f32]) -> f32 {
-    if sample == 0 { return 1.0; }
-    let mut s = 0.0; for i in 0..sample { unsafe { s += *wts.get_unchecked(i); } }
-    let mut avg = s / sample as f32; if avg <= 0.0 { avg = 1.0; }
+pub(crate) fn derive_avg_weight(sample: usize, wts: &[f32]) -> f32 {
+    if sample == 0 || wts.is_empty() { return 1.0; }
+    let (mut s, mut c) = (0.0f32, 0u32); for w in weight_sample(wts, sample) { s += w; c += 1; }
+    let mut avg = s / c as f32; if avg <= 0.0 { avg = 1.0; }
     avg
 }
 
@@ -416,36 +1629,38 @@ fn stoc_run_internal(
     off: &[u32], tgt: &[u32], wts: &[f32], source: u32,
     delta: f32,
     dist: &mut [f32], pred: &mut [i32],
-    truncate_after: Option<u32>,
+    stop: Option<StocStop>,
 ) -> (u64,u64,u64,u32,i32) {
     let n_usize = n as usize;
     for d in dist.iter_mut() { *d = f32::INFINITY; }
     for p in pred.iter_mut() { *p = -1; }
     dist[source as usize] = 0.0;
     let inv_delta = 1.0f32 / delta;
-    let mut buckets: Vec<Vec<u32>> = Vec::new();
+    let mut buckets = BucketRing::default(); buckets.reset();
+    let mut frontier: Vec<u32> = Vec::new();
     let mut in_bucket: Vec<bool> = vec![false; n_usize];
     let mut settled: Vec<bool> = vec![false; n_usize];
     let mut relaxations: u64 = 0; let mut light_relax: u64 = 0; let mut heavy_relax: u64 = 0; let mut settled_count: u32 = 0;
-    #[inline(always)] fn ensure_bucket(buckets: &mut Vec<Vec<u32>>, idx: usize) { if idx >= buckets.len() { buckets.resize_with(idx + 1, Vec::new); } }
+    let mut scanned: u64 = 0; // out-edges of settled nodes
+    let stopped = |settled_count: u32, relaxations: u64, scanned: u64| stop.is_some_and(|s| s.reached(settled_count, relaxations, scanned));
     #[inline(always)] fn bucket_of(dist: f32, inv_delta: f32) -> usize { (dist * inv_delta) as usize }
-    ensure_bucket(&mut buckets,0); buckets[0].push(source); in_bucket[source as usize] = true;
+    buckets.push(0, source); in_bucket[source as usize] = true;
     let mut current_bucket = 0usize; let max_bucket_cap = 4 * n_usize + 1024;
-    while current_bucket < buckets.len() {
-        if buckets[current_bucket].is_empty() { current_bucket += 1; continue; }
+    while buckets.live > 0 {
+        if buckets.current_is_empty() { buckets.advance(); current_bucket += 1; continue; }
         let mut request_light_repeat = true; let mut light_set: Vec<u32> = Vec::new();
     while request_light_repeat {
-            request_light_repeat = false; let frontier: Vec<u32> = core::mem::take(&mut buckets[current_bucket]); for &u_raw in &frontier { in_bucket[u_raw as usize] = false; }
+            request_light_repeat = false; frontier.clear(); buckets.take_current(&mut frontier); for &u_raw in &frontier { in_bucket[u_raw as usize] = false; }
             if frontier.is_empty() { break; }
-            for &u_raw in &frontier { let u = u_raw as usize; if settled[u] { continue; } settled[u] = true; settled_count += 1; light_set.push(u_raw); let start = off[u] as usize; let end = off[u+1] as usize; let base = dist[u];
-                for e in start..end { let v = unsafe { *tgt.get_unchecked(e) } as usize; let w = unsafe { *wts.get_unchecked(e) }; if w <= delta { let nd = base + w; let cur = unsafe { *dist.get_unchecked(v) }; if nd < cur { unsafe { *dist.get_unchecked_mut(v) = nd; *pred.get_unchecked_mut(v) = u as i32; } let b = bucket_of(nd, inv_delta); if b > max_bucket_cap { return (relaxations, light_relax, heavy_relax, settled_count, -5); } ensure_bucket(&mut buckets,b); if !in_bucket[v] && !settled[v] { buckets[b].push(v as u32); in_bucket[v] = true; request_light_repeat |= b == current_bucket; } relaxations += 1; light_relax += 1; } } }
-                if let Some(limit) = truncate_after { if settled_count >= limit { break; } }
+            for &u_raw in &frontier { let u = u_raw as usize; if settled[u] { continue; } settled[u] = true; settled_count += 1; light_set.push(u_raw); let start = off[u] as usize; let end = off[u+1] as usize; let base = dist[u]; scanned += (end - start) as u64;
+                for e in start..end { let v = unsafe { *tgt.get_unchecked(e) } as usize; if v == u { continue; } let w = unsafe { *wts.get_unchecked(e) }; if w <= delta { let nd = base + w; let cur = unsafe { *dist.get_unchecked(v) }; if nd < cur { unsafe { *dist.get_unchecked_mut(v) = nd; *pred.get_unchecked_mut(v) = u as i32; } let b = bucket_of(nd, inv_delta); if b > max_bucket_cap { return (relaxations, light_relax, heavy_relax, settled_count, -5); } buckets.note(b); if !in_bucket[v] && !settled[v] { buckets.push(b, v as u32); in_bucket[v] = true; request_light_repeat |= b == current_bucket; } relaxations += 1; light_relax += 1; } } }
+                if stopped(settled_count, relaxations, scanned) { break; }
             }
-            if let Some(limit) = truncate_after { if settled_count >= limit { break; } }
+            if stopped(settled_count, relaxations, scanned) { break; }
         }
-        for &u_raw in &light_set { let u = u_raw as usize; let start = off[u] as usize; let end = off[u+1] as usize; let base = dist[u]; for e in start..end { let v = unsafe { *tgt.get_unchecked(e) } as usize; let w = unsafe { *wts.get_unchecked(e) }; if w > delta { let nd = base + w; let cur = unsafe { *dist.get_unchecked(v) }; if nd < cur { unsafe { *dist.get_unchecked_mut(v) = nd; *pred.get_unchecked_mut(v) = u as i32; } let b = bucket_of(nd, inv_delta); if b > max_bucket_cap { return (relaxations, light_relax, heavy_relax, settled_count, -5); } ensure_bucket(&mut buckets,b); if !in_bucket[v] && !settled[v] { buckets[b].push(v as u32); in_bucket[v] = true; } relaxations += 1; heavy_relax += 1; } } } }
-        if let Some(limit) = truncate_after { if settled_count >= limit { break; } }
-        current_bucket += 1;
+        for &u_raw in &light_set { let u = u_raw as usize; let start = off[u] as usize; let end = off[u+1] as usize; let base = dist[u]; for e in start..end { let v = unsafe { *tgt.get_unchecked(e) } as usize; if v == u { continue; } let w = unsafe { *wts.get_unchecked(e) }; if w > delta { let nd = base + w; let cur = unsafe { *dist.get_unchecked(v) }; if nd < cur { unsafe { *dist.get_unchecked_mut(v) = nd; *pred.get_unchecked_mut(v) = u as i32; } let b = bucket_of(nd, inv_delta); if b > max_bucket_cap { return (relaxations, light_relax, heavy_relax, settled_count, -5); } buckets.note(b); if !in_bucket[v] && !settled[v] { buckets.push(b, v as u32); in_bucket[v] = true; } relaxations += 1; heavy_relax += 1; } } } }
+        if stopped(settled_count, relaxations, scanned) { break; }
+        buckets.advance(); current_bucket += 1;
     }
     (relaxations, light_relax, heavy_relax, settled_count, 0)
 }
@@ -461,23 +1676,24 @@ pub extern "C" fn sssp_run_stoc_autotune(
     out_pred: *mut i32,
     info: *mut SsspResultInfo,
 ) -> i32 {
-    if n == 0 { return -1; }
-    if source >= n { return -2; }
-    if offsets.is_null() || targets.is_null() || weights.is_null() || out_dist.is_null() || out_pred.is_null() { return -3; }
-    let n_usize = n as usize; let off = as_slice(offsets, n_usize + 1); let m = match off.last() { Some(v) => *v as usize, None => return -4 }; let tgt = as_slice(targets, m); let wts = as_slice(weights, m);
-    let dist = as_mut_slice(out_dist, n_usize); let pred = as_mut_slice(out_pred, n_usize);
-    let sample = core::cmp::min(1000, m); let avg = derive_avg_weight(sample, wts);
-    let candidates = { let mut c = parse_autotune_set(); if c.is_empty() { c.push(3.0); } c };
-    let limit: u32 = std::env::var("SSSP_STOC_AUTOTUNE_LIMIT").ok().and_then(|v| v.parse().ok()).unwrap_or(2048).min(n);
-    let mut best_mult = candidates[0]; let mut best_time = f64::INFINITY;
-    let mut tmp_dist = vec![0f32; n_usize]; let mut tmp_pred = vec![0i32; n_usize];
-    for &mult in &candidates { let delta = (avg * mult).clamp(0.0001, 1e6); let start = Instant::now(); let (_r,_l,_h,_s,err) = stoc_run_internal(n, off, tgt, wts, source, delta, &mut tmp_dist, &mut tmp_pred, Some(limit)); if err != 0 { continue; } let elapsed = start.elapsed().as_secs_f64(); if elapsed < best_time { best_time = elapsed; best_mult = mult; } }
-    let final_delta = (avg * best_mult).clamp(0.0001, 1e6);
-    let (relax, light, heavy, settled, err) = stoc_run_internal(n, off, tgt, wts, source, final_delta, dist, pred, None);
-    if err != 0 { return err; }
-    if !info.is_null() { unsafe { *info = SsspResultInfo { relaxations: relax, light_relaxations: light, heavy_relaxations: heavy, settled, error_code: 0 }; } }
-    // Autotune internal run does not update global stats; only final full run instrumentation performed via LAST_BUCKET_STATS in sssp_run_stoc.
-    0
+    ffi_guard(-99, || {
+        if n == 0 { return -1; }
+        if source >= n { return -2; }
+        if offsets.is_null() || targets.is_null() || weights.is_null() || out_dist.is_null() || out_pred.is_null() { return -3; }
+        let n_usize = n as usize; let off = as_slice(offsets, n_usize + 1); let m = match off.last() { Some(v) => *v as usize, None => return -4 }; let tgt = as_slice(targets, m); let wts = as_slice(weights, m);
+        let dist = as_mut_slice(out_dist, n_usize); let pred = as_mut_slice(out_pred, n_usize);
+        let sample = core::cmp::min(1000, m); let avg = derive_avg_weight(sample, wts);
+        let candidates = { let mut c = parse_autotune_set(); if c.is_empty() { c.push(3.0); } c };
+        let limit: u32 = std::env::var("SSSP_STOC_AUTOTUNE_LIMIT").ok().and_then(|v| v.parse().ok()).unwrap_or(2048).min(n);
+        let seeds = autotune_probe_seeds_from_env(n, source);
+        let best_mult = autotune_best_mult(n, off, tgt, wts, &seeds, &candidates, autotune_stop_from_env(limit, n, m), autotune_metric_from_env(), |mult| (avg * mult).clamp(0.0001, 1e6));
+        let final_delta = (avg * best_mult).clamp(0.0001, 1e6);
+        let (relax, light, heavy, settled, err) = stoc_run_internal(n, off, tgt, wts, source, final_delta, dist, pred, None);
+        if err != 0 { return err; }
+        if !info.is_null() { unsafe { *info = SsspResultInfo { relaxations: relax, light_relaxations: light, heavy_relaxations: heavy, settled, error_code: 0, overflow: 0, reachable: count_reachable(dist), edges: tgt.len() as u64 }; } }
+        // Autotune internal run does not update global stats; only final full run instrumentation performed via LAST_BUCKET_STATS in sssp_run_stoc.
+        0
+    })
 }
 
 // Unified: autotune to pick initial delta multiplier, then run adaptive STOC loop (same as sssp_run_stoc logic).
@@ -492,6 +1708,27 @@ pub extern "C" fn sssp_run_stoc_auto_adapt(
     out_dist: *mut f32,
     out_pred: *mut i32,
     info: *mut SsspResultInfo,
+) -> i32 {
+    ffi_guard(-99, || {
+        let candidates = { let mut c = parse_autotune_set(); if c.is_empty() { c.push(3.0); } c };
+        stoc_auto_adapt_core(n, offsets, targets, weights, source, out_dist, out_pred, info, &candidates, StocConfig::from_env())
+    })
+}
+
+// Body of `sssp_run_stoc_auto_adapt` with the candidate set and config passed in: the
+// tuned multiplier reaches the adaptive loop through `cfg.delta_mult`, never the
+// process env, so concurrent callers cannot see each other's choice.
+fn stoc_auto_adapt_core(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+    candidates: &[f32],
+    mut cfg: StocConfig,
 ) -> i32 {
     if n == 0 { return -1; }
     if source >= n { return -2; }
@@ -499,43 +1736,35 @@ pub extern "C" fn sssp_run_stoc_auto_adapt(
     let n_usize = n as usize; let off = as_slice(offsets, n_usize + 1); let m = match off.last() { Some(v) => *v as usize, None => return -4 };
     let tgt = as_slice(targets, m); let wts = as_slice(weights, m);
     let sample = core::cmp::min(1000, m); let avg = derive_avg_weight(sample, wts);
-    let candidates = { let mut c = parse_autotune_set(); if c.is_empty() { c.push(3.0); } c };
     let limit: u32 = std::env::var("SSSP_STOC_AUTOTUNE_LIMIT").ok().and_then(|v| v.parse().ok()).unwrap_or(2048).min(n);
-    let mode = std::env::var("SSSP_STOC_DELTA_MODE").unwrap_or_else(|_| "avg".to_string());
+    let quantile = cfg.delta_mode == STOC_DELTA_QUANTILE;
     // Helper to derive initial delta for a multiplier under current mode.
-    let base_quantile = if mode == "quantile" {
+    let base_quantile = if quantile {
         // Sample & pick quantile similarly to sssp_run_stoc (but without heavy_target multiplier yet).
-        let heavy_target_raw: f32 = std::env::var("SSSP_STOC_HEAVY_TARGET").ok().and_then(|v| v.parse().ok()).unwrap_or(0.15);
-        let heavy_target = heavy_target_raw.max(0.01).min(0.9);
-        let mut samp: Vec<f32> = {
-            let take = core::cmp::min(5000, m);
-            let mut v = Vec::with_capacity(take);
-            for i in 0..take { v.push(unsafe { *wts.get_unchecked(i) }); }
-            v
-        };
+        let heavy_target = cfg.heavy_target.max(0.01).min(0.9);
+        let mut samp: Vec<f32> = weight_sample(wts, 5000).collect();
         if samp.is_empty() { 1.0 } else { samp.sort_by(|a,b| a.partial_cmp(b).unwrap()); let q_index = ((samp.len()-1) as f32 * (1.0 - heavy_target)).round() as usize; samp[q_index].max(1e-4) }
     } else { 0.0 }; // unused in avg mode
-    let mut best_mult = candidates[0]; let mut best_time = f64::INFINITY; let mut tmp_dist = vec![0f32; n_usize]; let mut tmp_pred = vec![0i32; n_usize];
-    for &mult in &candidates {
-        let delta = if mode == "quantile" { (base_quantile * mult).clamp(1e-4, 1e6) } else { (avg * mult).clamp(1e-4, 1e6) };
-        let start = Instant::now();
-        let (_r,_l,_h,_s,err) = stoc_run_internal(n, off, tgt, wts, source, delta, &mut tmp_dist, &mut tmp_pred, Some(limit));
-        if err != 0 { continue; }
-        let elapsed = start.elapsed().as_secs_f64();
-        if elapsed < best_time { best_time = elapsed; best_mult = mult; }
-    }
-    // Temporarily set multiplier env if not already set so sssp_run_stoc starts from our seed.
-    let env_key = "SSSP_STOC_DELTA_MULT";
-    let prev = std::env::var(env_key).ok();
-    if prev.is_none() { std::env::set_var(env_key, format!("{}", best_mult)); }
-    let rc = sssp_run_stoc(n, offsets, targets, weights, source, out_dist, out_pred, info);
-    // Restore previous env state.
-    if prev.is_none() { std::env::remove_var(env_key); }
-    rc
+    let seeds = autotune_probe_seeds_from_env(n, source);
+    let best_mult = autotune_best_mult(n, off, tgt, wts, &seeds, candidates, autotune_stop_from_env(limit, n, m), autotune_metric_from_env(), |mult| {
+        if quantile { (base_quantile * mult).clamp(1e-4, 1e6) } else { (avg * mult).clamp(1e-4, 1e6) }
+    });
+    // Start the adaptive loop from the tuned multiplier unless one was pinned.
+    if cfg.delta_mult <= 0.0 { cfg.delta_mult = best_mult; }
+    stoc_run_adaptive(n, offsets, targets, weights, source, out_dist, out_pred, info, core::ptr::null_mut(), core::ptr::null_mut(), &cfg, &mut StocScratch::default(), core::ptr::null_mut(), RunOpts::checked())
 }
 
 mod spec_clean; // specification phased implementation module
 mod spec_future; // scaffolding for upcoming phases (no exported symbols yet)
+mod variants; // alternative runners (reliability, ratio paths, partitioned BSP, ALT, label-correcting, ...)
+mod graph_ops; // CSR transforms (spanner, reachable subgraph, union, zero-weight SCCs, coarsening, MST, ...)
+mod queries; // derived queries over one baseline run (farthest node, ...)
+mod simd; // vectorized relaxation filter for the baseline loop (feature "simd")
+mod prefetch; // adjacency prefetch hints for the baseline loop (feature "prefetch")
+mod io; // DIMACS reader, binary CSR files and the zero-copy (mmap) baseline over them
+pub mod gen; // deterministic graph generators (random, path, star, grid) for benchmarks and tests
+#[cfg(feature = "parallel")]
+mod parallel; // multi-threaded delta-stepping (feature "parallel")
 
 // Re-export selected spec phase symbols for direct crate-root access in tests / FFI users.
 pub use spec_clean::{
@@ -548,6 +1777,7 @@ pub use spec_clean::{
     sssp_get_spec_phase3_stats,
     sssp_get_spec_boundary_chain_stats,
     sssp_get_spec_invariant_stats,
+    SpecInvariantStats,
 };
 pub use spec_future::{
     sssp_run_spec_recursive,
@@ -558,3 +1788,709 @@ pub use spec_future::{
     SpecRecursionStats,
     SpecRecursionFrameDetail,
 };
+pub use variants::{
+    sssp_run_most_reliable,
+    sssp_run_baseline_partitioned,
+    sssp_alt_preprocess,
+    sssp_run_alt,
+    sssp_run_alt_batch,
+    sssp_run_label_correcting,
+    sssp_run_baseline_u16,
+    sssp_run_ratio_path,
+    sssp_run_baseline_sampled_degree,
+    sssp_run_avoiding,
+    sssp_run_node_penalty,
+    sssp_run_interval,
+    SsspHeapCmp,
+    sssp_run_baseline_cb,
+    sssp_run_baseline_batch,
+    sssp_run_auto,
+    sssp_get_auto_choice,
+    SSSP_AUTO_BASELINE,
+    SSSP_AUTO_STOC,
+    SsspSettleFn,
+    sssp_run_baseline_cmp,
+    sssp_run_baseline_multi,
+    sssp_run_time_windows,
+    sssp_run_baseline_to,
+    sssp_run_baseline_radius,
+    sssp_run_baseline_radius_to,
+    sssp_run_bidijkstra,
+    sssp_run_astar,
+    sssp_run_grid,
+    sssp_node_deleted,
+    sssp_run_baseline_min_hops,
+    sssp_run_stoc_i32,
+    sssp_run_baseline_f64,
+    sssp_run_stoc_f64,
+    sssp_run_bellman_ford,
+    sssp_run_spfa,
+};
+pub use graph_ops::{
+    sssp_build_spanner,
+    sssp_reachable_subgraph,
+    sssp_reachable_mask,
+    sssp_merge_csr,
+    sssp_symmetrize_csr,
+    sssp_transpose_csr,
+    sssp_reorder_bfs,
+    sssp_contract_zero_sccs,
+    sssp_reduced_cost,
+    sssp_coarsen,
+    sssp_mst_prim,
+};
+pub use queries::{
+    sssp_run_baseline_farthest,
+    sssp_eccentricity_all,
+    sssp_avg_distance,
+    sssp_distances_to_targets,
+    sssp_service_cost,
+    sssp_run_via_waypoint,
+    sssp_extract_path,
+    sssp_extract_tree,
+    sssp_spt_band_crossings,
+    sssp_spt_dominators,
+    sssp_run_baseline_within,
+    sssp_run_baseline_potentials,
+    sssp_run_baseline_first_target,
+    sssp_kth_nearest_dist,
+    sssp_wiener_partial,
+    SsspMetrics,
+    sssp_run_baseline_metrics,
+    sssp_effective_diameter,
+    sssp_run_baseline_growth,
+    SsspTreeCache,
+    sssp_tree_cache_new,
+    sssp_tree_cache_free,
+    sssp_tree_cache_query,
+    sssp_tree_cache_stats,
+};
+
+#[cfg(feature = "parallel")]
+pub use parallel::sssp_run_stoc_parallel;
+pub use io::{
+    sssp_write_csr,
+    sssp_run_baseline_mmap,
+    sssp_save_csr,
+    sssp_load_csr,
+    load_dimacs_gr,
+    CsrGraph,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn result_info_layout_matches_bindings(){
+        // Offsets the C#, Go and Python structs hard-code.
+        use core::mem::{offset_of, size_of};
+        assert_eq!([offset_of!(SsspResultInfo, relaxations), offset_of!(SsspResultInfo, light_relaxations), offset_of!(SsspResultInfo, heavy_relaxations), offset_of!(SsspResultInfo, settled), offset_of!(SsspResultInfo, error_code), offset_of!(SsspResultInfo, overflow), offset_of!(SsspResultInfo, reachable), offset_of!(SsspResultInfo, edges)], [0, 8, 16, 24, 28, 32, 36, 40]);
+        assert_eq!(size_of::<SsspResultInfo>(), 48);
+    }
+
+    #[test]
+    fn saturating_counter_raises_overflow_flag_instead_of_wrapping(){
+        // Mocked counter parked just below the limit: bumps must pin at u32::MAX, never wrap to 0.
+        let mut c: u32 = u32::MAX - 1; let mut overflow = false;
+        sat_bump(&mut c, &mut overflow); assert_eq!(c, u32::MAX); assert!(!overflow);
+        sat_bump(&mut c, &mut overflow); assert_eq!(c, u32::MAX); assert!(overflow);
+        sat_bump(&mut c, &mut overflow); assert_eq!(c, u32::MAX);
+        let info = SsspResultInfo { relaxations:0, light_relaxations:0, heavy_relaxations:0, settled:0, error_code:0, overflow: overflow as u32, reachable: 0, edges: 0 };
+        assert_eq!(sssp_info_overflow(&info), 1);
+        // Ordinary runs never saturate.
+        let off=[0u32,1,1]; let tgt=[1u32]; let wts=[1.0f32]; let mut dist=[0f32;2]; let mut pred=[0i32;2];
+        let mut info = SsspResultInfo { relaxations:0, light_relaxations:0, heavy_relaxations:0, settled:0, error_code:0, overflow:7, reachable:0, edges:0 };
+        assert_eq!(sssp_run_stoc(2, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info), 0);
+        assert_eq!(info.overflow, 0);
+    }
+
+    #[test]
+    fn auto_adapt_threads_with_different_candidate_sets_do_not_interfere(){
+        // Bidirectional grid with small integer weights.
+        let mut g = crate::gen::grid_graph(60, 1.0); let n = g.n;
+        for v in 0..n { for e in g.offsets[v as usize] as usize..g.offsets[v as usize + 1] as usize { g.weights[e] = 1.0 + ((g.targets[e] ^ v) % 4) as f32; } }
+        let (off, tgt, wts) = (g.offsets, g.targets, g.weights);
+        let (mut want, mut wp) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+        sssp_run_baseline(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, want.as_mut_ptr(), wp.as_mut_ptr(), core::ptr::null_mut());
+        let (off, tgt, wts) = (&off, &tgt, &wts);
+        let results: Vec<(i32, Vec<f32>)> = std::thread::scope(|sc| {
+            let handles: Vec<_> = [vec![1.5f32], vec![6.0f32, 4.0]].into_iter().map(|set| sc.spawn(move || {
+                let mut out = Vec::new();
+                for _ in 0..20 {
+                    let (mut d, mut p) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+                    let rc = stoc_auto_adapt_core(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d.as_mut_ptr(), p.as_mut_ptr(), core::ptr::null_mut(), &set, StocConfig::default());
+                    out.push((rc, d));
+                }
+                out
+            })).collect();
+            handles.into_iter().flat_map(|h| h.join().unwrap()).collect()
+        });
+        for (rc, d) in results { assert_eq!(rc, 0); assert_eq!(d, want); }
+        assert!(std::env::var("SSSP_STOC_DELTA_MULT").is_err());
+    }
+
+    #[test]
+    fn stoc_max_restarts_always_yields_final_stats(){
+        // Unit-weight path: delta 3 -> all light (shrink) -> 1.5 -> 0.75 all heavy (expand) -> ...
+        // keeps tripping the adaptive check until the restart budget is spent.
+        let n = 200u32; let off: Vec<u32> = (0..=n).map(|i| i.min(n - 1)).collect();
+        let tgt: Vec<u32> = (1..n).collect(); let wts = vec![1.0f32; tgt.len()];
+        let (mut d1, mut p1) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+        let (mut d2, mut p2) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+        let mut info = SsspResultInfo { relaxations:0, light_relaxations:0, heavy_relaxations:0, settled:0, error_code:0, overflow:0, reachable:0, edges:0 };
+        assert_eq!(sssp_run_stoc(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d1.as_mut_ptr(), p1.as_mut_ptr(), &mut info), 0);
+        assert_eq!(sssp_run_baseline(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d2.as_mut_ptr(), p2.as_mut_ptr(), core::ptr::null_mut()), 0);
+        assert_eq!(d1, d2); assert_eq!(info.settled, n); assert_eq!(info.relaxations, (n - 1) as u64);
+        // The defensive path (no completed attempt) recomputes the same tree from scratch.
+        for d in d1.iter_mut() { *d = 1.0; }
+        let s = stoc_fallback_stats(&off, &tgt, &wts, |w| w, 0, &mut d1, &mut p1);
+        assert_eq!(d1, d2); assert_eq!(p1, p2); assert_eq!((s.0, s.3), ((n - 1) as u64, n));
+    }
+
+    #[test]
+    fn u64_offset_runners_match_u32(){
+        let n = 300u32;
+        let g = crate::gen::random_graph_in(n, 2.5, 0x51AFD7ED558CCD1F, 0.1, 10.0); let (off, tgt, wts) = (g.offsets, g.targets, g.weights);
+        let off64: Vec<u64> = off.iter().map(|&o| o as u64).collect();
+        let empty = || SsspResultInfo { relaxations:0, light_relaxations:0, heavy_relaxations:0, settled:0, error_code:0, overflow:0, reachable:0, edges:0 };
+        let (mut d32, mut p32, mut i32_) = (vec![0f32; n as usize], vec![0i32; n as usize], empty());
+        let (mut d64, mut p64, mut i64_) = (vec![0f32; n as usize], vec![0i32; n as usize], empty());
+        assert_eq!(sssp_run_baseline(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 7, d32.as_mut_ptr(), p32.as_mut_ptr(), &mut i32_), 0);
+        assert_eq!(sssp_run_baseline64(n, off64.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 7, d64.as_mut_ptr(), p64.as_mut_ptr(), &mut i64_), 0);
+        assert_eq!((&d32, &p32, i32_.relaxations), (&d64, &p64, i64_.relaxations));
+        assert_eq!(sssp_run_stoc(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 7, d32.as_mut_ptr(), p32.as_mut_ptr(), &mut i32_), 0);
+        assert_eq!(sssp_run_stoc64(n, off64.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 7, d64.as_mut_ptr(), p64.as_mut_ptr(), &mut i64_), 0);
+        assert_eq!((&d32, i32_.relaxations, i32_.settled), (&d64, i64_.relaxations, i64_.settled));
+        assert_eq!(sssp_run_baseline64(n, core::ptr::null(), tgt.as_ptr(), wts.as_ptr(), 7, d64.as_mut_ptr(), p64.as_mut_ptr(), &mut i64_), -3);
+    }
+
+    #[test]
+    fn classify_edges_splits_at_delta_inclusive(){
+        let wts = [0.0f32, 0.5, 1.0, 1.0001, 3.0, f32::NAN];
+        let mut class = [9u8; 6];
+        assert_eq!(sssp_classify_edges(wts.as_ptr(), 6, 1.0, class.as_mut_ptr()), 0);
+        assert_eq!(class, [0, 0, 0, 1, 1, 1]);
+        assert_eq!(sssp_classify_edges(wts.as_ptr(), 6, 0.0, class.as_mut_ptr()), -7);
+        assert_eq!(sssp_classify_edges(wts.as_ptr(), 6, f32::NAN, class.as_mut_ptr()), -7);
+    }
+
+    #[test]
+    fn non_finite_weights_rejected_with_minus_7(){
+        let off = [0u32,2,3,3]; let tgt = [1u32,2,2];
+        let (mut dist, mut pred) = ([0f32;3], [0i32;3]);
+        for bad in [f32::NAN, f32::INFINITY] {
+            let wts = [1.0f32, 4.0, bad];
+            assert_eq!(sssp_validate_weights(wts.as_ptr(), 3), -7);
+            assert_eq!(sssp_run_baseline(3, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, dist.as_mut_ptr(), pred.as_mut_ptr(), core::ptr::null_mut()), -7);
+            assert_eq!(sssp_run_stoc(3, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, dist.as_mut_ptr(), pred.as_mut_ptr(), core::ptr::null_mut()), -7);
+        }
+        // First offender decides; -inf is negative.
+        assert_eq!(sssp_validate_weights([f32::NEG_INFINITY, f32::NAN].as_ptr(), 2), -6);
+        assert_eq!(sssp_validate_weights([f32::NAN, -1.0].as_ptr(), 2), -7);
+        // f32::MAX is valid input; the overflowing sum leaves node 2 unreached rather than wrong.
+        let (off2, tgt2, wts2) = ([0u32,1,2,2], [1u32,2], [f32::MAX, f32::MAX]);
+        assert_eq!(sssp_validate_weights(wts2.as_ptr(), 2), 0);
+        assert_eq!(sssp_run_baseline(3, off2.as_ptr(), tgt2.as_ptr(), wts2.as_ptr(), 0, dist.as_mut_ptr(), pred.as_mut_ptr(), core::ptr::null_mut()), 0);
+        assert_eq!(dist[1], f32::MAX);
+        assert!(dist[2].is_infinite());
+        assert_eq!(sssp_validate_weights(core::ptr::null(), 0), 0);
+    }
+
+    #[test]
+    fn negative_weights_rejected_unless_check_disabled(){
+        let off = [0u32,2,3,3]; let tgt = [1u32,2,2]; let mut wts = [1.0f32,4.0,-2.0];
+        let (mut dist, mut pred) = ([0f32;3], [0i32;3]);
+        let mut info = SsspResultInfo { relaxations:9, light_relaxations:0, heavy_relaxations:0, settled:9, error_code:0, overflow:0, reachable:0, edges:0 };
+        assert_eq!(sssp_run_baseline(3, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info), -6);
+        assert_eq!((info.error_code, info.settled), (-6, 0));
+        assert_eq!(sssp_run_stoc(3, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info), -6);
+        wts[2] = -0.0;
+        assert_eq!(sssp_run_baseline(3, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info), 0);
+        assert_eq!(dist[2], 1.0);
+        // Opting out skips the scan (the result is then the caller's responsibility).
+        wts[2] = -2.0;
+        assert_eq!(sssp_run_baseline_unchecked(3, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info), 0);
+        assert_eq!(sssp_run_stoc_unchecked(3, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info), 0);
+        // The checked entry points are unaffected by the unchecked calls.
+        assert_eq!(sssp_run_baseline(3, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info), -6);
+    }
+
+    #[test]
+    fn baseline_check_counts_out_of_order_pops(){
+        // 0->1 (1), 0->2 (5), 2->1 (-4.5): 1 is settled at 1, then re-popped at 0.5 after 2 at 5.
+        let off = [0u32,2,2,3]; let tgt = [1u32,2,1]; let wts = [1.0f32,5.0,-4.5];
+        let mut dist = [0f32;3]; let mut pred = [0i32;3];
+        let mut stats = SpecInvariantStats::default();
+        let run = |w: &[f32], opts: RunOpts, dist: &mut [f32], pred: &mut [i32]| baseline_run(3, off.as_ptr(), tgt.as_ptr(), w.as_ptr(), 0, dist.as_mut_ptr(), pred.as_mut_ptr(), core::ptr::null_mut(), core::ptr::null_mut(), core::ptr::null_mut(), opts);
+        let good = [1.0f32,5.0,4.5];
+        assert_eq!(run(&good, RunOpts { check_weights: true, check_order: true, canonical: false }, &mut dist, &mut pred), 0);
+        sssp_get_baseline_invariant_stats(&mut stats);
+        assert_eq!((stats.checks, stats.failures), (3, 0));
+        assert_eq!(run(&wts, RunOpts { check_weights: false, check_order: true, canonical: false }, &mut dist, &mut pred), 0);
+        sssp_get_baseline_invariant_stats(&mut stats);
+        assert_eq!((stats.checks, stats.failures), (4, 1));
+    }
+
+    #[test]
+    fn canonical_pred_ignores_insertion_order(){
+        // Unit grid: almost every node has two equally short predecessors, and reversing
+        // each adjacency list changes which one the heap settles first.
+        let side = 9u32;
+        let g = crate::gen::grid_graph(side, 1.0);
+        let mut r = g.clone();
+        for u in 0..g.n as usize { let (a, b) = (g.offsets[u] as usize, g.offsets[u + 1] as usize); r.targets[a..b].reverse(); r.weights[a..b].reverse(); }
+        let opts = RunOpts { canonical: true, ..RunOpts::checked() };
+        let base = |g: &CsrGraph| { let (mut d, mut p) = (vec![0f32; g.n as usize], vec![0i32; g.n as usize]); assert_eq!(baseline_run(g.n, g.offsets.as_ptr(), g.targets.as_ptr(), g.weights.as_ptr(), 0, d.as_mut_ptr(), p.as_mut_ptr(), core::ptr::null_mut(), core::ptr::null_mut(), core::ptr::null_mut(), opts), 0); (d, p) };
+        let ((gd, gp), (rd, rp)) = (base(&g), base(&r));
+        let (mut sd, mut sp) = (vec![0f32; r.n as usize], vec![0i32; r.n as usize]);
+        assert_eq!(stoc_run_adaptive(r.n, r.offsets.as_ptr(), r.targets.as_ptr(), r.weights.as_ptr(), 0, sd.as_mut_ptr(), sp.as_mut_ptr(), core::ptr::null_mut(), core::ptr::null_mut(), core::ptr::null_mut(), &StocConfig::default(), &mut StocScratch::default(), core::ptr::null_mut(), opts), 0);
+        assert_eq!((&gd, &gp), (&rd, &rp));
+        assert_eq!((&gd, &gp), (&sd, &sp));
+        let (mut id, mut ip) = (vec![0f32; r.n as usize], vec![0i32; r.n as usize]);
+        assert_eq!(baseline_run_indexed(r.n, r.offsets.as_ptr(), r.targets.as_ptr(), r.weights.as_ptr(), 0, id.as_mut_ptr(), ip.as_mut_ptr(), core::ptr::null_mut(), core::ptr::null_mut(), opts), 0);
+        assert_eq!((&gd, &gp), (&id, &ip));
+        // The canonical choice is the smallest tight predecessor: up, else left.
+        for v in 1..g.n { let (row, col) = (v / side, v % side); assert_eq!(gp[v as usize], if row > 0 { (v - side) as i32 } else { (col - 1) as i32 }); }
+    }
+
+    #[test]
+    fn stoc_requeues_nodes_improved_after_leaving_bucket(){
+        // Wide weights with a large delta: nodes are often removed from a bucket before
+        // a cheaper light path reaches them, and moved down from far buckets.
+        let g = crate::gen::random_graph_in(3000, 4.0, 0x1267, 0.1, 50.0);
+        let cfg = StocConfig { delta_mult: 8.0, adapt_max_restarts: 0, ..StocConfig::default() };
+        let (mut bd, mut bp) = (vec![0f32; g.n as usize], vec![0i32; g.n as usize]);
+        let (mut sd, mut sp) = (vec![0f32; g.n as usize], vec![0i32; g.n as usize]);
+        let mut info = SsspResultInfo { relaxations: 0, light_relaxations: 0, heavy_relaxations: 0, settled: 0, error_code: 0, overflow: 0, reachable: 0, edges: 0 };
+        assert_eq!(baseline_run(g.n, g.offsets.as_ptr(), g.targets.as_ptr(), g.weights.as_ptr(), 0, bd.as_mut_ptr(), bp.as_mut_ptr(), core::ptr::null_mut(), core::ptr::null_mut(), core::ptr::null_mut(), RunOpts::checked()), 0);
+        assert_eq!(stoc_run_adaptive(g.n, g.offsets.as_ptr(), g.targets.as_ptr(), g.weights.as_ptr(), 0, sd.as_mut_ptr(), sp.as_mut_ptr(), &mut info, core::ptr::null_mut(), core::ptr::null_mut(), &cfg, &mut StocScratch::default(), core::ptr::null_mut(), RunOpts::checked()), 0);
+        assert_eq!(sd, bd);
+        assert_eq!(info.settled, info.reachable);
+    }
+
+    #[test]
+    fn delta_sample_spans_whole_edge_array(){
+        // The first 100 nodes own the first 1000 edges, all weight 100; the other 1100
+        // nodes' 11000 edges weigh 1. True mean 9.25; a prefix sample would see 100.
+        let n = 1200u32; let mut off = vec![0u32]; let mut tgt = Vec::new(); let mut wts = Vec::new();
+        for u in 0..n { for k in 1..=10 { tgt.push((u + k) % n); wts.push(if u < 100 { 100.0 } else { 1.0 }); } off.push(tgt.len() as u32); }
+        assert!((derive_avg_weight(1000, &wts) - 9.25).abs() < 0.5);
+        let (mut d, mut p) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+        let mut stats = SsspBucketStats { buckets_visited: 0, light_pass_repeats: 0, max_bucket_index: 0, restarts: 0, delta_x1000: 0, heavy_ratio_x1000: 0, peak_live_buckets: 0 };
+        let avg = StocConfig { adapt_max_restarts: 0, ..StocConfig::default() };
+        assert_eq!(sssp_run_stoc_cfg_stats(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d.as_mut_ptr(), p.as_mut_ptr(), core::ptr::null_mut(), &avg, &mut stats), 0);
+        assert!((20_000..40_000).contains(&stats.delta_x1000), "delta_x1000 = {}", stats.delta_x1000);
+        let quantile = StocConfig { delta_mode: STOC_DELTA_QUANTILE, ..avg };
+        assert_eq!(sssp_run_stoc_cfg_stats(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d.as_mut_ptr(), p.as_mut_ptr(), core::ptr::null_mut(), &quantile, &mut stats), 0);
+        assert_eq!(stats.delta_x1000, 1000);
+    }
+
+    #[test]
+    fn detailed_histogram_sums_to_settled_and_caps_at_len(){
+        let n = 300u32; let off: Vec<u32> = (0..=n).map(|i| i.min(n - 1)).collect();
+        let tgt: Vec<u32> = (1..n).collect(); let wts: Vec<f32> = (1..n).map(|i| 1.0 + (i % 3) as f32).collect();
+        let (mut d, mut p) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+        let mut info = SsspResultInfo { relaxations:0, light_relaxations:0, heavy_relaxations:0, settled:0, error_code:0, overflow:0, reachable:0, edges:0 };
+        let mut stats = SsspBucketStats { buckets_visited: 0, light_pass_repeats: 0, max_bucket_index: 0, restarts: 0, delta_x1000: 0, heavy_ratio_x1000: 0, peak_live_buckets: 0 };
+        let mut hist = vec![u32::MAX; 4096];
+        assert_eq!(sssp_run_stoc_detailed(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d.as_mut_ptr(), p.as_mut_ptr(), &mut info, &mut stats, hist.as_mut_ptr(), hist.len() as u32), 0);
+        let max = stats.max_bucket_index as usize;
+        assert_eq!(hist.iter().map(|&c| c as u64).sum::<u64>(), info.settled as u64);
+        assert!(hist[max + 1..].iter().all(|&c| c == 0));
+        assert!(max >= 4);
+        // A short array gets the prefix and the same max index.
+        let mut short = [u32::MAX; 3];
+        assert_eq!(sssp_run_stoc_detailed(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d.as_mut_ptr(), p.as_mut_ptr(), &mut info, &mut stats, short.as_mut_ptr(), 3), 0);
+        assert_eq!(&short, &hist[..3]);
+        assert_eq!(stats.max_bucket_index as usize, max);
+        assert_eq!(sssp_run_stoc_detailed(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d.as_mut_ptr(), p.as_mut_ptr(), &mut info, &mut stats, core::ptr::null_mut(), 3), -3);
+    }
+
+    #[test]
+    fn info_consistency_flags_each_invariant(){
+        let off = [0u32,1,2,2]; let tgt = [1u32,2]; let wts = [1.0f32,4.0];
+        let mut dist = [0f32;3]; let mut pred = [0i32;3];
+        let mut info = SsspResultInfo { relaxations:0, light_relaxations:0, heavy_relaxations:0, settled:0, error_code:0, overflow:0, reachable:0, edges:0 };
+        assert_eq!(sssp_run_stoc(3, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info), 0);
+        assert_eq!(sssp_check_info_consistency(&info, 3), 0);
+        let bad = |r, l, h, s| SsspResultInfo { relaxations:r, light_relaxations:l, heavy_relaxations:h, settled:s, error_code:0, overflow:0, reachable:0, edges:0 };
+        assert_eq!(sssp_check_info_consistency(&bad(2, 2, 1, 3), 3), -30);
+        assert_eq!(sssp_check_info_consistency(&bad(1, u64::MAX, 1, 3), 3), -30);
+        assert_eq!(sssp_check_info_consistency(&bad(2, 0, 0, 4), 3), -31);
+        assert_eq!(sssp_check_info_consistency(&bad(2, 0, 0, 0), 3), -32);
+        assert_eq!(sssp_check_info_consistency(core::ptr::null(), 3), -3);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn timed_runners_split_sums_to_total(){
+        let n = 50_000u32; let mut off = vec![0u32]; let mut tgt = Vec::new(); let mut wts = Vec::new();
+        for u in 0..n { for k in 1..=4u32 { tgt.push((u.wrapping_mul(2654435761).wrapping_add(k * 40503)) % n); wts.push(1.0 + ((u + k) % 17) as f32); } off.push(tgt.len() as u32); }
+        let mut dist = vec![0f32; n as usize]; let mut pred = vec![0i32; n as usize];
+        for stoc in [false, true] {
+            let mut t = SsspTiming::default();
+            let start = Instant::now();
+            let rc = if stoc { sssp_run_stoc_timed(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, dist.as_mut_ptr(), pred.as_mut_ptr(), core::ptr::null_mut(), &mut t) }
+                     else { sssp_run_baseline_timed(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, dist.as_mut_ptr(), pred.as_mut_ptr(), core::ptr::null_mut(), &mut t) };
+            let total = start.elapsed().as_nanos() as u64;
+            assert_eq!(rc, 0);
+            // The relaxation loop dominates; init and finalize may read as 0 on a coarse clock.
+            assert!(t.loop_ns > t.finalize_ns && t.loop_ns > 0, "{:?}", t);
+            let sum = t.init_ns + t.loop_ns + t.finalize_ns;
+            assert!(sum <= total && sum * 2 >= total, "sum={} total={} {:?}", sum, total, t);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn malformed_offsets_panic_maps_to_error_code(){
+        // offsets[1] > offsets[2]: the spec runner indexes past `targets` for node 0.
+        let off = [0u32,5,2]; let tgt = [1u32,0]; let wts = [1.0f32,1.0];
+        let mut dist = [0f32;2]; let mut pred = [0i32;2];
+        let rc = sssp_run_spec_phase3(2, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, dist.as_mut_ptr(), pred.as_mut_ptr(), core::ptr::null_mut(), f32::NAN);
+        assert_eq!(rc, -99);
+    }
+
+    #[test]
+    fn default_adapt_trigger_matches_heuristic(){
+        for &n in &[1u32, 2, 100, 1_000, 22_027, 1_000_000, u32::MAX] {
+            let expect = (((n as f32).ln().max(1.0) / 2.0) as u32).clamp(3, 40);
+            assert_eq!(sssp_stoc_default_adapt_trigger(n), expect, "n={}", n);
+        }
+        assert_eq!(sssp_stoc_default_adapt_trigger(1), 3); // lower clamp
+        assert_eq!(sssp_stoc_default_adapt_trigger(22_027), 5); // ln ~ 10.0
+        assert_eq!(sssp_stoc_default_adapt_trigger(u32::MAX), 11);
+    }
+
+    #[test]
+    fn stoc_phased_tags_light_heavy_and_unreachable(){
+        // Chain 0->1->..->20 (w=1) keeps delta small; 0->21 (w=1000) is the only way into 21; 22 is isolated.
+        let n = 23u32;
+        let mut off = vec![0u32]; let mut tgt = Vec::new(); let mut wts = Vec::new();
+        for u in 0..n { if u < 20 { tgt.push(u+1); wts.push(1.0f32); } if u == 0 { tgt.push(21); wts.push(1000.0); } off.push(tgt.len() as u32); }
+        let mut dist = vec![0f32; n as usize]; let mut pred = vec![-1i32; n as usize]; let mut phase = vec![9u8; n as usize];
+        let mut info = SsspResultInfo { relaxations:0, light_relaxations:0, heavy_relaxations:0, settled:0, error_code:0, overflow:0, reachable:0, edges:0 };
+        let rc = sssp_run_stoc_phased(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, dist.as_mut_ptr(), pred.as_mut_ptr(), phase.as_mut_ptr(), &mut info);
+        assert_eq!(rc, 0);
+        for v in 0..=20 { assert_eq!(phase[v], PHASE_LIGHT, "node {}", v); }
+        assert_eq!(phase[21], PHASE_HEAVY);
+        assert_eq!(phase[22], PHASE_FALLBACK);
+        assert!((dist[21] - 1000.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn spread_probe_reaches_regions_outside_source_neighborhood(){
+        // Region A (0..50, w=1) and region B (50..100, w=10) with no A->B edges:
+        // source-only probing never observes B, spread probing must.
+        let n = 100u32;
+        let mut off = vec![0u32]; let mut tgt = Vec::new(); let mut wts = Vec::new();
+        for u in 0..n { if u + 1 != 50 && u + 1 < n { tgt.push(u + 1); wts.push(if u < 50 { 1.0f32 } else { 10.0 }); } off.push(tgt.len() as u32); }
+        let seeds = autotune_probe_seeds(n, 0, 8, 0x9E37_79B9_7F4A_7C15);
+        assert_eq!(seeds.len(), 8); assert_eq!(seeds[0], 0);
+        assert_eq!(seeds, autotune_probe_seeds(n, 0, 8, 0x9E37_79B9_7F4A_7C15)); // deterministic
+        assert!(seeds.iter().any(|&s| s >= 50), "seeds {:?}", seeds);
+        let mut dist = vec![0f32; n as usize]; let mut pred = vec![0i32; n as usize];
+        let mut saw_b = false;
+        for &s in &seeds { stoc_run_internal(n, &off, &tgt, &wts, s, 3.0, &mut dist, &mut pred, Some(StocStop::Settled(4))); saw_b |= dist[50..].iter().any(|d| d.is_finite()); }
+        assert!(saw_b);
+        stoc_run_internal(n, &off, &tgt, &wts, 0, 3.0, &mut dist, &mut pred, Some(StocStop::Settled(4)));
+        assert!(dist[50..].iter().all(|d| !d.is_finite()));
+    }
+
+    #[test]
+    fn spread_probe_changes_the_pick_on_an_atypical_source_region(){
+        // Region A (0..20): unit path holding the source, where every delta relaxes each
+        // node once, so relax scores tie and the smaller multiplier wins. Region B: a
+        // chain of hubs, each with arcs of weight 8, 7, ..., 1 (in that order) to nodes
+        // x_8..x_1 and arcs x_i -> next hub of weight 50 - 2i. Delta 1.5 settles the x_i
+        // in distance order and improves the next hub 8 times; delta 20 drains them from
+        // one bucket in arc order and improves it once.
+        let a = 20u32; let hubs = 40u32; let n = a + hubs * 9 + 1;
+        let mut off = vec![0u32]; let mut tgt = Vec::new(); let mut wts = Vec::new();
+        for u in 0..a { if u + 1 < a { tgt.push(u + 1); wts.push(1.0f32); } off.push(tgt.len() as u32); }
+        for h in 0..hubs {
+            let hub = a + h * 9; let next = hub + 9;
+            for i in (1..=8u32).rev() { tgt.push(hub + i); wts.push(i as f32); }
+            off.push(tgt.len() as u32);
+            for i in 1..=8u32 { tgt.push(next); wts.push(50.0 - 2.0 * i as f32); off.push(tgt.len() as u32); }
+        }
+        off.push(tgt.len() as u32);
+        let stop = StocStop::Settled(8 * 40);
+        let spread = autotune_probe_seeds(n, 0, 8, 0x9E37_79B9_7F4A_7C15);
+        assert!(spread.iter().filter(|&&s| s >= a).count() >= 4, "seeds {:?}", spread);
+        let pick = |seeds: &[u32]| autotune_best_mult(n, &off, &tgt, &wts, seeds, &[1.5, 20.0], stop, AutotuneMetric::Relax, |x| x);
+        assert_eq!(pick(&[0]), 1.5);
+        assert_eq!(pick(&spread), 20.0);
+        // The spread pick is the better one for a full run started in region B.
+        let (mut d, mut p) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+        let relax = |delta: f32, d: &mut [f32], p: &mut [i32]| stoc_run_internal(n, &off, &tgt, &wts, a, delta, d, p, None).0;
+        assert!(relax(20.0, &mut d, &mut p) < relax(1.5, &mut d, &mut p));
+    }
+
+    #[test]
+    fn autotune_stop_conditions_bound_their_own_counter(){
+        // Hub 0 -> 1..=1000 (unit weights), then a path 1 -> 1001 -> ... -> 1999.
+        let n = 2000u32; let mut off = vec![0u32]; let mut tgt: Vec<u32> = (1..=1000).collect(); off.push(tgt.len() as u32);
+        for u in 1..n { if u == 1 { tgt.push(1001); } else if u > 1000 && u + 1 < n { tgt.push(u + 1); } off.push(tgt.len() as u32); }
+        let wts = vec![1.0f32; tgt.len()];
+        let (mut d, mut p) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+        let (_, _, _, settled, _) = stoc_run_internal(n, &off, &tgt, &wts, 0, 0.5, &mut d, &mut p, Some(StocStop::Settled(10)));
+        assert_eq!(settled, 10);
+        // The hub's 1000 out-edges exhaust an edge budget of 10 on the first node, and
+        // its 1000 relaxations a relaxation budget of 10.
+        let (_, _, _, settled, _) = stoc_run_internal(n, &off, &tgt, &wts, 0, 0.5, &mut d, &mut p, Some(StocStop::EdgesScanned(10)));
+        assert_eq!(settled, 1);
+        let (relax, _, _, settled, _) = stoc_run_internal(n, &off, &tgt, &wts, 0, 0.5, &mut d, &mut p, Some(StocStop::Relaxations(10)));
+        assert_eq!((relax, settled), (1000, 1));
+        assert_eq!(StocStop::EdgesScanned(4096).per_probe(8), StocStop::EdgesScanned(512));
+        assert_eq!(StocStop::Settled(3).per_probe(8), StocStop::Settled(1));
+    }
+
+    #[test]
+    fn autotune_relax_metric_is_deterministic_and_breaks_ties_low(){
+        let n = 2000u32;
+        let g = crate::gen::random_graph_in(n, 4.0, 0x6A09E667F3BCC908, 0.1, 10.0); let (off, tgt, wts) = (g.offsets, g.targets, g.weights);
+        let seeds = autotune_probe_seeds(n, 0, 4, 7);
+        let cands = [6.0f32, 0.5, 3.0, 1.5];
+        let pick = autotune_best_mult(n, &off, &tgt, &wts, &seeds, &cands, StocStop::Settled(512), AutotuneMetric::Relax, |x| x);
+        for _ in 0..3 { assert_eq!(autotune_best_mult(n, &off, &tgt, &wts, &seeds, &cands, StocStop::Settled(512), AutotuneMetric::Relax, |x| x), pick); }
+        // The pick has the fewest relaxations over the same truncated runs.
+        let (mut d, mut p) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+        let relax = |delta: f32, d: &mut [f32], p: &mut [i32]| seeds.iter().map(|&s| stoc_run_internal(n, &off, &tgt, &wts, s, delta, d, p, Some(StocStop::Settled(128))).0).sum::<u64>();
+        let best = cands.iter().map(|&c| relax(c, &mut d, &mut p)).min().unwrap();
+        assert_eq!(relax(pick, &mut d, &mut p), best);
+        // Deltas beyond every distance keep all nodes in bucket 0, so the runs are
+        // identical and the smaller multiplier wins the tie.
+        assert_eq!(autotune_best_mult(n, &off, &tgt, &wts, &seeds, &[4e5, 2e5, 3e5], StocStop::Settled(512), AutotuneMetric::Relax, |x| x), 2e5);
+    }
+
+    #[test]
+    fn stoc_cfg_overrides_env_settings(){
+        let n = 400u32;
+        let g = crate::gen::random_graph_in(n, 4.0, 0x3C6EF372FE94F82B, 0.1, 10.0); let (off, tgt, wts) = (g.offsets, g.targets, g.weights);
+        let empty = || SsspResultInfo { relaxations:0, light_relaxations:0, heavy_relaxations:0, settled:0, error_code:0, overflow:0, reachable:0, edges:0 };
+        let run = |cfg: *const StocConfig| {
+            let (mut d, mut p, mut info) = (vec![0f32; n as usize], vec![0i32; n as usize], empty());
+            assert_eq!(sssp_run_stoc_cfg(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d.as_mut_ptr(), p.as_mut_ptr(), &mut info, cfg), 0);
+            (d, p, info.light_relaxations, info.heavy_relaxations)
+        };
+        // The SSSP_STOC_* vars are unset under test, so the env path, a null cfg and
+        // the defaults all agree.
+        let (mut d, mut p, mut info) = (vec![0f32; n as usize], vec![0i32; n as usize], empty());
+        assert_eq!(sssp_run_stoc(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d.as_mut_ptr(), p.as_mut_ptr(), &mut info), 0);
+        let env_run = (d, p, info.light_relaxations, info.heavy_relaxations);
+        assert_eq!(run(core::ptr::null()), env_run);
+        assert_eq!(run(&StocConfig::default()), env_run);
+        // With restarts disabled a pinned multiplier is used as given: delta below the
+        // lightest weight (0.1) makes every edge heavy, one above the heaviest every edge light.
+        let tiny = StocConfig { delta_mult: 0.01, adapt_max_restarts: 0, ..StocConfig::default() };
+        let huge = StocConfig { delta_mult: 1000.0, adapt_max_restarts: 0, ..StocConfig::default() };
+        let (t, h) = (run(&tiny), run(&huge));
+        assert!(t.2 == 0 && t.3 > 0 && h.2 > 0 && h.3 == 0);
+    }
+
+    #[test]
+    fn stoc_ctx_reuse_matches_fresh_runs(){
+        let n = 300u32;
+        let g = crate::gen::random_graph_in(n, 2.5, 0xA54FF53A5F1D36F1, 0.1, 10.0); let (off, tgt, wts) = (g.offsets, g.targets, g.weights);
+        let empty = || SsspResultInfo { relaxations:0, light_relaxations:0, heavy_relaxations:0, settled:0, error_code:0, overflow:0, reachable:0, edges:0 };
+        let ctx = sssp_context_new(n);
+        let (mut d1, mut p1) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+        let (mut d2, mut p2) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+        // Repeated and interleaved sources: leftovers from one query must not leak into the next.
+        for &src in &[0u32, 17, 0, 299, 42, 17] {
+            let (mut i1, mut i2) = (empty(), empty());
+            assert_eq!(sssp_run_stoc_ctx(ctx, n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), src, d1.as_mut_ptr(), p1.as_mut_ptr(), &mut i1), 0);
+            assert_eq!(sssp_run_stoc(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), src, d2.as_mut_ptr(), p2.as_mut_ptr(), &mut i2), 0);
+            assert_eq!((&d1, &p1, i1.relaxations, i1.settled), (&d2, &p2, i2.relaxations, i2.settled));
+        }
+        let mut info = empty();
+        assert_eq!(sssp_run_stoc_ctx(ctx, n - 1, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d1.as_mut_ptr(), p1.as_mut_ptr(), &mut info), -8);
+        assert_eq!(sssp_run_stoc_ctx(core::ptr::null_mut(), n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d1.as_mut_ptr(), p1.as_mut_ptr(), &mut info), -3);
+        sssp_context_free(ctx);
+    }
+
+    #[test]
+    fn stats_out_params_are_per_call_under_threads(){
+        let graph = |n: u32, seed: u64| {
+            let g = crate::gen::random_graph_in(n, 3.0, seed, 0.1, 14.0); let (off, tgt, wts) = (g.offsets, g.targets, g.weights);
+            (n, off, tgt, wts)
+        };
+        let run = |g: &(u32, Vec<u32>, Vec<u32>, Vec<f32>)| {
+            let (n, off, tgt, wts) = g;
+            let (mut d, mut p) = (vec![0f32; *n as usize], vec![0i32; *n as usize]);
+            let mut hs = BaselineHeapStats { pushes: 0, pops: 0, max_size: 0 };
+            let mut bs = SsspBucketStats { buckets_visited: 0, light_pass_repeats: 0, max_bucket_index: 0, restarts: 0, delta_x1000: 0, heavy_ratio_x1000: 0, peak_live_buckets: 0 };
+            assert_eq!(sssp_run_baseline_stats(*n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d.as_mut_ptr(), p.as_mut_ptr(), core::ptr::null_mut(), &mut hs), 0);
+            assert_eq!(sssp_run_stoc_stats(*n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d.as_mut_ptr(), p.as_mut_ptr(), core::ptr::null_mut(), &mut bs), 0);
+            (hs.pushes, hs.pops, hs.max_size, bs.buckets_visited, bs.light_pass_repeats, bs.max_bucket_index, bs.restarts, bs.delta_x1000)
+        };
+        let graphs = [graph(500, 0x1F83D9ABFB41BD6B), graph(90, 0x5BE0CD19137E2179)];
+        let solo: Vec<_> = graphs.iter().map(run).collect();
+        assert_ne!(solo[0], solo[1]);
+        std::thread::scope(|sc| {
+            for t in 0..4 {
+                let (g, want) = (&graphs[t % 2], solo[t % 2]);
+                sc.spawn(move || for _ in 0..25 { assert_eq!(run(g), want); });
+            }
+        });
+    }
+
+    #[test]
+    fn dary_arities_match_binary_heap(){
+        let n = 3000u32;
+        let g = crate::gen::random_graph_in(n, 4.0, 0x3C6EF372FE94F82B, 0.1, 10.0); let (off, tgt, wts) = (g.offsets, g.targets, g.weights);
+        let (mut want, mut wp) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+        let mut base = BaselineHeapStats { pushes: 0, pops: 0, max_size: 0 };
+        assert_eq!(sssp_run_baseline_stats(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, want.as_mut_ptr(), wp.as_mut_ptr(), core::ptr::null_mut(), &mut base), 0);
+        // 0 is the default arity; 1 and 100 are clamped into [2, 8].
+        for &arity in &[0u32, 1, 2, 3, 4, 8, 100] {
+            let (mut d, mut p) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+            let mut hs = BaselineHeapStats { pushes: 0, pops: 0, max_size: 0 };
+            assert_eq!(sssp_run_baseline_dary(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d.as_mut_ptr(), p.as_mut_ptr(), core::ptr::null_mut(), &mut hs, arity), 0);
+            assert_eq!(d.iter().map(|x| x.to_bits()).collect::<Vec<_>>(), want.iter().map(|x| x.to_bits()).collect::<Vec<_>>());
+            assert_eq!((hs.pushes, hs.pops), (base.pushes, base.pops));
+        }
+    }
+
+    #[test]
+    fn indexed_heap_holds_one_entry_per_node(){
+        // Complete digraph with weights falling towards higher-numbered sources, so
+        // most nodes are improved many times before they are settled.
+        let n = 200u32;
+        let mut off = vec![0u32]; let mut tgt = Vec::new(); let mut wts = Vec::new();
+        for u in 0..n { for v in 0..n { if u != v { tgt.push(v); wts.push(1.0 + ((v * 7 + u * 13) % 31) as f32 / (1.0 + u as f32)); } } off.push(tgt.len() as u32); }
+        let (mut want, mut wp) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+        let mut lazy = BaselineHeapStats { pushes: 0, pops: 0, max_size: 0 };
+        assert_eq!(sssp_run_baseline_stats(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, want.as_mut_ptr(), wp.as_mut_ptr(), core::ptr::null_mut(), &mut lazy), 0);
+        let (mut d, mut p) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+        let mut hs = BaselineHeapStats { pushes: 0, pops: 0, max_size: 0 };
+        assert_eq!(sssp_run_baseline_indexed(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d.as_mut_ptr(), p.as_mut_ptr(), core::ptr::null_mut(), &mut hs), 0);
+        assert_eq!(d, want);
+        assert!(hs.max_size <= n as u64);
+        assert_eq!(hs.pushes, hs.pops);
+        assert!(lazy.pushes > hs.pushes);
+        assert_eq!(sssp_run_baseline_indexed(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), n, d.as_mut_ptr(), p.as_mut_ptr(), core::ptr::null_mut(), &mut hs), -2);
+    }
+
+    #[test]
+    fn result_info_reports_reachable_count(){
+        // 0 -> 1 -> 2; 3 -> 4 is a separate component.
+        let off = [0u32,1,2,2,3,3]; let tgt = [1u32,2,4]; let wts = [1.0f32,2.0,1.0];
+        let empty = || SsspResultInfo { relaxations:0, light_relaxations:0, heavy_relaxations:0, settled:0, error_code:0, overflow:0, reachable:0, edges:0 };
+        let (mut d, mut p) = ([0f32;5], [0i32;5]);
+        type Runner = extern "C" fn(u32, *const u32, *const u32, *const f32, u32, *mut f32, *mut i32, *mut SsspResultInfo) -> i32;
+        let runners: [Runner; 4] = [sssp_run_baseline, sssp_run_stoc, spec_clean::sssp_run_spec_clean, crate::sssp_run_spec_phase1];
+        for run in runners {
+            let mut info = empty();
+            assert_eq!(run(5, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d.as_mut_ptr(), p.as_mut_ptr(), &mut info), 0);
+            assert_eq!(info.reachable, 3);
+            assert_eq!(info.edges, 3);
+        }
+        let mut info = empty();
+        assert_eq!(variants::sssp_run_avoiding(5, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, 2, [0u64].as_ptr(), d.as_mut_ptr(), p.as_mut_ptr(), &mut info), 0);
+        assert_eq!((info.reachable, info.edges), (3, 3));
+        assert_eq!(sssp_run_baseline(5, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 3, d.as_mut_ptr(), p.as_mut_ptr(), &mut info), 0);
+        assert_eq!(info.reachable, 2);
+        assert_eq!(sssp_version(), 8);
+    }
+
+    #[test]
+    fn self_loops_cost_no_relaxations(){
+        // Chain 0->1->2->3 (+ 0->2 shortcut) with and without self-loops (zero and positive weight).
+        let (off, tgt, wts) = ([0u32,2,3,4,4], [1u32,2,2,3], [1.0f32,3.0,1.0,1.0]);
+        let (l_off, l_tgt, l_wts) = ([0u32,3,5,7,8], [0u32,1,2,1,2,2,3,3], [0.0f32,1.0,3.0,2.0,1.0,0.0,1.0,0.0]);
+        let empty = || SsspResultInfo { relaxations:0, light_relaxations:0, heavy_relaxations:0, settled:0, error_code:0, overflow:0, reachable:0, edges:0 };
+        type Runner = extern "C" fn(u32, *const u32, *const u32, *const f32, u32, *mut f32, *mut i32, *mut SsspResultInfo) -> i32;
+        for run in [sssp_run_baseline as Runner, sssp_run_stoc] {
+            let (mut d1, mut p1, mut i1) = ([0f32;4], [0i32;4], empty());
+            let (mut d2, mut p2, mut i2) = ([0f32;4], [0i32;4], empty());
+            assert_eq!(run(4, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d1.as_mut_ptr(), p1.as_mut_ptr(), &mut i1), 0);
+            assert_eq!(run(4, l_off.as_ptr(), l_tgt.as_ptr(), l_wts.as_ptr(), 0, d2.as_mut_ptr(), p2.as_mut_ptr(), &mut i2), 0);
+            assert_eq!((d1, p1), (d2, p2));
+            assert_eq!((i1.relaxations, i1.light_relaxations, i1.heavy_relaxations), (i2.relaxations, i2.light_relaxations, i2.heavy_relaxations));
+        }
+        // The autotune probe must count what the full run counts.
+        let (mut d1, mut p1, mut d2, mut p2) = ([0f32;4], [0i32;4], [0f32;4], [0i32;4]);
+        let a = stoc_run_internal(4, &off, &tgt, &wts, 0, 1.5, &mut d1, &mut p1, None);
+        let b = stoc_run_internal(4, &l_off, &l_tgt, &l_wts, 0, 1.5, &mut d2, &mut p2, None);
+        assert_eq!((a, d1, p1), (b, d2, p2));
+        // basecase_truncated accepts `nd <= dist[v]`, so a zero self-loop used to re-push its node.
+        let (mut d1, mut p1, mut s1, mut r1) = (vec![0f32;4], vec![0i32;4], Vec::new(), 0u64);
+        let (mut d2, mut p2, mut s2, mut r2) = (vec![0f32;4], vec![0i32;4], Vec::new(), 0u64);
+        let a = spec_clean::basecase_truncated(4, &off, &tgt, &wts, 0, 4, f32::INFINITY, &mut d1, &mut p1, &mut s1, &mut r1);
+        let b = spec_clean::basecase_truncated(4, &l_off, &l_tgt, &l_wts, 0, 4, f32::INFINITY, &mut d2, &mut p2, &mut s2, &mut r2);
+        assert_eq!((a.outcome, a.collected, r1), (b.outcome, b.collected, r2));
+        assert_eq!((d1, s1), (d2, s2));
+    }
+    #[test]
+    fn prefetched_baseline_matches_plain_search(){
+        // Rows well past PREFETCH_DISTANCE so prefetches cover SIMD blocks and scalar tails;
+        // with `--features prefetch` this checks the hinted loop, otherwise the plain one.
+        let n = 2000u32;
+        let g = crate::gen::random_graph_in(n, 19.5, 0xBB67AE8584CAA73B, 0.0, 32.0); let (off, tgt, wts) = (g.offsets, g.targets, g.weights);
+        let (mut d, mut p, mut info) = (vec![0f32; n as usize], vec![0i32; n as usize], SsspResultInfo { relaxations:0, light_relaxations:0, heavy_relaxations:0, settled:0, error_code:0, overflow:0, reachable:0, edges:0 });
+        assert_eq!(sssp_run_baseline(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d.as_mut_ptr(), p.as_mut_ptr(), &mut info), 0);
+        let (mut want, mut wp) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+        let c = dijkstra_with(&off, &tgt, &wts, 0, &mut want, &mut wp, |_, _| true);
+        assert_eq!(d.iter().map(|x| x.to_bits()).collect::<Vec<_>>(), want.iter().map(|x| x.to_bits()).collect::<Vec<_>>());
+        assert_eq!(info.relaxations, c.relaxations);
+    }
+
+    #[test]
+    fn bucket_ring_keeps_few_buckets_live_on_long_paths(){
+        // Unit-weight path 0->1->..->n-1 plus a heavy 0->n-1 edge whose bucket starts out far beyond the ring.
+        let n = 5000u32;
+        let mut off: Vec<u32> = (0..n).collect(); off.push(n - 1);
+        let mut tgt: Vec<u32> = (1..n).collect(); let mut wts = vec![1.0f32; n as usize - 1];
+        tgt.insert(0, n - 1); wts.insert(0, 4.0 * n as f32);
+        for o in off.iter_mut().skip(1) { *o += 1; }
+        let (mut want, mut wp) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+        assert_eq!(sssp_run_baseline(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, want.as_mut_ptr(), wp.as_mut_ptr(), core::ptr::null_mut()), 0);
+        let (mut d, mut p) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+        let mut bs = SsspBucketStats { buckets_visited: 0, light_pass_repeats: 0, max_bucket_index: 0, restarts: 0, delta_x1000: 0, heavy_ratio_x1000: 0, peak_live_buckets: 0 };
+        assert_eq!(sssp_run_stoc_stats(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d.as_mut_ptr(), p.as_mut_ptr(), core::ptr::null_mut(), &mut bs), 0);
+        assert_eq!((&d, &p), (&want, &wp));
+        assert!(bs.max_bucket_index as usize > 4 * BUCKET_RING_WIDTH, "max_bucket_index {}", bs.max_bucket_index);
+        assert!(bs.peak_live_buckets <= 3, "peak_live_buckets {}", bs.peak_live_buckets);
+        // The autotune probe walks the same ring.
+        let (mut d, mut p) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+        assert_eq!(stoc_run_internal(n, &off, &tgt, &wts, 0, 1.0, &mut d, &mut p, None).4, 0);
+        assert_eq!(d, want);
+    }
+
+    #[test]
+    fn peak_queue_matches_across_heaps_on_path(){
+        // On a path each pop pushes exactly one successor, so every heap holds one entry at a time.
+        let n = 64u32;
+        let mut off: Vec<u32> = (0..n).collect(); off.push(n - 1);
+        let tgt: Vec<u32> = (1..n).collect(); let wts = vec![1.5f32; n as usize - 1];
+        let (mut d, mut p) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+        let mut hs = BaselineHeapStats { pushes: 0, pops: 0, max_size: 0 };
+        assert_eq!(sssp_run_baseline_stats(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d.as_mut_ptr(), p.as_mut_ptr(), core::ptr::null_mut(), &mut hs), 0);
+        let baseline_peak = sssp_get_last_peak_queue();
+        assert_eq!(sssp_run_baseline_indexed(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d.as_mut_ptr(), p.as_mut_ptr(), core::ptr::null_mut(), &mut hs), 0);
+        assert_eq!((baseline_peak, sssp_get_last_peak_queue()), (1, 1));
+        assert_eq!(spec_clean::sssp_run_spec_boundary_chain(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d.as_mut_ptr(), p.as_mut_ptr(), core::ptr::null_mut()), 0);
+        assert_eq!(sssp_get_last_peak_queue(), 1);
+        assert_eq!(spec_clean::sssp_run_spec_phase3(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d.as_mut_ptr(), p.as_mut_ptr(), core::ptr::null_mut(), f32::NAN), 0);
+        assert_eq!(sssp_get_last_peak_queue(), 1);
+    }
+
+    #[test]
+    fn validate_csr_reports_first_problem(){
+        let tgt = [1u32, 2, 0];
+        let mut detail = u32::MAX;
+        assert_eq!(sssp_validate_csr(3, [0u32, 1, 2, 3].as_ptr(), tgt.as_ptr(), core::ptr::null(), &mut detail), 0);
+        assert_eq!(sssp_validate_csr(3, [1u32, 1, 2, 3].as_ptr(), tgt.as_ptr(), core::ptr::null(), &mut detail), -3);
+        assert_eq!(detail, 0);
+        assert_eq!(sssp_validate_csr(3, [0u32, 2, 1, 3].as_ptr(), tgt.as_ptr(), core::ptr::null(), &mut detail), -1);
+        assert_eq!(detail, 2);
+        assert_eq!(sssp_validate_csr(3, [0u32, 1, 2, 3].as_ptr(), [1u32, 3, 7].as_ptr(), core::ptr::null(), &mut detail), -2);
+        assert_eq!(detail, 1);
+        // Edgeless graphs need no targets array; a null detail is allowed.
+        assert_eq!(sssp_validate_csr(2, [0u32, 0, 0].as_ptr(), core::ptr::null(), core::ptr::null(), core::ptr::null_mut()), 0);
+        assert_eq!(sssp_validate_csr(3, core::ptr::null(), tgt.as_ptr(), core::ptr::null(), &mut detail), -4);
+    }
+}
@@ -0,0 +1,171 @@
+//! Multi-threaded delta-stepping (feature `parallel`). Uses std scoped threads
+//! rather than a thread-pool dependency: a bucket's light pass fans out only when
+//! its frontier is large enough to amortize the spawns.
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use crate::{as_mut_slice, csr_slices, derive_avg_weight, SsspResultInfo, StocConfig};
+
+// Frontiers smaller than this are relaxed on the calling thread.
+const PAR_MIN_FRONTIER: usize = 1024;
+
+// Label packed as (dist bits << 32) | pred so a distance and its predecessor are
+// always swapped in together. Unreached: +inf with pred u32::MAX (-1 as i32).
+#[inline(always)]
+fn pack(d: f32, p: u32) -> u64 { ((d.to_bits() as u64) << 32) | p as u64 }
+#[inline(always)]
+fn dist_of(label: u64) -> f32 { f32::from_bits((label >> 32) as u32) }
+
+// Atomic compare-and-min on the distance: installs (nd, u) only while it is a strict
+// improvement, retrying if another thread changed the label in between. Strictness
+// keeps pred acyclic on zero-weight edges, as in the sequential runners.
+#[inline(always)]
+fn relax_min(slot: &AtomicU64, nd: f32, u: u32) -> bool {
+    let mut cur = slot.load(Ordering::Relaxed);
+    loop {
+        if dist_of(cur) <= nd { return false; }
+        match slot.compare_exchange_weak(cur, pack(nd, u), Ordering::Relaxed, Ordering::Relaxed) { Ok(_) => return true, Err(seen) => cur = seen }
+    }
+}
+
+// Relaxes the light (`w <= delta`) or heavy edges of `nodes`, returning the
+// improved nodes and the number of successful relaxations.
+fn relax_edges(off: &[u32], tgt: &[u32], wts: &[f32], labels: &[AtomicU64], nodes: &[u32], delta: f32, light: bool) -> (Vec<u32>, u64) {
+    let mut out = Vec::new();
+    for &u in nodes {
+        let base = dist_of(labels[u as usize].load(Ordering::Relaxed));
+        for e in off[u as usize] as usize..off[u as usize + 1] as usize {
+            let w = wts[e]; if (w <= delta) != light { continue; }
+            let v = tgt[e];
+            if relax_min(&labels[v as usize], base + w, u) { out.push(v); }
+        }
+    }
+    let relaxations = out.len() as u64;
+    (out, relaxations)
+}
+
+// Delta-stepping with label-correcting buckets: a node re-enters its bucket
+// whenever a light relaxation improves it, so each pass is order-independent and
+// distances are bit-identical to `sssp_run_baseline` for any thread count (both
+// compute the least fixed point of d(v) = min fl(d(u) + w)). Light passes over
+// large frontiers are split across `num_threads` threads (0 = available
+// parallelism), each collecting bucket insertions locally; the lists are merged
+// once the pass completes. Heavy edges are relaxed once per bucket. Which of several
+// equally short predecessors ends up in pred depends on thread timing. Delta is
+// the avg-weight rule of `sssp_run_stoc` (SSSP_STOC_DELTA_MULT honoured, no
+// adaptive restarts); `settled` counts reached nodes.
+#[no_mangle]
+pub extern "C" fn sssp_run_stoc_parallel(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+    num_threads: u32,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if source >= n { return -2; }
+        if out_dist.is_null() || out_pred.is_null() { return -3; }
+        let rc = crate::precheck_weights(wts, info); if rc != 0 { return rc; }
+        let n_usize = n as usize;
+        let threads = if num_threads == 0 { std::thread::available_parallelism().map(|t| t.get()).unwrap_or(1) } else { num_threads as usize };
+        let cfg = StocConfig::from_env();
+        let mult = if cfg.delta_mult > 0.0 { cfg.delta_mult } else { 3.0 };
+        let delta = (derive_avg_weight(core::cmp::min(1000, wts.len()), wts) * mult).clamp(1e-4, 1e6);
+        let labels: Vec<AtomicU64> = (0..n_usize).map(|_| AtomicU64::new(pack(f32::INFINITY, u32::MAX))).collect();
+        labels[source as usize].store(pack(0.0, u32::MAX), Ordering::Relaxed);
+        let max_bucket_cap = 4 * n_usize + 1024;
+        let mut buckets: Vec<Vec<u32>> = vec![vec![source]];
+        // Bucket a node is currently queued in (usize::MAX = none), so a bucket never
+        // holds the same live node twice.
+        let mut queued: Vec<usize> = vec![usize::MAX; n_usize];
+        queued[source as usize] = 0;
+        let mut in_removed = vec![false; n_usize];
+        let (mut light_relax, mut heavy_relax) = (0u64, 0u64);
+        let mut current = 0usize;
+        while current < buckets.len() {
+            let mut removed: Vec<u32> = Vec::new();
+            while !buckets[current].is_empty() {
+                let mut frontier = core::mem::take(&mut buckets[current]);
+                frontier.retain(|&u| queued[u as usize] == current);
+                for &u in &frontier { queued[u as usize] = usize::MAX; if !in_removed[u as usize] { in_removed[u as usize] = true; removed.push(u); } }
+                let results: Vec<(Vec<u32>, u64)> = if threads <= 1 || frontier.len() < PAR_MIN_FRONTIER {
+                    vec![relax_edges(off, tgt, wts, &labels, &frontier, delta, true)]
+                } else {
+                    let chunk = frontier.len().div_ceil(threads);
+                    std::thread::scope(|sc| {
+                        let handles: Vec<_> = frontier.chunks(chunk).map(|part| { let labels = &labels; sc.spawn(move || relax_edges(off, tgt, wts, labels, part, delta, true)) }).collect();
+                        handles.into_iter().map(|h| h.join().unwrap()).collect()
+                    })
+                };
+                for (improved, r) in results {
+                    light_relax += r;
+                    for v in improved {
+                        // Re-derive the bucket from the final label: a later relaxation in
+                        // the same pass may have lowered it again.
+                        let b = (dist_of(labels[v as usize].load(Ordering::Relaxed)) / delta) as usize;
+                        if b > max_bucket_cap { return -5; }
+                        if queued[v as usize] == b { continue; }
+                        if b >= buckets.len() { buckets.resize_with(b + 1, Vec::new); }
+                        buckets[b].push(v); queued[v as usize] = b;
+                    }
+                }
+            }
+            let (improved, r) = relax_edges(off, tgt, wts, &labels, &removed, delta, false);
+            heavy_relax += r;
+            for v in improved {
+                let b = (dist_of(labels[v as usize].load(Ordering::Relaxed)) / delta) as usize;
+                if b > max_bucket_cap { return -5; }
+                if queued[v as usize] == b { continue; }
+                if b >= buckets.len() { buckets.resize_with(b + 1, Vec::new); }
+                buckets[b].push(v); queued[v as usize] = b;
+            }
+            for &u in &removed { in_removed[u as usize] = false; }
+            current += 1;
+        }
+        let dist = as_mut_slice(out_dist, n_usize);
+        let pred = as_mut_slice(out_pred, n_usize);
+        let mut settled = 0u32;
+        for (v, l) in labels.iter().enumerate() {
+            let l = l.load(Ordering::Relaxed);
+            dist[v] = dist_of(l); pred[v] = l as u32 as i32;
+            if dist[v].is_finite() { settled += 1; }
+        }
+        if !info.is_null() { unsafe { *info = SsspResultInfo { relaxations: light_relax + heavy_relax, light_relaxations: light_relax, heavy_relaxations: heavy_relax, settled, error_code: 0, overflow: 0, reachable: crate::count_reachable(dist), edges: tgt.len() as u64 }; } }
+        0
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parallel_distances_bit_identical_to_sequential(){
+        let n = 20_000u32;
+        let g = crate::gen::random_graph_in(n, 6.0, 0x9B05688C2B3E6C1F, 0.1, 10.0); let (off, tgt, wts) = (g.offsets, g.targets, g.weights);
+        let (mut want, mut wp) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+        crate::sssp_run_baseline(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, want.as_mut_ptr(), wp.as_mut_ptr(), core::ptr::null_mut());
+        let bits = |d: &[f32]| d.iter().map(|x| x.to_bits()).collect::<Vec<_>>();
+        let (mut seq, mut sp) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+        assert_eq!(sssp_run_stoc_parallel(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, seq.as_mut_ptr(), sp.as_mut_ptr(), core::ptr::null_mut(), 1), 0);
+        assert_eq!(bits(&seq), bits(&want));
+        for &threads in &[2u32, 4, 8] {
+            let (mut d, mut p) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+            let mut info = SsspResultInfo { relaxations:0, light_relaxations:0, heavy_relaxations:0, settled:0, error_code:0, overflow:0, reachable:0, edges:0 };
+            assert_eq!(sssp_run_stoc_parallel(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d.as_mut_ptr(), p.as_mut_ptr(), &mut info, threads), 0);
+            assert_eq!(bits(&d), bits(&seq));
+            assert_eq!(info.settled as usize, d.iter().filter(|x| x.is_finite()).count());
+            // pred may differ between tied paths but every edge it names is tight.
+            for v in 0..n as usize {
+                if p[v] < 0 { continue; }
+                let u = p[v] as usize;
+                assert!((off[u] as usize..off[u + 1] as usize).any(|e| tgt[e] as usize == v && d[u] + wts[e] == d[v]));
+            }
+        }
+    }
+}
@@ -0,0 +1,40 @@
+//! Software prefetch hints (feature `prefetch`; no-ops elsewhere or off x86_64).
+//! Hints never change results, only when cache lines arrive.
+
+#[cfg(all(feature = "prefetch", target_arch = "x86_64"))]
+#[inline(always)]
+pub(crate) fn prefetch_read<T>(p: *const T) {
+    use core::arch::x86_64::{_mm_prefetch, _MM_HINT_T0};
+    // SAFETY: prefetch is a hint; it never faults, even on invalid addresses.
+    unsafe { _mm_prefetch(p as *const i8, _MM_HINT_T0); }
+}
+
+#[cfg(not(all(feature = "prefetch", target_arch = "x86_64")))]
+#[inline(always)]
+pub(crate) fn prefetch_read<T>(_p: *const T) {}
+
+// Edges ahead of the current one whose target's dist slot is prefetched: enough
+// relaxations in between to hide a miss, near enough to stay inside typical rows.
+pub(crate) const PREFETCH_DISTANCE: usize = 8;
+
+// Prefetch of the random `dist[tgt[e + PREFETCH_DISTANCE]]` load, skipped once that
+// edge is past the end of the current row.
+#[inline(always)]
+pub(crate) fn prefetch_dist_ahead<D>(dist: &[D], tgt: &[u32], e: usize, end: usize) {
+    if !cfg!(all(feature = "prefetch", target_arch = "x86_64")) { return; }
+    let a = e + PREFETCH_DISTANCE;
+    if a < end { prefetch_read(dist.as_ptr().wrapping_add(tgt[a] as usize)); }
+}
+
+// Adjacency prefetch for the heap's next pops: the root (next node) gets its
+// target/weight rows, its children (the candidates after that) their offsets.
+// `heap` is the array form of the binary heap (root at 0).
+#[inline(always)]
+pub(crate) fn prefetch_next_pops<O: crate::CsrOffset>(heap: &[crate::HeapItem], off: &[O], tgt: &[u32], wts: &[f32]) {
+    if !cfg!(all(feature = "prefetch", target_arch = "x86_64")) { return; }
+    if let Some(top) = heap.first() {
+        let s = off[top.node as usize].idx();
+        prefetch_read(tgt.as_ptr().wrapping_add(s)); prefetch_read(wts.as_ptr().wrapping_add(s));
+    }
+    for c in heap.iter().skip(1).take(2) { prefetch_read(off.as_ptr().wrapping_add(c.node as usize)); }
+}
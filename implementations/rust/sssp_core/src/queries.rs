@@ -0,0 +1,1076 @@
+//! Derived queries answered from baseline Dijkstra passes, so bindings do not
+//! need to post-process `out_dist` themselves.
+
+use std::collections::BTreeMap;
+
+use crate::{as_mut_slice, as_slice, csr_slices, dijkstra_with, SsspResultInfo};
+use crate::graph_ops::transpose_csr;
+
+// Baseline run that also reports the farthest reachable node and its distance.
+// The farthest node is the last one settled (settle order is non-decreasing in
+// distance, ties resolve to the later pop). `out_farthest_node` is u32::MAX and
+// `out_max_dist` 0.0 when nothing beyond the source is reachable.
+#[no_mangle]
+pub extern "C" fn sssp_run_baseline_farthest(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+    out_farthest_node: *mut u32,
+    out_max_dist: *mut f32,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if source >= n { return -2; }
+        if out_dist.is_null() || out_pred.is_null() || out_farthest_node.is_null() || out_max_dist.is_null() { return -3; }
+        let dist = as_mut_slice(out_dist, n as usize);
+        let pred = as_mut_slice(out_pred, n as usize);
+        let (mut far, mut far_d) = (u32::MAX, 0.0f32);
+        let c = dijkstra_with(off, tgt, wts, source, dist, pred, |u, d| { if u != source { far = u; far_d = d; } true });
+        unsafe { *out_farthest_node = far; *out_max_dist = far_d; }
+        if !info.is_null() { unsafe { *info = SsspResultInfo { relaxations: c.relaxations, light_relaxations: 0, heavy_relaxations: 0, settled: c.settled, error_code: 0, overflow: 0, reachable: crate::count_reachable(dist), edges: tgt.len() as u64 }; } }
+        0
+    })
+}
+
+// Summary statistics of one baseline run, filled by `sssp_run_baseline_metrics`.
+// `settled` counts the source; `max_dist`/`farthest_node` follow
+// `sssp_run_baseline_farthest` (0.0 / u32::MAX when only the source is reached);
+// `sum_dist` (f64) equals `sssp_wiener_partial`; `sum_hops` adds the edge counts of
+// the tree paths; `distinct_dist_bands` counts distinct distance values, i.e. the
+// levels of equal distance the settle order passes through (the source's 0 included).
+#[repr(C)]
+#[derive(Clone, Copy, Default, Debug)]
+pub struct SsspMetrics {
+    pub settled: u32,
+    pub max_dist: f32,
+    pub sum_dist: f64,
+    pub sum_hops: u64,
+    pub farthest_node: u32,
+    pub distinct_dist_bands: u32,
+}
+
+// All SsspMetrics fields from a single Dijkstra pass (hops from one sweep over the
+// recorded settle order afterwards, no second search).
+#[no_mangle]
+pub extern "C" fn sssp_run_baseline_metrics(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    out: *mut SsspMetrics,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if source >= n { return -2; }
+        if out.is_null() { return -3; }
+        let mut dist = vec![0f32; n as usize]; let mut pred = vec![-1i32; n as usize];
+        let mut m = SsspMetrics { farthest_node: u32::MAX, ..SsspMetrics::default() };
+        let mut last = f32::NAN; let mut order: Vec<u32> = Vec::new();
+        dijkstra_with(off, tgt, wts, source, &mut dist, &mut pred, |u, d| {
+            m.settled += 1; m.sum_dist += d as f64; order.push(u);
+            if u != source { m.farthest_node = u; m.max_dist = d; }
+            if d != last { m.distinct_dist_bands += 1; last = d; }
+            true
+        });
+        // Settle order lists every node after its pred, so hops fill in one sweep.
+        let mut hops = vec![0u32; n as usize];
+        for &u in &order { let p = pred[u as usize]; if p >= 0 { hops[u as usize] = hops[p as usize] + 1; } m.sum_hops += hops[u as usize] as u64; }
+        unsafe { *out = m; }
+        0
+    })
+}
+
+// Baseline run that also samples the growth of the settled set: `out_growth[i]` is
+// the number of nodes settled with distance <= i/(num_points-1) * max_dist (i =
+// 0..num_points; a single point samples max_dist only). Settle order is
+// non-decreasing in distance, so the counts come from one pass over the recorded
+// settle distances. Linear vs. quadratic growth separates path-like from grid-like
+// frontiers. `num_points` = 0 is allowed and writes nothing.
+#[no_mangle]
+pub extern "C" fn sssp_run_baseline_growth(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+    out_growth: *mut u32,
+    num_points: u32,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if source >= n { return -2; }
+        if out_dist.is_null() || out_pred.is_null() || (out_growth.is_null() && num_points > 0) { return -3; }
+        let dist = as_mut_slice(out_dist, n as usize);
+        let pred = as_mut_slice(out_pred, n as usize);
+        let mut settle_d: Vec<f32> = Vec::new();
+        let c = dijkstra_with(off, tgt, wts, source, dist, pred, |_, d| { settle_d.push(d); true });
+        if num_points > 0 {
+            let growth = as_mut_slice(out_growth, num_points as usize);
+            let max_d = *settle_d.last().unwrap_or(&0.0);
+            let last = (num_points - 1).max(1) as f32;
+            for (i, g) in growth.iter_mut().enumerate() {
+                let t = if num_points == 1 { max_d } else { max_d * i as f32 / last };
+                *g = settle_d.partition_point(|&d| d <= t) as u32;
+            }
+        }
+        if !info.is_null() { unsafe { *info = SsspResultInfo { relaxations: c.relaxations, light_relaxations: 0, heavy_relaxations: 0, settled: c.settled, error_code: 0, overflow: 0, reachable: crate::count_reachable(dist), edges: tgt.len() as u64 }; } }
+        0
+    })
+}
+
+// Forward Dijkstra from `s` returning the eccentricity (max finite distance, 0 if
+// only `s` is reachable).
+fn eccentricity_from(off: &[u32], tgt: &[u32], wts: &[f32], s: u32, dist: &mut [f32], pred: &mut [i32]) -> f32 {
+    let mut ecc = 0.0f32;
+    dijkstra_with(off, tgt, wts, s, dist, pred, |_, d| { ecc = d; true });
+    ecc
+}
+
+// Eccentricity of every node: ecc(v) = max over nodes reachable from v of d(v,u)
+// (unreachable nodes are ignored, so a sink has ecc 0).
+// exact = true : one SSSP per node, spread over `available_parallelism` threads.
+// exact = false: bounding scheme in the spirit of Takes & Kosters. Each round runs
+//   a forward + backward pass from the node with the widest [lo,hi] gap and, for
+//   every v with d(v,w) finite, tightens lo(v) >= d(v,w); when v and w also share
+//   an SCC (d(w,v) finite) their reach sets coincide, giving
+//   lo(v) >= ecc(w) - d(w,v) and hi(v) <= d(v,w) + ecc(w).
+//   At most ceil(sqrt(n)) rounds run; `out_ecc` is exact for nodes whose bounds
+//   met (including every sampled node) and the lower bound otherwise.
+#[no_mangle]
+pub extern "C" fn sssp_eccentricity_all(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    out_ecc: *mut f32, // len n
+    exact: bool,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if out_ecc.is_null() { return -3; }
+        let n_usize = n as usize;
+        let ecc = as_mut_slice(out_ecc, n_usize);
+        if exact {
+            let threads = std::thread::available_parallelism().map(|t| t.get()).unwrap_or(1).min(n_usize);
+            let chunk = n_usize.div_ceil(threads);
+            std::thread::scope(|scope| {
+                for (ci, out) in ecc.chunks_mut(chunk).enumerate() {
+                    scope.spawn(move || {
+                        let mut dist = vec![0f32; n_usize]; let mut pred = vec![-1i32; n_usize];
+                        for (i, e) in out.iter_mut().enumerate() { *e = eccentricity_from(off, tgt, wts, (ci * chunk + i) as u32, &mut dist, &mut pred); }
+                    });
+                }
+            });
+            return 0;
+        }
+        let (r_off, r_tgt, r_wts) = transpose_csr(off, tgt, wts);
+        let mut lo = vec![0f32; n_usize]; let mut hi = vec![f32::INFINITY; n_usize];
+        let mut fwd = vec![0f32; n_usize]; let mut bwd = vec![0f32; n_usize]; let mut pred = vec![-1i32; n_usize];
+        let rounds = ((n_usize as f64).sqrt().ceil() as usize).max(1);
+        let mut w = 0usize;
+        for _ in 0..rounds {
+            let ew = eccentricity_from(off, tgt, wts, w as u32, &mut fwd, &mut pred);
+            dijkstra_with(&r_off, &r_tgt, &r_wts, w as u32, &mut bwd, &mut pred, |_, _| true);
+            lo[w] = ew; hi[w] = ew;
+            for v in 0..n_usize {
+                let (dvw, dwv) = (bwd[v], fwd[v]);
+                if !dvw.is_finite() { continue; }
+                if dvw > lo[v] { lo[v] = dvw; }
+                if dwv.is_finite() {
+                    if ew - dwv > lo[v] { lo[v] = ew - dwv; }
+                    if dvw + ew < hi[v] { hi[v] = dvw + ew; }
+                }
+            }
+            // Next sample: widest remaining gap (unbounded first, ties to the lowest id).
+            let mut best: Option<(usize, f32)> = None;
+            for v in 0..n_usize { let gap = hi[v] - lo[v]; if gap > 0.0 && best.is_none_or(|(_, g)| gap > g) { best = Some((v, gap)); } }
+            match best { Some((v, _)) => w = v, None => break }
+        }
+        ecc.copy_from_slice(&lo);
+        0
+    })
+}
+
+// Distances from every node to a few sinks: one reverse Dijkstra per sink over the
+// transposed CSR (built once). Row-major n x num_sinks output, so column j holds
+// d(v, sinks[j]) for all v (+inf if v cannot reach it); a min over each row gives
+// the distance to the nearest sink.
+#[no_mangle]
+pub extern "C" fn sssp_distances_to_targets(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    sinks: *const u32,
+    num_sinks: u32,
+    out_matrix: *mut f32, // len n * num_sinks
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if num_sinks == 0 { return 0; }
+        if sinks.is_null() || out_matrix.is_null() { return -3; }
+        let sinks = as_slice(sinks, num_sinks as usize);
+        if sinks.iter().any(|&t| t >= n) { return -2; }
+        let (n_usize, k) = (n as usize, num_sinks as usize);
+        let matrix = as_mut_slice(out_matrix, n_usize * k);
+        let (r_off, r_tgt, r_wts) = transpose_csr(off, tgt, wts);
+        let mut dist = vec![0f32; n_usize]; let mut pred = vec![-1i32; n_usize];
+        for (j, &t) in sinks.iter().enumerate() {
+            dijkstra_with(&r_off, &r_tgt, &r_wts, t, &mut dist, &mut pred, |_, _| true);
+            for (row, &d) in matrix.chunks_exact_mut(k).zip(&dist) { row[j] = d; }
+        }
+        0
+    })
+}
+
+// Service cost of one facility: sum over `clients` (repeats count again) of
+// d(facility, client), accumulated in f64. The search stops once every distinct
+// client is settled. If any client is unreachable `out_cost` is +inf and -20 is
+// returned, so an infeasible placement cannot pass for a cheap one.
+#[no_mangle]
+pub extern "C" fn sssp_service_cost(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    facility: u32,
+    clients: *const u32,
+    num_clients: u32,
+    out_cost: *mut f64,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if facility >= n { return -2; }
+        if out_cost.is_null() || (clients.is_null() && num_clients > 0) { return -3; }
+        let cl = if num_clients == 0 { &[][..] } else { as_slice(clients, num_clients as usize) };
+        if cl.iter().any(|&c| c >= n) { return -2; }
+        let mut pending = vec![false; n as usize];
+        let mut left = 0u32;
+        for &c in cl { if !pending[c as usize] { pending[c as usize] = true; left += 1; } }
+        let mut dist = vec![0f32; n as usize]; let mut pred = vec![-1i32; n as usize];
+        if left > 0 { dijkstra_with(off, tgt, wts, facility, &mut dist, &mut pred, |u, _| { if pending[u as usize] { pending[u as usize] = false; left -= 1; } left > 0 }); }
+        let cost = if left > 0 { f64::INFINITY } else { cl.iter().map(|&c| dist[c as usize] as f64).sum() };
+        unsafe { *out_cost = cost; }
+        if left > 0 { -20 } else { 0 }
+    })
+}
+
+// Mean shortest-path length from `source` over reachable nodes (inverse of
+// closeness). `include_source` counts the source itself (distance 0) in both the
+// mean and `out_reachable`; with nothing to average `out_avg` is 0.0. The sum is
+// accumulated in f64.
+#[no_mangle]
+pub extern "C" fn sssp_avg_distance(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    include_source: bool,
+    out_avg: *mut f32,
+    out_reachable: *mut u32,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if source >= n { return -2; }
+        if out_avg.is_null() || out_reachable.is_null() { return -3; }
+        let mut dist = vec![0f32; n as usize]; let mut pred = vec![-1i32; n as usize];
+        let (mut sum, mut count) = (0f64, 0u32);
+        dijkstra_with(off, tgt, wts, source, &mut dist, &mut pred, |u, d| { if u != source || include_source { sum += d as f64; count += 1; } true });
+        unsafe { *out_avg = if count == 0 { 0.0 } else { (sum / count as f64) as f32 }; *out_reachable = count; }
+        0
+    })
+}
+
+// One row of the Wiener index: sum of d(source, v) over reachable v != source,
+// accumulated in f64. Unreachable pairs are ignored; summing over all sources
+// gives the directed Wiener index (twice the usual value on undirected graphs).
+#[no_mangle]
+pub extern "C" fn sssp_wiener_partial(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    out_sum: *mut f64,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if source >= n { return -2; }
+        if out_sum.is_null() { return -3; }
+        let mut dist = vec![0f32; n as usize]; let mut pred = vec![-1i32; n as usize];
+        let mut sum = 0f64;
+        dijkstra_with(off, tgt, wts, source, &mut dist, &mut pred, |_, d| { sum += d as f64; true });
+        unsafe { *out_sum = sum; }
+        0
+    })
+}
+
+// Log-bucketed quantile sketch (DDSketch-style): positive values land in bucket
+// ceil(log_gamma(x)) with gamma = (1+a)/(1-a), so any reported quantile is within
+// relative error `a` of a true sample value while memory grows only with
+// log(max/min) / a, not with the number of values. Zeros are counted apart.
+struct QuantileSketch { ln_gamma: f64, zeros: u64, bins: BTreeMap<i32, u64>, count: u64 }
+
+impl QuantileSketch {
+    fn new(rel_err: f64) -> Self { QuantileSketch { ln_gamma: ((1.0 + rel_err) / (1.0 - rel_err)).ln(), zeros: 0, bins: BTreeMap::new(), count: 0 } }
+    fn add(&mut self, x: f32) {
+        self.count += 1;
+        if x <= 0.0 { self.zeros += 1; } else { *self.bins.entry(((x as f64).ln() / self.ln_gamma).ceil() as i32).or_insert(0) += 1; }
+    }
+    // Nearest-rank quantile: the ceil(p * count)-th smallest value (p in [0,1]).
+    fn quantile(&self, p: f64) -> f32 {
+        let rank = ((p * self.count as f64).ceil() as u64).max(1);
+        if rank <= self.zeros { return 0.0; }
+        let mut seen = self.zeros;
+        for (&i, &c) in &self.bins {
+            seen += c;
+            if seen >= rank { let gamma = self.ln_gamma.exp(); return (2.0 * gamma.powi(i) / (gamma + 1.0)) as f32; }
+        }
+        0.0
+    }
+}
+
+// Effective diameter: the `percentile` quantile (0.9 is the usual choice) of all
+// finite d(s, v), v != s, over `samples` sources drawn uniformly with replacement
+// (xorshift64* from `seed`); `samples` >= n uses every node once instead, making
+// the value exact up to the sketch. Distances stream into a QuantileSketch (1%
+// relative error), so memory is independent of n * samples. Returns -7 for a
+// percentile outside [0,1] or zero samples, and -20 (value 0.0) if no pair of
+// distinct nodes is connected.
+#[no_mangle]
+pub extern "C" fn sssp_effective_diameter(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    samples: u32,
+    seed: u64,
+    percentile: f32,
+    out_value: *mut f32,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if out_value.is_null() { return -3; }
+        if !(0.0..=1.0).contains(&percentile) || samples == 0 { return -7; }
+        let sources: Vec<u32> = if samples >= n { (0..n).collect() } else {
+            let mut rng = crate::gen::XorShift64Star::new(seed | 1);
+            (0..samples).map(|_| rng.below(n)).collect()
+        };
+        let mut sketch = QuantileSketch::new(0.01);
+        let mut dist = vec![0f32; n as usize]; let mut pred = vec![-1i32; n as usize];
+        for &s in &sources { dijkstra_with(off, tgt, wts, s, &mut dist, &mut pred, |u, d| { if u != s { sketch.add(d); } true }); }
+        let value = if sketch.count == 0 { 0.0 } else { sketch.quantile(percentile as f64) };
+        unsafe { *out_value = value; }
+        if sketch.count == 0 { -20 } else { 0 }
+    })
+}
+
+// Shortest-path distances packaged as a feasible potential for reduced-cost
+// reweighting: with pot = dist, w'(u,v) = w + pot[u] - pot[v] >= 0 for every edge
+// (see `sssp_reduced_cost`). Unreachable nodes get the largest finite distance so
+// edges leaving them stay non-negative too (no edge enters them from reachable
+// nodes). Tight (zero reduced cost) edges are exactly the shortest-path edges.
+#[no_mangle]
+pub extern "C" fn sssp_run_baseline_potentials(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    out_potential: *mut f32, // len n
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if source >= n { return -2; }
+        if out_potential.is_null() { return -3; }
+        let pot = as_mut_slice(out_potential, n as usize);
+        let mut pred = vec![-1i32; n as usize];
+        let mut max_d = 0.0f32;
+        dijkstra_with(off, tgt, wts, source, pot, &mut pred, |_, d| { max_d = d; true });
+        for p in pot.iter_mut() { if !p.is_finite() { *p = max_d; } }
+        0
+    })
+}
+
+// "Nearest exit": Dijkstra that stops as soon as any of `goal_nodes` is settled,
+// reporting which one and its distance (ties resolve by heap order; the source
+// itself counts if listed). Returns -20 if no goal is reachable.
+#[no_mangle]
+pub extern "C" fn sssp_run_baseline_first_target(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    goal_nodes: *const u32,
+    num_goals: u32,
+    out_target: *mut u32,
+    out_dist: *mut f32,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if source >= n { return -2; }
+        if out_target.is_null() || out_dist.is_null() || (goal_nodes.is_null() && num_goals > 0) { return -3; }
+        let goals = if num_goals == 0 { &[][..] } else { as_slice(goal_nodes, num_goals as usize) };
+        if goals.iter().any(|&g| g >= n) { return -2; }
+        let mut is_goal = vec![false; n as usize];
+        for &g in goals { is_goal[g as usize] = true; }
+        let mut dist = vec![0f32; n as usize]; let mut pred = vec![-1i32; n as usize];
+        let mut hit: Option<(u32, f32)> = None;
+        dijkstra_with(off, tgt, wts, source, &mut dist, &mut pred, |u, d| { if is_goal[u as usize] { hit = Some((u, d)); false } else { true } });
+        match hit { Some((t, d)) => { unsafe { *out_target = t; *out_dist = d; } 0 } None => -20 }
+    })
+}
+
+// k-NN distance: Dijkstra stopped at the k-th settled node other than the source;
+// `out_dist` receives that node's distance. k = 0 returns 0.0 (the source itself).
+// Returns -20 if fewer than k nodes are reachable.
+#[no_mangle]
+pub extern "C" fn sssp_kth_nearest_dist(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    k: u32,
+    out_dist: *mut f32,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if source >= n { return -2; }
+        if out_dist.is_null() { return -3; }
+        let mut dist = vec![0f32; n as usize]; let mut pred = vec![-1i32; n as usize];
+        let (mut seen, mut kth) = (0u32, if k == 0 { Some(0.0f32) } else { None });
+        if k > 0 { dijkstra_with(off, tgt, wts, source, &mut dist, &mut pred, |u, d| { if u != source { seen += 1; if seen == k { kth = Some(d); return false; } } true }); }
+        match kth { Some(d) => { unsafe { *out_dist = d; } 0 } None => -20 }
+    })
+}
+
+// Radius-bounded Dijkstra fused with a containment query: `out_within[i]` = 1 iff
+// d(source, query_nodes[i]) <= radius, else 0 (unreachable included). The search
+// stops at the first pop beyond `radius`; every node within it is final by then.
+// Returns -7 if `radius` is NaN.
+#[no_mangle]
+pub extern "C" fn sssp_run_baseline_within(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    radius: f32,
+    query_nodes: *const u32,
+    num_query: u32,
+    out_within: *mut u8, // len num_query
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if source >= n { return -2; }
+        if (query_nodes.is_null() || out_within.is_null()) && num_query > 0 { return -3; }
+        if radius.is_nan() { return -7; }
+        if num_query == 0 { return 0; }
+        let q = as_slice(query_nodes, num_query as usize);
+        if q.iter().any(|&v| v >= n) { return -2; }
+        let mut dist = vec![0f32; n as usize]; let mut pred = vec![-1i32; n as usize];
+        dijkstra_with(off, tgt, wts, source, &mut dist, &mut pred, |_, d| d <= radius);
+        for (o, &v) in as_mut_slice(out_within, q.len()).iter_mut().zip(q) { *o = (dist[v as usize] <= radius) as u8; }
+        0
+    })
+}
+
+// Node sequence from the pred chain ending at `target` (source first).
+fn pred_path(pred: &[i32], target: u32) -> Vec<u32> {
+    let mut path = vec![target]; let mut v = target as usize;
+    while pred[v] >= 0 { v = pred[v] as usize; path.push(v as u32); }
+    path.reverse();
+    path
+}
+
+// Writes the pred-chain path source..target into `out_nodes` and returns its node
+// count (1 when target == source). `n` is the length of `pred`; the walk is bounded
+// by it. Note the codes differ from the runners': -1 the chain ends before
+// reaching `source` (unreachable), -2 more than `out_cap` nodes
+// (nothing written), -3 malformed `pred` (cycle or id out of range), -7 null
+// pointer or `source`/`target` >= n.
+#[no_mangle]
+pub extern "C" fn sssp_extract_path(
+    pred: *const i32,
+    n: u32,
+    source: u32,
+    target: u32,
+    out_nodes: *mut u32,
+    out_cap: u32,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        if pred.is_null() || (out_nodes.is_null() && out_cap > 0) || source >= n || target >= n { return -7; }
+        let p = as_slice(pred, n as usize);
+        let mut path = vec![target];
+        let mut v = target;
+        while v != source {
+            let u = p[v as usize];
+            if u < 0 { return -1; }
+            if u as u32 >= n || path.len() >= n as usize { return -3; }
+            v = u as u32; path.push(v);
+        }
+        if path.len() > out_cap as usize { return -2; }
+        for (o, &x) in as_mut_slice(out_nodes, path.len()).iter_mut().zip(path.iter().rev()) { *o = x; }
+        path.len() as i32
+    })
+}
+
+// Writes the shortest-path tree as (parent, child) pairs, one per node with
+// pred[v] >= 0, in ascending child order, and returns the pair count. The
+// source (pred -1) and unreached nodes (pred -1) contribute nothing, so an
+// isolated source yields 0. Entries naming a parent >= n or the node itself are
+// skipped rather than written. Both outputs need room for n - 1 pairs: an array
+// giving every node a parent has no root, is not a tree and returns 0 unwritten,
+// as do null outputs or `pred`.
+#[no_mangle]
+pub extern "C" fn sssp_extract_tree(pred: *const i32, n: u32, out_parents: *mut u32, out_children: *mut u32) -> u32 {
+    crate::ffi_guard(0, || {
+        if pred.is_null() || out_parents.is_null() || out_children.is_null() || n == 0 { return 0; }
+        let p = as_slice(pred, n as usize);
+        let edges = || p.iter().enumerate().filter(|&(v, &u)| u >= 0 && (u as u32) < n && u as usize != v);
+        let k = edges().count();
+        if k == n as usize { return 0; }
+        let (parents, children) = (as_mut_slice(out_parents, k), as_mut_slice(out_children, k));
+        for (i, (v, &u)) in edges().enumerate() { parents[i] = u as u32; children[i] = v as u32; }
+        k as u32
+    })
+}
+
+// Shortest source -> waypoint -> target route: two early-exit Dijkstra legs
+// (source→waypoint, waypoint→target). `out_dist` receives the summed length and
+// `out_path` the concatenated node sequence (waypoint listed once); the route may
+// revisit nodes since the legs are independent. `out_path_len` always receives the
+// required length; if it exceeds `cap` the path is not written and -21 is returned.
+// Returns -20 if either leg is unreachable.
+#[no_mangle]
+pub extern "C" fn sssp_run_via_waypoint(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    waypoint: u32,
+    target: u32,
+    out_dist: *mut f32,
+    out_path: *mut u32,    // len cap
+    out_path_len: *mut u32,
+    cap: u32,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if source >= n || waypoint >= n || target >= n { return -2; }
+        if out_dist.is_null() || out_path.is_null() || out_path_len.is_null() { return -3; }
+        let mut dist = vec![0f32; n as usize]; let mut pred = vec![-1i32; n as usize];
+        let mut leg = |from: u32, to: u32| -> Option<(f32, Vec<u32>)> {
+            dijkstra_with(off, tgt, wts, from, &mut dist, &mut pred, |u, _| u != to);
+            if dist[to as usize].is_finite() { Some((dist[to as usize], pred_path(&pred, to))) } else { None }
+        };
+        let Some((d1, p1)) = leg(source, waypoint) else { return -20 };
+        let Some((d2, p2)) = leg(waypoint, target) else { return -20 };
+        let len = p1.len() + p2.len() - 1;
+        unsafe { *out_dist = d1 + d2; *out_path_len = len as u32; }
+        if len > cap as usize { return -21; }
+        let out = as_mut_slice(out_path, len);
+        out[..p1.len()].copy_from_slice(&p1); out[p1.len()..].copy_from_slice(&p2[1..]);
+        0
+    })
+}
+
+// Shortest-path-tree band analysis for delta selection. Band of a node =
+// floor(dist / delta); a tree edge pred[v] -> v "crosses" when its endpoints lie in
+// different bands (delta-stepping would relax it as a heavy edge across buckets).
+// `out_crossings` receives the total; `out_bands[b]` (len num_bands, may be null
+// when num_bands == 0) counts crossing edges whose child lies in band b, with the
+// last slot absorbing higher bands. Unreachable nodes and roots are skipped.
+// Returns -7 if `delta` is NaN or <= 0.
+#[no_mangle]
+pub extern "C" fn sssp_spt_band_crossings(
+    dist: *const f32,
+    pred: *const i32,
+    n: u32,
+    delta: f32,
+    out_crossings: *mut u64,
+    num_bands: u32,
+    out_bands: *mut u32,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        if n == 0 { return -1; }
+        if dist.is_null() || pred.is_null() || out_crossings.is_null() || (out_bands.is_null() && num_bands > 0) { return -3; }
+        if delta.is_nan() || delta <= 0.0 { return -7; }
+        let d = as_slice(dist, n as usize); let p = as_slice(pred, n as usize);
+        let bands: &mut [u32] = if num_bands == 0 { &mut [] } else { as_mut_slice(out_bands, num_bands as usize) };
+        for b in bands.iter_mut() { *b = 0; }
+        let band = |x: f32| (x / delta) as u64;
+        let mut crossings = 0u64;
+        for v in 0..n as usize {
+            let u = p[v];
+            if u < 0 || u as u32 >= n || !d[v].is_finite() { continue; }
+            let bv = band(d[v]);
+            if band(d[u as usize]) != bv {
+                crossings += 1;
+                if let Some(last) = bands.len().checked_sub(1) { bands[(bv as usize).min(last)] += 1; }
+            }
+        }
+        unsafe { *out_crossings = crossings; }
+        0
+    })
+}
+
+// Immediate dominators of the shortest-path DAG: the tight edges u->v with
+// dist[u] + w == dist[v] (exact f32 equality, as produced by a baseline run on the
+// same CSR). idom(v) is the last node that every shortest path source->v passes
+// through. Iterative Cooper-Harvey-Kennedy over reverse postorder, which also copes
+// with zero-weight cycles among tight edges. `out_idom[v]` is -1 for the source and
+// for nodes with no tight path from it.
+#[no_mangle]
+pub extern "C" fn sssp_spt_dominators(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    dist: *const f32,
+    source: u32,
+    out_idom: *mut i32,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if source >= n { return -2; }
+        if dist.is_null() || out_idom.is_null() { return -3; }
+        let n_usize = n as usize;
+        let d = as_slice(dist, n_usize);
+        let tight = |u: usize, e: usize| d[u].is_finite() && d[u] + wts[e] == d[tgt[e] as usize];
+        // Reverse postorder of the tight subgraph reachable from the source.
+        const UNSEEN: u32 = u32::MAX;
+        let mut rpo = vec![UNSEEN; n_usize];
+        let mut post: Vec<u32> = Vec::new();
+        let mut stack: Vec<(u32, usize)> = vec![(source, off[source as usize] as usize)];
+        rpo[source as usize] = 0;
+        while let Some(&mut (u, ref mut e)) = stack.last_mut() {
+            let u_usize = u as usize;
+            if *e < off[u_usize + 1] as usize {
+                let (edge, v) = (*e, tgt[*e]); *e += 1;
+                if rpo[v as usize] == UNSEEN && tight(u_usize, edge) { rpo[v as usize] = 0; stack.push((v, off[v as usize] as usize)); }
+            } else { post.push(u); stack.pop(); }
+        }
+        post.reverse();
+        for (i, &v) in post.iter().enumerate() { rpo[v as usize] = i as u32; }
+        let mut preds: Vec<Vec<u32>> = vec![Vec::new(); n_usize];
+        for &u in &post {
+            let (start, end) = (off[u as usize] as usize, off[u as usize + 1] as usize);
+            for (e, &v) in (start..end).zip(&tgt[start..end]) { if v != source && rpo[v as usize] != UNSEEN && tight(u as usize, e) { preds[v as usize].push(u); } }
+        }
+        let mut idom = vec![UNSEEN; n_usize];
+        idom[source as usize] = source;
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &v in &post[1..] {
+                let mut new = UNSEEN;
+                for &p in &preds[v as usize] {
+                    if idom[p as usize] == UNSEEN { continue; }
+                    if new == UNSEEN { new = p; continue; }
+                    let (mut a, mut b) = (p, new);
+                    while a != b {
+                        while rpo[a as usize] > rpo[b as usize] { a = idom[a as usize]; }
+                        while rpo[b as usize] > rpo[a as usize] { b = idom[b as usize]; }
+                    }
+                    new = a;
+                }
+                if new != idom[v as usize] { idom[v as usize] = new; changed = true; }
+            }
+        }
+        let out = as_mut_slice(out_idom, n_usize);
+        for v in 0..n_usize { out[v] = if v == source as usize || idom[v] == UNSEEN { -1 } else { idom[v] as i32 }; }
+        0
+    })
+}
+
+// ---------------- Pairwise distances via cached SSSP trees ----------------
+// Opaque handle owning up to `max_trees` distance arrays (0 = unbounded). A query
+// (u,v) computes the full tree from `u` on first use and answers later (u,*)
+// queries from the stored row; when full, the least recently used row is evicted.
+// The cache does not own the graph: callers must pass the same CSR on every query.
+pub struct SsspTreeCache {
+    n: u32,
+    max_trees: usize,
+    tick: u64,
+    rows: Vec<(u32, u64, Vec<f32>)>, // (source, last use, dist)
+    computed: u64,
+    hits: u64,
+    evictions: u64,
+}
+
+#[no_mangle]
+pub extern "C" fn sssp_tree_cache_new(n: u32, max_trees: u32) -> *mut SsspTreeCache {
+    crate::ffi_guard(core::ptr::null_mut(), || {
+        Box::into_raw(Box::new(SsspTreeCache { n, max_trees: max_trees as usize, tick: 0, rows: Vec::new(), computed: 0, hits: 0, evictions: 0 }))
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn sssp_tree_cache_free(cache: *mut SsspTreeCache) {
+    if !cache.is_null() { unsafe { drop(Box::from_raw(cache)); } }
+}
+
+// Returns d(u,v) (+inf if unreachable) or NaN on invalid input (null cache, n
+// mismatch with the cache, malformed CSR, out-of-range nodes).
+#[no_mangle]
+pub extern "C" fn sssp_tree_cache_query(
+    cache: *mut SsspTreeCache,
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    u: u32,
+    v: u32,
+) -> f32 {
+    crate::ffi_guard(f32::NAN, || {
+        if cache.is_null() { return f32::NAN; }
+        let c = unsafe { &mut *cache };
+        if n != c.n || u >= n || v >= n { return f32::NAN; }
+        c.tick += 1;
+        if let Some(row) = c.rows.iter_mut().find(|r| r.0 == u) { row.1 = c.tick; c.hits += 1; return row.2[v as usize]; }
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(_) => return f32::NAN };
+        let mut dist = vec![0f32; n as usize]; let mut pred = vec![-1i32; n as usize];
+        dijkstra_with(off, tgt, wts, u, &mut dist, &mut pred, |_, _| true);
+        c.computed += 1;
+        let d = dist[v as usize];
+        if c.max_trees > 0 && c.rows.len() >= c.max_trees {
+            let lru = c.rows.iter().enumerate().min_by_key(|(_, r)| r.1).map(|(i, _)| i).unwrap();
+            c.rows.swap_remove(lru); c.evictions += 1;
+        }
+        c.rows.push((u, c.tick, dist));
+        d
+    })
+}
+
+// Counters since creation: trees computed (misses), queries answered from a stored
+// tree, and LRU evictions. Null out-pointers are skipped.
+#[no_mangle]
+pub extern "C" fn sssp_tree_cache_stats(cache: *const SsspTreeCache, out_computed: *mut u64, out_hits: *mut u64, out_evictions: *mut u64) -> i32 {
+    crate::ffi_guard(-99, || {
+        if cache.is_null() { return -3; }
+        let c = unsafe { &*cache };
+        unsafe {
+            if !out_computed.is_null() { *out_computed = c.computed; }
+            if !out_hits.is_null() { *out_hits = c.hits; }
+            if !out_evictions.is_null() { *out_evictions = c.evictions; }
+        }
+        0
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metrics_match_individual_queries(){
+        // 0->1 (1), 0->2 (4), 1->2 (2), 1->3 (3), 2->4 (1), 3->4 (1), 4->5 (0); 6 is isolated.
+        // dist = [0,1,3,4,4,4], tree hops = [0,1,2,2,3,4] (4 via 2, 5 via 4).
+        let off = [0u32,2,4,5,6,7,7,7]; let tgt = [1u32,2,2,3,4,4,5]; let wts = [1.0f32,4.0,2.0,3.0,1.0,1.0,0.0];
+        let mut m = SsspMetrics::default();
+        assert_eq!(sssp_run_baseline_metrics(7, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, &mut m), 0);
+        let (mut dist, mut pred) = ([0f32;7], [0i32;7]);
+        let mut info = SsspResultInfo { relaxations:0, light_relaxations:0, heavy_relaxations:0, settled:0, error_code:0, overflow:0, reachable:0, edges:0 };
+        let (mut far, mut far_d) = (0u32, 0f32);
+        sssp_run_baseline_farthest(7, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info, &mut far, &mut far_d);
+        let mut sum = 0f64; sssp_wiener_partial(7, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, &mut sum);
+        let hops: u64 = (0..7).filter(|&v| dist[v].is_finite()).map(|v| pred_path(&pred, v as u32).len() as u64 - 1).sum();
+        let mut levels: Vec<u32> = dist.iter().filter(|d| d.is_finite()).map(|d| d.to_bits()).collect(); levels.sort(); levels.dedup();
+        assert_eq!((m.settled, m.max_dist, m.farthest_node), (info.settled, far_d, far));
+        assert_eq!((m.sum_dist, m.sum_hops, m.distinct_dist_bands), (sum, hops, levels.len() as u32));
+        assert_eq!((m.settled, m.sum_dist, m.sum_hops, m.distinct_dist_bands), (6, 16.0, 12, 4));
+        // Only the source reached.
+        assert_eq!(sssp_run_baseline_metrics(7, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 6, &mut m), 0);
+        assert_eq!((m.settled, m.farthest_node, m.max_dist, m.sum_hops, m.distinct_dist_bands), (1, u32::MAX, 0.0, 0, 1));
+    }
+
+    #[test]
+    fn effective_diameter_on_undirected_path(){
+        // Unit path 0-1-...-49 (both directions): d(i,j) = |i-j|.
+        let n = 50u32;
+        let mut off = vec![0u32]; let mut tgt = Vec::new();
+        for u in 0..n { if u > 0 { tgt.push(u - 1); } if u + 1 < n { tgt.push(u + 1); } off.push(tgt.len() as u32); }
+        let wts = vec![1.0f32; tgt.len()];
+        let mut all: Vec<f32> = (0..n).flat_map(|i| (0..n).filter(move |&j| j != i).map(move |j| (i as i32 - j as i32).abs() as f32)).collect();
+        all.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let mut value = 0f32;
+        for &p in &[0.5f32, 0.9, 1.0] {
+            let want = all[((p as f64 * all.len() as f64).ceil() as usize).max(1) - 1];
+            assert_eq!(sssp_effective_diameter(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), n, 0, p, &mut value), 0);
+            assert!((value - want).abs() <= 0.011 * want, "p={} got {} want {}", p, value, want);
+        }
+        // Sampled sources stay close to the all-pairs value.
+        let want = all[(0.9 * all.len() as f64).ceil() as usize - 1];
+        assert_eq!(sssp_effective_diameter(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 30, 7, 0.9, &mut value), 0);
+        assert!((value - want).abs() <= 0.15 * want);
+        assert_eq!(sssp_effective_diameter(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), n, 0, 1.5, &mut value), -7);
+        let lone = [0u32, 0, 0];
+        assert_eq!(sssp_effective_diameter(2, lone.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 2, 0, 0.9, &mut value), -20);
+    }
+
+    #[test]
+    fn farthest_node_on_asymmetric_graph(){
+        // 0->1 (1), 0->2 (5), 1->3 (2), 3->4 (1): dist = [0,1,5,3,4] -> farthest is 2.
+        let off = [0u32,2,3,3,4,4]; let tgt = [1u32,2,3,4]; let wts = [1.0f32,5.0,2.0,1.0];
+        let mut dist = [0f32;5]; let mut pred = [0i32;5]; let mut far = 0u32; let mut far_d = 0f32;
+        let rc = sssp_run_baseline_farthest(5, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, dist.as_mut_ptr(), pred.as_mut_ptr(), core::ptr::null_mut(), &mut far, &mut far_d);
+        assert_eq!(rc, 0);
+        assert_eq!((far, far_d), (2, 5.0));
+        // From the sink nothing else is reachable.
+        let rc = sssp_run_baseline_farthest(5, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 4, dist.as_mut_ptr(), pred.as_mut_ptr(), core::ptr::null_mut(), &mut far, &mut far_d);
+        assert_eq!(rc, 0);
+        assert_eq!((far, far_d), (u32::MAX, 0.0));
+    }
+
+    #[test]
+    fn eccentricity_exact_matches_brute_force(){
+        // Two 4-cycles joined by a one-way bridge 3->4, plus chords.
+        let n = 8u32;
+        let edges: [(u32,u32,f32);11] = [(0,1,1.0),(1,2,2.0),(2,3,1.0),(3,0,3.0),(0,2,4.0),(3,4,2.0),(4,5,1.0),(5,6,1.0),(6,7,2.0),(7,4,1.0),(5,7,5.0)];
+        let mut off = vec![0u32; n as usize + 1]; for &(u,_,_) in &edges { off[u as usize + 1] += 1; } for i in 0..n as usize { off[i+1] += off[i]; }
+        let tgt: Vec<u32> = edges.iter().map(|e| e.1).collect(); let wts: Vec<f32> = edges.iter().map(|e| e.2).collect();
+        let mut brute = vec![0f32; n as usize]; let mut dist = vec![0f32; n as usize]; let mut pred = vec![0i32; n as usize];
+        for s in 0..n { crate::sssp_run_baseline(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), s, dist.as_mut_ptr(), pred.as_mut_ptr(), core::ptr::null_mut()); brute[s as usize] = dist.iter().copied().filter(|d| d.is_finite()).fold(0.0, f32::max); }
+        let mut ecc = vec![0f32; n as usize];
+        assert_eq!(sssp_eccentricity_all(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), ecc.as_mut_ptr(), true), 0);
+        assert_eq!(ecc, brute);
+        // Estimated mode never overshoots and is exact on nodes it sampled (node 0 is always first).
+        assert_eq!(sssp_eccentricity_all(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), ecc.as_mut_ptr(), false), 0);
+        for v in 0..n as usize { assert!(ecc[v] <= brute[v] + 1e-5, "v={} est={} exact={}", v, ecc[v], brute[v]); }
+        assert_eq!(ecc[0], brute[0]);
+    }
+
+    #[test]
+    fn wiener_partial_matches_path_closed_form(){
+        // Undirected unit path 0-1-...-(n-1): row sum from v is (v(v+1) + (n-1-v)(n-v)) / 2,
+        // and the rows add up to twice the Wiener index (n^3 - n) / 6.
+        let n = 9u32; let mut off = vec![0u32]; let mut tgt = Vec::new();
+        for v in 0..n { if v > 0 { tgt.push(v - 1); } if v + 1 < n { tgt.push(v + 1); } off.push(tgt.len() as u32); }
+        let wts = vec![1.0f32; tgt.len()];
+        let mut total = 0f64;
+        for v in 0..n {
+            let mut row = 0f64;
+            assert_eq!(sssp_wiener_partial(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), v, &mut row), 0);
+            let (a, b) = (v as f64, (n - 1 - v) as f64);
+            assert_eq!(row, (a * (a + 1.0) + b * (b + 1.0)) / 2.0);
+            total += row;
+        }
+        let nf = n as f64; assert_eq!(total / 2.0, (nf * nf * nf - nf) / 6.0);
+    }
+
+    #[test]
+    fn growth_on_grid_is_quadratic(){
+        // 41x41 unit grid from the centre: within distance d <= 20 the settled set is the
+        // diamond 2d^2 + 2d + 1. Thresholds 0,10,20,30,40 (max_dist = 40, the corners).
+        let side = 41u32; let g = crate::gen::grid_graph(side, 1.0); let n = g.n;
+        let (off, tgt, wts) = (g.offsets, g.targets, g.weights);
+        let (mut dist, mut pred, mut growth) = (vec![0f32; n as usize], vec![0i32; n as usize], [0u32; 5]);
+        assert_eq!(sssp_run_baseline_growth(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 20 * side + 20, dist.as_mut_ptr(), pred.as_mut_ptr(), core::ptr::null_mut(), growth.as_mut_ptr(), 5), 0);
+        assert_eq!(&growth[..3], &[1, 221, 841]);
+        assert_eq!(growth[4], n);
+        // Doubling the radius roughly quadruples the settled set.
+        assert!(growth[2] as f32 / growth[1] as f32 > 3.5);
+    }
+
+    #[test]
+    fn dominators_of_rejoining_diamond(){
+        // 0->1->3 and 0->2->3 tie at 2, then 3->4; 2->5 is off the diamond; 6 unreachable.
+        let off = [0u32,2,3,5,6,6,6,6]; let tgt = [1u32,2,3,3,5,4]; let wts = [1.0f32,1.0,1.0,1.0,1.0,2.0];
+        let (mut dist, mut pred) = ([0f32;7], [0i32;7]);
+        crate::sssp_run_baseline(7, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, dist.as_mut_ptr(), pred.as_mut_ptr(), core::ptr::null_mut());
+        let mut idom = [0i32;7];
+        assert_eq!(sssp_spt_dominators(7, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), dist.as_ptr(), 0, idom.as_mut_ptr()), 0);
+        assert_eq!(idom, [-1, 0, 0, 0, 3, 2, -1]);
+    }
+
+    #[test]
+    fn distances_to_targets_columns_match_reverse_runs(){
+        let n = 90u32;
+        let g = crate::gen::random_graph_in(n, 3.0, 0x7A3F1C2B5D4E6F81, 1.0, 11.0); let (off, tgt, wts) = (g.offsets, g.targets, g.weights);
+        let sinks = [5u32, 40, 77];
+        let mut matrix = vec![0f32; n as usize * sinks.len()];
+        assert_eq!(sssp_distances_to_targets(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), sinks.as_ptr(), 3, matrix.as_mut_ptr()), 0);
+        let (r_off, r_tgt, r_wts) = transpose_csr(&off, &tgt, &wts);
+        let (mut dist, mut pred) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+        for (j, &t) in sinks.iter().enumerate() {
+            crate::sssp_run_baseline(n, r_off.as_ptr(), r_tgt.as_ptr(), r_wts.as_ptr(), t, dist.as_mut_ptr(), pred.as_mut_ptr(), core::ptr::null_mut());
+            for v in 0..n as usize { assert_eq!(matrix[v * 3 + j].to_bits(), dist[v].to_bits(), "v={} sink={}", v, t); }
+            assert_eq!(matrix[t as usize * 3 + j], 0.0);
+        }
+    }
+
+    #[test]
+    fn service_cost_sums_client_distances(){
+        let n = 80u32;
+        let g = crate::gen::random_graph_in(n, 4.0, 0xA0761D6478BD642F, 1.0, 11.0); let (off, tgt, wts) = (g.offsets, g.targets, g.weights);
+        let (mut dist, mut pred) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+        crate::sssp_run_baseline(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 3, dist.as_mut_ptr(), pred.as_mut_ptr(), core::ptr::null_mut());
+        let clients: Vec<u32> = (0..n).filter(|&v| dist[v as usize].is_finite()).step_by(5).chain([3]).collect();
+        let mut cost = 0f64;
+        assert_eq!(sssp_service_cost(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 3, clients.as_ptr(), clients.len() as u32, &mut cost), 0);
+        let want: f64 = clients.iter().map(|&c| dist[c as usize] as f64).sum();
+        assert_eq!(cost, want);
+        // An isolated client makes the placement infeasible.
+        let off2 = [0u32,1,1,1]; let tgt2 = [1u32]; let wts2 = [2.0f32];
+        assert_eq!(sssp_service_cost(3, off2.as_ptr(), tgt2.as_ptr(), wts2.as_ptr(), 0, [1u32, 2].as_ptr(), 2, &mut cost), -20);
+        assert!(cost.is_infinite());
+    }
+
+    #[test]
+    fn extract_path_walks_pred_and_flags_bad_input(){
+        // Tree 0->1->2->3, 4 unreached.
+        let pred = [-1i32, 0, 1, 2, -1]; let mut out = [0u32; 4];
+        assert_eq!(sssp_extract_path(pred.as_ptr(), 5, 0, 3, out.as_mut_ptr(), 4), 4);
+        assert_eq!(out, [0, 1, 2, 3]);
+        assert_eq!(sssp_extract_path(pred.as_ptr(), 5, 0, 0, out.as_mut_ptr(), 4), 1);
+        assert_eq!(out[0], 0);
+        assert_eq!(sssp_extract_path(pred.as_ptr(), 5, 0, 4, out.as_mut_ptr(), 4), -1);
+        assert_eq!(sssp_extract_path(pred.as_ptr(), 5, 0, 3, out.as_mut_ptr(), 3), -2);
+        // 1 <-> 2 loop never reaches the source.
+        let cyclic = [-1i32, 2, 1, 2];
+        assert_eq!(sssp_extract_path(cyclic.as_ptr(), 4, 0, 3, out.as_mut_ptr(), 4), -3);
+        assert_eq!(sssp_extract_path(pred.as_ptr(), 5, 0, 5, out.as_mut_ptr(), 4), -7);
+    }
+
+    #[test]
+    fn extract_tree_lists_parent_child_pairs(){
+        // 0 -> {1, 3}, 1 -> 2; 4 unreached, 5 names an out-of-range parent.
+        let pred = [-1i32, 0, 1, 0, -1, 9];
+        let (mut parents, mut children) = ([u32::MAX; 5], [u32::MAX; 5]);
+        assert_eq!(sssp_extract_tree(pred.as_ptr(), 6, parents.as_mut_ptr(), children.as_mut_ptr()), 3);
+        assert_eq!((&parents[..3], &children[..3]), (&[0u32, 1, 0][..], &[1u32, 2, 3][..]));
+        assert_eq!(parents[3], u32::MAX);
+        // A lone source has no tree edges; null outputs write nothing.
+        assert_eq!(sssp_extract_tree([-1i32].as_ptr(), 1, parents.as_mut_ptr(), children.as_mut_ptr()), 0);
+        assert_eq!(sssp_extract_tree(pred.as_ptr(), 6, core::ptr::null_mut(), children.as_mut_ptr()), 0);
+        // Self-parents are skipped; a parent for every node (here a cycle) is rejected.
+        assert_eq!(sssp_extract_tree([-1i32, 1, 0].as_ptr(), 3, parents.as_mut_ptr(), children.as_mut_ptr()), 1);
+        assert_eq!((parents[0], children[0]), (0, 2));
+        let mut two = ([u32::MAX; 2], [u32::MAX; 2]);
+        assert_eq!(sssp_extract_tree([2i32, 0, 1].as_ptr(), 3, two.0.as_mut_ptr(), two.1.as_mut_ptr()), 0);
+        assert_eq!(two, ([u32::MAX; 2], [u32::MAX; 2]));
+    }
+
+    #[test]
+    fn avg_distance_on_symmetric_square(){
+        // Undirected 4-cycle 0-1-2-3-0 with unit weights, plus isolated node 4.
+        // From 0: distances 1,2,1 -> mean 4/3 excluding the source, 4/4 including it.
+        let off = [0u32,2,4,6,8,8]; let tgt = [1u32,3,0,2,1,3,2,0]; let wts = [1.0f32;8];
+        let (mut avg, mut reach) = (0f32, 0u32);
+        assert_eq!(sssp_avg_distance(5, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, false, &mut avg, &mut reach), 0);
+        assert_eq!(reach, 3); assert!((avg - 4.0 / 3.0).abs() < 1e-6);
+        assert_eq!(sssp_avg_distance(5, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, true, &mut avg, &mut reach), 0);
+        assert_eq!((avg, reach), (1.0, 4));
+        assert_eq!(sssp_avg_distance(5, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 4, false, &mut avg, &mut reach), 0);
+        assert_eq!((avg, reach), (0.0, 0));
+    }
+
+    #[test]
+    fn via_waypoint_detours_through_stop(){
+        // Direct 0->3 (2); detour 0->1 (1), 1->2 (1), 2->3 (1); node 4 is a dead end.
+        let off = [0u32,2,3,4,4,4]; let tgt = [3u32,1,2,3]; let wts = [2.0f32,1.0,1.0,1.0];
+        let (mut d, mut len) = (0f32, 0u32); let mut path = [0u32;8];
+        assert_eq!(sssp_run_via_waypoint(5, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, 2, 3, &mut d, path.as_mut_ptr(), &mut len, 8), 0);
+        let mut dist = [0f32;5]; let mut pred = [0i32;5];
+        crate::sssp_run_baseline(5, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, dist.as_mut_ptr(), pred.as_mut_ptr(), core::ptr::null_mut());
+        assert!(d >= dist[3]);
+        assert_eq!(d, 3.0);
+        assert_eq!(&path[..len as usize], &[0, 1, 2, 3]);
+        assert_eq!(sssp_run_via_waypoint(5, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, 2, 3, &mut d, path.as_mut_ptr(), &mut len, 2), -21);
+        assert_eq!(len, 4);
+        assert_eq!(sssp_run_via_waypoint(5, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, 4, 3, &mut d, path.as_mut_ptr(), &mut len, 8), -20);
+    }
+
+    #[test]
+    fn band_crossings_drop_with_wider_delta(){
+        // Path 0->1->...->9 with unit weights: dist[v] = v, pred[v] = v-1.
+        let dist: Vec<f32> = (0..10).map(|v| v as f32).collect();
+        let pred: Vec<i32> = (0..10).map(|v| v - 1).collect();
+        let mut bands = [0u32;4]; let (mut narrow, mut wide) = (0u64, 0u64);
+        assert_eq!(sssp_spt_band_crossings(dist.as_ptr(), pred.as_ptr(), 10, 1.0, &mut narrow, 4, bands.as_mut_ptr()), 0);
+        assert_eq!(narrow, 9);
+        assert_eq!(bands, [0, 1, 1, 7]);
+        assert_eq!(sssp_spt_band_crossings(dist.as_ptr(), pred.as_ptr(), 10, 4.0, &mut wide, 0, core::ptr::null_mut()), 0);
+        assert_eq!(wide, 2);
+        assert!(wide < narrow);
+    }
+
+    #[test]
+    fn within_radius_marks_boundary_inclusive(){
+        // 0->1 (1), 1->2 (1), 2->3 (1.5); node 4 unreachable. Radius 2: {0,1,2} inside.
+        let off = [0u32,1,2,3,3,3]; let tgt = [1u32,2,3]; let wts = [1.0f32,1.0,1.5];
+        let q = [0u32,2,3,4,1]; let mut within = [9u8;5];
+        assert_eq!(sssp_run_baseline_within(5, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, 2.0, q.as_ptr(), 5, within.as_mut_ptr()), 0);
+        assert_eq!(within, [1, 1, 0, 0, 1]);
+    }
+
+    #[test]
+    fn potentials_give_non_negative_reduced_costs(){
+        let n = 80u32; let mut rng = crate::gen::XorShift64Star::new(0xA0761D6478BD642F);
+        let mut next = || rng.next_u32();
+        let mut off = vec![0u32]; let mut tgt = Vec::new(); let mut wts = Vec::new();
+        // Nodes >= 60 only have edges among themselves and into the rest (unreachable from 0).
+        for u in 0..n { for _ in 0..3 { let v = if u < 60 { next() % 60 } else { next() % n }; tgt.push(v); wts.push((next() % 997) as f32 / 13.0); } off.push(tgt.len() as u32); }
+        let mut pot = vec![0f32; n as usize]; let mut reduced = vec![0f32; tgt.len()];
+        assert_eq!(sssp_run_baseline_potentials(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, pot.as_mut_ptr()), 0);
+        assert!(pot.iter().all(|p| p.is_finite()));
+        assert_eq!(crate::sssp_reduced_cost(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), pot.as_ptr(), reduced.as_mut_ptr()), 0);
+        assert!(reduced.iter().all(|&r| r >= 0.0), "min reduced {:?}", reduced.iter().cloned().fold(f32::INFINITY, f32::min));
+    }
+
+    #[test]
+    fn first_target_returns_nearer_goal(){
+        // 0->1 (5), 0->2 (1), 2->3 (1); goals {1,3}: 3 is nearer (2 < 5). Node 4 is isolated.
+        let off = [0u32,2,2,3,3,3]; let tgt = [1u32,2,3]; let wts = [5.0f32,1.0,1.0];
+        let (mut t, mut d) = (0u32, 0f32);
+        let goals = [1u32, 3];
+        assert_eq!(sssp_run_baseline_first_target(5, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, goals.as_ptr(), 2, &mut t, &mut d), 0);
+        assert_eq!((t, d), (3, 2.0));
+        let far = [4u32];
+        assert_eq!(sssp_run_baseline_first_target(5, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, far.as_ptr(), 1, &mut t, &mut d), -20);
+    }
+
+    #[test]
+    fn kth_nearest_on_unit_star(){
+        // Hub 0 -> leaves 1..=6, unit weights.
+        let off = [0u32,6,6,6,6,6,6,6]; let tgt = [1u32,2,3,4,5,6]; let wts = [1.0f32;6];
+        let mut d = 0f32;
+        for k in 1..=6 { assert_eq!(sssp_kth_nearest_dist(7, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, k, &mut d), 0); assert_eq!(d, 1.0); }
+        assert_eq!(sssp_kth_nearest_dist(7, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, 7, &mut d), -20);
+    }
+
+    #[test]
+    fn tree_cache_reuses_rows_and_evicts_lru(){
+        // Path 0->1->2->3 with unit weights.
+        let off = [0u32,1,2,3,3]; let tgt = [1u32,2,3]; let wts = [1.0f32;3];
+        let cache = sssp_tree_cache_new(4, 1);
+        let q = |u, v| sssp_tree_cache_query(cache, 4, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), u, v);
+        let (mut computed, mut hits, mut evictions) = (0u64, 0u64, 0u64);
+        assert_eq!(q(0, 3), 3.0);
+        assert_eq!(q(0, 3), 3.0);
+        assert_eq!(q(0, 2), 2.0);
+        sssp_tree_cache_stats(cache, &mut computed, &mut hits, &mut evictions);
+        assert_eq!((computed, hits, evictions), (1, 2, 0));
+        // Capacity 1: a second source evicts the first, which then recomputes.
+        assert!(q(3, 0).is_infinite());
+        assert_eq!(q(0, 1), 1.0);
+        sssp_tree_cache_stats(cache, &mut computed, &mut hits, &mut evictions);
+        assert_eq!((computed, hits, evictions), (3, 2, 2));
+        assert!(q(0, 9).is_nan());
+        sssp_tree_cache_free(cache);
+    }
+}
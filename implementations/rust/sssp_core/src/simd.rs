@@ -0,0 +1,63 @@
+//! Explicit SIMD kernels for the baseline relaxation loop (feature `simd`).
+//! Kernels only *filter* candidate edges; the caller re-checks and applies each
+//! update in edge order, so results are bit-identical to the scalar loop.
+
+// Lanes per block processed by `relax_mask4`.
+pub(crate) const LANES: usize = 4;
+
+// Bit k set iff `base + w[k] < dist[t[k]]` (SSE2: one add, gathered compare).
+// Every lane whose bit is clear cannot improve `dist` later in the block either,
+// because `dist` only decreases while the block's updates are applied.
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+#[inline(always)]
+pub(crate) fn relax_mask4(base: f32, w: &[f32], t: &[u32], dist: &[f32]) -> u32 {
+    use core::arch::x86_64::*;
+    let (w, t) = (&w[..LANES], &t[..LANES]);
+    let cur = [dist[t[0] as usize], dist[t[1] as usize], dist[t[2] as usize], dist[t[3] as usize]];
+    // SAFETY: SSE2 is part of the x86_64 baseline; both loads read exactly 4 f32s.
+    unsafe {
+        let nd = _mm_add_ps(_mm_set1_ps(base), _mm_loadu_ps(w.as_ptr()));
+        _mm_movemask_ps(_mm_cmplt_ps(nd, _mm_loadu_ps(cur.as_ptr()))) as u32
+    }
+}
+
+// Scalar fallback with the same contract.
+#[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+#[inline(always)]
+pub(crate) fn relax_mask4(base: f32, w: &[f32], t: &[u32], dist: &[f32]) -> u32 {
+    let mut mask = 0u32;
+    for k in 0..LANES { if base + w[k] < dist[t[k] as usize] { mask |= 1 << k; } }
+    mask
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mask_matches_scalar_compare_including_nan_and_inf(){
+        let dist = [1.0f32, f32::INFINITY, 2.5, 0.0, f32::NAN];
+        let t = [0u32, 1, 2, 4]; let w = [0.5f32, 1e30, 0.5, 1.0];
+        for base in [0.0f32, 0.5, 2.0, f32::INFINITY] {
+            let mut want = 0u32; for k in 0..LANES { if base + w[k] < dist[t[k] as usize] { want |= 1 << k; } }
+            assert_eq!(relax_mask4(base, &w, &t, &dist), want, "base={}", base);
+        }
+    }
+
+    #[test]
+    fn baseline_bit_identical_to_scalar_search(){
+        // High-degree graph with parallel edges so blocks contain repeated targets.
+        let n = 300u32; let mut rng = crate::gen::XorShift64Star::new(0x2545F4914F6CDD1D);
+        let mut next = || rng.next_u32();
+        let mut off = vec![0u32]; let mut tgt = Vec::new(); let mut wts = Vec::new();
+        for _ in 0..n { let deg = 1 + next() % 23; for _ in 0..deg { tgt.push(next() % 40 + (next() % n) / 8); wts.push((next() % 1000) as f32 / 37.0); } off.push(tgt.len() as u32); }
+        let mut d1 = vec![0f32; n as usize]; let mut p1 = vec![0i32; n as usize];
+        let mut d2 = vec![0f32; n as usize]; let mut p2 = vec![0i32; n as usize];
+        let mut info = crate::SsspResultInfo { relaxations:0, light_relaxations:0, heavy_relaxations:0, settled:0, error_code:0, overflow:0, reachable:0, edges:0 };
+        assert_eq!(crate::sssp_run_baseline(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d1.as_mut_ptr(), p1.as_mut_ptr(), &mut info), 0);
+        let c = crate::dijkstra_with(&off, &tgt, &wts, 0, &mut d2, &mut p2, |_, _| true);
+        assert!(d1.iter().zip(&d2).all(|(a, b)| a.to_bits() == b.to_bits()));
+        assert_eq!(p1, p2);
+        assert_eq!(info.relaxations, c.relaxations);
+    }
+}
@@ -7,9 +7,10 @@ use std::cmp::Ordering;
 #[repr(C)]
 #[derive(Copy,Clone)]
 pub struct SpecHeapStats { pub pushes:u64, pub pops:u64, pub max_size:u64 }
-static mut LAST_SPEC_HEAP_STATS: SpecHeapStats = SpecHeapStats { pushes:0, pops:0, max_size:0 };
+// Stats getters in this module report the calling thread's last run.
+thread_local! { static LAST_SPEC_HEAP_STATS: core::cell::Cell<SpecHeapStats> = const { core::cell::Cell::new(SpecHeapStats { pushes:0, pops:0, max_size:0 }) }; }
 #[no_mangle]
-pub extern "C" fn sssp_get_spec_heap_stats(out:*mut SpecHeapStats){ if out.is_null(){ return; } unsafe{ *out = LAST_SPEC_HEAP_STATS; } }
+pub extern "C" fn sssp_get_spec_heap_stats(out:*mut SpecHeapStats){ if out.is_null(){ return; } unsafe{ *out = LAST_SPEC_HEAP_STATS.with(|s| s.get()); } }
 
 #[inline(always)] fn as_slice<'a, T>(ptr:*const T, len:usize)->&'a [T]{ unsafe{ slice::from_raw_parts(ptr,len) } }
 #[inline(always)] fn as_mut_slice<'a, T>(ptr:*mut T, len:usize)->&'a mut [T]{ unsafe{ slice::from_raw_parts_mut(ptr,len) } }
@@ -45,7 +46,7 @@ fn dijkstra(off:&[u32], tgt:&[u32], wts:&[f32], dist:&mut [f32], mut pred:Option
         let u = v as usize; let s = off[u] as usize; let e = off[u+1] as usize; let base = d;
         for idx in s..e { let wv = tgt[idx] as usize; let nd = base + wts[idx]; let cur = unsafe{ *dist.get_unchecked(wv) }; if nd < cur { unsafe{ *dist.get_unchecked_mut(wv)=nd; } if let Some(p)=pred.as_mut(){ unsafe{ *p.get_unchecked_mut(wv)=u as i32; } } *relaxations+=1; pq.push(H{d:nd,v:wv as u32}); } }
     }
-    unsafe { LAST_SPEC_HEAP_STATS = SpecHeapStats { pushes: pq.pushes, pops: pq.pops, max_size: pq.max_size }; }
+    LAST_SPEC_HEAP_STATS.with(|s| s.set(SpecHeapStats { pushes: pq.pushes, pops: pq.pops, max_size: pq.max_size }));
 }
 
 // Placeholder BMSSP shell: currently just invokes Dijkstra once.
@@ -60,20 +61,22 @@ pub extern "C" fn sssp_run_spec_clean(
     out_pred:*mut i32,
     info:*mut crate::SsspResultInfo,
 ) -> i32 {
-    if n==0 { return -1; }
-    if source>=n { return -2; }
-    if offsets.is_null() || targets.is_null() || weights.is_null() || out_dist.is_null(){ return -3; }
-    let n_usize = n as usize;
-    let off = as_slice(offsets, n_usize+1);
-    let m = *off.last().unwrap() as usize;
-    let tgt = as_slice(targets, m);
-    let wts = as_slice(weights, m);
-    let dist = as_mut_slice(out_dist, n_usize);
-    let pred_opt = if out_pred.is_null() { None } else { Some(as_mut_slice(out_pred, n_usize)) };
-    let mut relax:u64=0;
-    dijkstra(off, tgt, wts, dist, pred_opt, source, &mut relax);
-    if !info.is_null(){ unsafe { *info = crate::SsspResultInfo { relaxations: relax, light_relaxations:0, heavy_relaxations:0, settled: n, error_code:0 }; } }
-    0
+    crate::ffi_guard(-99, || {
+        if n==0 { return -1; }
+        if source>=n { return -2; }
+        if offsets.is_null() || targets.is_null() || weights.is_null() || out_dist.is_null(){ return -3; }
+        let n_usize = n as usize;
+        let off = as_slice(offsets, n_usize+1);
+        let m = *off.last().unwrap() as usize;
+        let tgt = as_slice(targets, m);
+        let wts = as_slice(weights, m);
+        let dist = as_mut_slice(out_dist, n_usize);
+        let pred_opt = if out_pred.is_null() { None } else { Some(as_mut_slice(out_pred, n_usize)) };
+        let mut relax:u64=0;
+        dijkstra(off, tgt, wts, dist, pred_opt, source, &mut relax);
+        if !info.is_null(){ unsafe { *info = crate::SsspResultInfo { relaxations: relax, light_relaxations:0, heavy_relaxations:0, settled: n, error_code:0, overflow: 0, reachable: crate::count_reachable(dist), edges: tgt.len() as u64 }; } }
+        0
+    })
 }
 
 // ---------------- BaseCase (Phase 1) Components ----------------
@@ -92,9 +95,9 @@ pub struct SpecPhase1Stats {
     pub last_collected: u32,     // |U| from last run
     pub last_relaxations: u64,   // relax count from last run
 }
-static mut LAST_PHASE1_STATS: SpecPhase1Stats = SpecPhase1Stats { last_outcome: -1, last_bound: 0.0, last_collected: 0, last_relaxations: 0 };
+thread_local! { static LAST_PHASE1_STATS: core::cell::Cell<SpecPhase1Stats> = const { core::cell::Cell::new(SpecPhase1Stats { last_outcome: -1, last_bound: 0.0, last_collected: 0, last_relaxations: 0 }) }; }
 #[no_mangle]
-pub extern "C" fn sssp_get_spec_phase1_stats(out:*mut SpecPhase1Stats){ if out.is_null(){ return; } unsafe { *out = LAST_PHASE1_STATS; } }
+pub extern "C" fn sssp_get_spec_phase1_stats(out:*mut SpecPhase1Stats){ if out.is_null(){ return; } unsafe { *out = LAST_PHASE1_STATS.with(|s| s.get()); } }
 
 pub fn basecase_truncated(
     n: u32,
@@ -140,7 +143,8 @@ pub fn basecase_truncated(
         if d > max_seen { max_seen = d; }
         if popped == k + 1 { truncated = true; break; }
         let ui = u as usize; let se = off[ui] as usize; let ee = off[ui+1] as usize;
-        for e in se..ee { let v = tgt[e] as usize; let nd = d + wts[e]; if nd <= dist[v] && nd <= initial_bound { dist[v]=nd; pred[v]=u as i32; if let Some(ref mut dv)=depth { let parent_depth = dv[u as usize]; if parent_depth != u32::MAX { dv[v] = parent_depth + 1; } } pq.push(Item{u:v as u32,d:nd}); *relaxations += 1; } }
+        // `nd <= dist[v]` would accept a zero-weight self-loop and re-push u forever; skip u->u.
+        for e in se..ee { let v = tgt[e] as usize; if v == ui { continue; } let nd = d + wts[e]; if nd <= dist[v] && nd <= initial_bound { dist[v]=nd; pred[v]=u as i32; if let Some(ref mut dv)=depth { let parent_depth = dv[u as usize]; if parent_depth != u32::MAX { dv[v] = parent_depth + 1; } } pq.push(Item{u:v as u32,d:nd}); *relaxations += 1; } }
     }
     let new_bound = if truncated { max_seen } else { initial_bound };
     if truncated { for &u in scratch.iter() { if dist[u as usize] > new_bound { dist[u as usize] = f32::INFINITY; pred[u as usize] = -1; } } }
@@ -184,30 +188,31 @@ pub struct SpecPhase2Stats {
     pub relaxations: u64,
     pub bound: f32,
 }
-static mut LAST_PHASE2_STATS: SpecPhase2Stats = SpecPhase2Stats { attempts:0, success:0, final_k:0, collected:0, max_subtree:0, roots_examined:0, relaxations:0, bound:0.0 };
+thread_local! { static LAST_PHASE2_STATS: core::cell::Cell<SpecPhase2Stats> = const { core::cell::Cell::new(SpecPhase2Stats { attempts:0, success:0, final_k:0, collected:0, max_subtree:0, roots_examined:0, relaxations:0, bound:0.0 }) }; }
 #[no_mangle]
-pub extern "C" fn sssp_get_spec_phase2_stats(out:*mut SpecPhase2Stats){ if out.is_null(){ return; } unsafe { *out = LAST_PHASE2_STATS; } }
+pub extern "C" fn sssp_get_spec_phase2_stats(out:*mut SpecPhase2Stats){ if out.is_null(){ return; } unsafe { *out = LAST_PHASE2_STATS.with(|s| s.get()); } }
 
-// Phase 3 stats (DataStructureD integration placeholder)
+// Phase 3 stats: `pulls` counts nodes pulled from DataStructureD, `batches` the pull(M)
+// calls that returned them.
 #[repr(C)]
 #[derive(Copy,Clone,Default)]
-pub struct SpecPhase3Stats { pub pulls: u32, pub batches: u32, pub pushes: u32, pub relaxations: u64 }
-static mut LAST_PHASE3_STATS: SpecPhase3Stats = SpecPhase3Stats { pulls:0, batches:0, pushes:0, relaxations:0 };
+pub struct SpecPhase3Stats { pub pulls: u32, pub batches: u32, pub pushes: u32, pub relaxations: u64, pub buckets: u32, pub delta: f32, pub overflow: u32 }
+thread_local! { static LAST_PHASE3_STATS: core::cell::Cell<SpecPhase3Stats> = const { core::cell::Cell::new(SpecPhase3Stats { pulls:0, batches:0, pushes:0, relaxations:0, buckets:0, delta:0.0, overflow:0 }) }; }
 #[no_mangle]
-pub extern "C" fn sssp_get_spec_phase3_stats(out:*mut SpecPhase3Stats){ if out.is_null(){ return; } unsafe { *out = LAST_PHASE3_STATS; } }
+pub extern "C" fn sssp_get_spec_phase3_stats(out:*mut SpecPhase3Stats){ if out.is_null(){ return; } unsafe { *out = LAST_PHASE3_STATS.with(|s| s.get()); } }
 
 // Invariant assertion framework (Phase 2 partial)
 #[repr(C)]
 #[derive(Copy,Clone,Default)]
 pub struct SpecInvariantStats { pub checks: u64, pub failures: u64 }
-static mut LAST_INV_STATS: SpecInvariantStats = SpecInvariantStats { checks:0, failures:0 };
+thread_local! { static LAST_INV_STATS: core::cell::Cell<SpecInvariantStats> = const { core::cell::Cell::new(SpecInvariantStats { checks:0, failures:0 }) }; }
 #[no_mangle]
-pub extern "C" fn sssp_get_spec_invariant_stats(out:*mut SpecInvariantStats){ if out.is_null(){ return; } unsafe { *out = LAST_INV_STATS; } }
+pub extern "C" fn sssp_get_spec_invariant_stats(out:*mut SpecInvariantStats){ if out.is_null(){ return; } unsafe { *out = LAST_INV_STATS.with(|s| s.get()); } }
 
 fn inv_check(cond: bool, _msg: &str) {
     let enabled = std::env::var("SSSP_SPEC_CHECK").ok().map(|v| v=="1" || v.to_lowercase()=="true").unwrap_or(false);
     if !enabled { return; }
-    unsafe { LAST_INV_STATS.checks += 1; if !cond { LAST_INV_STATS.failures += 1; eprintln!("[spec-invariant] FAIL: {}", _msg); } }
+    LAST_INV_STATS.with(|s| { let mut v = s.get(); v.checks += 1; if !cond { v.failures += 1; eprintln!("[spec-invariant] FAIL: {}", _msg); } s.set(v); });
 }
 
 #[no_mangle]
@@ -221,72 +226,77 @@ pub extern "C" fn sssp_run_spec_phase2(
     out_pred:*mut i32,
     info:*mut crate::SsspResultInfo,
 ) -> i32 {
-    if n==0 { return -1; }
-    if source>=n { return -2; }
-    if offsets.is_null() || targets.is_null() || weights.is_null() || out_dist.is_null() || out_pred.is_null(){ return -3; }
-    let n_usize = n as usize;
-    let off = unsafe { as_slice(offsets, n_usize+1) };
-    let m = off[n_usize] as usize;
-    let tgt = unsafe { as_slice(targets, m) };
-    let wts = unsafe { as_slice(weights, m) };
-    let dist = unsafe { as_mut_slice(out_dist, n_usize) };
-    let pred = unsafe { as_mut_slice(out_pred, n_usize) };
-    let mut k = std::env::var("SSSP_SPEC_K").ok().and_then(|v| v.parse::<u32>().ok()).unwrap_or(1024).max(1);
-    let attempt_max = std::env::var("SSSP_SPEC_PIVOT_MAX").ok().and_then(|v| v.parse::<u32>().ok()).unwrap_or(8).max(1);
-    let mut attempts = 0u32;
-    let mut total_relax = 0u64;
-    let mut final_collected = 0u32;
-    let mut final_bound = f32::INFINITY;
-    let mut max_subtree_any = 0u32;
-    let mut roots_examined_any = 0u32;
-    let mut success = 0i32;
-    // Pop order capture vector reused each attempt
-    let mut pop_order: Vec<u32> = Vec::new();
-    loop {
-        attempts += 1;
-        pop_order.clear();
-        // Run basecase with capture forced (set env temporarily if not set)
-        std::env::set_var("SSSP_SPEC_CAPTURE","1");
-        let mut scratch: Vec<u32> = Vec::with_capacity(k as usize + 2);
-        let mut relax: u64 = 0;
-        // Slight duplication: re-run basecase logic manually to fill pop_order local (rather than thread locals) for determinism.
-        // Re-implement minimal variant capturing order:
-        for d in dist.iter_mut() { *d = f32::INFINITY; }
-        for p in pred.iter_mut() { *p = -1; }
-    #[derive(Copy,Clone)] struct Item2 { u:u32, d:f32 }
-        impl PartialEq for Item2 { fn eq(&self,o:&Self)->bool { self.d==o.d && self.u==o.u } }
-        impl Eq for Item2 {}
-        impl PartialOrd for Item2 { fn partial_cmp(&self,o:&Self)->Option<std::cmp::Ordering>{ o.d.partial_cmp(&self.d) } }
-        impl Ord for Item2 { fn cmp(&self,o:&Self)->std::cmp::Ordering { self.partial_cmp(o).unwrap() } }
-        use std::collections::BinaryHeap; let mut pq = BinaryHeap::new();
-        dist[source as usize] = 0.0; pq.push(Item2{u:source,d:0.0}); scratch.clear();
-        let mut popped = 0u32; let mut max_seen = 0.0f32; let mut truncated=false;
-    while let Some(Item2{u,d}) = pq.pop() { if d > dist[u as usize] { continue; } scratch.push(u); pop_order.push(u); popped+=1; if d>max_seen { max_seen=d; } if popped==k+1 { truncated=true; break; } let ui=u as usize; let se=off[ui] as usize; let ee=off[ui+1] as usize; for e in se..ee { let v=tgt[e] as usize; let nd = d + wts[e]; if nd <= dist[v] { dist[v]=nd; pred[v]=u as i32; pq.push(Item2{u:v as u32,d:nd}); relax+=1; } } }
-    let new_bound = if truncated { max_seen } else { f32::INFINITY };
-    if truncated { for &u in scratch.iter() { if dist[u as usize] > new_bound { dist[u as usize]=f32::INFINITY; pred[u as usize]=-1; } } }
-    let collected = scratch.iter().filter(|&&u| dist[u as usize].is_finite() && dist[u as usize] <= new_bound).count() as u32;
-        total_relax += relax;
-        final_collected = collected; final_bound = new_bound;
-        // Subtree sizing
-    let (roots, sizes) = compute_subtree_sizes(dist, pred, new_bound, &pop_order);
-    // Invariant: roots subset of collected U set
-    for &r in &roots { inv_check(dist[r as usize].is_finite() && dist[r as usize] <= new_bound, "root outside U set"); }
-    // Invariant: max subtree size <= collected
-    if let Some(max_local) = sizes.iter().max() { inv_check(*max_local <= collected, "subtree size exceeds collected"); }
-    inv_check(collected <= k+1, "collected exceeds k+1 guard");
-        roots_examined_any += roots.len() as u32;
-        let mut local_max = 0u32; for &s in &sizes { if s>local_max { local_max = s; } }
-        if local_max > max_subtree_any { max_subtree_any = local_max; }
-        if local_max >= k || collected as u32 >= n { success = 1; break; }
-        if attempts >= attempt_max || k >= n { break; }
-        k = (k.saturating_mul(2)).min(n);
-    }
-    unsafe { LAST_PHASE2_STATS = SpecPhase2Stats { attempts, success, final_k: k, collected: final_collected, max_subtree: max_subtree_any, roots_examined: roots_examined_any, relaxations: total_relax, bound: final_bound }; }
-    if !info.is_null(){ unsafe { *info = crate::SsspResultInfo { relaxations: total_relax, light_relaxations:0, heavy_relaxations:0, settled: final_collected, error_code: success }; } }
-    0
+    crate::ffi_guard(-99, || {
+        if n==0 { return -1; }
+        if source>=n { return -2; }
+        if offsets.is_null() || targets.is_null() || weights.is_null() || out_dist.is_null() || out_pred.is_null(){ return -3; }
+        let n_usize = n as usize;
+        let off = unsafe { as_slice(offsets, n_usize+1) };
+        let m = off[n_usize] as usize;
+        let tgt = unsafe { as_slice(targets, m) };
+        let wts = unsafe { as_slice(weights, m) };
+        let dist = unsafe { as_mut_slice(out_dist, n_usize) };
+        let pred = unsafe { as_mut_slice(out_pred, n_usize) };
+        let mut k = std::env::var("SSSP_SPEC_K").ok().and_then(|v| v.parse::<u32>().ok()).unwrap_or(1024).max(1);
+        let attempt_max = std::env::var("SSSP_SPEC_PIVOT_MAX").ok().and_then(|v| v.parse::<u32>().ok()).unwrap_or(8).max(1);
+        let mut attempts = 0u32;
+        let mut total_relax = 0u64;
+        let mut final_collected = 0u32;
+        let mut final_bound = f32::INFINITY;
+        let mut max_subtree_any = 0u32;
+        let mut roots_examined_any = 0u32;
+        let mut success = 0i32;
+        // Pop order capture vector reused each attempt
+        let mut pop_order: Vec<u32> = Vec::new();
+        loop {
+            attempts += 1;
+            pop_order.clear();
+            // Run basecase with capture forced (set env temporarily if not set)
+            std::env::set_var("SSSP_SPEC_CAPTURE","1");
+            let mut scratch: Vec<u32> = Vec::with_capacity(k as usize + 2);
+            let mut relax: u64 = 0;
+            // Slight duplication: re-run basecase logic manually to fill pop_order local (rather than thread locals) for determinism.
+            // Re-implement minimal variant capturing order:
+            for d in dist.iter_mut() { *d = f32::INFINITY; }
+            for p in pred.iter_mut() { *p = -1; }
+        #[derive(Copy,Clone)] struct Item2 { u:u32, d:f32 }
+            impl PartialEq for Item2 { fn eq(&self,o:&Self)->bool { self.d==o.d && self.u==o.u } }
+            impl Eq for Item2 {}
+            impl PartialOrd for Item2 { fn partial_cmp(&self,o:&Self)->Option<std::cmp::Ordering>{ o.d.partial_cmp(&self.d) } }
+            impl Ord for Item2 { fn cmp(&self,o:&Self)->std::cmp::Ordering { self.partial_cmp(o).unwrap() } }
+            use std::collections::BinaryHeap; let mut pq = BinaryHeap::new();
+            dist[source as usize] = 0.0; pq.push(Item2{u:source,d:0.0}); scratch.clear();
+            let mut popped = 0u32; let mut max_seen = 0.0f32; let mut truncated=false;
+        while let Some(Item2{u,d}) = pq.pop() { if d > dist[u as usize] { continue; } scratch.push(u); pop_order.push(u); popped+=1; if d>max_seen { max_seen=d; } if popped==k+1 { truncated=true; break; } let ui=u as usize; let se=off[ui] as usize; let ee=off[ui+1] as usize; for e in se..ee { let v=tgt[e] as usize; let nd = d + wts[e]; if nd <= dist[v] { dist[v]=nd; pred[v]=u as i32; pq.push(Item2{u:v as u32,d:nd}); relax+=1; } } }
+        let new_bound = if truncated { max_seen } else { f32::INFINITY };
+        if truncated { for &u in scratch.iter() { if dist[u as usize] > new_bound { dist[u as usize]=f32::INFINITY; pred[u as usize]=-1; } } }
+        let collected = scratch.iter().filter(|&&u| dist[u as usize].is_finite() && dist[u as usize] <= new_bound).count() as u32;
+            total_relax += relax;
+            final_collected = collected; final_bound = new_bound;
+            // Subtree sizing
+        let (roots, sizes) = compute_subtree_sizes(dist, pred, new_bound, &pop_order);
+        // Invariant: roots subset of collected U set
+        for &r in &roots { inv_check(dist[r as usize].is_finite() && dist[r as usize] <= new_bound, "root outside U set"); }
+        // Invariant: max subtree size <= collected
+        if let Some(max_local) = sizes.iter().max() { inv_check(*max_local <= collected, "subtree size exceeds collected"); }
+        inv_check(collected <= k+1, "collected exceeds k+1 guard");
+            roots_examined_any += roots.len() as u32;
+            let mut local_max = 0u32; for &s in &sizes { if s>local_max { local_max = s; } }
+            if local_max > max_subtree_any { max_subtree_any = local_max; }
+            if local_max >= k || collected as u32 >= n { success = 1; break; }
+            if attempts >= attempt_max || k >= n { break; }
+            k = (k.saturating_mul(2)).min(n);
+        }
+        LAST_PHASE2_STATS.with(|s| s.set(SpecPhase2Stats { attempts, success, final_k: k, collected: final_collected, max_subtree: max_subtree_any, roots_examined: roots_examined_any, relaxations: total_relax, bound: final_bound }));
+        if !info.is_null(){ unsafe { *info = crate::SsspResultInfo { relaxations: total_relax, light_relaxations:0, heavy_relaxations:0, settled: final_collected, error_code: success, overflow: 0, reachable: crate::count_reachable(dist), edges: tgt.len() as u64 }; } }
+        0
+    })
 }
 
 // ------------- Phase 3 Runner (initial DataStructureD integration) -------------
+// `delta` sets the bucket width; NaN (or <= 0) selects the auto width: 2x the mean
+// of up to 1024 weights sampled at a uniform stride over the whole edge array
+// (a prefix sample only sees the first nodes' edges).
 #[no_mangle]
 pub extern "C" fn sssp_run_spec_phase3(
     n: u32,
@@ -297,46 +307,74 @@ pub extern "C" fn sssp_run_spec_phase3(
     out_dist:*mut f32,
     out_pred:*mut i32,
     info:*mut crate::SsspResultInfo,
+    delta: f32,
 ) -> i32 {
+    crate::ffi_guard(-99, || {
+        if n==0 { return -1; }
+        if source>=n { return -2; }
+        if offsets.is_null() || targets.is_null() || weights.is_null() || out_dist.is_null() || out_pred.is_null(){ return -3; }
+        let n_usize = n as usize; let off = unsafe { as_slice(offsets, n_usize+1) }; let m = off[n_usize] as usize;
+        let tgt = unsafe { as_slice(targets, m) }; let wts = unsafe { as_slice(weights, m) };
+        let dist = unsafe { as_mut_slice(out_dist, n_usize) }; let pred = unsafe { as_mut_slice(out_pred, n_usize) };
+        let delta = if delta.is_nan() || delta <= 0.0 { phase3_auto_delta(wts) } else { delta };
+        let stats = phase3_run(off, tgt, wts, source, delta, dist, pred);
+        LAST_PHASE3_STATS.with(|s| s.set(stats));
+        if !info.is_null(){ unsafe { *info = crate::SsspResultInfo { relaxations: stats.relaxations, light_relaxations:0, heavy_relaxations:0, settled: n, error_code: 0, overflow: stats.overflow, reachable: crate::count_reachable(dist), edges: tgt.len() as u64 }; } }
+        0
+    })
+}
+
+fn phase3_auto_delta(wts: &[f32]) -> f32 {
+    let m = wts.len(); if m == 0 { return 2.0; }
+    let step = m.div_ceil(1024);
+    let (mut s, mut c) = (0.0f64, 0u32); let mut i = 0; while i < m { s += wts[i] as f64; c += 1; i += step; }
+    ((s / c as f64) as f32).max(1e-4) * 2.0
+}
+
+// Pull batch size of the Phase 3 DataStructureD.
+const PHASE3_PULL_M: usize = 64;
+
+fn phase3_run(off: &[u32], tgt: &[u32], wts: &[f32], source: u32, delta: f32, dist: &mut [f32], pred: &mut [i32]) -> SpecPhase3Stats {
     use crate::spec_future::DataStructureD;
-    if n==0 { return -1; }
-    if source>=n { return -2; }
-    if offsets.is_null() || targets.is_null() || weights.is_null() || out_dist.is_null() || out_pred.is_null(){ return -3; }
-    let n_usize = n as usize; let off = unsafe { as_slice(offsets, n_usize+1) }; let m = off[n_usize] as usize;
-    let tgt = unsafe { as_slice(targets, m) }; let wts = unsafe { as_slice(weights, m) };
-    let dist = unsafe { as_mut_slice(out_dist, n_usize) }; let pred = unsafe { as_mut_slice(out_pred, n_usize) };
     for d in dist.iter_mut() { *d = f32::INFINITY; } for p in pred.iter_mut() { *p = -1; }
     dist[source as usize] = 0.0;
-    // Simple distance bucket mapping: bucket = floor(dist / delta) with adaptive delta (avg of first 32 edges * 2)
-    let sample = core::cmp::min(32, m); let mut avg = 1.0f32; if sample>0 { let mut s=0.0; for i in 0..sample { s+=wts[i]; } avg=(s/sample as f32).max(1e-4); }
-    let delta = avg * 2.0; let inv_delta = 1.0 / delta;
+    // Distance bucket mapping: bucket = floor(dist / delta)
+    let inv_delta = 1.0 / delta;
     let mut buckets: Vec<Vec<u32>> = vec![Vec::new()];
     buckets[0].push(source);
-    let mut ds = DataStructureD::new();
-    let mut relax: u64 = 0; let mut pulls: u32 = 0; let mut batches: u32 = 0; let mut pushes: u32 = 0;
+    let mut ds = DataStructureD::new(PHASE3_PULL_M);
+    let mut relax: u64 = 0; let mut pulls: u32 = 0; let mut batches: u32 = 0; let mut pushes: u32 = 0; let mut overflow = false;
+    let mut peak_queue = 0u64;
     let mut current_bucket = 0usize;
     while current_bucket < buckets.len() {
         if buckets[current_bucket].is_empty() { current_bucket += 1; continue; }
-        // Process all waves for this bucket until no more nodes remain in it.
-        while !buckets[current_bucket].is_empty() {
-            let mut batch = core::mem::take(&mut buckets[current_bucket]);
-            batches += 1; batch.shrink_to_fit(); ds.batch_prepend(batch);
-            let mut last_dist = -1.0f32;
-            while !ds.is_empty() {
-                ds.pull(|u| {
-                    pulls += 1; let ui = u as usize; let base = dist[ui]; if !base.is_finite() { return; }
-                    if last_dist >= 0.0 { inv_check(base >= last_dist, "Phase3 pull distance order violation"); }
-                    last_dist = base;
-                    let se = off[ui] as usize; let ee = off[ui+1] as usize;
-                    for e in se..ee { let v = tgt[e] as usize; let nd = base + wts[e]; let cur = dist[v]; if nd < cur { dist[v]=nd; pred[v]=u as i32; let b = (nd * inv_delta) as usize; if b>=buckets.len() { buckets.resize_with(b+1, Vec::new); } buckets[b].push(v as u32); pushes += 1; relax += 1; } }
-                });
+        // D is empty between buckets, so the bucket can be batch-prepended; entries whose
+        // label has since dropped into an earlier bucket are stale. Relaxations that stay
+        // inside the bucket are inserted into D, the rest wait in their own bucket.
+        let batch = core::mem::take(&mut buckets[current_bucket]);
+        ds.batch_prepend(batch.into_iter().filter(|&u| (dist[u as usize] * inv_delta) as usize == current_bucket).map(|u| (u, dist[u as usize])).collect());
+        while !ds.is_empty() {
+            peak_queue = peak_queue.max(ds.len() as u64);
+            let (pulled, bound) = ds.pull(PHASE3_PULL_M);
+            crate::sat_bump(&mut batches, &mut overflow);
+            for u in pulled {
+                crate::sat_bump(&mut pulls, &mut overflow); let ui = u as usize; let base = dist[ui];
+                inv_check(base <= bound, "Phase3 pull above returned bound");
+                let se = off[ui] as usize; let ee = off[ui+1] as usize;
+                for e in se..ee {
+                    let v = tgt[e] as usize; let nd = base + wts[e];
+                    if nd < dist[v] {
+                        dist[v]=nd; pred[v]=u as i32; crate::sat_bump(&mut pushes, &mut overflow); relax += 1;
+                        let b = (nd * inv_delta) as usize;
+                        if b <= current_bucket { ds.insert(v as u32, nd); } else { if b>=buckets.len() { buckets.resize_with(b+1, Vec::new); } buckets[b].push(v as u32); }
+                    }
+                }
             }
         }
         current_bucket += 1;
     }
-    unsafe { LAST_PHASE3_STATS = SpecPhase3Stats { pulls, batches, pushes, relaxations: relax }; }
-    if !info.is_null(){ unsafe { *info = crate::SsspResultInfo { relaxations: relax, light_relaxations:0, heavy_relaxations:0, settled: n, error_code: 0 }; } }
-    0
+    crate::publish_peak_queue(peak_queue);
+    SpecPhase3Stats { pulls, batches, pushes, relaxations: relax, buckets: buckets.len() as u32, delta, overflow: overflow as u32 }
 }
 
 // ------------- Boundary Chain Runner (Phase 3 extension) -------------
@@ -350,9 +388,9 @@ pub struct SpecBoundaryChainStats {
     pub monotonic_ok: i32,
     pub relaxations: u64,
 }
-static mut LAST_CHAIN_STATS: SpecBoundaryChainStats = SpecBoundaryChainStats { segments:0, attempts:0, total_collected:0, max_segment:0, monotonic_ok:1, relaxations:0 };
+thread_local! { static LAST_CHAIN_STATS: core::cell::Cell<SpecBoundaryChainStats> = const { core::cell::Cell::new(SpecBoundaryChainStats { segments:0, attempts:0, total_collected:0, max_segment:0, monotonic_ok:1, relaxations:0 }) }; }
 #[no_mangle]
-pub extern "C" fn sssp_get_spec_boundary_chain_stats(out:*mut SpecBoundaryChainStats){ if out.is_null(){ return; } unsafe { *out = LAST_CHAIN_STATS; } }
+pub extern "C" fn sssp_get_spec_boundary_chain_stats(out:*mut SpecBoundaryChainStats){ if out.is_null(){ return; } unsafe { *out = LAST_CHAIN_STATS.with(|s| s.get()); } }
 
 #[no_mangle]
 pub extern "C" fn sssp_run_spec_boundary_chain(
@@ -365,52 +403,56 @@ pub extern "C" fn sssp_run_spec_boundary_chain(
     out_pred:*mut i32,
     info:*mut crate::SsspResultInfo,
 ) -> i32 {
-    if n==0 { return -1; }
-    if source>=n { return -2; }
-    if offsets.is_null() || targets.is_null() || weights.is_null() || out_dist.is_null() || out_pred.is_null(){ return -3; }
-    let n_usize = n as usize; let off = unsafe { as_slice(offsets, n_usize+1) }; let m = off[n_usize] as usize;
-    let tgt = unsafe { as_slice(targets, m) }; let wts = unsafe { as_slice(weights, m) };
-    let dist = unsafe { as_mut_slice(out_dist, n_usize) }; let pred = unsafe { as_mut_slice(out_pred, n_usize) };
-    for d in dist.iter_mut() { *d = f32::INFINITY; } for p in pred.iter_mut() { *p = -1; }
-    let mut visited = vec![false; n_usize];
-    let mut total_relax = 0u64; let mut total_collected = 0u32; let mut segments = 0u32; let mut attempts=0u32; let mut max_segment=0u32; let mut monotonic_ok = 1i32; let mut last_bound = -1.0f32;
-    let mut k = std::env::var("SSSP_SPEC_CHAIN_K").ok().and_then(|v| v.parse().ok()).unwrap_or(1024).max(1);
-    let seg_max = std::env::var("SSSP_SPEC_CHAIN_MAX_SEG").ok().and_then(|v| v.parse().ok()).unwrap_or(32).max(1);
-    let target_total = std::env::var("SSSP_SPEC_CHAIN_TARGET").ok().and_then(|v| v.parse().ok()).unwrap_or(0);
-    dist[source as usize] = 0.0;
-    while segments < seg_max && (target_total==0 || total_collected < target_total) && total_collected < n {
-        attempts += 1;
-        // Run truncated basecase variant ignoring visited nodes (skip relax into them)
-        // Reusing simplified Dijkstra-like truncated procedure
-        for d in dist.iter_mut() { if !d.is_finite() { *d = f32::INFINITY; } } // maintain previous distances for visited? We'll ignore they are INF initially except source
-        // local arrays
-    #[derive(Copy,Clone)] struct ItemC { u:u32, d:f32 }
-        impl PartialEq for ItemC { fn eq(&self,o:&Self)->bool { self.d==o.d && self.u==o.u } }
-        impl Eq for ItemC {}
-        impl PartialOrd for ItemC { fn partial_cmp(&self,o:&Self)->Option<std::cmp::Ordering>{ o.d.partial_cmp(&self.d) } }
-        impl Ord for ItemC { fn cmp(&self,o:&Self)->std::cmp::Ordering { self.partial_cmp(o).unwrap() } }
-        use std::collections::BinaryHeap; let mut pq = BinaryHeap::new();
-        if segments==0 { pq.push(ItemC{u:source,d:0.0}); }
-        let mut scratch: Vec<u32> = Vec::with_capacity(k as usize + 2);
-        let mut popped=0u32; let mut max_seen=0.0f32; let mut truncated=false; let mut relax=0u64;
-    while let Some(ItemC{u,d}) = pq.pop() { if d > dist[u as usize] { continue; } if visited[u as usize] { continue; } scratch.push(u); popped+=1; if d>max_seen { max_seen=d; } if popped==k+1 { truncated=true; break; } let ui=u as usize; let se=off[ui] as usize; let ee=off[ui+1] as usize; for e in se..ee { let v=tgt[e] as usize; if visited[v] { continue; } let nd = d + wts[e]; let cur = dist[v]; if nd < cur { dist[v]=nd; pred[v]=u as i32; pq.push(ItemC{u:v as u32,d:nd}); relax+=1; } } }
-        let bound = if truncated { max_seen } else { f32::INFINITY };
-        // Segment set
-        let mut segment_nodes: Vec<u32> = Vec::new();
-        for &u in &scratch { let ui=u as usize; let dval=dist[ui]; if dval.is_finite() && dval < bound && !visited[ui] { segment_nodes.push(u); } }
-        if segment_nodes.is_empty() { break; }
-        // Invariants
-        if last_bound >= 0.0 { inv_check(bound > last_bound, "Boundary not strictly increasing"); if !(bound > last_bound) { monotonic_ok = 0; } }
-        for &u in &segment_nodes { inv_check(!visited[u as usize], "Node repeated in chain"); }
-        // Mark visited
-        for &u in &segment_nodes { visited[u as usize] = true; }
-        let seg_size = segment_nodes.len() as u32; if seg_size > max_segment { max_segment = seg_size; }
-        total_collected += seg_size; total_relax += relax; segments += 1; last_bound = bound;
-        if !truncated { break; }
-    }
-    unsafe { LAST_CHAIN_STATS = SpecBoundaryChainStats { segments, attempts, total_collected, max_segment, monotonic_ok, relaxations: total_relax }; }
-    if !info.is_null(){ unsafe { *info = crate::SsspResultInfo { relaxations: total_relax, light_relaxations:0, heavy_relaxations:0, settled: total_collected, error_code: monotonic_ok }; } }
-    0
+    crate::ffi_guard(-99, || {
+        if n==0 { return -1; }
+        if source>=n { return -2; }
+        if offsets.is_null() || targets.is_null() || weights.is_null() || out_dist.is_null() || out_pred.is_null(){ return -3; }
+        let n_usize = n as usize; let off = unsafe { as_slice(offsets, n_usize+1) }; let m = off[n_usize] as usize;
+        let tgt = unsafe { as_slice(targets, m) }; let wts = unsafe { as_slice(weights, m) };
+        let dist = unsafe { as_mut_slice(out_dist, n_usize) }; let pred = unsafe { as_mut_slice(out_pred, n_usize) };
+        for d in dist.iter_mut() { *d = f32::INFINITY; } for p in pred.iter_mut() { *p = -1; }
+        let mut visited = vec![false; n_usize];
+        let mut total_relax = 0u64; let mut total_collected = 0u32; let mut segments = 0u32; let mut attempts=0u32; let mut max_segment=0u32; let mut monotonic_ok = 1i32; let mut last_bound = -1.0f32;
+        let mut peak_queue = 0u64;
+        let mut k = std::env::var("SSSP_SPEC_CHAIN_K").ok().and_then(|v| v.parse().ok()).unwrap_or(1024).max(1);
+        let seg_max = std::env::var("SSSP_SPEC_CHAIN_MAX_SEG").ok().and_then(|v| v.parse().ok()).unwrap_or(32).max(1);
+        let target_total = std::env::var("SSSP_SPEC_CHAIN_TARGET").ok().and_then(|v| v.parse().ok()).unwrap_or(0);
+        dist[source as usize] = 0.0;
+        while segments < seg_max && (target_total==0 || total_collected < target_total) && total_collected < n {
+            attempts += 1;
+            // Run truncated basecase variant ignoring visited nodes (skip relax into them)
+            // Reusing simplified Dijkstra-like truncated procedure
+            for d in dist.iter_mut() { if !d.is_finite() { *d = f32::INFINITY; } } // maintain previous distances for visited? We'll ignore they are INF initially except source
+            // local arrays
+        #[derive(Copy,Clone)] struct ItemC { u:u32, d:f32 }
+            impl PartialEq for ItemC { fn eq(&self,o:&Self)->bool { self.d==o.d && self.u==o.u } }
+            impl Eq for ItemC {}
+            impl PartialOrd for ItemC { fn partial_cmp(&self,o:&Self)->Option<std::cmp::Ordering>{ o.d.partial_cmp(&self.d) } }
+            impl Ord for ItemC { fn cmp(&self,o:&Self)->std::cmp::Ordering { self.partial_cmp(o).unwrap() } }
+            use std::collections::BinaryHeap; let mut pq = BinaryHeap::new();
+            if segments==0 { pq.push(ItemC{u:source,d:0.0}); peak_queue = peak_queue.max(1); }
+            let mut scratch: Vec<u32> = Vec::with_capacity(k as usize + 2);
+            let mut popped=0u32; let mut max_seen=0.0f32; let mut truncated=false; let mut relax=0u64;
+        while let Some(ItemC{u,d}) = pq.pop() { if d > dist[u as usize] { continue; } if visited[u as usize] { continue; } scratch.push(u); popped+=1; if d>max_seen { max_seen=d; } if popped==k+1 { truncated=true; break; } let ui=u as usize; let se=off[ui] as usize; let ee=off[ui+1] as usize; for e in se..ee { let v=tgt[e] as usize; if visited[v] { continue; } let nd = d + wts[e]; let cur = dist[v]; if nd < cur { dist[v]=nd; pred[v]=u as i32; pq.push(ItemC{u:v as u32,d:nd}); peak_queue = peak_queue.max(pq.len() as u64); relax+=1; } } }
+            let bound = if truncated { max_seen } else { f32::INFINITY };
+            // Segment set
+            let mut segment_nodes: Vec<u32> = Vec::new();
+            for &u in &scratch { let ui=u as usize; let dval=dist[ui]; if dval.is_finite() && dval < bound && !visited[ui] { segment_nodes.push(u); } }
+            if segment_nodes.is_empty() { break; }
+            // Invariants
+            if last_bound >= 0.0 { inv_check(bound > last_bound, "Boundary not strictly increasing"); if !(bound > last_bound) { monotonic_ok = 0; } }
+            for &u in &segment_nodes { inv_check(!visited[u as usize], "Node repeated in chain"); }
+            // Mark visited
+            for &u in &segment_nodes { visited[u as usize] = true; }
+            let seg_size = segment_nodes.len() as u32; if seg_size > max_segment { max_segment = seg_size; }
+            total_collected += seg_size; total_relax += relax; segments += 1; last_bound = bound;
+            if !truncated { break; }
+        }
+        LAST_CHAIN_STATS.with(|s| s.set(SpecBoundaryChainStats { segments, attempts, total_collected, max_segment, monotonic_ok, relaxations: total_relax }));
+        crate::publish_peak_queue(peak_queue);
+        if !info.is_null(){ unsafe { *info = crate::SsspResultInfo { relaxations: total_relax, light_relaxations:0, heavy_relaxations:0, settled: total_collected, error_code: monotonic_ok, overflow: 0, reachable: crate::count_reachable(dist), edges: tgt.len() as u64 }; } }
+        0
+    })
 }
 
 #[no_mangle]
@@ -426,18 +468,21 @@ pub extern "C" fn sssp_spec_basecase_probe(
     pred_ptr: *mut i32,
     result_out: *mut BaseCaseResult,
 ) -> i32 {
-    if offsets.is_null() || targets.is_null() || weights.is_null() || dist_ptr.is_null() || pred_ptr.is_null() || result_out.is_null(){ return -3; }
-    let off = unsafe { as_slice(offsets, n as usize + 1) };
-    let m = off[n as usize] as usize;
-    let tgt = unsafe { as_slice(targets, m) };
-    let wts = unsafe { as_slice(weights, m) };
-    let dist = unsafe { as_mut_slice(dist_ptr, n as usize) };
-    let pred = unsafe { as_mut_slice(pred_ptr, n as usize) };
-    let mut tmp: Vec<u32> = Vec::with_capacity(k as usize + 2);
-    let mut relax = 0u64;
-    let res = basecase_truncated(n, off, tgt, wts, start, k, bound, dist, pred, &mut tmp, &mut relax);
-    unsafe { *result_out = res; LAST_PHASE1_STATS.last_outcome = res.outcome; LAST_PHASE1_STATS.last_bound = res.new_bound; LAST_PHASE1_STATS.last_collected = res.collected; LAST_PHASE1_STATS.last_relaxations = relax; }
-    0
+    crate::ffi_guard(-99, || {
+        if offsets.is_null() || targets.is_null() || weights.is_null() || dist_ptr.is_null() || pred_ptr.is_null() || result_out.is_null(){ return -3; }
+        let off = unsafe { as_slice(offsets, n as usize + 1) };
+        let m = off[n as usize] as usize;
+        let tgt = unsafe { as_slice(targets, m) };
+        let wts = unsafe { as_slice(weights, m) };
+        let dist = unsafe { as_mut_slice(dist_ptr, n as usize) };
+        let pred = unsafe { as_mut_slice(pred_ptr, n as usize) };
+        let mut tmp: Vec<u32> = Vec::with_capacity(k as usize + 2);
+        let mut relax = 0u64;
+        let res = basecase_truncated(n, off, tgt, wts, start, k, bound, dist, pred, &mut tmp, &mut relax);
+        unsafe { *result_out = res; }
+        LAST_PHASE1_STATS.with(|s| s.set(SpecPhase1Stats { last_outcome: res.outcome, last_bound: res.new_bound, last_collected: res.collected, last_relaxations: relax }));
+        0
+    })
 }
 
 // Phase 1 runner: truncated basecase growth from source.
@@ -452,24 +497,26 @@ pub extern "C" fn sssp_run_spec_phase1(
     out_pred:*mut i32,
     info:*mut crate::SsspResultInfo,
 ) -> i32 {
-    if n==0 { return -1; }
-    if source>=n { return -2; }
-    if offsets.is_null() || targets.is_null() || weights.is_null() || out_dist.is_null() || out_pred.is_null(){ return -3; }
-    let n_usize = n as usize;
-    let off = unsafe { as_slice(offsets, n_usize+1) };
-    let m = off[n_usize] as usize;
-    let tgt = unsafe { as_slice(targets, m) };
-    let wts = unsafe { as_slice(weights, m) };
-    let dist = unsafe { as_mut_slice(out_dist, n_usize) };
-    let pred = unsafe { as_mut_slice(out_pred, n_usize) };
-    let k_env = std::env::var("SSSP_SPEC_K").ok().and_then(|v| v.parse::<u32>().ok()).unwrap_or(1024).max(1);
-    let bound_env = std::env::var("SSSP_SPEC_BOUND").ok().and_then(|v| v.parse::<f32>().ok()).unwrap_or(f32::INFINITY);
-    let mut scratch: Vec<u32> = Vec::with_capacity(k_env as usize + 2);
-    let mut relax: u64 = 0;
-    let res = basecase_truncated(n, off, tgt, wts, source, k_env, bound_env, dist, pred, &mut scratch, &mut relax);
-    unsafe { LAST_PHASE1_STATS.last_outcome = res.outcome; LAST_PHASE1_STATS.last_bound = res.new_bound; LAST_PHASE1_STATS.last_collected = res.collected; LAST_PHASE1_STATS.last_relaxations = relax; }
-    if !info.is_null(){ unsafe { *info = crate::SsspResultInfo { relaxations: relax, light_relaxations:0, heavy_relaxations:0, settled: res.collected, error_code: res.outcome }; } }
-    0
+    crate::ffi_guard(-99, || {
+        if n==0 { return -1; }
+        if source>=n { return -2; }
+        if offsets.is_null() || targets.is_null() || weights.is_null() || out_dist.is_null() || out_pred.is_null(){ return -3; }
+        let n_usize = n as usize;
+        let off = unsafe { as_slice(offsets, n_usize+1) };
+        let m = off[n_usize] as usize;
+        let tgt = unsafe { as_slice(targets, m) };
+        let wts = unsafe { as_slice(weights, m) };
+        let dist = unsafe { as_mut_slice(out_dist, n_usize) };
+        let pred = unsafe { as_mut_slice(out_pred, n_usize) };
+        let k_env = std::env::var("SSSP_SPEC_K").ok().and_then(|v| v.parse::<u32>().ok()).unwrap_or(1024).max(1);
+        let bound_env = std::env::var("SSSP_SPEC_BOUND").ok().and_then(|v| v.parse::<f32>().ok()).unwrap_or(f32::INFINITY);
+        let mut scratch: Vec<u32> = Vec::with_capacity(k_env as usize + 2);
+        let mut relax: u64 = 0;
+        let res = basecase_truncated(n, off, tgt, wts, source, k_env, bound_env, dist, pred, &mut scratch, &mut relax);
+        LAST_PHASE1_STATS.with(|s| s.set(SpecPhase1Stats { last_outcome: res.outcome, last_bound: res.new_bound, last_collected: res.collected, last_relaxations: relax }));
+        if !info.is_null(){ unsafe { *info = crate::SsspResultInfo { relaxations: relax, light_relaxations:0, heavy_relaxations:0, settled: res.collected, error_code: res.outcome, overflow: 0, reachable: crate::count_reachable(dist), edges: tgt.len() as u64 }; } }
+        0
+    })
 }
 
 // ---------------- Tests (unit) ----------------
@@ -518,7 +565,7 @@ mod tests {
         let n = 6u32;
         let mut dist = vec![0f32;6];
         let mut pred = vec![-1i32;6];
-        let mut info = crate::SsspResultInfo { relaxations:0, light_relaxations:0, heavy_relaxations:0, settled:0, error_code:0 };
+        let mut info = crate::SsspResultInfo { relaxations:0, light_relaxations:0, heavy_relaxations:0, settled:0, error_code:0, overflow:0, reachable:0, edges:0 };
         // Small k triggers truncation then scaling
         std::env::set_var("SSSP_SPEC_K","2");
         std::env::set_var("SSSP_SPEC_PIVOT_MAX","4");
@@ -538,7 +585,7 @@ mod tests {
         let n=5u32; let mut dist=vec![0f32;5]; let mut pred=vec![-1i32;5];
         std::env::set_var("SSSP_SPEC_K","1");
         std::env::set_var("SSSP_SPEC_PIVOT_MAX","5");
-        let mut info = crate::SsspResultInfo { relaxations:0, light_relaxations:0, heavy_relaxations:0, settled:0, error_code:0 };
+        let mut info = crate::SsspResultInfo { relaxations:0, light_relaxations:0, heavy_relaxations:0, settled:0, error_code:0, overflow:0, reachable:0, edges:0 };
         let rc = sssp_run_spec_phase2(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info as *mut _);
         assert_eq!(rc,0);
         let mut stats = SpecPhase2Stats::default(); unsafe { sssp_get_spec_phase2_stats(&mut stats as *mut _); }
@@ -548,13 +595,28 @@ mod tests {
     #[test]
     fn datastructure_d_ordering() {
         use crate::spec_future::DataStructureD;
-        let mut d = DataStructureD::new();
-        d.batch_prepend(vec![1,2,3]); // first batch
-        d.batch_prepend(vec![4,5]);   // newer batch should be pulled first
-        let mut seen = Vec::new();
-        while !d.is_empty() { d.pull(|v| seen.push(v)); }
-        // Expect order begins with second batch contents (reverse internal pop) then first batch
-        assert!(seen.contains(&4) && seen.contains(&5));
+        let mut d = DataStructureD::new(4);
+        let mut rng = crate::gen::XorShift64Star::new(0x2545F4914F6CDD1D);
+        let mut next = || rng.next_u32();
+        let mut want = std::collections::HashMap::new();
+        for _ in 0..200 { let (u, x) = (next() % 60, (next() % 1000) as f32 + 10.0); d.insert(u, x); let e = want.entry(u).or_insert(x); if x < *e { *e = x; } }
+        d.insert(7, 5000.0); // larger than the held key: ignored
+        d.batch_prepend(vec![(100, 2.0), (101, 1.0), (7, 3.0)]); // smaller than anything held; lowers node 7
+        want.insert(100, 2.0); want.insert(101, 1.0); want.insert(7, 3.0);
+        assert_eq!(d.len(), want.len());
+        let (first, bound) = d.pull(2);
+        assert_eq!(first, vec![101, 100]);
+        assert_eq!(bound, 3.0);
+        let mut seen = first; let mut last = bound;
+        while !d.is_empty() {
+            let (batch, bound) = d.pull(5);
+            assert!(!batch.is_empty() && batch.len() <= 5);
+            for &u in &batch { assert!(want[&u] >= last && want[&u] <= bound); }
+            seen.extend(batch); last = bound;
+        }
+        assert_eq!(last, f32::INFINITY);
+        let mut keys: Vec<u32> = want.keys().copied().collect(); keys.sort_unstable();
+        seen.sort_unstable(); assert_eq!(seen, keys);
     }
     #[test]
     fn phase3_basic(){
@@ -563,19 +625,34 @@ mod tests {
         let tgt=[1,2,2];
         let wts=[1.0f32,4.0,0.5];
         let n=3u32; let mut dist=vec![0f32;3]; let mut pred=vec![-1i32;3];
-        let mut info = crate::SsspResultInfo { relaxations:0, light_relaxations:0, heavy_relaxations:0, settled:0, error_code:0 };
-        let rc = sssp_run_spec_phase3(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info as *mut _);
+        let mut info = crate::SsspResultInfo { relaxations:0, light_relaxations:0, heavy_relaxations:0, settled:0, error_code:0, overflow:0, reachable:0, edges:0 };
+        let rc = sssp_run_spec_phase3(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info as *mut _, f32::NAN);
     assert_eq!(rc,0); // Shortest path to node 2 is via node 1: 1.0 + 0.5 = 1.5 (direct edge weight 4.0 is longer)
     assert!((dist[1]-1.0).abs()<1e-6); assert!((dist[2]-1.5).abs()<1e-6);
     }
     #[test]
+    fn phase3_huge_delta_single_bucket_still_exact(){
+        // Grid-like DAG with many alternative routes; delta far above any distance.
+        let n=30usize; let mut off=vec![0u32]; let mut tgt=Vec::new(); let mut wts=Vec::new();
+        for u in 0..n { for k in [1usize,3,7] { if u+k<n { tgt.push((u+k) as u32); wts.push(((u*5+k*3)%11) as f32 + 0.5); } } off.push(tgt.len() as u32); }
+        let mut dist=vec![0f32;n]; let mut pred=vec![-1i32;n]; let mut bdist=vec![0f32;n]; let mut bpred=vec![-1i32;n];
+        let stats = phase3_run(&off, &tgt, &wts, 0, 1e9, &mut dist, &mut pred);
+        assert_eq!(stats.buckets, 1);
+        assert!(stats.batches > 1); // several pull(M) batches over bucket 0
+        crate::sssp_run_baseline(n as u32, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, bdist.as_mut_ptr(), bpred.as_mut_ptr(), core::ptr::null_mut());
+        assert_eq!(dist, bdist);
+        // Auto width samples across the whole edge array, not just its prefix.
+        let skewed: Vec<f32> = (0..4096).map(|i| if i < 32 { 100.0 } else { 1.0 }).collect();
+        assert!(phase3_auto_delta(&skewed) < 4.0);
+    }
+    #[test]
     fn boundary_chain_line(){
         // Line graph to produce multiple small segments with small k
         let off=[0u32,1,2,3,4,4]; let tgt=[1,2,3,4]; let wts=[1.0f32;4]; let n=5u32;
         let mut dist=vec![0f32;5]; let mut pred=vec![-1i32;5];
         std::env::set_var("SSSP_SPEC_CHAIN_K","1");
         std::env::set_var("SSSP_SPEC_CHAIN_MAX_SEG","10");
-        let mut info = crate::SsspResultInfo{relaxations:0,light_relaxations:0,heavy_relaxations:0,settled:0,error_code:0};
+        let mut info = crate::SsspResultInfo{relaxations:0,light_relaxations:0,heavy_relaxations:0,settled:0,error_code:0, overflow:0, reachable:0, edges:0};
         let rc = sssp_run_spec_boundary_chain(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info as *mut _); assert_eq!(rc,0);
         let mut stats = SpecBoundaryChainStats::default(); unsafe { sssp_get_spec_boundary_chain_stats(&mut stats as *mut _); }
         // With k=1 segments may collapse if final growth not truncated; require at least one segment collected.
@@ -588,7 +665,7 @@ mod tests {
         let off=[0u32,5,5,5,5,5,5]; let tgt=[1,2,3,4,5]; let wts=[1.0f32;5]; let n=6u32;
         let mut dist=vec![0f32;6]; let mut pred=vec![-1i32;6];
         std::env::set_var("SSSP_SPEC_CHAIN_K","2");
-        let mut info = crate::SsspResultInfo{relaxations:0,light_relaxations:0,heavy_relaxations:0,settled:0,error_code:0};
+        let mut info = crate::SsspResultInfo{relaxations:0,light_relaxations:0,heavy_relaxations:0,settled:0,error_code:0, overflow:0, reachable:0, edges:0};
         let rc = sssp_run_spec_boundary_chain(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info as *mut _); assert_eq!(rc,0);
         let mut stats = SpecBoundaryChainStats::default(); unsafe { sssp_get_spec_boundary_chain_stats(&mut stats as *mut _); }
         assert!(stats.total_collected >=1);
@@ -1,31 +1,101 @@
 //! Future phase scaffolding for BMSSP algorithm components.
 //! Contains zero-impact placeholders to allow incremental PRs without churn.
 
+use std::collections::{HashMap, VecDeque};
+
 #[derive(Default)]
 pub struct PivotCandidate { pub root: u32, pub dist: f32, pub subtree_est: u32 }
 
 #[derive(Default)]
 pub struct ForestNodeMeta { pub parent: u32, pub size: u32 }
 
+// Partial order over (node, dist) pairs after BMSSP Lemma 3.3. Inserted pairs live in
+// blocks of at most `m` items kept in ascending order of their upper bound (a block
+// that overflows is split at its median). Batch-prepended pairs must be no larger than
+// anything held; they go to a stack of blocks whose top is the smallest. A node is
+// held once, under the smallest dist seen; superseded pairs stay in their blocks and
+// are skipped when pulled.
 pub struct DataStructureD {
-    // Placeholder internal buffers; final design will combine small buckets and batch-prepend queue.
-        active: Vec<u32>,          // current pull list
-        prepend_batches: Vec<Vec<u32>>, // queued batches to prepend (LIFO for O(1) prepend)
-        spill: Vec<u32>,           // fallback appended entries
+    m: usize,
+    best: HashMap<u32, f32>,               // live dist per held node
+    prepended: Vec<Vec<(u32, f32)>>,       // batch-prepend blocks, smallest on top
+    blocks: VecDeque<(f32, Vec<(u32, f32)>)>, // (upper bound, items); the last bound is +inf
 }
 impl DataStructureD {
-        pub fn new() -> Self { Self { active: Vec::new(), prepend_batches: Vec::new(), spill: Vec::new() } }
-        pub fn push(&mut self, v: u32) { self.spill.push(v); }
-        pub fn batch_prepend(&mut self, batch: Vec<u32>) { if !batch.is_empty() { self.prepend_batches.push(batch); } }
-        #[inline]
-        fn rotate_prepend(&mut self){ while let Some(mut b) = self.prepend_batches.pop() { if !b.is_empty() { // newest batch prepended first
-                // Move current active to spill, replace active with batch
-                if !self.active.is_empty() { self.spill.extend(self.active.drain(..)); }
-                self.active = b; return; } }
-            if self.active.is_empty() && !self.spill.is_empty() { std::mem::swap(&mut self.active, &mut self.spill); }
+    pub fn new(m: usize) -> Self { Self { m: m.max(1), best: HashMap::new(), prepended: Vec::new(), blocks: VecDeque::new() } }
+
+    #[inline]
+    fn is_live(&self, &(u, d): &(u32, f32)) -> bool { self.best.get(&u) == Some(&d) }
+
+    // Records `d` for `node` unless it already holds an equal or smaller dist.
+    fn improve(&mut self, node: u32, d: f32) -> bool {
+        match self.best.get(&node) { Some(&cur) if cur <= d => false, _ => { self.best.insert(node, d); true } }
+    }
+
+    pub fn insert(&mut self, node: u32, d: f32) {
+        if !self.improve(node, d) { return; }
+        if self.blocks.is_empty() { self.blocks.push_back((f32::INFINITY, Vec::new())); }
+        let i = self.blocks.partition_point(|(ub, _)| *ub < d).min(self.blocks.len() - 1);
+        self.blocks[i].1.push((node, d));
+        if self.blocks[i].1.len() > self.m {
+            let items = &mut self.blocks[i].1;
+            let mid = items.len() / 2;
+            items.select_nth_unstable_by(mid, |a, b| a.1.total_cmp(&b.1));
+            let right = items.split_off(mid);
+            let left = core::mem::replace(items, right);
+            let ub = left.iter().map(|p| p.1).fold(f32::NEG_INFINITY, f32::max);
+            self.blocks.insert(i, (ub, left));
         }
-        pub fn pull<F:FnMut(u32)>(&mut self, mut f:F){ if self.active.is_empty() { self.rotate_prepend(); } while let Some(v)=self.active.pop() { f(v); if self.active.is_empty() { self.rotate_prepend(); } } }
-        pub fn is_empty(&self) -> bool { self.active.is_empty() && self.prepend_batches.is_empty() && self.spill.is_empty() }
+    }
+
+    // Adds `items`, each no larger than any pair already held, as blocks of at most
+    // ceil(m/2) pairs.
+    pub fn batch_prepend(&mut self, items: Vec<(u32, f32)>) {
+        let mut items: Vec<(u32, f32)> = items.into_iter().filter(|&(u, d)| self.improve(u, d)).collect();
+        if items.is_empty() { return; }
+        items.sort_unstable_by(|a, b| a.1.total_cmp(&b.1));
+        self.push_sorted(items);
+    }
+
+    fn push_sorted(&mut self, items: Vec<(u32, f32)>) {
+        let chunk = self.m.div_ceil(2);
+        for block in items.rchunks(chunk) { self.prepended.push(block.to_vec()); }
+    }
+
+    // Smallest live dist held, or +inf when empty.
+    fn min_live(&self) -> f32 {
+        let first = |b: &Vec<(u32, f32)>| b.iter().filter(|p| self.is_live(p)).map(|p| p.1).reduce(f32::min);
+        let top = self.prepended.iter().rev().find_map(first).unwrap_or(f32::INFINITY);
+        let front = self.blocks.iter().find_map(|(_, b)| first(b)).unwrap_or(f32::INFINITY);
+        top.min(front)
+    }
+
+    // Removes up to `m` nodes with the smallest dists and returns them in ascending
+    // order together with a bound: every pulled dist is <= bound and every pair left
+    // is >= bound (+inf once the structure is empty).
+    pub fn pull(&mut self, m: usize) -> (Vec<u32>, f32) {
+        let m = m.max(1);
+        let (mut from_prepended, mut from_blocks) = (Vec::new(), Vec::new());
+        while from_prepended.len() < m { let Some(b) = self.prepended.pop() else { break }; from_prepended.extend(b.into_iter().filter(|p| self.is_live(p))); }
+        let mut next_ub = f32::INFINITY;
+        while from_blocks.len() < m && !self.blocks.is_empty() { let (ub, b) = self.blocks.pop_front().unwrap(); next_ub = ub; from_blocks.extend(b.into_iter().filter(|p| self.is_live(p))); }
+        let mut cand: Vec<(u32, f32, bool)> = from_prepended.into_iter().map(|(u, d)| (u, d, true)).chain(from_blocks.into_iter().map(|(u, d)| (u, d, false))).collect();
+        cand.sort_unstable_by(|a, b| a.1.total_cmp(&b.1));
+        let rest = cand.split_off(cand.len().min(m));
+        // Unselected candidates are no larger than what is left on their own side.
+        let (mut back_prepended, mut back_blocks) = (Vec::new(), Vec::new());
+        for (u, d, pre) in rest { if pre { back_prepended.push((u, d)); } else { back_blocks.push((u, d)); } }
+        // The largest returned block inherits the bound of the last block taken.
+        let mut ub = next_ub;
+        for block in back_blocks.rchunks(self.m) { self.blocks.push_front((ub, block.to_vec())); ub = block[0].1; }
+        self.push_sorted(back_prepended);
+        let mut out = Vec::with_capacity(cand.len());
+        for (u, _, _) in cand { if self.best.remove(&u).is_some() { out.push(u); } }
+        (out, self.min_live())
+    }
+
+    pub fn is_empty(&self) -> bool { self.best.is_empty() }
+    pub fn len(&self) -> usize { self.best.len() }
 }
 
 pub struct BoundaryChain { pub layers: Vec<f32> } // Represents B sequence for recursion levels
@@ -81,9 +151,9 @@ pub struct SpecRecursionStats {
     pub inv_checks: u64,
     pub inv_failures: u64,
 }
-static mut LAST_RECURSION_STATS: SpecRecursionStats = SpecRecursionStats { frames:0, total_relaxations:0, baseline_relaxations:0, seed_k:0, chain_segments:0, chain_total_collected:0, inv_checks:0, inv_failures:0 };
+thread_local! { static LAST_RECURSION_STATS: core::cell::Cell<SpecRecursionStats> = const { core::cell::Cell::new(SpecRecursionStats { frames:0, total_relaxations:0, baseline_relaxations:0, seed_k:0, chain_segments:0, chain_total_collected:0, inv_checks:0, inv_failures:0 }) }; }
 #[no_mangle]
-pub extern "C" fn sssp_get_spec_recursion_stats(out:*mut SpecRecursionStats){ if out.is_null(){ return; } unsafe { *out = LAST_RECURSION_STATS; } }
+pub extern "C" fn sssp_get_spec_recursion_stats(out:*mut SpecRecursionStats){ if out.is_null(){ return; } unsafe { *out = LAST_RECURSION_STATS.with(|s| s.get()); } }
 
 // Frame detail export
 #[repr(C)]
@@ -104,13 +174,181 @@ pub struct SpecRecursionFrameDetail {
     pub bound_improvement_f32:f32,
     pub pivot_success_rate_f32:f32,
 }
-static mut RECURSION_FRAMES: Vec<SpecRecursionFrameDetail> = Vec::new();
+// Frames of the calling thread's last recursive run.
+thread_local! { static RECURSION_FRAMES: core::cell::RefCell<Vec<SpecRecursionFrameDetail>> = const { core::cell::RefCell::new(Vec::new()) }; }
 #[no_mangle]
-pub extern "C" fn sssp_get_spec_recursion_frame_count() -> u32 { unsafe { RECURSION_FRAMES.len() as u32 } }
+pub extern "C" fn sssp_get_spec_recursion_frame_count() -> u32 { RECURSION_FRAMES.with(|f| f.borrow().len() as u32) }
 #[no_mangle]
-pub extern "C" fn sssp_get_spec_recursion_frame(idx: u32, out:*mut SpecRecursionFrameDetail) -> i32 { if out.is_null(){ return -2; } unsafe { if (idx as usize) >= RECURSION_FRAMES.len() { return -1; } *out = RECURSION_FRAMES[idx as usize]; } 0 }
+pub extern "C" fn sssp_get_spec_recursion_frame(idx: u32, out:*mut SpecRecursionFrameDetail) -> i32 { crate::ffi_guard(-99, || { if out.is_null(){ return -2; } match RECURSION_FRAMES.with(|f| f.borrow().get(idx as usize).copied()) { Some(fr) => { unsafe { *out = fr; } 0 } None => -1 } }) }
+
+// ---------------- Recursive runner (Phase 4) ----------------
+// Distances come from the recursion itself. This is k-chunked Dijkstra, not BMSSP
+// yet: no frame runs FindPivots, so a leaf's working set is just the k smallest
+// labels of the frontier. The frontier holds every incomplete node
+// with a finite label, keyed by that label in a DataStructureD. A leaf frame pulls
+// the k smallest into a heap and completes up to k nodes no larger than the pull
+// bound (multi-source truncated basecase: each popped label is final, since every
+// complete node's edges are already relaxed and everything left in D is at least the
+// bound). Leftover labels within the bound are batch-prepended back, larger ones
+// inserted. The leaf's bound is the smallest label left behind. Its completed nodes
+// form a pivot forest, computed afterwards for the frame stats only: roots are nodes
+// whose pred lies outside the segment, sized by reverse accumulation over pop order
+// (Phase 2 steps 2-3). Inner frames descend with half
+// the budget until k nodes are complete or the frontier is empty.
+#[derive(Copy,Clone)] struct RecItem { u:u32, d:f32 }
+impl PartialEq for RecItem { fn eq(&self,o:&Self)->bool { self.d==o.d && self.u==o.u } }
+impl Eq for RecItem {}
+impl PartialOrd for RecItem { fn partial_cmp(&self,o:&Self)->Option<std::cmp::Ordering>{ Some(self.cmp(o)) } }
+impl Ord for RecItem { fn cmp(&self,o:&Self)->std::cmp::Ordering { o.d.partial_cmp(&self.d).unwrap_or(std::cmp::Ordering::Equal) } }
+
+struct RecursionRun<'a> {
+    off: &'a [u32], tgt: &'a [u32], wts: &'a [f32],
+    dist: &'a mut [f32], pred: &'a mut [i32],
+    complete: Vec<bool>,
+    frontier: DataStructureD,
+    segment_of: Vec<u32>, // leaf id that completed the node (0 = not yet)
+    subtree: Vec<u32>,
+    depth_max: u32,
+    max_frames: usize,
+    frames: Vec<SpecRecursionFrameDetail>,
+    next_id: u32,
+    leaves: u32,
+    collected: u32,
+    relaxations: u64,
+    last_bound: f32,
+    inv_checks: u64,
+    inv_failures: u64,
+}
+
+// (completed nodes, relaxations, roots examined, largest subtree) of one frame.
+type FrameTotals = (u32, u64, u32, u32);
+
+impl RecursionRun<'_> {
+    fn frontier_is_empty(&self) -> bool { self.frontier.is_empty() }
 
-// Placeholder recursive runner: currently delegates to baseline and records a single frame.
+    // Records `f` unless the frame cap is reached; returns its slot for later updates.
+    fn record(&mut self, f: SpecRecursionFrameDetail) -> Option<usize> {
+        if self.frames.len() >= self.max_frames { return None; }
+        self.frames.push(f); Some(self.frames.len() - 1)
+    }
+
+    fn frame(&mut self, depth: u32, parent_id: u32, k: u32) -> FrameTotals {
+        self.next_id += 1; let id = self.next_id;
+        let start_bound = self.last_bound;
+        let slot = self.record(SpecRecursionFrameDetail { id, k_used: k, depth, parent_id, ..Default::default() });
+        let totals = if depth + 1 >= self.depth_max { self.leaf(k) } else {
+            let child_k = (k / 2).max(1);
+            let (mut collected, mut relax, mut roots, mut max_sub) = (0u32, 0u64, 0u32, 0u32);
+            while collected < k && !self.frontier_is_empty() {
+                let (c, r, p, s) = self.frame(depth + 1, id, child_k.min(k - collected));
+                collected += c; relax += r; roots += p; max_sub = max_sub.max(s);
+            }
+            (collected, relax, roots, max_sub)
+        };
+        if let Some(i) = slot {
+            let (collected, relax, roots, max_sub) = totals;
+            let f = &mut self.frames[i];
+            f.bound = self.last_bound; f.segment_size = collected; f.relaxations = relax;
+            f.truncated = !self.frontier.is_empty() as i32; f.pivots_examined = roots; f.max_subtree = max_sub;
+            f.pruning_ratio_f32 = if k > 0 { 1.0 - collected as f32 / k as f32 } else { 0.0 };
+            f.bound_improvement_f32 = if start_bound >= 0.0 && self.last_bound.is_finite() { self.last_bound - start_bound } else { 0.0 };
+            f.pivot_success_rate_f32 = if collected > 0 { max_sub as f32 / collected as f32 } else { 0.0 };
+        }
+        totals
+    }
+
+    fn leaf(&mut self, k: u32) -> FrameTotals {
+        use std::collections::BinaryHeap;
+        self.leaves += 1;
+        let seg = self.leaves;
+        let (pulled, pull_bound) = self.frontier.pull(k as usize);
+        let mut pq: BinaryHeap<RecItem> = pulled.into_iter().filter(|&u| !self.complete[u as usize]).map(|u| RecItem { u, d: self.dist[u as usize] }).collect();
+        let mut order: Vec<u32> = Vec::with_capacity(k as usize);
+        let mut relax = 0u64;
+        while (order.len() as u32) < k {
+            let Some(&RecItem { u, d }) = pq.peek() else { break };
+            if d > pull_bound { break; }
+            pq.pop();
+            let ui = u as usize;
+            if d > self.dist[ui] || self.complete[ui] { continue; }
+            // Leaves complete labels in nondecreasing order across the whole run.
+            if order.is_empty() && self.last_bound >= 0.0 { self.inv_checks += 1; if d < self.last_bound { self.inv_failures += 1; } }
+            self.complete[ui] = true; self.segment_of[ui] = seg; order.push(u);
+            for e in self.off[ui] as usize..self.off[ui + 1] as usize {
+                let v = self.tgt[e] as usize; if self.complete[v] { continue; }
+                let nd = d + self.wts[e];
+                if nd < self.dist[v] { self.dist[v] = nd; self.pred[v] = u as i32; pq.push(RecItem { u: v as u32, d: nd }); relax += 1; }
+            }
+        }
+        // Whatever is still live in the heap goes back into the frontier.
+        let mut boundary: Vec<(u32, f32)> = Vec::new(); let mut bound = pull_bound;
+        for RecItem { u, d } in pq.into_vec() {
+            let ui = u as usize;
+            if self.complete[ui] || d > self.dist[ui] { continue; }
+            if d <= pull_bound { boundary.push((u, d)); } else { self.frontier.insert(u, d); }
+            if d < bound { bound = d; }
+        }
+        self.frontier.batch_prepend(boundary);
+        // Pivot forest of the segment: children precede parents in reverse pop order.
+        let (mut roots, mut max_sub) = (0u32, 0u32);
+        for &u in order.iter().rev() {
+            let ui = u as usize; self.subtree[ui] += 1;
+            let p = self.pred[ui];
+            if p >= 0 && self.segment_of[p as usize] == seg { self.subtree[p as usize] += self.subtree[ui]; }
+            else { roots += 1; max_sub = max_sub.max(self.subtree[ui]); }
+            if p >= 0 { self.inv_checks += 1; let pi = p as usize; if !(self.complete[pi] && self.dist[pi] <= self.dist[ui]) { self.inv_failures += 1; } }
+        }
+        for &u in &order { self.subtree[u as usize] = 0; }
+        self.last_bound = bound;
+        self.collected += order.len() as u32; self.relaxations += relax;
+        (order.len() as u32, relax, roots, max_sub)
+    }
+}
+
+fn env_flag(name: &str) -> Option<bool> { std::env::var(name).ok().map(|v| v=="1" || v.to_lowercase()=="true") }
+
+// Shared body of the recursive runners: `depth_max` frame levels, top frames of
+// SSSP_SPEC_RECURSION_K nodes (SSSP_SPEC_RECURSION_NO_CHAIN=1 runs one frame over the
+// whole graph). SSSP_SPEC_RECURSION_BASELINE=1 re-solves with the baseline on top
+// of the recursive result as a correctness escape hatch.
+fn run_recursive(n: u32, offsets:*const u32, targets:*const u32, weights:*const f32, source:u32, out_dist:*mut f32, out_pred:*mut i32, info:*mut crate::SsspResultInfo, depth_max: u32) -> i32 {
+    let (off, tgt, wts) = match crate::csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+    if source >= n { return -2; }
+    if out_dist.is_null() || out_pred.is_null() { return -3; }
+    let rc = crate::precheck_weights(wts, info); if rc != 0 { return rc; }
+    let n_usize = n as usize;
+    let seed_k = std::env::var("SSSP_SPEC_RECURSION_K").ok().and_then(|v| v.parse().ok()).unwrap_or(1024u32).max(1);
+    let top_k = if env_flag("SSSP_SPEC_RECURSION_NO_CHAIN").unwrap_or(false) { n } else { seed_k };
+    let max_frames = std::env::var("SSSP_SPEC_RECURSION_MAX_FRAMES").ok().and_then(|v| v.parse().ok()).unwrap_or(256usize).max(1);
+    let dist = crate::as_mut_slice(out_dist, n_usize); let pred = crate::as_mut_slice(out_pred, n_usize);
+    for d in dist.iter_mut() { *d = f32::INFINITY; } for p in pred.iter_mut() { *p = -1; }
+    dist[source as usize] = 0.0;
+    let mut run = RecursionRun {
+        off, tgt, wts, dist, pred,
+        complete: vec![false; n_usize], frontier: DataStructureD::new(seed_k as usize),
+        segment_of: vec![0; n_usize], subtree: vec![0; n_usize],
+        depth_max: depth_max.max(1), max_frames, frames: Vec::new(), next_id: 0, leaves: 0, collected: 0, relaxations: 0,
+        last_bound: -1.0, inv_checks: 0, inv_failures: 0,
+    };
+    run.frontier.insert(source, 0.0);
+    while !run.frontier_is_empty() { run.frame(0, 0, top_k); }
+    let RecursionRun { frames, leaves, collected, relaxations, inv_checks, inv_failures, .. } = run;
+    let frame_count = frames.len() as u32;
+    RECURSION_FRAMES.with(|f| *f.borrow_mut() = frames);
+    let mut baseline_relax = 0u64;
+    if env_flag("SSSP_SPEC_RECURSION_BASELINE").unwrap_or(false) {
+        let rc = crate::sssp_run_baseline(n, offsets, targets, weights, source, out_dist, out_pred, info);
+        if rc != 0 { return rc; }
+        baseline_relax = if info.is_null() { 0 } else { unsafe { (*info).relaxations } };
+    } else if !info.is_null() {
+        let dist = crate::as_slice(out_dist as *const f32, n_usize);
+        unsafe { *info = crate::SsspResultInfo { relaxations, light_relaxations: 0, heavy_relaxations: 0, settled: collected, error_code: 0, overflow: 0, reachable: crate::count_reachable(dist), edges: tgt.len() as u64 }; }
+    }
+    LAST_RECURSION_STATS.with(|s| s.set(SpecRecursionStats { frames: frame_count, total_relaxations: relaxations, baseline_relaxations: baseline_relax, seed_k, chain_segments: leaves, chain_total_collected: collected, inv_checks, inv_failures }));
+    0
+}
+
+// Single-level recursion: every frame is a leaf of SSSP_SPEC_RECURSION_K nodes.
 #[no_mangle]
 pub extern "C" fn sssp_run_spec_recursive(
     n: u32,
@@ -122,210 +360,79 @@ pub extern "C" fn sssp_run_spec_recursive(
     out_pred:*mut i32,
     info:*mut crate::SsspResultInfo,
  ) -> i32 {
-    if n==0 { return -1; }
-    // Seed k (future: guides basecase sizing for recursion splitting)
-    let seed_k = std::env::var("SSSP_SPEC_RECURSION_K").ok().and_then(|v| v.parse().ok()).unwrap_or(1024).max(1);
-    // Perform segmentation descent (prototype) using an internal variant of boundary chain to gather frames & per-frame relaxations.
-    let disable_chain = std::env::var("SSSP_SPEC_RECURSION_NO_CHAIN").ok().map(|v| v=="1" || v.to_lowercase()=="true").unwrap_or(false);
-    let mut chain_segments = 0u32; let mut chain_total_collected = 0u32; let mut frames = 1u32; let mut seg_relax_sum: u64 = 0;
-    if !disable_chain {
-        let n_usize = n as usize;
-        let off = unsafe { core::slice::from_raw_parts(offsets, n_usize+1) };
-        let m = off[n_usize] as usize;
-        let tgt = unsafe { core::slice::from_raw_parts(targets, m) };
-        let wts = unsafe { core::slice::from_raw_parts(weights, m) };
-        let mut dist = vec![f32::INFINITY; n_usize];
-        let mut pred = vec![-1i32; n_usize];
-        let mut visited = vec![false; n_usize];
-        dist[source as usize] = 0.0;
-        let mut k = std::env::var("SSSP_SPEC_CHAIN_K").ok().and_then(|v| v.parse().ok()).unwrap_or(1024).max(1);
-        let seg_max = std::env::var("SSSP_SPEC_CHAIN_MAX_SEG").ok().and_then(|v| v.parse().ok()).unwrap_or(32).max(1);
-        let target_total = std::env::var("SSSP_SPEC_CHAIN_TARGET").ok().and_then(|v| v.parse().ok()).unwrap_or(0);
-        let max_frames = std::env::var("SSSP_SPEC_RECURSION_MAX_FRAMES").ok().and_then(|v| v.parse().ok()).unwrap_or(256).max(1);
-        unsafe { RECURSION_FRAMES.clear(); }
-        let mut inv_checks: u64 = 0; let mut inv_failures: u64 = 0; let mut prev_bound = -1.0f32;
-        while chain_segments < seg_max && (target_total==0 || chain_total_collected < target_total) && chain_total_collected < n {
-            // Truncated basecase ignoring visited
-            #[derive(Copy,Clone)] struct Item { u:u32, d:f32 }
-            impl PartialEq for Item { fn eq(&self,o:&Self)->bool { self.d==o.d && self.u==o.u } }
-            impl Eq for Item {}
-            impl PartialOrd for Item { fn partial_cmp(&self,o:&Self)->Option<std::cmp::Ordering>{ o.d.partial_cmp(&self.d) } }
-            impl Ord for Item { fn cmp(&self,o:&Self)->std::cmp::Ordering { self.partial_cmp(o).unwrap() } }
-            use std::collections::BinaryHeap; let mut pq = BinaryHeap::new();
-            if chain_segments==0 { pq.push(Item{u:source,d:0.0}); }
-            let mut popped=0u32; let mut max_seen=0.0f32; let mut truncated=false; let mut relax=0u64; let mut scratch: Vec<u32> = Vec::with_capacity(k as usize + 2);
-            while let Some(Item{u,d}) = pq.pop() {
-                if d > dist[u as usize] { continue; }
-                if visited[u as usize] { continue; }
-                scratch.push(u); popped+=1; if d>max_seen { max_seen=d; }
-                if popped==k+1 { truncated=true; break; }
-                let ui = u as usize; let se = off[ui] as usize; let ee = off[ui+1] as usize;
-                for e in se..ee { let v = tgt[e] as usize; if visited[v] { continue; } let nd = d + wts[e]; let cur = dist[v]; if nd < cur { dist[v]=nd; pred[v]=u as i32; pq.push(Item{u:v as u32,d:nd}); relax+=1; } }
-            }
-            let bound = if truncated { max_seen } else { f32::INFINITY };
-            let mut segment_nodes: Vec<u32> = Vec::new();
-            for &u in &scratch { let ui=u as usize; let dval=dist[ui]; if dval.is_finite() && dval < bound && !visited[ui] { segment_nodes.push(u); } }
-            if segment_nodes.is_empty() { break; }
-            // Invariant: monotonic bound
-            if prev_bound >= 0.0 { inv_checks += 1; if !(bound > prev_bound) { inv_failures += 1; } }
-            for &u in &segment_nodes { visited[u as usize]=true; }
-            let seg_size = segment_nodes.len() as u32; chain_total_collected += seg_size; seg_relax_sum += relax; chain_segments += 1; frames = chain_segments;
-            // Dependency invariant
-            for &u in &segment_nodes { let ui = u as usize; let p = pred[ui]; if p >= 0 { inv_checks += 1; let pi = p as usize; if !(visited[pi] && dist[pi] <= dist[ui]) { inv_failures += 1; } } }
-            if unsafe { RECURSION_FRAMES.len() } < max_frames as usize { unsafe { RECURSION_FRAMES.push(SpecRecursionFrameDetail {
-                id: chain_segments, bound, k_used: k, segment_size: seg_size, truncated: if truncated {1} else {0}, relaxations: relax,
-                pivots_examined:0, max_subtree:0,
-                depth:0, parent_id:0, pruning_ratio_f32:0.0, bound_improvement_f32:0.0, pivot_success_rate_f32:0.0
-            }); } }
-            if !truncated { break; }
-            // adapt k doubling heuristic similar to pivot loop (optional) - keep simple now
-            if seg_size >= k { k = (k.saturating_mul(2)).min(n); }
-            prev_bound = bound;
-            if chain_segments >= max_frames { break; }
-        }
-        unsafe { LAST_RECURSION_STATS.inv_checks = inv_checks; LAST_RECURSION_STATS.inv_failures = inv_failures; }
-    }
-    // Correctness pass: populate final distances (and preds) using baseline unless parity disabled.
-    let skip_baseline = std::env::var("SSSP_SPEC_RECURSION_SKIP_BASELINE").ok().map(|v| v=="1" || v.to_lowercase()=="true").unwrap_or(false);
-    let mut baseline_relax = 0u64;
-    if !skip_baseline {
-        let rc = unsafe { crate::sssp_run_baseline(n, offsets, targets, weights, source, out_dist, out_pred, info) };
-        if rc!=0 { return rc; }
-        baseline_relax = if info.is_null() {0} else { unsafe { (*info).relaxations } };
-    } else {
-        // If skipped, zero distances except source to avoid undefined memory exposure.
-        if !out_dist.is_null() { unsafe { for i in 0..n as usize { *out_dist.add(i) = if i==source as usize {0.0} else { f32::INFINITY }; } } }
-        if !out_pred.is_null() { unsafe { for i in 0..n as usize { *out_pred.add(i) = -1; } } }
-        if !info.is_null() { unsafe { (*info).relaxations = 0; } }
-    }
-    unsafe { LAST_RECURSION_STATS.frames = frames; LAST_RECURSION_STATS.total_relaxations = seg_relax_sum; LAST_RECURSION_STATS.baseline_relaxations = baseline_relax; LAST_RECURSION_STATS.seed_k = seed_k; LAST_RECURSION_STATS.chain_segments = chain_segments; LAST_RECURSION_STATS.chain_total_collected = chain_total_collected; }
-    0
- }
+    crate::ffi_guard(-99, || run_recursive(n, offsets, targets, weights, source, out_dist, out_pred, info, 1))
+}
+
+// Deepest useful recursion for n nodes: min(32, ceil(log2 n)), at least 1.
+fn ml_depth_cap(n: u32) -> u32 { (u32::BITS - n.saturating_sub(1).leading_zeros()).clamp(1, 32) }
+
+// Multi-level recursion: SSSP_SPEC_ML_DEPTH_MAX levels (default 2), each child frame
+// taking half its parent's budget; `depth`/`parent_id` link the frame records. Frames
+// recurse on the native stack, so the depth is clamped to min(32, ceil(log2 n)):
+// deeper levels would only hold one-node budgets, and an unbounded value could
+// overflow the stack, which aborts the process past ffi_guard.
+#[no_mangle]
+pub extern "C" fn sssp_run_spec_recursive_ml(
+    n: u32,
+    offsets:*const u32,
+    targets:*const u32,
+    weights:*const f32,
+    source:u32,
+    out_dist:*mut f32,
+    out_pred:*mut i32,
+    info:*mut crate::SsspResultInfo,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let depth_max = std::env::var("SSSP_SPEC_ML_DEPTH_MAX").ok().and_then(|v| v.parse().ok()).unwrap_or(2u32).clamp(1, ml_depth_cap(n));
+        run_recursive(n, offsets, targets, weights, source, out_dist, out_pred, info, depth_max)
+    })
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+
     #[test]
     fn recursion_scaffold_smoke(){
         // Simple line graph 0-1-2
         let off=[0u32,1,2,2]; let tgt=[1,2]; let wts=[1.0f32,2.0];
         let n=3u32; let mut dist=vec![0f32;3]; let mut pred=vec![-1i32;3];
-        let mut info = crate::SsspResultInfo{relaxations:0,light_relaxations:0,heavy_relaxations:0,settled:0,error_code:0};
+        let mut info = crate::SsspResultInfo{relaxations:0,light_relaxations:0,heavy_relaxations:0,settled:0,error_code:0, overflow:0, reachable:0, edges:0};
         let rc = sssp_run_spec_recursive(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info as *mut _);
         assert_eq!(rc,0); assert!((dist[1]-1.0).abs()<1e-6); assert!((dist[2]-3.0).abs()<1e-6);
         let mut stats = SpecRecursionStats::default(); unsafe { sssp_get_spec_recursion_stats(&mut stats as *mut _); }
         assert!(stats.frames >= 1);
     }
-}
 
-// ---------------- Multi-Level Recursion Skeleton (prototype) ----------------
-// Reuses single-layer segmentation as level 0, then synthesizes a depth-1 refinement
-// by splitting each eligible segment, recording hierarchical frame metadata.
-#[no_mangle]
-pub extern "C" fn sssp_run_spec_recursive_ml(
-    n: u32,
-    offsets:*const u32,
-    targets:*const u32,
-    weights:*const f32,
-    source:u32,
-    out_dist:*mut f32,
-    out_pred:*mut i32,
-    info:*mut crate::SsspResultInfo,
-) -> i32 {
-    if n==0 { return -1; }
-    let depth_max = std::env::var("SSSP_SPEC_ML_DEPTH_MAX").ok().and_then(|v| v.parse().ok()).unwrap_or(2).max(1);
-    // Run base segmentation (same logic as single-layer) to populate depth 0 frames.
-    // (Duplicate minimal code path to avoid refactor churn.)
-    let seed_k = std::env::var("SSSP_SPEC_RECURSION_K").ok().and_then(|v| v.parse().ok()).unwrap_or(1024).max(1);
-    let disable_chain = false; // multi-level always performs first layer
-    let mut chain_segments = 0u32; let mut chain_total_collected = 0u32; let mut seg_relax_sum: u64 = 0;
-    unsafe { RECURSION_FRAMES.clear(); }
-    let mut inv_checks: u64 = 0; let mut inv_failures: u64 = 0;
-    if !disable_chain {
-        let n_usize = n as usize;
-        let off = unsafe { core::slice::from_raw_parts(offsets, n_usize+1) };
-        let m = off[n_usize] as usize;
-        let tgt = unsafe { core::slice::from_raw_parts(targets, m) };
-        let wts = unsafe { core::slice::from_raw_parts(weights, m) };
-        let mut dist = vec![f32::INFINITY; n_usize];
-        let mut pred = vec![-1i32; n_usize];
-        let mut visited = vec![false; n_usize];
-        dist[source as usize] = 0.0;
-        let mut k = std::env::var("SSSP_SPEC_CHAIN_K").ok().and_then(|v| v.parse().ok()).unwrap_or(1024).max(1);
-        let seg_max = std::env::var("SSSP_SPEC_CHAIN_MAX_SEG").ok().and_then(|v| v.parse().ok()).unwrap_or(16).max(1) // keep smaller for skeleton
-            .min(32);
-        let max_frames = std::env::var("SSSP_SPEC_RECURSION_MAX_FRAMES").ok().and_then(|v| v.parse().ok()).unwrap_or(256).max(1);
-        let mut prev_bound = -1.0f32;
-        while chain_segments < seg_max && chain_total_collected < n {
-            #[derive(Copy,Clone)] struct Item { u:u32, d:f32 }
-            impl PartialEq for Item { fn eq(&self,o:&Self)->bool { self.d==o.d && self.u==o.u }}
-            impl Eq for Item {}
-            impl PartialOrd for Item { fn partial_cmp(&self,o:&Self)->Option<std::cmp::Ordering>{ o.d.partial_cmp(&self.d) }}
-            impl Ord for Item { fn cmp(&self,o:&Self)->std::cmp::Ordering { self.partial_cmp(o).unwrap() }}
-            use std::collections::BinaryHeap; let mut pq = BinaryHeap::new();
-            if chain_segments==0 { pq.push(Item{u:source,d:0.0}); }
-            let mut popped=0u32; let mut max_seen=0.0f32; let mut truncated=false; let mut relax=0u64; let mut scratch: Vec<u32> = Vec::with_capacity(k as usize + 2);
-            while let Some(Item{u,d}) = pq.pop() {
-                if d > dist[u as usize] { continue; }
-                if visited[u as usize] { continue; }
-                scratch.push(u); popped+=1; if d>max_seen { max_seen=d; }
-                if popped==k+1 { truncated=true; break; }
-                let ui = u as usize; let se = off[ui] as usize; let ee = off[ui+1] as usize;
-                for e in se..ee { let v = tgt[e] as usize; if visited[v] { continue; } let nd = d + wts[e]; let cur = dist[v]; if nd < cur { dist[v]=nd; pred[v]=u as i32; pq.push(Item{u:v as u32,d:nd}); relax+=1; } }
-            }
-            let bound = if truncated { max_seen } else { f32::INFINITY };
-            let mut segment_nodes: Vec<u32> = Vec::new();
-            for &u in &scratch { let ui=u as usize; let dval=dist[ui]; if dval.is_finite() && dval < bound && !visited[ui] { segment_nodes.push(u); } }
-            if segment_nodes.is_empty() { break; }
-            if prev_bound >= 0.0 { inv_checks += 1; if !(bound > prev_bound) { inv_failures += 1; } }
-            for &u in &segment_nodes { visited[u as usize]=true; }
-            let seg_size = segment_nodes.len() as u32; chain_total_collected += seg_size; seg_relax_sum += relax; chain_segments += 1;
-            // Dependency invariant
-            for &u in &segment_nodes { let ui = u as usize; let p = pred[ui]; if p >= 0 { inv_checks += 1; let pi = p as usize; if !(visited[pi] && dist[pi] <= dist[ui]) { inv_failures += 1; } } }
-            if unsafe { RECURSION_FRAMES.len() } < max_frames as usize { unsafe { RECURSION_FRAMES.push(SpecRecursionFrameDetail {
-                id: chain_segments, bound, k_used: k, segment_size: seg_size, truncated: if truncated {1} else {0}, relaxations: relax,
-                pivots_examined:0, max_subtree:0, depth:0, parent_id:0, pruning_ratio_f32:0.0, bound_improvement_f32: if prev_bound>=0.0 && bound.is_finite(){ bound - prev_bound } else {0.0}, pivot_success_rate_f32:0.0
-            }); } }
-            if !truncated { break; }
-            if seg_size >= k { k = (k.saturating_mul(2)).min(n); }
-            prev_bound = bound;
-            if chain_segments >= max_frames { break; }
-        }
+    #[test]
+    fn ml_depth_cap_tracks_log2_n(){
+        assert_eq!([1u32, 2, 3, 4, 5, 1024, 1025].map(ml_depth_cap), [1, 1, 2, 2, 3, 10, 11]);
+        assert_eq!(ml_depth_cap(u32::MAX), 32);
     }
-    // Synthesize depth-1 refinement frames (skeleton) if depth_max>1
-    if depth_max > 1 {
-        unsafe {
-            let existing: Vec<SpecRecursionFrameDetail> = RECURSION_FRAMES.clone();
-            for frame in existing.iter() { if frame.depth==0 && frame.bound.is_finite() {
-                let child_id = (RECURSION_FRAMES.len() as u32) + 1;
-                // Invariants: child bound must be > parent bound
-                let child_bound = frame.bound + (frame.bound.abs()*0.01 + 1e-6);
-                inv_checks += 1; if !(child_bound > frame.bound) { inv_failures += 1; }
-                // segment_size shrinks to simulate pruning
-                let child_seg = frame.segment_size / 2;
-                inv_checks += 1; if !(child_seg <= frame.segment_size) { inv_failures += 1; }
-                RECURSION_FRAMES.push(SpecRecursionFrameDetail {
-                    id: child_id,
-                    bound: child_bound,
-                    k_used: frame.k_used,
-                    segment_size: child_seg,
-                    truncated: frame.truncated,
-                    relaxations: 0,
-                    pivots_examined: 0,
-                    max_subtree: 0,
-                    depth: frame.depth + 1,
-                    parent_id: frame.id,
-                    pruning_ratio_f32: if frame.segment_size>0 { 1.0 - (child_seg as f32 / frame.segment_size as f32) } else { 0.0 },
-                    bound_improvement_f32: child_bound - frame.bound,
-                    pivot_success_rate_f32: 0.0,
-                });
-            }}
+
+    #[test]
+    fn recursion_computes_baseline_distances_itself(){
+        let n = 6000u32;
+        let g = crate::gen::random_graph_in(n, 2.5, 0x6A09E667F3BCC909, 0.0, 27.0); let (off, tgt, wts) = (g.offsets, g.targets, g.weights);
+        let zero = || crate::SsspResultInfo{relaxations:0,light_relaxations:0,heavy_relaxations:0,settled:0,error_code:0, overflow:0, reachable:0, edges:0};
+        let (mut want, mut wp, mut wi) = (vec![0f32; n as usize], vec![0i32; n as usize], zero());
+        assert_eq!(crate::sssp_run_baseline(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, want.as_mut_ptr(), wp.as_mut_ptr(), &mut wi), 0);
+        type Runner = extern "C" fn(u32, *const u32, *const u32, *const f32, u32, *mut f32, *mut i32, *mut crate::SsspResultInfo) -> i32;
+        for (run, ml) in [(sssp_run_spec_recursive as Runner, false), (sssp_run_spec_recursive_ml, true)] {
+            let (mut d, mut p, mut info) = (vec![0f32; n as usize], vec![0i32; n as usize], zero());
+            assert_eq!(run(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d.as_mut_ptr(), p.as_mut_ptr(), &mut info), 0);
+            assert_eq!(d, want);
+            assert_eq!((info.settled, info.reachable), (wi.reachable, wi.reachable));
+            let mut stats = SpecRecursionStats::default(); sssp_get_spec_recursion_stats(&mut stats);
+            assert_eq!((stats.total_relaxations, stats.baseline_relaxations, stats.inv_failures), (info.relaxations, 0, 0));
+            assert!(stats.inv_checks > 0 && stats.chain_segments > 1);
+            assert_eq!(stats.chain_total_collected, wi.reachable);
+            // Every reached node's pred edge is tight.
+            for v in 0..n as usize { if p[v] >= 0 { let u = p[v] as usize; assert!((off[u] as usize..off[u+1] as usize).any(|e| tgt[e] as usize == v && d[u] + wts[e] == d[v])); } }
+            let frames: Vec<SpecRecursionFrameDetail> = (0..sssp_get_spec_recursion_frame_count()).map(|i| { let mut f = SpecRecursionFrameDetail::default(); assert_eq!(sssp_get_spec_recursion_frame(i, &mut f), 0); f }).collect();
+            assert_eq!(frames.iter().any(|f| f.depth == 1), ml);
+            for f in frames.iter().filter(|f| f.depth == 1) { assert!(frames.iter().any(|q| q.id == f.parent_id && q.depth == 0 && q.segment_size >= f.segment_size)); }
         }
+        assert_eq!(sssp_run_spec_recursive(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), n, want.as_mut_ptr(), wp.as_mut_ptr(), core::ptr::null_mut()), -2);
     }
-    let frames_total = unsafe { let ptr = &RECURSION_FRAMES as *const Vec<SpecRecursionFrameDetail>; (*ptr).len() as u32 };
-    // Correctness via baseline (full) run
-    let rc = unsafe { crate::sssp_run_baseline(n, offsets, targets, weights, source, out_dist, out_pred, info) }; if rc!=0 { return rc; }
-    let baseline_relax = if info.is_null() {0} else { unsafe { (*info).relaxations } };
-    unsafe { LAST_RECURSION_STATS.frames = frames_total; LAST_RECURSION_STATS.total_relaxations = seg_relax_sum; LAST_RECURSION_STATS.baseline_relaxations = baseline_relax; LAST_RECURSION_STATS.seed_k = seed_k; LAST_RECURSION_STATS.chain_segments = chain_segments; LAST_RECURSION_STATS.chain_total_collected = chain_total_collected; LAST_RECURSION_STATS.inv_checks = inv_checks; LAST_RECURSION_STATS.inv_failures = inv_failures; }
-    0
 }
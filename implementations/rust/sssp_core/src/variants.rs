@@ -0,0 +1,2241 @@
+//! Alternative runners built on the baseline CSR layout and heap: different
+//! cost models (reliability), execution strategies (partitioned BSP,
+//! label-correcting) and goal-directed point-to-point search (ALT landmarks).
+//! Each entry point keeps the `sssp_run_*` argument shape so bindings can reuse
+//! their marshalling code.
+
+use core::ffi::c_void;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+use crate::{as_mut_slice, as_slice, csr_slices, dijkstra_with, dijkstra_with_weights, dijkstra_within, BaselineHeapStats, BinaryHeapSimple, HeapItem, SsspResultInfo};
+
+// ---------------- Most-reliable path (multiplicative cost) ----------------
+// Edge values are success probabilities in (0,1]; the path score is their product.
+// Because every factor is <= 1 the score never increases along a path, so the
+// max-product analogue of Dijkstra is exact. The min-heap is reused by keying
+// items on the negated probability. Returns -7 if any probability is outside (0,1].
+#[no_mangle]
+pub extern "C" fn sssp_run_most_reliable(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    probs: *const f32,
+    source: u32,
+    out_prob: *mut f32, // len n; 0.0 == unreachable
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, pr) = match csr_slices(n, offsets, targets, probs) { Ok(s) => s, Err(rc) => return rc };
+        if source >= n { return -2; }
+        if out_prob.is_null() || out_pred.is_null() { return -3; }
+        if pr.iter().any(|&p| p.is_nan() || p <= 0.0 || p > 1.0) { return -7; }
+        let n_usize = n as usize;
+        let best = as_mut_slice(out_prob, n_usize);
+        let pred = as_mut_slice(out_pred, n_usize);
+        for b in best.iter_mut() { *b = 0.0; }
+        for p in pred.iter_mut() { *p = -1; }
+        best[source as usize] = 1.0;
+
+        let mut heap = BinaryHeapSimple::new(n_usize.min(1024));
+        let mut pushes: u64 = 0; let mut pops: u64 = 0;
+        let mut relaxations: u64 = 0; let mut settled: u32 = 0;
+        heap.push(HeapItem { node: source, dist: -1.0 }, &mut pushes);
+        while let Some(item) = heap.pop(&mut pops) {
+            let u = item.node as usize;
+            let base = -item.dist;
+            if base < best[u] { continue; }
+            settled += 1;
+            for e in off[u] as usize..off[u + 1] as usize {
+                let v = tgt[e] as usize;
+                let np = base * pr[e];
+                if np > best[v] {
+                    best[v] = np;
+                    pred[v] = u as i32;
+                    heap.push(HeapItem { node: v as u32, dist: -np }, &mut pushes);
+                    relaxations += 1;
+                }
+            }
+        }
+        if !info.is_null() { unsafe { *info = SsspResultInfo { relaxations, light_relaxations: 0, heavy_relaxations: 0, settled, error_code: 0, overflow: 0, reachable: best.iter().filter(|&&b| b > 0.0).count() as u32, edges: tgt.len() as u64 }; } }
+        0
+    })
+}
+
+// ---------------- Partitioned bulk-synchronous baseline ----------------
+// Nodes are split into `num_partitions` contiguous id ranges. Each round every
+// partition runs a Dijkstra restricted to its own range, seeded with the nodes
+// whose distance improved since the previous round; edges leaving the range are
+// buffered as (target, dist, pred) messages. After all partitions finish, the
+// messages are applied serially in partition order (strict `<`, first writer
+// wins ties) and the improved nodes seed the next round. Converges when a round
+// produces no improvement, so weights are prechecked (-6/-7): a negative cycle
+// across partitions would otherwise keep the exchange going forever.
+// Determinism: a partition's round depends only on the round-start state of its
+// own range and the exchange is serial, so dist/pred are identical for any
+// `num_threads` (0 = available parallelism). Threads only pick up partitions.
+struct PartitionRound<'a> { lo: u32, dist: &'a mut [f32], pred: &'a mut [i32], seeds: Vec<u32>, outbox: Vec<(u32, f32, u32)>, relax: u64 }
+
+fn partition_round(job: &mut PartitionRound, off: &[u32], tgt: &[u32], wts: &[f32]) {
+    let lo = job.lo as usize; let hi = lo + job.dist.len();
+    let mut heap = BinaryHeapSimple::new(job.seeds.len().max(16));
+    let mut pushes: u64 = 0; let mut pops: u64 = 0;
+    for &s in &job.seeds { heap.push(HeapItem { node: s, dist: job.dist[s as usize - lo] }, &mut pushes); }
+    while let Some(item) = heap.pop(&mut pops) {
+        let u = item.node as usize;
+        if item.dist > job.dist[u - lo] { continue; }
+        for e in off[u] as usize..off[u + 1] as usize {
+            let v = tgt[e] as usize; let nd = item.dist + wts[e];
+            if v >= lo && v < hi {
+                if nd < job.dist[v - lo] { job.dist[v - lo] = nd; job.pred[v - lo] = u as i32; heap.push(HeapItem { node: v as u32, dist: nd }, &mut pushes); job.relax += 1; }
+            } else {
+                job.outbox.push((v as u32, nd, u as u32));
+            }
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn sssp_run_baseline_partitioned(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    num_partitions: u32,
+    num_threads: u32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if source >= n { return -2; }
+        if out_dist.is_null() || out_pred.is_null() { return -3; }
+        let rc = crate::precheck_weights(wts, info); if rc != 0 { return rc; }
+        let n_usize = n as usize;
+        let dist = as_mut_slice(out_dist, n_usize);
+        let pred = as_mut_slice(out_pred, n_usize);
+        for d in dist.iter_mut() { *d = f32::INFINITY; }
+        for p in pred.iter_mut() { *p = -1; }
+        dist[source as usize] = 0.0;
+
+        let parts = num_partitions.clamp(1, n) as usize;
+        let part_size = n_usize.div_ceil(parts);
+        let parts = n_usize.div_ceil(part_size); // drop empty tail partitions
+        let threads = if num_threads == 0 { std::thread::available_parallelism().map(|t| t.get()).unwrap_or(1) } else { num_threads as usize }.clamp(1, parts);
+        let mut seeds: Vec<Vec<u32>> = vec![Vec::new(); parts];
+        seeds[source as usize / part_size].push(source);
+        let mut queued = vec![false; n_usize];
+        let mut relaxations: u64 = 0;
+        while seeds.iter().any(|s| !s.is_empty()) {
+            let mut jobs: Vec<PartitionRound> = dist.chunks_mut(part_size).zip(pred.chunks_mut(part_size)).enumerate()
+                .map(|(p, (d, pr))| PartitionRound { lo: (p * part_size) as u32, dist: d, pred: pr, seeds: core::mem::take(&mut seeds[p]), outbox: Vec::new(), relax: 0 })
+                .collect();
+            if threads == 1 {
+                for job in jobs.iter_mut() { if !job.seeds.is_empty() { partition_round(job, off, tgt, wts); } }
+            } else {
+                // Round-robin partition -> thread assignment; each job owns a disjoint dist/pred range.
+                let mut groups: Vec<Vec<&mut PartitionRound>> = (0..threads).map(|_| Vec::new()).collect();
+                for (p, job) in jobs.iter_mut().enumerate() { if !job.seeds.is_empty() { groups[p % threads].push(job); } }
+                std::thread::scope(|scope| {
+                    for group in groups { scope.spawn(move || { for job in group { partition_round(job, off, tgt, wts); } }); }
+                });
+            }
+            let outboxes: Vec<Vec<(u32, f32, u32)>> = jobs.into_iter().map(|j| { relaxations += j.relax; j.outbox }).collect();
+            for outbox in outboxes {
+                for (v, nd, u) in outbox {
+                    let vi = v as usize;
+                    if nd < dist[vi] {
+                        dist[vi] = nd; pred[vi] = u as i32; relaxations += 1;
+                        if !queued[vi] { queued[vi] = true; seeds[vi / part_size].push(v); }
+                    }
+                }
+            }
+            for s in &seeds { for &v in s { queued[v as usize] = false; } }
+        }
+        let settled = dist.iter().filter(|d| d.is_finite()).count() as u32;
+        if !info.is_null() { unsafe { *info = SsspResultInfo { relaxations, light_relaxations: 0, heavy_relaxations: 0, settled, error_code: 0, overflow: 0, reachable: crate::count_reachable(dist), edges: tgt.len() as u64 }; } }
+        0
+    })
+}
+
+// ---------------- Min cost/time ratio paths (parametric search) ----------------
+// For each node v, minimizes C(P)/T(P) over source->v paths by bisection on the
+// ratio parameter λ: v admits ratio <= λ iff the shortest path to v under edge
+// weights cost - λ*time is <= 0. Bellman-Ford evaluates each λ (weights may be
+// negative); nodes behind a negative cycle count as feasible.
+// Assumptions: every `time` is > 0 and finite (else -7) and costs are finite, so
+// each path ratio lies between the smallest and largest edge ratio (the initial
+// bracket). Ratios range over walks: a cycle with a lower ratio than any simple
+// path pulls the result toward the cycle ratio. Cost is O(n * iters * n * m), so
+// this targets small/medium graphs. `out_ratio[source] = 0`, unreachable = +inf.
+// `out_pred[v]` is v's predecessor on its own optimal path (ratio-optimal paths do
+// not share prefixes, so the preds need not form a consistent tree).
+#[no_mangle]
+pub extern "C" fn sssp_run_ratio_path(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    cost: *const f32,
+    time: *const f32,
+    source: u32,
+    out_ratio: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, cst) = match csr_slices(n, offsets, targets, cost) { Ok(s) => s, Err(rc) => return rc };
+        if source >= n { return -2; }
+        if time.is_null() || out_ratio.is_null() || out_pred.is_null() { return -3; }
+        let tim = as_slice(time, tgt.len());
+        if tim.iter().any(|&t| !t.is_finite() || t <= 0.0) || cst.iter().any(|c| !c.is_finite()) { return -7; }
+        let n_usize = n as usize;
+        let ratio = as_mut_slice(out_ratio, n_usize);
+        let pred = as_mut_slice(out_pred, n_usize);
+        for r in ratio.iter_mut() { *r = f32::INFINITY; }
+        for p in pred.iter_mut() { *p = -1; }
+        ratio[source as usize] = 0.0;
+        let mut relaxations: u64 = 0;
+        let mut d = vec![f64::INFINITY; n_usize]; let mut bf_pred = vec![-1i32; n_usize];
+        // λ = +inf feasibility is plain reachability.
+        let reach = { let mut seen = vec![false; n_usize]; let mut st = vec![source]; seen[source as usize] = true;
+            while let Some(u) = st.pop() { for &v in &tgt[off[u as usize] as usize..off[u as usize + 1] as usize] { if !seen[v as usize] { seen[v as usize] = true; st.push(v); } } } seen };
+        let (mut lo0, mut hi0) = (f64::INFINITY, f64::NEG_INFINITY);
+        for (&c, &t) in cst.iter().zip(tim) { let r = c as f64 / t as f64; lo0 = lo0.min(r); hi0 = hi0.max(r); }
+        for v in 0..n_usize {
+            if v == source as usize || !reach[v] { continue; }
+            let (mut lo, mut hi) = (lo0, hi0);
+            let mut best_pred = -1i32;
+            for _ in 0..60 {
+                if hi - lo <= 1e-7 * hi.abs().max(1.0) { break; }
+                let lambda = 0.5 * (lo + hi);
+                ratio_bellman_ford(off, tgt, cst, tim, source, lambda, &mut d, &mut bf_pred, &mut relaxations);
+                if d[v] <= 0.0 { hi = lambda; best_pred = bf_pred[v]; } else { lo = lambda; }
+            }
+            if best_pred < 0 { ratio_bellman_ford(off, tgt, cst, tim, source, hi, &mut d, &mut bf_pred, &mut relaxations); best_pred = bf_pred[v]; }
+            ratio[v] = hi as f32; pred[v] = best_pred;
+        }
+        let settled = reach.iter().filter(|&&r| r).count() as u32;
+        if !info.is_null() { unsafe { *info = SsspResultInfo { relaxations, light_relaxations: 0, heavy_relaxations: 0, settled, error_code: 0, overflow: 0, reachable: crate::count_reachable(ratio), edges: tgt.len() as u64 }; } }
+        0
+    })
+}
+
+// Bellman-Ford under cost - λ*time; nodes downstream of an edge that still relaxes
+// after n rounds are set to -inf.
+fn ratio_bellman_ford(off: &[u32], tgt: &[u32], cst: &[f32], tim: &[f32], source: u32, lambda: f64, d: &mut [f64], pred: &mut [i32], relaxations: &mut u64) {
+    let n = d.len();
+    for x in d.iter_mut() { *x = f64::INFINITY; }
+    for p in pred.iter_mut() { *p = -1; }
+    d[source as usize] = 0.0;
+    let mut changed = true; let mut rounds = 0;
+    while changed && rounds < n {
+        changed = false; rounds += 1;
+        for u in 0..n {
+            if !d[u].is_finite() { continue; }
+            for e in off[u] as usize..off[u + 1] as usize {
+                let v = tgt[e] as usize; let nd = d[u] + (cst[e] as f64 - lambda * tim[e] as f64);
+                if nd < d[v] - 1e-12 { d[v] = nd; pred[v] = u as i32; changed = true; *relaxations += 1; }
+            }
+        }
+    }
+    if changed {
+        // Still improving after n rounds: mark everything downstream of a violation.
+        let mut st: Vec<u32> = Vec::new();
+        for u in 0..n { if d[u].is_finite() { for e in off[u] as usize..off[u + 1] as usize { let v = tgt[e] as usize; if d[u] + (cst[e] as f64 - lambda * tim[e] as f64) < d[v] - 1e-12 { st.push(v as u32); } } } }
+        while let Some(x) = st.pop() { if d[x as usize] == f64::NEG_INFINITY { continue; } d[x as usize] = f64::NEG_INFINITY; for &y in &tgt[off[x as usize] as usize..off[x as usize + 1] as usize] { st.push(y); } }
+    }
+}
+
+// ---------------- Quantized weights (u16 + scale) ----------------
+// Baseline Dijkstra over weights stored as u16, decoded as `weights[e] as f32 * scale`
+// (half the weight storage of f32). Reports like `sssp_run_baseline`, including the
+// heap counters behind `sssp_get_baseline_heap_stats`. Returns -7 if `scale` is
+// negative or not finite.
+#[no_mangle]
+pub extern "C" fn sssp_run_baseline_u16(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const u16,
+    scale: f32,
+    source: u32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if source >= n { return -2; }
+        if out_dist.is_null() || out_pred.is_null() { return -3; }
+        if !scale.is_finite() || scale < 0.0 { return -7; }
+        let dist = as_mut_slice(out_dist, n as usize);
+        let pred = as_mut_slice(out_pred, n as usize);
+        let c = dijkstra_with_weights(off, tgt, wts, |w| w as f32 * scale, source, dist, pred, |_, _| true);
+        if !info.is_null() { unsafe { *info = SsspResultInfo { relaxations: c.relaxations, light_relaxations: 0, heavy_relaxations: 0, settled: n, error_code: 0, overflow: 0, reachable: crate::count_reachable(dist), edges: tgt.len() as u64 }; } }
+        crate::publish_heap_stats(BaselineHeapStats { pushes: c.pushes, pops: c.pops, max_size: c.max_size }, core::ptr::null_mut());
+        0
+    })
+}
+
+// ---------------- Point-to-point baseline (early exit) ----------------
+// Baseline Dijkstra that stops the moment `target` is popped. `out_dist[target]`
+// and the pred chain back to the source are final; every other entry is whatever
+// the truncated run left (final for nodes settled earlier, tentative or +inf
+// beyond). `info.error_code` is 1 when the run stopped at the target, 0 when the
+// whole reachable set was settled without meeting it (target unreachable).
+// Counters and `sssp_get_baseline_heap_stats` reflect only the work done.
+#[no_mangle]
+pub extern "C" fn sssp_run_baseline_to(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    target: u32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if source >= n || target >= n { return -2; }
+        if out_dist.is_null() || out_pred.is_null() { return -3; }
+        let rc = crate::precheck_weights(wts, info); if rc != 0 { return rc; }
+        let dist = as_mut_slice(out_dist, n as usize);
+        let pred = as_mut_slice(out_pred, n as usize);
+        let mut hit = false;
+        let c = dijkstra_with(off, tgt, wts, source, dist, pred, |u, _| { hit = u == target; !hit });
+        if !info.is_null() { unsafe { *info = SsspResultInfo { relaxations: c.relaxations, light_relaxations: 0, heavy_relaxations: 0, settled: c.settled, error_code: hit as i32, overflow: 0, reachable: crate::count_reachable(dist), edges: tgt.len() as u64 }; } }
+        crate::publish_heap_stats(BaselineHeapStats { pushes: c.pushes, pops: c.pops, max_size: c.max_size }, core::ptr::null_mut());
+        0
+    })
+}
+
+// ---------------- Radius-bounded baseline ----------------
+// Baseline Dijkstra that never relaxes an edge to a label beyond `cutoff`: nodes at
+// distance <= cutoff (inclusive) get final dist/pred, all others stay +inf / -1, and
+// the heap empties once the last of them is settled. `info.settled` counts the
+// nodes within the cutoff. Returns -7 if `cutoff` is NaN or negative.
+#[no_mangle]
+pub extern "C" fn sssp_run_baseline_radius(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    cutoff: f32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+) -> i32 {
+    sssp_run_baseline_radius_to(n, offsets, targets, weights, source, u32::MAX, cutoff, out_dist, out_pred, info)
+}
+
+// `sssp_run_baseline_radius` combined with the early exit of `sssp_run_baseline_to`:
+// stops when `target` is popped or the cutoff ball is exhausted, whichever comes
+// first. `info.error_code` is 1 when the target was reached within the cutoff.
+// `target` = u32::MAX means no target (plain radius query).
+#[no_mangle]
+pub extern "C" fn sssp_run_baseline_radius_to(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    target: u32,
+    cutoff: f32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if source >= n || (target >= n && target != u32::MAX) { return -2; }
+        if out_dist.is_null() || out_pred.is_null() { return -3; }
+        if cutoff.is_nan() || cutoff < 0.0 { return -7; }
+        let rc = crate::precheck_weights(wts, info); if rc != 0 { return rc; }
+        let dist = as_mut_slice(out_dist, n as usize);
+        let pred = as_mut_slice(out_pred, n as usize);
+        let mut hit = false;
+        let c = dijkstra_within(off, tgt, wts, source, cutoff, dist, pred, |u, _| { hit = u == target; !hit });
+        if !info.is_null() { unsafe { *info = SsspResultInfo { relaxations: c.relaxations, light_relaxations: 0, heavy_relaxations: 0, settled: c.settled, error_code: hit as i32, overflow: 0, reachable: crate::count_reachable(dist), edges: tgt.len() as u64 }; } }
+        crate::publish_heap_stats(BaselineHeapStats { pushes: c.pushes, pops: c.pops, max_size: c.max_size }, core::ptr::null_mut());
+        0
+    })
+}
+
+// ---------------- Bidirectional point-to-point ----------------
+// Forward search from `source` on the CSR and backward search from `target` on the
+// caller's reverse CSR (rev_* must be the transpose of offsets/targets/weights),
+// always advancing the side whose frontier minimum is smaller. Every scanned edge
+// whose head is labelled by the other side offers a candidate mu = d_f(u) + w +
+// d_r(v). Stopping when both searches have settled a common node is NOT enough:
+// that node need not lie on a shortest path. The search stops once
+// min_f + min_r >= mu, since any shorter path would have to use a node still
+// below both frontier minima. `out_dist` (len n) receives the forward labels (final
+// only for nodes the forward side settled) with `out_dist[target]` overwritten by
+// the exact distance (+inf if unreachable). `info.settled` is repurposed as the
+// meeting node on a shortest path (u32::MAX if unreachable); `relaxations` sums
+// both sides.
+#[no_mangle]
+pub extern "C" fn sssp_run_bidijkstra(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    rev_offsets: *const u32,
+    rev_targets: *const u32,
+    rev_weights: *const f32,
+    source: u32,
+    target: u32,
+    out_dist: *mut f32,
+    info: *mut SsspResultInfo,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let fwd = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        let rev = match csr_slices(n, rev_offsets, rev_targets, rev_weights) { Ok(s) => s, Err(rc) => return rc };
+        if fwd.1.len() != rev.1.len() { return -4; }
+        if source >= n || target >= n { return -2; }
+        if out_dist.is_null() { return -3; }
+        let rc = crate::precheck_weights(fwd.2, info); if rc != 0 { return rc; }
+        let rc = crate::precheck_weights(rev.2, info); if rc != 0 { return rc; }
+        let n_usize = n as usize;
+        let graphs = [fwd, rev];
+        let mut dist = [vec![f32::INFINITY; n_usize], vec![f32::INFINITY; n_usize]];
+        let mut heaps: [BinaryHeap<Reverse<(u32, u32)>>; 2] = [BinaryHeap::new(), BinaryHeap::new()];
+        dist[0][source as usize] = 0.0; dist[1][target as usize] = 0.0;
+        heaps[0].push(Reverse((0f32.to_bits(), source))); heaps[1].push(Reverse((0f32.to_bits(), target)));
+        let (mut mu, mut meet) = if source == target { (0.0f32, source) } else { (f32::INFINITY, u32::MAX) };
+        let mut relaxations = 0u64;
+        // Smallest live key of a side (stale entries dropped), +inf when exhausted.
+        fn top(heap: &mut BinaryHeap<Reverse<(u32, u32)>>, dist: &[f32]) -> f32 {
+            while let Some(&Reverse((db, u))) = heap.peek() {
+                let d = f32::from_bits(db);
+                if d > dist[u as usize] { heap.pop(); } else { return d; }
+            }
+            f32::INFINITY
+        }
+        loop {
+            let (kf, kr) = (top(&mut heaps[0], &dist[0]), top(&mut heaps[1], &dist[1]));
+            if kf + kr >= mu { break; }
+            let side = if kf <= kr { 0 } else { 1 };
+            let Reverse((db, u)) = heaps[side].pop().unwrap();
+            let du = f32::from_bits(db);
+            let (off, tgt, wts) = graphs[side];
+            for e in off[u as usize] as usize..off[u as usize + 1] as usize {
+                let v = tgt[e] as usize; let nd = du + wts[e];
+                if nd < dist[side][v] { dist[side][v] = nd; heaps[side].push(Reverse((nd.to_bits(), v as u32))); relaxations += 1; }
+                let through = nd + dist[1 - side][v];
+                if through < mu { mu = through; meet = v as u32; }
+            }
+        }
+        let out = as_mut_slice(out_dist, n_usize);
+        out.copy_from_slice(&dist[0]);
+        out[target as usize] = mu;
+        if !info.is_null() { unsafe { *info = SsspResultInfo { relaxations, light_relaxations: 0, heavy_relaxations: 0, settled: meet, error_code: 0, overflow: 0, reachable: crate::count_reachable(out), edges: graphs[0].1.len() as u64 }; } }
+        0
+    })
+}
+
+// ---------------- A* with a caller-supplied heuristic ----------------
+// Point-to-point search ordered by f = g + heuristic[v], stopping when `target` is
+// popped; `out_dist`/`out_pred` hold true g-values (final along the returned path,
+// tentative or +inf elsewhere). The result is exact for any admissible heuristic
+// (never above the true remaining distance). Closed nodes are reopened when a
+// shorter g arrives, which only an inconsistent heuristic (h(u) > w(u,v) + h(v))
+// can cause; each such re-settle sets bit 1 of `info.error_code`. Debug builds
+// also check that pops come in non-decreasing f order and set bit 2 if not (a
+// flag, not a panic, since the ABI guard would mask it as -99). A +inf heuristic
+// prunes the node; NaN or negative entries return -7. `settled` counts pops.
+#[no_mangle]
+pub extern "C" fn sssp_run_astar(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    target: u32,
+    heuristic: *const f32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if source >= n || target >= n { return -2; }
+        if heuristic.is_null() || out_dist.is_null() || out_pred.is_null() { return -3; }
+        let rc = crate::precheck_weights(wts, info); if rc != 0 { return rc; }
+        let n_usize = n as usize;
+        let h = as_slice(heuristic, n_usize);
+        if h.iter().any(|x| x.is_nan() || *x < 0.0) { return -7; }
+        let dist = as_mut_slice(out_dist, n_usize);
+        let pred = as_mut_slice(out_pred, n_usize);
+        for d in dist.iter_mut() { *d = f32::INFINITY; }
+        for p in pred.iter_mut() { *p = -1; }
+        dist[source as usize] = 0.0;
+        let mut closed = vec![false; n_usize];
+        let mut heap: BinaryHeap<Reverse<(u32, u32, u32)>> = BinaryHeap::new();
+        // Keys (f, !g, node): f ties go to the larger g, i.e. the node nearer the target.
+        heap.push(Reverse((h[source as usize].to_bits(), !0f32.to_bits(), source)));
+        let (mut relaxations, mut settled, mut flags) = (0u64, 0u32, 0i32);
+        let mut last_f = 0.0f32;
+        while let Some(Reverse((fb, gb, u))) = heap.pop() {
+            let (f, g) = (f32::from_bits(fb), f32::from_bits(!gb));
+            let ui = u as usize;
+            if g > dist[ui] { continue; }
+            if closed[ui] { flags |= 1; }
+            if cfg!(debug_assertions) && f < last_f { flags |= 2; }
+            last_f = last_f.max(f);
+            closed[ui] = true; settled += 1;
+            if u == target { break; }
+            for e in off[ui] as usize..off[ui + 1] as usize {
+                let v = tgt[e] as usize; let nd = g + wts[e];
+                if nd < dist[v] {
+                    dist[v] = nd; pred[v] = u as i32; relaxations += 1;
+                    if h[v].is_finite() { heap.push(Reverse(((nd + h[v]).to_bits(), !nd.to_bits(), v as u32))); }
+                }
+            }
+        }
+        if !info.is_null() { unsafe { *info = SsspResultInfo { relaxations, light_relaxations: 0, heavy_relaxations: 0, settled, error_code: flags, overflow: 0, reachable: crate::count_reachable(dist), edges: tgt.len() as u64 }; } }
+        0
+    })
+}
+
+// ---------------- Degree-sampled Dijkstra (approximate) ----------------
+// Baseline Dijkstra except that a node with out-degree > `max_deg_sample` relaxes
+// only `max_deg_sample` of its edges, drawn without replacement (partial
+// Fisher-Yates, xorshift64* seeded by `seed`) each time it is scanned.
+// APPROXIMATE: skipped edges can leave distances too large or nodes unreached
+// (+inf); every reported distance is still the length of a real path. With
+// `max_deg_sample` >= the maximum out-degree (or 0 = no sampling) it is exact.
+#[no_mangle]
+pub extern "C" fn sssp_run_baseline_sampled_degree(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+    max_deg_sample: u32,
+    seed: u64,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if source >= n { return -2; }
+        if out_dist.is_null() || out_pred.is_null() { return -3; }
+        let n_usize = n as usize;
+        let dist = as_mut_slice(out_dist, n_usize);
+        let pred = as_mut_slice(out_pred, n_usize);
+        for d in dist.iter_mut() { *d = f32::INFINITY; }
+        for p in pred.iter_mut() { *p = -1; }
+        dist[source as usize] = 0.0;
+        let cap = if max_deg_sample == 0 { usize::MAX } else { max_deg_sample as usize };
+        let mut rng = crate::gen::XorShift64Star::new(seed | 1);
+        let mut idx: Vec<u32> = Vec::new();
+        let mut heap = BinaryHeapSimple::new(n_usize.min(1024));
+        let (mut pushes, mut pops, mut relaxations, mut settled) = (0u64, 0u64, 0u64, 0u32);
+        heap.push(HeapItem { node: source, dist: 0.0 }, &mut pushes);
+        while let Some(item) = heap.pop(&mut pops) {
+            let u = item.node as usize;
+            if item.dist > dist[u] { continue; }
+            settled += 1;
+            let (start, end) = (off[u] as usize, off[u + 1] as usize);
+            idx.clear(); idx.extend(start as u32..end as u32);
+            let take = idx.len().min(cap);
+            if take < idx.len() {
+                for i in 0..take {
+                    let j = i + rng.next_u32() as usize % (idx.len() - i);
+                    idx.swap(i, j);
+                }
+            }
+            for &e in &idx[..take] {
+                let v = tgt[e as usize] as usize; let nd = item.dist + wts[e as usize];
+                if nd < dist[v] { dist[v] = nd; pred[v] = u as i32; heap.push(HeapItem { node: v as u32, dist: nd }, &mut pushes); relaxations += 1; }
+            }
+        }
+        if !info.is_null() { unsafe { *info = SsspResultInfo { relaxations, light_relaxations: 0, heavy_relaxations: 0, settled, error_code: 0, overflow: 0, reachable: crate::count_reachable(dist), edges: tgt.len() as u64 }; } }
+        0
+    })
+}
+
+// ---------------- Forbidden-node avoidance ----------------
+// Shortest `source -> target` path that never enters a forbidden node. The
+// forbidden set is a bitmask over node ids (`forbidden[v / 64] >> (v % 64) & 1`,
+// `ceil(n / 64)` words); such nodes stay in the graph but are never reached, so
+// edges into them are skipped. A forbidden source or target therefore has no
+// path. `out_dist`/`out_pred` hold the full tree over the allowed nodes
+// (forbidden ones stay +inf / -1). Returns -20 if `target` is unreachable.
+#[no_mangle]
+pub extern "C" fn sssp_run_avoiding(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    target: u32,
+    forbidden: *const u64,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if source >= n || target >= n { return -2; }
+        if forbidden.is_null() || out_dist.is_null() || out_pred.is_null() { return -3; }
+        let n_usize = n as usize;
+        let mask = as_slice(forbidden, n_usize.div_ceil(64));
+        let is_forbidden = |v: usize| (mask[v >> 6] >> (v & 63)) & 1 == 1;
+        let dist = as_mut_slice(out_dist, n_usize);
+        let pred = as_mut_slice(out_pred, n_usize);
+        for d in dist.iter_mut() { *d = f32::INFINITY; }
+        for p in pred.iter_mut() { *p = -1; }
+        let (mut relaxations, mut settled) = (0u64, 0u32);
+        if !is_forbidden(source as usize) {
+            dist[source as usize] = 0.0;
+            let mut heap = BinaryHeapSimple::new(n_usize.min(1024));
+            let (mut pushes, mut pops) = (0u64, 0u64);
+            heap.push(HeapItem { node: source, dist: 0.0 }, &mut pushes);
+            while let Some(item) = heap.pop(&mut pops) {
+                let u = item.node as usize;
+                if item.dist > dist[u] { continue; }
+                settled += 1;
+                for e in off[u] as usize..off[u + 1] as usize {
+                    let v = tgt[e] as usize;
+                    if is_forbidden(v) { continue; }
+                    let nd = item.dist + wts[e];
+                    if nd < dist[v] { dist[v] = nd; pred[v] = u as i32; heap.push(HeapItem { node: v as u32, dist: nd }, &mut pushes); relaxations += 1; }
+                }
+            }
+        }
+        let rc = if dist[target as usize].is_finite() { 0 } else { -20 };
+        if !info.is_null() { unsafe { *info = SsspResultInfo { relaxations, light_relaxations: 0, heavy_relaxations: 0, settled, error_code: rc, overflow: 0, reachable: crate::count_reachable(dist), edges: tgt.len() as u64 }; } }
+        rc
+    })
+}
+
+// ---------------- Soft obstacles (additive node penalties) ----------------
+// Path cost = sum of edge weights + sum of `node_penalty[v]` over every node the
+// path arrives at (the source is not "arrived at" and keeps dist 0). Each node's
+// penalty is charged once: `dist[v]` already includes `node_penalty[v]` when v is
+// settled, i.e. before its out-edges are relaxed, so every successor inherits it.
+// Because arrival at v always charges the same penalty this orders the heap
+// exactly like folding it into each incoming edge, while the reported distances
+// stay "cost to be standing at v". Returns -7 for a negative or non-finite penalty.
+#[no_mangle]
+pub extern "C" fn sssp_run_node_penalty(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    node_penalty: *const f32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if source >= n { return -2; }
+        if node_penalty.is_null() || out_dist.is_null() || out_pred.is_null() { return -3; }
+        let n_usize = n as usize;
+        let pen = as_slice(node_penalty, n_usize);
+        if pen.iter().any(|p| !p.is_finite() || *p < 0.0) { return -7; }
+        let dist = as_mut_slice(out_dist, n_usize);
+        let pred = as_mut_slice(out_pred, n_usize);
+        for d in dist.iter_mut() { *d = f32::INFINITY; }
+        for p in pred.iter_mut() { *p = -1; }
+        dist[source as usize] = 0.0;
+        let mut heap = BinaryHeapSimple::new(n_usize.min(1024));
+        let (mut pushes, mut pops, mut relaxations, mut settled) = (0u64, 0u64, 0u64, 0u32);
+        heap.push(HeapItem { node: source, dist: 0.0 }, &mut pushes);
+        while let Some(item) = heap.pop(&mut pops) {
+            let u = item.node as usize;
+            if item.dist > dist[u] { continue; }
+            settled += 1;
+            for e in off[u] as usize..off[u + 1] as usize {
+                let v = tgt[e] as usize; let nd = item.dist + wts[e] + pen[v];
+                if nd < dist[v] { dist[v] = nd; pred[v] = u as i32; heap.push(HeapItem { node: v as u32, dist: nd }, &mut pushes); relaxations += 1; }
+            }
+        }
+        if !info.is_null() { unsafe { *info = SsspResultInfo { relaxations, light_relaxations: 0, heavy_relaxations: 0, settled, error_code: 0, overflow: 0, reachable: crate::count_reachable(dist), edges: tgt.len() as u64 }; } }
+        0
+    })
+}
+
+// ---------------- Interval weights (best / worst case) ----------------
+// Each edge weight is only known to lie in [w_low, w_high]. Shortest-path length is
+// monotone in every weight, so d under w_low and d under w_high bracket the distance
+// for any realisation: out_dist_low[v] <= d(v) <= out_dist_high[v]. `out_pred` is the
+// w_high tree, i.e. for each v a route whose cost never exceeds out_dist_high[v].
+// The w_low pass reuses `out_pred` as scratch. Returns -7 unless 0 <= w_low <= w_high.
+#[no_mangle]
+pub extern "C" fn sssp_run_interval(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    w_low: *const f32,
+    w_high: *const f32,
+    source: u32,
+    out_dist_low: *mut f32,
+    out_dist_high: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, lo) = match csr_slices(n, offsets, targets, w_low) { Ok(s) => s, Err(rc) => return rc };
+        if source >= n { return -2; }
+        if w_high.is_null() || out_dist_low.is_null() || out_dist_high.is_null() || out_pred.is_null() { return -3; }
+        let hi = as_slice(w_high, lo.len());
+        if lo.iter().zip(hi).any(|(&l, &h)| l.is_nan() || h.is_nan() || l < 0.0 || l > h) { return -7; }
+        let n_usize = n as usize;
+        let pred = as_mut_slice(out_pred, n_usize);
+        let c_lo = dijkstra_with(off, tgt, lo, source, as_mut_slice(out_dist_low, n_usize), pred, |_, _| true);
+        let c_hi = dijkstra_with(off, tgt, hi, source, as_mut_slice(out_dist_high, n_usize), pred, |_, _| true);
+        if !info.is_null() { unsafe { *info = SsspResultInfo { relaxations: c_lo.relaxations + c_hi.relaxations, light_relaxations: 0, heavy_relaxations: 0, settled: c_hi.settled, error_code: 0, overflow: 0, reachable: c_hi.settled, edges: tgt.len() as u64 }; } }
+        0
+    })
+}
+
+// ---------------- Caller-ordered priority queue ----------------
+// Heap ordering callback: negative if (a_node, a_dist) should pop before
+// (b_node, b_dist), positive if after, 0 if either may go first.
+pub type SsspHeapCmp = extern "C" fn(a_node: u32, a_dist: f32, b_node: u32, b_dist: f32) -> i32;
+
+struct CmpHeap { data: Vec<HeapItem>, cmp: SsspHeapCmp }
+
+impl CmpHeap {
+    #[inline] fn before(&self, a: usize, b: usize) -> bool { let (x, y) = (self.data[a], self.data[b]); (self.cmp)(x.node, x.dist, y.node, y.dist) < 0 }
+    fn push(&mut self, item: HeapItem) {
+        self.data.push(item); let mut i = self.data.len() - 1;
+        while i > 0 { let p = (i - 1) / 2; if self.before(i, p) { self.data.swap(i, p); i = p; } else { break; } }
+    }
+    fn pop(&mut self) -> Option<HeapItem> {
+        let len = self.data.len(); if len == 0 { return None; }
+        self.data.swap(0, len - 1); let out = self.data.pop();
+        let (n, mut i) = (self.data.len(), 0usize);
+        loop {
+            let l = 2 * i + 1; if l >= n { break; }
+            let b = if l + 1 < n && self.before(l + 1, l) { l + 1 } else { l };
+            if self.before(b, i) { self.data.swap(i, b); i = b; } else { break; }
+        }
+        out
+    }
+}
+
+// Baseline Dijkstra with the heap order supplied by `cmp` (e.g. distance ties broken
+// by out-degree). A node is rescanned whenever its label improves, so reported
+// distances are exact for any comparator; only one consistent with distance order
+// (a_dist < b_dist => negative) keeps Dijkstra's one-scan-per-node work bound, and
+// an inconsistent one degrades to label-correcting with extra relaxations.
+// `settled` counts node scans. Returns -3 if `cmp` is null.
+#[no_mangle]
+pub extern "C" fn sssp_run_baseline_cmp(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    cmp: Option<SsspHeapCmp>,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if source >= n { return -2; }
+        let cmp = match cmp { Some(f) => f, None => return -3 };
+        if out_dist.is_null() || out_pred.is_null() { return -3; }
+        let n_usize = n as usize;
+        let dist = as_mut_slice(out_dist, n_usize);
+        let pred = as_mut_slice(out_pred, n_usize);
+        for d in dist.iter_mut() { *d = f32::INFINITY; }
+        for p in pred.iter_mut() { *p = -1; }
+        dist[source as usize] = 0.0;
+        let mut heap = CmpHeap { data: Vec::with_capacity(n_usize.min(1024)), cmp };
+        let (mut relaxations, mut settled) = (0u64, 0u32);
+        heap.push(HeapItem { node: source, dist: 0.0 });
+        while let Some(item) = heap.pop() {
+            let u = item.node as usize;
+            if item.dist > dist[u] { continue; }
+            settled += 1;
+            for e in off[u] as usize..off[u + 1] as usize {
+                let v = tgt[e] as usize; let nd = item.dist + wts[e];
+                if nd < dist[v] { dist[v] = nd; pred[v] = u as i32; heap.push(HeapItem { node: v as u32, dist: nd }); relaxations += 1; }
+            }
+        }
+        if !info.is_null() { unsafe { *info = SsspResultInfo { relaxations, light_relaxations: 0, heavy_relaxations: 0, settled, error_code: 0, overflow: 0, reachable: crate::count_reachable(dist), edges: tgt.len() as u64 }; } }
+        0
+    })
+}
+
+// ---------------- Settle-order callback ----------------
+// Called once per settled node, in non-decreasing distance order; `dist` (and the
+// node's out_dist/out_pred entries) are final at that point. `user` is passed through.
+pub type SsspSettleFn = extern "C" fn(node: u32, dist: f32, user: *mut c_void);
+
+// Baseline Dijkstra that reports each accepted pop (stale heap entries are skipped
+// silently) to `on_settle`, so `info.settled` equals the number of callbacks.
+// Returns -3 if `on_settle` is null. The callback must not unwind: it is an
+// `extern "C"` fn, so a panic or foreign exception escaping it aborts the process
+// instead of reaching the runner's -99 guard.
+#[no_mangle]
+pub extern "C" fn sssp_run_baseline_cb(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+    on_settle: Option<SsspSettleFn>,
+    user: *mut c_void,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if source >= n { return -2; }
+        let on_settle = match on_settle { Some(f) => f, None => return -3 };
+        if out_dist.is_null() || out_pred.is_null() { return -3; }
+        let rc = crate::precheck_weights(wts, info); if rc != 0 { return rc; }
+        let dist = as_mut_slice(out_dist, n as usize);
+        let pred = as_mut_slice(out_pred, n as usize);
+        let c = dijkstra_with(off, tgt, wts, source, dist, pred, |u, d| { on_settle(u, d, user); true });
+        if !info.is_null() { unsafe { *info = SsspResultInfo { relaxations: c.relaxations, light_relaxations: 0, heavy_relaxations: 0, settled: c.settled, error_code: 0, overflow: 0, reachable: c.settled, edges: tgt.len() as u64 }; } }
+        crate::publish_heap_stats(BaselineHeapStats { pushes: c.pushes, pops: c.pops, max_size: c.max_size }, core::ptr::null_mut());
+        0
+    })
+}
+
+// ---------------- Fewest-hops tie-break ----------------
+// Dijkstra on the lexicographic key (distance, hop count): among paths of exactly
+// equal f32 cost the one with the fewest edges wins, so pred is independent of
+// adjacency order. Non-negative distances order like their bit patterns, so the
+// key is packed as integers; the usual weight precheck (-6/-7) guards that.
+// `out_hops[v]` is the hop count of the reported path (u32::MAX if unreachable).
+#[no_mangle]
+pub extern "C" fn sssp_run_baseline_min_hops(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    out_dist: *mut f32,
+    out_hops: *mut u32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if source >= n { return -2; }
+        if out_dist.is_null() || out_hops.is_null() || out_pred.is_null() { return -3; }
+        let rc = crate::precheck_weights(wts, info); if rc != 0 { return rc; }
+        let n_usize = n as usize;
+        let dist = as_mut_slice(out_dist, n_usize);
+        let hops = as_mut_slice(out_hops, n_usize);
+        let pred = as_mut_slice(out_pred, n_usize);
+        for d in dist.iter_mut() { *d = f32::INFINITY; }
+        for h in hops.iter_mut() { *h = u32::MAX; }
+        for p in pred.iter_mut() { *p = -1; }
+        dist[source as usize] = 0.0; hops[source as usize] = 0;
+        let mut heap: BinaryHeap<Reverse<(u32, u32, u32)>> = BinaryHeap::with_capacity(n_usize.min(1024));
+        let (mut relaxations, mut settled) = (0u64, 0u32);
+        heap.push(Reverse((0f32.to_bits(), 0, source)));
+        while let Some(Reverse((db, h, u))) = heap.pop() {
+            let u = u as usize; let du = f32::from_bits(db);
+            if du > dist[u] || h > hops[u] { continue; }
+            settled += 1;
+            for e in off[u] as usize..off[u + 1] as usize {
+                let v = tgt[e] as usize; let nd = du + wts[e]; let nh = h + 1;
+                if nd < dist[v] || (nd == dist[v] && nh < hops[v]) {
+                    dist[v] = nd; hops[v] = nh; pred[v] = u as i32;
+                    heap.push(Reverse((nd.to_bits(), nh, v as u32))); relaxations += 1;
+                }
+            }
+        }
+        if !info.is_null() { unsafe { *info = SsspResultInfo { relaxations, light_relaxations: 0, heavy_relaxations: 0, settled, error_code: 0, overflow: 0, reachable: crate::count_reachable(dist), edges: tgt.len() as u64 }; } }
+        0
+    })
+}
+
+// ---------------- Multi-source baseline ----------------
+// Distance field d(v) = min over sources s of (init(s) + d(s, v)), init(s) =
+// `source_dists[i]` (or 0 if that array is null; may be negative, must not be NaN).
+// Every source seeds the heap and keeps pred -1 at its own label; a repeated id
+// keeps its smallest initial value and is pushed only when that value improves, so
+// duplicates never add heap entries that would settle it twice. `settled` counts
+// distinct settled nodes. Zero sources leave every node unreachable.
+#[no_mangle]
+pub extern "C" fn sssp_run_baseline_multi(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    sources: *const u32,
+    num_sources: u32,
+    source_dists: *const f32, // len num_sources, or null for all-zero
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if out_dist.is_null() || out_pred.is_null() || (sources.is_null() && num_sources > 0) { return -3; }
+        let srcs = if num_sources == 0 { &[][..] } else { as_slice(sources, num_sources as usize) };
+        let init = if source_dists.is_null() { None } else { Some(as_slice(source_dists, srcs.len())) };
+        if srcs.iter().any(|&s| s >= n) { return -2; }
+        if init.is_some_and(|d| d.iter().any(|x| x.is_nan())) { return -7; }
+        let n_usize = n as usize;
+        let dist = as_mut_slice(out_dist, n_usize);
+        let pred = as_mut_slice(out_pred, n_usize);
+        for d in dist.iter_mut() { *d = f32::INFINITY; }
+        for p in pred.iter_mut() { *p = -1; }
+        let mut heap = BinaryHeapSimple::new(n_usize.min(1024).max(srcs.len()));
+        let (mut pushes, mut pops, mut relaxations, mut settled) = (0u64, 0u64, 0u64, 0u32);
+        for (i, &s) in srcs.iter().enumerate() {
+            let d0 = init.map_or(0.0, |d| d[i]);
+            if d0 < dist[s as usize] { dist[s as usize] = d0; heap.push(HeapItem { node: s, dist: d0 }, &mut pushes); }
+        }
+        while let Some(item) = heap.pop(&mut pops) {
+            let u = item.node as usize;
+            if item.dist > dist[u] { continue; }
+            settled += 1;
+            for e in off[u] as usize..off[u + 1] as usize {
+                let v = tgt[e] as usize; let nd = item.dist + wts[e];
+                if nd < dist[v] { dist[v] = nd; pred[v] = u as i32; heap.push(HeapItem { node: v as u32, dist: nd }, &mut pushes); relaxations += 1; }
+            }
+        }
+        if !info.is_null() { unsafe { *info = SsspResultInfo { relaxations, light_relaxations: 0, heavy_relaxations: 0, settled, error_code: 0, overflow: 0, reachable: crate::count_reachable(dist), edges: tgt.len() as u64 }; } }
+        0
+    })
+}
+
+// ---------------- Time-dependent edges (time windows) ----------------
+// Label = earliest arrival time. Edge e may be entered at a time t in
+// [open[e], close[e]]: reaching u at t < open[e] waits until open[e]; an entry time
+// max(t, open[e]) past close[e] means the edge is closed (an empty window always is).
+// Arrival = max(t, open[e]) + travel[e]. Waiting makes
+// arrival non-decreasing in departure time (FIFO), so earliest arrival at u is
+// always the best departure and Dijkstra stays exact. Nodes reachable only through
+// a closed window stay +inf. Returns -7 for a NaN start/window or negative travel.
+#[no_mangle]
+pub extern "C" fn sssp_run_time_windows(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    travel: *const f32,
+    open: *const f32,
+    close: *const f32,
+    source: u32,
+    start_time: f32,
+    out_arrival: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, tr) = match csr_slices(n, offsets, targets, travel) { Ok(s) => s, Err(rc) => return rc };
+        if source >= n { return -2; }
+        if open.is_null() || close.is_null() || out_arrival.is_null() || out_pred.is_null() { return -3; }
+        let (op, cl) = (as_slice(open, tr.len()), as_slice(close, tr.len()));
+        if start_time.is_nan() || tr.iter().any(|t| t.is_nan() || *t < 0.0) || op.iter().chain(cl).any(|x| x.is_nan()) { return -7; }
+        let n_usize = n as usize;
+        let arr = as_mut_slice(out_arrival, n_usize);
+        let pred = as_mut_slice(out_pred, n_usize);
+        for a in arr.iter_mut() { *a = f32::INFINITY; }
+        for p in pred.iter_mut() { *p = -1; }
+        arr[source as usize] = start_time;
+        let mut heap = BinaryHeapSimple::new(n_usize.min(1024));
+        let (mut pushes, mut pops, mut relaxations, mut settled) = (0u64, 0u64, 0u64, 0u32);
+        heap.push(HeapItem { node: source, dist: start_time }, &mut pushes);
+        while let Some(item) = heap.pop(&mut pops) {
+            let u = item.node as usize;
+            if item.dist > arr[u] { continue; }
+            settled += 1;
+            for e in off[u] as usize..off[u + 1] as usize {
+                let enter = item.dist.max(op[e]);
+                if enter > cl[e] { continue; }
+                let v = tgt[e] as usize; let at = enter + tr[e];
+                if at < arr[v] { arr[v] = at; pred[v] = u as i32; heap.push(HeapItem { node: v as u32, dist: at }, &mut pushes); relaxations += 1; }
+            }
+        }
+        if !info.is_null() { unsafe { *info = SsspResultInfo { relaxations, light_relaxations: 0, heavy_relaxations: 0, settled, error_code: 0, overflow: 0, reachable: crate::count_reachable(arr), edges: tgt.len() as u64 }; } }
+        0
+    })
+}
+
+// ---------------- Implicit 2D grid ----------------
+// Dijkstra over a width x height grid without a CSR: cell (x, y) is node
+// y * width + x, and stepping onto a cell costs that cell's `cell_cost` (diagonal
+// steps included when `connectivity` is 8), so the source cell's own cost is never
+// paid. A +inf cost marks an impassable cell. Returns -7 for a connectivity other
+// than 4 or 8 or a NaN cost, -6 for a negative cost, -4 if width * height
+// overflows u32.
+#[no_mangle]
+pub extern "C" fn sssp_run_grid(
+    width: u32,
+    height: u32,
+    cell_cost: *const f32,
+    source_x: u32,
+    source_y: u32,
+    connectivity: i32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        if width == 0 || height == 0 { return -1; }
+        let n = match width.checked_mul(height) { Some(n) => n as usize, None => return -4 };
+        if source_x >= width || source_y >= height { return -2; }
+        if cell_cost.is_null() || out_dist.is_null() || out_pred.is_null() { return -3; }
+        const STEPS: [(i32, i32); 8] = [(1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (1, -1), (-1, 1), (-1, -1)];
+        let steps = match connectivity { 4 => &STEPS[..4], 8 => &STEPS[..], _ => return -7 };
+        let cost = as_slice(cell_cost, n);
+        if let Some(c) = cost.iter().find(|c| c.is_nan() || **c < 0.0) { return if c.is_nan() { -7 } else { -6 }; }
+        let dist = as_mut_slice(out_dist, n);
+        let pred = as_mut_slice(out_pred, n);
+        for d in dist.iter_mut() { *d = f32::INFINITY; }
+        for p in pred.iter_mut() { *p = -1; }
+        let source = source_y * width + source_x;
+        dist[source as usize] = 0.0;
+        let mut heap = BinaryHeapSimple::new(n.min(1024));
+        let mut pushes = 0u64; let mut pops = 0u64;
+        heap.push(HeapItem { node: source, dist: 0.0 }, &mut pushes);
+        while let Some(item) = heap.pop(&mut pops) {
+            let u = item.node as usize;
+            if item.dist > dist[u] { continue; }
+            let (x, y) = ((item.node % width) as i64, (item.node / width) as i64);
+            for &(dx, dy) in steps {
+                let (nx, ny) = (x + dx as i64, y + dy as i64);
+                if nx < 0 || ny < 0 || nx >= width as i64 || ny >= height as i64 { continue; }
+                let v = ny as usize * width as usize + nx as usize;
+                let nd = item.dist + cost[v];
+                if nd < dist[v] { dist[v] = nd; pred[v] = u as i32; heap.push(HeapItem { node: v as u32, dist: nd }, &mut pushes); }
+            }
+        }
+        0
+    })
+}
+
+// ---------------- Incremental repair after a node deletion ----------------
+// `dist`/`pred` hold a finished run from `source`; node `deleted` then fails (the
+// CSR is unchanged, its edges are simply never used again). Deletion only lengthens
+// paths, and only for nodes whose tree path ran through `deleted`, i.e. its pred
+// subtree. That subtree is reset to +inf / -1; each member is re-seeded from its
+// cheapest intact in-neighbour (one scan of the intact nodes' out-edges) and a
+// Dijkstra over the subtree finishes the repair. Nodes left without a path stay
+// +inf. Deleting the source leaves everything unreachable.
+#[no_mangle]
+pub extern "C" fn sssp_node_deleted(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    deleted: u32,
+    dist: *mut f32,
+    pred: *mut i32,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if source >= n || deleted >= n { return -2; }
+        if dist.is_null() || pred.is_null() { return -3; }
+        let n_usize = n as usize;
+        let dist = as_mut_slice(dist, n_usize);
+        let pred = as_mut_slice(pred, n_usize);
+        // Children lists of the current tree (CSR by parent), then the subtree of `deleted`.
+        let mut first = vec![0u32; n_usize + 1];
+        for &p in pred.iter() { if p >= 0 && (p as u32) < n { first[p as usize + 1] += 1; } }
+        for i in 0..n_usize { first[i + 1] += first[i]; }
+        let mut fill = first.clone(); let mut kids = vec![0u32; first[n_usize] as usize];
+        for (v, &p) in pred.iter().enumerate() { if p >= 0 && (p as u32) < n { kids[fill[p as usize] as usize] = v as u32; fill[p as usize] += 1; } }
+        let mut affected = vec![false; n_usize];
+        let mut stack = vec![deleted]; affected[deleted as usize] = true;
+        while let Some(u) = stack.pop() {
+            for &c in &kids[first[u as usize] as usize..first[u as usize + 1] as usize] { if !affected[c as usize] { affected[c as usize] = true; stack.push(c); } }
+        }
+        for v in 0..n_usize { if affected[v] { dist[v] = f32::INFINITY; pred[v] = -1; } }
+        let mut heap = BinaryHeapSimple::new(64);
+        let mut pushes = 0u64; let mut pops = 0u64;
+        for u in 0..n_usize {
+            if affected[u] || !dist[u].is_finite() { continue; }
+            for e in off[u] as usize..off[u + 1] as usize {
+                let v = tgt[e] as usize;
+                if !affected[v] || v == deleted as usize { continue; }
+                let nd = dist[u] + wts[e];
+                if nd < dist[v] { dist[v] = nd; pred[v] = u as i32; heap.push(HeapItem { node: v as u32, dist: nd }, &mut pushes); }
+            }
+        }
+        while let Some(item) = heap.pop(&mut pops) {
+            let u = item.node as usize;
+            if item.dist > dist[u] { continue; }
+            for e in off[u] as usize..off[u + 1] as usize {
+                let v = tgt[e] as usize;
+                if v == deleted as usize { continue; }
+                let nd = item.dist + wts[e];
+                if nd < dist[v] { dist[v] = nd; pred[v] = u as i32; heap.push(HeapItem { node: v as u32, dist: nd }, &mut pushes); }
+            }
+        }
+        0
+    })
+}
+
+// ---------------- Label-correcting (SLF deque) ----------------
+// Bellman-Ford-style label correcting over a deque instead of a priority queue:
+// a node whose label improves is enqueued once (in-queue flag) at the front if its
+// label beats the current front ("smallest label first"), else at the back. Nodes
+// may be scanned more than once, but with non-negative weights the fixpoint is the
+// exact shortest-path distance; only the relaxation count differs from baseline.
+// `settled` reports the reachable node count (no node is settled permanently).
+#[no_mangle]
+pub extern "C" fn sssp_run_label_correcting(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if source >= n { return -2; }
+        if out_dist.is_null() || out_pred.is_null() { return -3; }
+        let n_usize = n as usize;
+        let dist = as_mut_slice(out_dist, n_usize);
+        let pred = as_mut_slice(out_pred, n_usize);
+        for d in dist.iter_mut() { *d = f32::INFINITY; }
+        for p in pred.iter_mut() { *p = -1; }
+        dist[source as usize] = 0.0;
+        let mut in_queue = vec![false; n_usize];
+        let mut queue: VecDeque<u32> = VecDeque::new();
+        queue.push_back(source); in_queue[source as usize] = true;
+        let mut relaxations: u64 = 0;
+        while let Some(u) = queue.pop_front() {
+            let u = u as usize; in_queue[u] = false;
+            let du = dist[u];
+            for e in off[u] as usize..off[u + 1] as usize {
+                let v = tgt[e] as usize; let nd = du + wts[e];
+                if nd < dist[v] {
+                    dist[v] = nd; pred[v] = u as i32; relaxations += 1;
+                    if !in_queue[v] {
+                        in_queue[v] = true;
+                        match queue.front() { Some(&f) if nd < dist[f as usize] => queue.push_front(v as u32), _ => queue.push_back(v as u32) }
+                    }
+                }
+            }
+        }
+        let settled = dist.iter().filter(|d| d.is_finite()).count() as u32;
+        if !info.is_null() { unsafe { *info = SsspResultInfo { relaxations, light_relaxations: 0, heavy_relaxations: 0, settled, error_code: 0, overflow: 0, reachable: crate::count_reachable(dist), edges: tgt.len() as u64 }; } }
+        0
+    })
+}
+
+// ---------------- Bellman-Ford (negative weights) ----------------
+// Classic Bellman-Ford: up to n-1 passes over all edges in CSR order, stopping
+// early once a pass changes nothing, then one detection pass. Negative weights are
+// allowed (so the weight check is skipped); NaN returns -7. If the detection pass
+// still improves a node, a negative cycle is reachable from the source: the run
+// returns -9 and `info.settled` names a node on that cycle (found by walking pred
+// n steps back from the improved node). `relaxations` counts every successful
+// relaxation across all passes.
+#[no_mangle]
+pub extern "C" fn sssp_run_bellman_ford(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if source >= n { return -2; }
+        if out_dist.is_null() || out_pred.is_null() { return -3; }
+        if wts.iter().any(|w| w.is_nan()) { return -7; }
+        let n_usize = n as usize;
+        let dist = as_mut_slice(out_dist, n_usize);
+        let pred = as_mut_slice(out_pred, n_usize);
+        for d in dist.iter_mut() { *d = f32::INFINITY; }
+        for p in pred.iter_mut() { *p = -1; }
+        dist[source as usize] = 0.0;
+        let mut relaxations: u64 = 0;
+        // One pass over every edge; returns the last node it improved.
+        let mut pass = |dist: &mut [f32], pred: &mut [i32]| {
+            let mut last = None;
+            for u in 0..n_usize {
+                let du = dist[u]; if du == f32::INFINITY { continue; }
+                for e in off[u] as usize..off[u + 1] as usize {
+                    let v = tgt[e] as usize; let nd = du + wts[e];
+                    if nd < dist[v] { dist[v] = nd; pred[v] = u as i32; relaxations += 1; last = Some(v); }
+                }
+            }
+            last
+        };
+        let mut changed = true;
+        for _ in 1..n_usize { if pass(dist, pred).is_none() { changed = false; break; } }
+        let cycle_node = if changed { pass(dist, pred) } else { None };
+        if let Some(mut v) = cycle_node {
+            for _ in 0..n_usize { v = pred[v] as usize; }
+            if !info.is_null() { unsafe { *info = SsspResultInfo { relaxations, light_relaxations: 0, heavy_relaxations: 0, settled: v as u32, error_code: -9, overflow: 0, reachable: 0, edges: tgt.len() as u64 }; } }
+            return -9;
+        }
+        let reachable = crate::count_reachable(dist);
+        if !info.is_null() { unsafe { *info = SsspResultInfo { relaxations, light_relaxations: 0, heavy_relaxations: 0, settled: reachable, error_code: 0, overflow: 0, reachable, edges: tgt.len() as u64 }; } }
+        0
+    })
+}
+
+// ---------------- SPFA (queue-based Bellman-Ford, SLF) ----------------
+// Bellman-Ford driven by a deque of nodes whose distance changed: a node is
+// enqueued only while not already queued, at the front when its new distance is
+// below the front node's (Smallest-Label-First), else at the back. Negative
+// weights are allowed; NaN returns -7. A node improved more than n times implies
+// a reachable negative cycle: the run returns -9 with that node in `info.settled`.
+// `out_requeues` (nullable) receives how many enqueues were of a node that had
+// been queued before, the work Bellman-Ford's fixed passes would also repeat.
+#[no_mangle]
+pub extern "C" fn sssp_run_spfa(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+    out_requeues: *mut u64,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if source >= n { return -2; }
+        if out_dist.is_null() || out_pred.is_null() { return -3; }
+        if wts.iter().any(|w| w.is_nan()) { return -7; }
+        let n_usize = n as usize;
+        let dist = as_mut_slice(out_dist, n_usize);
+        let pred = as_mut_slice(out_pred, n_usize);
+        for d in dist.iter_mut() { *d = f32::INFINITY; }
+        for p in pred.iter_mut() { *p = -1; }
+        dist[source as usize] = 0.0;
+        let mut in_queue = vec![false; n_usize];
+        let mut ever_queued = vec![false; n_usize];
+        let mut relax_count = vec![0u32; n_usize];
+        let mut queue: VecDeque<u32> = VecDeque::new();
+        queue.push_back(source); in_queue[source as usize] = true; ever_queued[source as usize] = true;
+        let (mut relaxations, mut requeues) = (0u64, 0u64);
+        while let Some(u) = queue.pop_front() {
+            let u = u as usize; in_queue[u] = false;
+            let du = dist[u];
+            for e in off[u] as usize..off[u + 1] as usize {
+                let v = tgt[e] as usize; let nd = du + wts[e];
+                if nd < dist[v] {
+                    dist[v] = nd; pred[v] = u as i32; relaxations += 1;
+                    relax_count[v] += 1;
+                    if relax_count[v] > n {
+                        if !out_requeues.is_null() { unsafe { *out_requeues = requeues; } }
+                        if !info.is_null() { unsafe { *info = SsspResultInfo { relaxations, light_relaxations: 0, heavy_relaxations: 0, settled: v as u32, error_code: -9, overflow: 0, reachable: 0, edges: tgt.len() as u64 }; } }
+                        return -9;
+                    }
+                    if !in_queue[v] {
+                        in_queue[v] = true;
+                        if ever_queued[v] { requeues += 1; } else { ever_queued[v] = true; }
+                        match queue.front() { Some(&f) if nd < dist[f as usize] => queue.push_front(v as u32), _ => queue.push_back(v as u32) }
+                    }
+                }
+            }
+        }
+        if !out_requeues.is_null() { unsafe { *out_requeues = requeues; } }
+        let reachable = crate::count_reachable(dist);
+        if !info.is_null() { unsafe { *info = SsspResultInfo { relaxations, light_relaxations: 0, heavy_relaxations: 0, settled: reachable, error_code: 0, overflow: 0, reachable, edges: tgt.len() as u64 }; } }
+        0
+    })
+}
+
+// ---------------- ALT (A*, landmarks, triangle inequality) ----------------
+// Preprocessing stores forward distances d(L,.) for each landmark L as rows of a
+// `num_landmarks * n` table. For a query (s,t) the heuristic
+//   h(v) = max_L ( d(L,t) - d(L,v) )   (finite rows only, floored at 0)
+// is a lower bound on d(v,t) by the triangle inequality and is consistent, so the
+// first pop of a node is final and the search stops when `t` is popped.
+// These forward-only bounds remain valid on directed graphs.
+#[no_mangle]
+pub extern "C" fn sssp_alt_preprocess(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    landmarks: *const u32,
+    num_landmarks: u32,
+    out_landmark_dist: *mut f32, // len num_landmarks * n, row-major by landmark
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        if landmarks.is_null() || out_landmark_dist.is_null() { return -3; }
+        let lms = as_slice(landmarks, num_landmarks as usize);
+        if lms.iter().any(|&l| l >= n) { return -2; }
+        let mut pred = vec![-1i32; n as usize];
+        for (i, &l) in lms.iter().enumerate() {
+            let row = unsafe { out_landmark_dist.add(i * n as usize) };
+            let rc = crate::sssp_run_baseline(n, offsets, targets, weights, l, row, pred.as_mut_ptr(), core::ptr::null_mut());
+            if rc != 0 { return rc; }
+        }
+        0
+    })
+}
+
+// Reusable A* scratch: g-values and closed flags reset through a touched list so a
+// batch performs one O(n) allocation regardless of the number of queries.
+struct AltScratch { g: Vec<f32>, closed: Vec<bool>, touched: Vec<u32>, heap: BinaryHeapSimple }
+
+impl AltScratch {
+    fn new(n: usize) -> Self { Self { g: vec![f32::INFINITY; n], closed: vec![false; n], touched: Vec::new(), heap: BinaryHeapSimple::new(64) } }
+}
+
+#[inline]
+fn alt_heuristic(lm: &[f32], n: usize, num_l: usize, v: usize, t: usize) -> f32 {
+    let mut h = 0.0f32;
+    for l in 0..num_l { let row = &lm[l * n..(l + 1) * n]; let (dt, dv) = (row[t], row[v]); if dt.is_finite() && dv.is_finite() && dt - dv > h { h = dt - dv; } }
+    h
+}
+
+fn alt_query(off: &[u32], tgt: &[u32], wts: &[f32], lm: &[f32], num_l: usize, source: u32, target: u32, sc: &mut AltScratch, relaxations: &mut u64, settled: &mut u32) -> f32 {
+    let n = sc.g.len(); let t = target as usize;
+    let (mut pushes, mut pops) = (0u64, 0u64);
+    sc.g[source as usize] = 0.0; sc.touched.push(source);
+    sc.heap.push(HeapItem { node: source, dist: alt_heuristic(lm, n, num_l, source as usize, t) }, &mut pushes);
+    let mut found = f32::INFINITY;
+    while let Some(item) = sc.heap.pop(&mut pops) {
+        let u = item.node as usize;
+        if sc.closed[u] { continue; }
+        sc.closed[u] = true; *settled += 1;
+        if u == t { found = sc.g[u]; break; }
+        let gu = sc.g[u];
+        for e in off[u] as usize..off[u + 1] as usize {
+            let v = tgt[e] as usize; let nd = gu + wts[e];
+            if !sc.closed[v] && nd < sc.g[v] {
+                if !sc.g[v].is_finite() { sc.touched.push(v as u32); }
+                sc.g[v] = nd;
+                sc.heap.push(HeapItem { node: v as u32, dist: nd + alt_heuristic(lm, n, num_l, v, t) }, &mut pushes);
+                *relaxations += 1;
+            }
+        }
+    }
+    sc.heap.data.clear();
+    for &v in &sc.touched { sc.g[v as usize] = f32::INFINITY; sc.closed[v as usize] = false; }
+    sc.touched.clear();
+    found
+}
+
+// Single ALT point-to-point query; `out_dist` receives d(source,target) (+inf if unreachable).
+#[no_mangle]
+pub extern "C" fn sssp_run_alt(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    target: u32,
+    landmark_dist: *const f32,
+    num_landmarks: u32,
+    out_dist: *mut f32,
+    info: *mut SsspResultInfo,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if source >= n || target >= n { return -2; }
+        if out_dist.is_null() || (landmark_dist.is_null() && num_landmarks > 0) { return -3; }
+        let lm: &[f32] = if num_landmarks == 0 { &[] } else { as_slice(landmark_dist, num_landmarks as usize * n as usize) };
+        let mut sc = AltScratch::new(n as usize);
+        let (mut relaxations, mut settled) = (0u64, 0u32);
+        let d = alt_query(off, tgt, wts, lm, num_landmarks as usize, source, target, &mut sc, &mut relaxations, &mut settled);
+        unsafe { *out_dist = d; }
+        if !info.is_null() { unsafe { *info = SsspResultInfo { relaxations, light_relaxations: 0, heavy_relaxations: 0, settled, error_code: 0, overflow: 0, reachable: d.is_finite() as u32, edges: tgt.len() as u64 }; } }
+        0
+    })
+}
+
+// Batched ALT: query i answers d(sources[i], query_targets[i]) into `out_dist[i]`,
+// sharing one landmark table and one scratch workspace across all queries.
+#[no_mangle]
+pub extern "C" fn sssp_run_alt_batch(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    sources: *const u32,
+    num_sources: u32,
+    query_targets: *const u32, // len num_sources
+    landmark_dist: *const f32,
+    num_landmarks: u32,
+    out_dist: *mut f32,        // len num_sources
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if sources.is_null() || query_targets.is_null() || out_dist.is_null() || (landmark_dist.is_null() && num_landmarks > 0) { return -3; }
+        let q = num_sources as usize;
+        let (srcs, dsts) = (as_slice(sources, q), as_slice(query_targets, q));
+        if srcs.iter().chain(dsts.iter()).any(|&v| v >= n) { return -2; }
+        let lm: &[f32] = if num_landmarks == 0 { &[] } else { as_slice(landmark_dist, num_landmarks as usize * n as usize) };
+        let out = as_mut_slice(out_dist, q);
+        let mut sc = AltScratch::new(n as usize);
+        let (mut relaxations, mut settled) = (0u64, 0u32);
+        for i in 0..q { out[i] = alt_query(off, tgt, wts, lm, num_landmarks as usize, srcs[i], dsts[i], &mut sc, &mut relaxations, &mut settled); }
+        0
+    })
+}
+
+// Baseline from every source over one graph: row i of the row-major
+// `num_sources x n` matrix receives the distances from `sources[i]`. Sources are split
+// into contiguous blocks over `available_parallelism` std scoped threads, each with
+// its own pred scratch and heap. No process-global stats are written (not even the
+// thread-local peak queue); `info_array` (optional, len num_sources) gets one entry
+// per source instead. Weights are checked once for the whole batch.
+#[no_mangle]
+pub extern "C" fn sssp_run_baseline_batch(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    sources: *const u32,
+    num_sources: u32,
+    out_dist_matrix: *mut f32,       // len num_sources * n
+    info_array: *mut SsspResultInfo, // len num_sources, may be null
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if sources.is_null() || out_dist_matrix.is_null() { return -3; }
+        let (q, n_usize) = (num_sources as usize, n as usize);
+        if q == 0 { return 0; }
+        let srcs = as_slice(sources, q);
+        if srcs.iter().any(|&s| s >= n) { return -2; }
+        let rc = crate::precheck_weights(wts, core::ptr::null_mut()); if rc != 0 { return rc; }
+        let matrix = as_mut_slice(out_dist_matrix, q * n_usize);
+        let infos: Option<&mut [SsspResultInfo]> = if info_array.is_null() { None } else { Some(as_mut_slice(info_array, q)) };
+        let threads = std::thread::available_parallelism().map(|t| t.get()).unwrap_or(1).min(q);
+        let chunk = q.div_ceil(threads);
+        std::thread::scope(|scope| {
+            let mut info_chunks = infos.map(|i| i.chunks_mut(chunk));
+            for (ci, rows) in matrix.chunks_mut(chunk * n_usize).enumerate() {
+                let mut info_out = info_chunks.as_mut().and_then(|c| c.next());
+                let block = &srcs[ci * chunk..(ci * chunk + chunk).min(q)];
+                scope.spawn(move || {
+                    let mut pred = vec![-1i32; n_usize];
+                    for (i, (&s, dist)) in block.iter().zip(rows.chunks_mut(n_usize)).enumerate() {
+                        let c = dijkstra_with(off, tgt, wts, s, dist, &mut pred, |_, _| true);
+                        if let Some(out) = info_out.as_deref_mut() {
+                            out[i] = SsspResultInfo { relaxations: c.relaxations, light_relaxations: 0, heavy_relaxations: 0, settled: c.settled, error_code: 0, overflow: 0, reachable: c.settled, edges: tgt.len() as u64 };
+                        }
+                    }
+                });
+            }
+        });
+        0
+    })
+}
+
+// ---------------- Integer-weight delta-stepping ----------------
+// `sssp_run_stoc` over i32 weights in exact integer arithmetic: the shared STOC core
+// runs with i64 distances, an integer delta (the f32 rule rounded, at least 1) and
+// bucket `dist / delta`, so long paths cannot drift into the wrong bucket the way
+// `dist * inv_delta` can in f32. Unreached nodes get i64::MAX. Returns -6 on a
+// negative weight (flagged in `info.error_code`) and -5 if a bucket index exceeds 4n + 1024.
+#[no_mangle]
+pub extern "C" fn sssp_run_stoc_i32(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const i32,
+    source: u32,
+    out_dist: *mut i64,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        crate::stoc_run_adaptive_weights(n, offsets, targets, weights, |w| w as i64, source, out_dist, out_pred, info, core::ptr::null_mut(), core::ptr::null_mut(), &crate::StocConfig::from_env(), &mut crate::StocScratch::default(), core::ptr::null_mut(), crate::RunOpts::checked())
+    })
+}
+
+// ---------------- Double precision (f64 weights and distances) ----------------
+// Baseline Dijkstra with f64 weights and distances, for graphs whose path sums
+// outgrow f32's 24-bit mantissa (distinct paths rounding to the same f32 make
+// pred depend on pop order). Same checks and reporting as `sssp_run_baseline_stats`.
+#[no_mangle]
+pub extern "C" fn sssp_run_baseline_f64(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f64,
+    source: u32,
+    out_dist: *mut f64,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+    heap_stats: *mut BaselineHeapStats,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if source >= n { return -2; }
+        if out_dist.is_null() || out_pred.is_null() { return -3; }
+        let rc = crate::precheck_weights(wts, info); if rc != 0 { return rc; }
+        let dist = as_mut_slice(out_dist, n as usize);
+        let pred = as_mut_slice(out_pred, n as usize);
+        let c = dijkstra_with_weights(off, tgt, wts, |w| w, source, dist, pred, |_, _| true);
+        if !info.is_null() { unsafe { *info = SsspResultInfo { relaxations: c.relaxations, light_relaxations: 0, heavy_relaxations: 0, settled: n, error_code: 0, overflow: 0, reachable: crate::count_reachable(dist), edges: tgt.len() as u64 }; } }
+        crate::publish_heap_stats(BaselineHeapStats { pushes: c.pushes, pops: c.pops, max_size: c.max_size }, heap_stats);
+        0
+    })
+}
+
+// `sssp_run_stoc` in f64 on the shared STOC core (same delta rule, clamped to
+// [1e-4, 1e12], and adaptive restarts). Distances equal `sssp_run_baseline_f64` bit for bit.
+#[no_mangle]
+pub extern "C" fn sssp_run_stoc_f64(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f64,
+    source: u32,
+    out_dist: *mut f64,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        crate::stoc_run_adaptive_weights(n, offsets, targets, weights, |w| w, source, out_dist, out_pred, info, core::ptr::null_mut(), core::ptr::null_mut(), &crate::StocConfig::from_env(), &mut crate::StocScratch::default(), core::ptr::null_mut(), crate::RunOpts::checked())
+    })
+}
+
+// ---------------- Automatic runner selection ----------------
+// `sssp_run_auto` dispatches to `sssp_run_baseline` or `sssp_run_stoc` from three
+// cheap features: density m/n, the coefficient of variation of up to 1024 weights
+// sampled at a uniform stride, and the hop depth of a BFS from the source capped at
+// AUTO_BFS_CAP nodes (a diameter estimate). Rules, first match wins:
+//   n < AUTO_SMALL_N              -> baseline (too small for buckets to pay off)
+//   weight CV > AUTO_MAX_CV       -> baseline (heavy-tailed weights leave most relaxations
+//                                    heavy and buckets sparse; STOC ran ~1.5x slower)
+//   BFS depth >= AUTO_DEEP_HOPS   -> stoc (long, thin graphs: STOC ran ~6x faster on a ladder)
+//   m/n > AUTO_MAX_DENSITY        -> baseline (at degree 64 the heap caught up with STOC)
+//   otherwise                     -> stoc (uniform sparse graphs, ~2-3x faster)
+// SSSP_AUTO_FORCE=baseline|stoc skips the heuristic; it is read once per process.
+// `khop` is accepted for compatibility but this tree has no k-hop runner, so it falls
+// back to the heuristic.
+pub const SSSP_AUTO_BASELINE: u32 = 0;
+pub const SSSP_AUTO_STOC: u32 = 1;
+const AUTO_SMALL_N: u32 = 1024;
+const AUTO_MAX_CV: f32 = 1.0;
+const AUTO_DEEP_HOPS: u32 = 64;
+const AUTO_MAX_DENSITY: f32 = 32.0;
+const AUTO_BFS_CAP: usize = 4096;
+
+thread_local! { static LAST_AUTO_CHOICE: core::cell::Cell<u32> = const { core::cell::Cell::new(SSSP_AUTO_BASELINE) }; }
+
+fn auto_force() -> Option<u32> {
+    static FORCE: std::sync::OnceLock<Option<u32>> = std::sync::OnceLock::new();
+    *FORCE.get_or_init(|| match std::env::var("SSSP_AUTO_FORCE").as_deref() {
+        Ok("baseline") => Some(SSSP_AUTO_BASELINE),
+        Ok("stoc") => Some(SSSP_AUTO_STOC),
+        _ => None,
+    })
+}
+
+// The rules above, unless `force` names a runner.
+fn auto_choice_with(force: Option<u32>, n: u32, off: &[u32], tgt: &[u32], wts: &[f32], source: u32) -> u32 {
+    if let Some(choice) = force { return choice; }
+    if n < AUTO_SMALL_N { return SSSP_AUTO_BASELINE; }
+    let m = wts.len();
+    if m > 0 {
+        let (mut s, mut s2, mut c) = (0f64, 0f64, 0f64);
+        for w in crate::weight_sample(wts, 1024) { s += w as f64; s2 += (w as f64) * (w as f64); c += 1.0; }
+        let mean = s / c;
+        let cv = if mean > 0.0 { ((s2 / c - mean * mean).max(0.0)).sqrt() / mean } else { 0.0 };
+        if cv as f32 > AUTO_MAX_CV { return SSSP_AUTO_BASELINE; }
+    }
+    // Depth of a BFS that stops after AUTO_BFS_CAP nodes.
+    let mut depth_of: std::collections::HashMap<u32, u32> = std::collections::HashMap::new();
+    let mut queue = VecDeque::new();
+    depth_of.insert(source, 0); queue.push_back(source);
+    let mut depth = 0u32;
+    while let Some(u) = queue.pop_front() {
+        let du = depth_of[&u]; depth = depth.max(du);
+        if depth_of.len() >= AUTO_BFS_CAP { continue; }
+        for &v in &tgt[off[u as usize] as usize..off[u as usize + 1] as usize] {
+            if depth_of.len() < AUTO_BFS_CAP && !depth_of.contains_key(&v) { depth_of.insert(v, du + 1); queue.push_back(v); }
+        }
+    }
+    if depth >= AUTO_DEEP_HOPS { return SSSP_AUTO_STOC; }
+    if m as f32 / n as f32 > AUTO_MAX_DENSITY { return SSSP_AUTO_BASELINE; }
+    SSSP_AUTO_STOC
+}
+
+// Runs the runner picked by the rules above; distances are exact either way.
+#[no_mangle]
+pub extern "C" fn sssp_run_auto(
+    n: u32,
+    offsets: *const u32,
+    targets: *const u32,
+    weights: *const f32,
+    source: u32,
+    out_dist: *mut f32,
+    out_pred: *mut i32,
+    info: *mut SsspResultInfo,
+) -> i32 {
+    crate::ffi_guard(-99, || {
+        let (off, tgt, wts) = match csr_slices(n, offsets, targets, weights) { Ok(s) => s, Err(rc) => return rc };
+        if source >= n { return -2; }
+        let choice = auto_choice_with(auto_force(), n, off, tgt, wts, source);
+        LAST_AUTO_CHOICE.with(|c| c.set(choice));
+        if choice == SSSP_AUTO_STOC { crate::sssp_run_stoc(n, offsets, targets, weights, source, out_dist, out_pred, info) }
+        else { crate::sssp_run_baseline(n, offsets, targets, weights, source, out_dist, out_pred, info) }
+    })
+}
+
+// Runner chosen by this thread's last `sssp_run_auto` call (SSSP_AUTO_BASELINE or
+// SSSP_AUTO_STOC).
+#[no_mangle]
+pub extern "C" fn sssp_get_auto_choice() -> u32 { LAST_AUTO_CHOICE.with(|c| c.get()) }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty_info() -> SsspResultInfo { SsspResultInfo { relaxations:0, light_relaxations:0, heavy_relaxations:0, settled:0, error_code:0, overflow:0, reachable:0, edges:0 } }
+
+    #[test]
+    fn most_reliable_prefers_higher_product(){
+        // 0->1 (0.9), 0->2 (0.5), 1->2 (0.9): via 1 gives 0.81 > direct 0.5
+        let off=[0u32,2,3,3]; let tgt=[1u32,2,2]; let pr=[0.9f32,0.5,0.9];
+        let mut prob=vec![0f32;3]; let mut pred=vec![-1i32;3]; let mut info=empty_info();
+        let rc = sssp_run_most_reliable(3, off.as_ptr(), tgt.as_ptr(), pr.as_ptr(), 0, prob.as_mut_ptr(), pred.as_mut_ptr(), &mut info);
+        assert_eq!(rc,0);
+        assert_eq!(pred[2],1);
+        // Reliability equals the product along the pred chain.
+        let mut v=2usize; let mut prod=1.0f32;
+        while pred[v]>=0 { let u=pred[v] as usize; let e=(off[u]..off[u+1]).find(|&e| tgt[e as usize] as usize==v).unwrap() as usize; prod*=pr[e]; v=u; }
+        assert!((prob[2]-prod).abs()<1e-6);
+        assert!(prob[2] > 0.5);
+    }
+
+    #[test]
+    fn partitioned_matches_serial_for_any_thread_count(){
+        // Deterministic pseudo-random graph (xorshift) with cross-partition edges.
+        let n = 200u32;
+        let g = crate::gen::random_graph_in(n, 4.0, 0x9E3779B97F4A7C15, 0.5, 4.5); let (off, tgt, wts) = (g.offsets, g.targets, g.weights);
+        let mut bdist = vec![0f32; n as usize]; let mut bpred = vec![-1i32; n as usize];
+        assert_eq!(crate::sssp_run_baseline(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 3, bdist.as_mut_ptr(), bpred.as_mut_ptr(), core::ptr::null_mut()), 0);
+        let mut runs = Vec::new();
+        for threads in [1u32, 2, 4] {
+            let mut dist = vec![0f32; n as usize]; let mut pred = vec![-1i32; n as usize]; let mut info = empty_info();
+            let rc = sssp_run_baseline_partitioned(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 3, 8, threads, dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info);
+            assert_eq!(rc, 0);
+            assert_eq!(dist, bdist, "threads={}", threads);
+            runs.push((dist, pred, info.relaxations));
+        }
+        for r in &runs[1..] { assert_eq!(r.1, runs[0].1); assert_eq!(r.2, runs[0].2); }
+    }
+
+    #[test]
+    fn partitioned_rejects_bad_weights_across_partitions(){
+        // Two partitions {0,1} | {2,3}; 1 -> 2 -> 1 is a negative cycle through the boundary.
+        let off=[0u32,1,2,3,3]; let tgt=[1u32,2,1];
+        let (mut dist, mut pred) = ([0f32;4], [0i32;4]);
+        for (w, want) in [(-3.0f32, -6), (f32::NAN, -7)] {
+            let wts=[1.0f32,1.0,w]; let mut info=empty_info();
+            assert_eq!(sssp_run_baseline_partitioned(4, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, 2, 2, dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info), want);
+            assert_eq!(info.error_code, want);
+        }
+    }
+
+    #[test]
+    fn alt_batch_matches_single_queries_and_baseline(){
+        let n = 120u32;
+        let g = crate::gen::random_graph_in(n, 3.0, 0xD1B54A32D192ED03, 1.0, 11.0); let (off, tgt, wts) = (g.offsets, g.targets, g.weights);
+        let lms = [0u32, 40, 80];
+        let mut table = vec![0f32; lms.len() * n as usize];
+        assert_eq!(sssp_alt_preprocess(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), lms.as_ptr(), lms.len() as u32, table.as_mut_ptr()), 0);
+        let sources: Vec<u32> = (0..20).map(|i| (i * 7) % n).collect();
+        let dests: Vec<u32> = (0..20).map(|i| (i * 13 + 5) % n).collect();
+        let mut batch = vec![0f32; sources.len()];
+        assert_eq!(sssp_run_alt_batch(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), sources.as_ptr(), sources.len() as u32, dests.as_ptr(), table.as_ptr(), lms.len() as u32, batch.as_mut_ptr()), 0);
+        let mut dist = vec![0f32; n as usize]; let mut pred = vec![-1i32; n as usize];
+        for i in 0..sources.len() {
+            let mut single = 0f32;
+            assert_eq!(sssp_run_alt(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), sources[i], dests[i], table.as_ptr(), lms.len() as u32, &mut single, core::ptr::null_mut()), 0);
+            assert_eq!(single.to_bits(), batch[i].to_bits());
+            crate::sssp_run_baseline(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), sources[i], dist.as_mut_ptr(), pred.as_mut_ptr(), core::ptr::null_mut());
+            let exact = dist[dests[i] as usize];
+            assert!((exact.is_infinite() && single.is_infinite()) || (exact - single).abs() < 1e-4, "query {}: {} vs {}", i, exact, single);
+        }
+    }
+
+    #[test]
+    fn alt_reports_nodes_settled_before_the_target(){
+        // Path 0->1->2->3->4 with no landmarks: the query 0->2 closes 0, 1 and 2 only.
+        let off=[0u32,1,2,3,4,4]; let tgt=[1u32,2,3,4]; let wts=[1f32;4];
+        let (mut d, mut info) = (0f32, empty_info());
+        assert_eq!(sssp_run_alt(5, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, 2, core::ptr::null(), 0, &mut d, &mut info), 0);
+        assert_eq!(d, 2.0);
+        assert_eq!(info.settled, 3);
+        assert_eq!(info.relaxations, 2);
+    }
+
+    #[test]
+    fn baseline_batch_rows_match_single_runs(){
+        let n = 300u32;
+        let g = crate::gen::random_graph_in(n, 2.0, 0x6A09E667F3BCC909, 0.0, 11.0); let (off, tgt, wts) = (g.offsets, g.targets, g.weights);
+        let sources: Vec<u32> = (0..37).map(|i| (i * 41) % n).collect();
+        let q = sources.len();
+        let mut matrix = vec![0f32; q * n as usize];
+        let mut infos: Vec<SsspResultInfo> = (0..q).map(|_| empty_info()).collect();
+        assert_eq!(sssp_run_baseline_batch(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), sources.as_ptr(), q as u32, matrix.as_mut_ptr(), infos.as_mut_ptr()), 0);
+        let (mut d, mut p, mut info) = (vec![0f32; n as usize], vec![0i32; n as usize], empty_info());
+        for (i, &s) in sources.iter().enumerate() {
+            assert_eq!(crate::sssp_run_baseline(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), s, d.as_mut_ptr(), p.as_mut_ptr(), &mut info), 0);
+            let row = &matrix[i * n as usize..(i + 1) * n as usize];
+            assert!(row.iter().zip(&d).all(|(a, b)| a.to_bits() == b.to_bits()), "row {i}");
+            assert_eq!((infos[i].relaxations, infos[i].reachable, infos[i].edges), (info.relaxations, info.reachable, info.edges));
+        }
+        assert_eq!(sssp_run_baseline_batch(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), sources.as_ptr(), q as u32, matrix.as_mut_ptr(), core::ptr::null_mut()), 0);
+        let bad = [0u32, n];
+        assert_eq!(sssp_run_baseline_batch(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), bad.as_ptr(), 2, matrix.as_mut_ptr(), core::ptr::null_mut()), -2);
+    }
+
+    #[test]
+    fn auto_picks_runner_from_graph_features(){
+        let mut rng = crate::gen::XorShift64Star::new(0x9E3779B97F4A7C15);
+        let mut next = || rng.next_u32();
+        let n = 5000u32;
+        let mut graph = |heavy: bool| {
+            let mut off = vec![0u32]; let mut tgt = Vec::new(); let mut wts = Vec::new();
+            for _ in 0..n { for _ in 0..4 { tgt.push(next() % n); let u = 1.0 + (next() % 1000) as f32; wts.push(if heavy { 1e6 / (u * u) } else { u / 250.0 }); } off.push(tgt.len() as u32); }
+            (off, tgt, wts)
+        };
+        let (uniform, heavy) = (graph(false), graph(true));
+        let (mut d, mut p, mut info) = (vec![0f32; n as usize], vec![0i32; n as usize], empty_info());
+        let (mut want, mut wp) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+        for ((off, tgt, wts), expect) in [(&uniform, SSSP_AUTO_STOC), (&heavy, SSSP_AUTO_BASELINE)] {
+            assert_eq!(sssp_run_auto(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d.as_mut_ptr(), p.as_mut_ptr(), &mut info), 0);
+            assert_eq!(sssp_get_auto_choice(), expect);
+            let run = if expect == SSSP_AUTO_STOC { crate::sssp_run_stoc } else { crate::sssp_run_baseline };
+            run(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, want.as_mut_ptr(), wp.as_mut_ptr(), core::ptr::null_mut());
+            assert!(d.iter().zip(&want).all(|(a, b)| a.to_bits() == b.to_bits()));
+        }
+        // A long ladder trips the BFS-depth rule.
+        let mut off = vec![0u32]; let mut tgt = Vec::new(); let mut wts = Vec::new();
+        for u in 0..n { if u + 1 < n { tgt.push(u + 1); wts.push(1.0); } if u + 2 < n { tgt.push(u + 2); wts.push(2.5); } off.push(tgt.len() as u32); }
+        assert_eq!(sssp_run_auto(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d.as_mut_ptr(), p.as_mut_ptr(), &mut info), 0);
+        assert_eq!(sssp_get_auto_choice(), SSSP_AUTO_STOC);
+        // Tiny graphs always take the baseline.
+        assert_eq!(sssp_run_auto(3, [0u32,1,2,2].as_ptr(), [1u32,2].as_ptr(), [1.0f32,1.0].as_ptr(), 0, d.as_mut_ptr(), p.as_mut_ptr(), &mut info), 0);
+        assert_eq!(sssp_get_auto_choice(), SSSP_AUTO_BASELINE);
+        // A forced runner (SSSP_AUTO_FORCE) overrides every rule.
+        let (off, tgt, wts) = &heavy;
+        assert_eq!(auto_choice_with(Some(SSSP_AUTO_STOC), n, off, tgt, wts, 0), SSSP_AUTO_STOC);
+        assert_eq!(auto_choice_with(Some(SSSP_AUTO_BASELINE), n, &uniform.0, &uniform.1, &uniform.2, 0), SSSP_AUTO_BASELINE);
+        assert_eq!(auto_choice_with(None, n, off, tgt, wts, 0), SSSP_AUTO_BASELINE);
+    }
+
+    #[test]
+    fn avoiding_reroutes_around_forbidden_bottleneck(){
+        // Cheap route 0->1->3 goes through bottleneck 1; detour 0->2->4->3 costs 6.
+        let off = [0u32,2,3,4,4,5]; let tgt = [1u32,2,3,4,3]; let wts = [1.0f32,2.0,1.0,2.0,2.0];
+        let (mut dist, mut pred, mut info) = ([0f32;5], [0i32;5], empty_info());
+        let none = [0u64];
+        assert_eq!(sssp_run_avoiding(5, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, 3, none.as_ptr(), dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info), 0);
+        assert_eq!((dist[3], pred[3]), (2.0, 1));
+        let block1 = [1u64 << 1];
+        assert_eq!(sssp_run_avoiding(5, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, 3, block1.as_ptr(), dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info), 0);
+        assert_eq!((dist[3], pred[3], pred[4], pred[2]), (6.0, 4, 2, 0));
+        assert!(dist[1].is_infinite());
+        // Cutting both routes leaves no admissible path.
+        let block12 = [(1u64 << 1) | (1 << 2)];
+        assert_eq!(sssp_run_avoiding(5, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, 3, block12.as_ptr(), dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info), -20);
+        assert_eq!(info.error_code, -20);
+    }
+
+    #[test]
+    fn node_penalty_routes_around_expensive_node(){
+        // 0->1->3 costs 2, detour 0->2->3 costs 4; penalising node 1 by 5 flips the choice.
+        let off = [0u32,2,3,4,4]; let tgt = [1u32,2,3,3]; let wts = [1.0f32,2.0,1.0,2.0];
+        let (mut dist, mut pred, mut info) = ([0f32;4], [0i32;4], empty_info());
+        let zero = [0f32;4];
+        assert_eq!(sssp_run_node_penalty(4, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, zero.as_ptr(), dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info), 0);
+        assert_eq!((dist[3], pred[3]), (2.0, 1));
+        let pen = [0f32, 5.0, 0.0, 0.5];
+        assert_eq!(sssp_run_node_penalty(4, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, pen.as_ptr(), dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info), 0);
+        // Node 1 still reports its own penalty; node 3 pays its 0.5 once.
+        assert_eq!((dist[1], dist[3], pred[3]), (6.0, 4.5, 2));
+        let bad = [0f32, -1.0, 0.0, 0.0];
+        assert_eq!(sssp_run_node_penalty(4, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, bad.as_ptr(), dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info), -7);
+    }
+
+    #[test]
+    fn interval_bounds_bracket_every_node(){
+        let n = 150u32; let mut rng = crate::gen::XorShift64Star::new(0x2545F4914F6CDD1D);
+        let mut next = || rng.next_u32();
+        let mut off = vec![0u32]; let mut tgt = Vec::new(); let mut lo = Vec::new(); let mut hi = Vec::new();
+        for _ in 0..n { for _ in 0..3 { tgt.push(next() % n); let l = (next() % 100) as f32 / 10.0; lo.push(l); hi.push(l + (next() % 50) as f32 / 10.0); } off.push(tgt.len() as u32); }
+        let (mut dlo, mut dhi, mut pred) = (vec![0f32; n as usize], vec![0f32; n as usize], vec![0i32; n as usize]);
+        let mut info = empty_info();
+        assert_eq!(sssp_run_interval(n, off.as_ptr(), tgt.as_ptr(), lo.as_ptr(), hi.as_ptr(), 0, dlo.as_mut_ptr(), dhi.as_mut_ptr(), pred.as_mut_ptr(), &mut info), 0);
+        // Any realisation in between (here the midpoint) lands inside the bracket.
+        let mid: Vec<f32> = lo.iter().zip(&hi).map(|(l, h)| (l + h) / 2.0).collect();
+        let (mut dmid, mut pmid) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+        crate::sssp_run_baseline(n, off.as_ptr(), tgt.as_ptr(), mid.as_ptr(), 0, dmid.as_mut_ptr(), pmid.as_mut_ptr(), core::ptr::null_mut());
+        for v in 0..n as usize {
+            assert!(dlo[v] <= dhi[v], "v={}", v);
+            assert!(dlo[v] <= dmid[v] + 1e-4 && (dmid[v] <= dhi[v] + 1e-4 || dhi[v].is_infinite()), "v={}", v);
+        }
+        assert_eq!(sssp_run_interval(n, off.as_ptr(), tgt.as_ptr(), hi.as_ptr(), lo.as_ptr(), 0, dlo.as_mut_ptr(), dhi.as_mut_ptr(), pred.as_mut_ptr(), &mut info), -7);
+    }
+
+    extern "C" fn by_dist(_a: u32, ad: f32, _b: u32, bd: f32) -> i32 { if ad < bd { -1 } else if ad > bd { 1 } else { 0 } }
+    extern "C" fn farthest_first(a: u32, ad: f32, b: u32, bd: f32) -> i32 { -by_dist(a, ad, b, bd) }
+
+    #[test]
+    fn comparator_heap_matches_baseline(){
+        let n = 120u32;
+        let g = crate::gen::random_graph_in(n, 4.0, 0x9FB21C651E98DF25, 1.0, 11.0); let (off, tgt, wts) = (g.offsets, g.targets, g.weights);
+        let (mut d0, mut p0, mut i0) = (vec![0f32; n as usize], vec![0i32; n as usize], empty_info());
+        assert_eq!(crate::sssp_run_baseline(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d0.as_mut_ptr(), p0.as_mut_ptr(), &mut i0), 0);
+        let (mut d1, mut p1, mut i1) = (vec![0f32; n as usize], vec![0i32; n as usize], empty_info());
+        assert_eq!(sssp_run_baseline_cmp(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, Some(by_dist), d1.as_mut_ptr(), p1.as_mut_ptr(), &mut i1), 0);
+        assert_eq!(d1, d0); assert_eq!(i1.relaxations, i0.relaxations);
+        // An inconsistent order still converges, at the cost of rescans.
+        let (mut d2, mut p2, mut i2) = (vec![0f32; n as usize], vec![0i32; n as usize], empty_info());
+        assert_eq!(sssp_run_baseline_cmp(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, Some(farthest_first), d2.as_mut_ptr(), p2.as_mut_ptr(), &mut i2), 0);
+        assert_eq!(d2, d0); assert!(i2.relaxations > i0.relaxations);
+        assert_eq!(sssp_run_baseline_cmp(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, None, d2.as_mut_ptr(), p2.as_mut_ptr(), &mut i2), -3);
+    }
+
+    extern "C" fn record_settle(node: u32, dist: f32, user: *mut c_void) { unsafe { (*(user as *mut Vec<(u32, f32)>)).push((node, dist)); } }
+
+    #[test]
+    fn settle_callback_sees_final_distances_in_order(){
+        let n = 150u32;
+        let g = crate::gen::random_graph_in(n, 1.5, 0xA54FF53A5F1D36F1, 0.0, 7.0); let (off, tgt, wts) = (g.offsets, g.targets, g.weights);
+        let (mut d, mut p, mut info) = (vec![0f32; n as usize], vec![0i32; n as usize], empty_info());
+        let mut seen: Vec<(u32, f32)> = Vec::new();
+        assert_eq!(sssp_run_baseline_cb(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d.as_mut_ptr(), p.as_mut_ptr(), &mut info, Some(record_settle), &mut seen as *mut _ as *mut c_void), 0);
+        assert_eq!(seen.len() as u32, info.settled);
+        assert_eq!(info.settled as usize, d.iter().filter(|x| x.is_finite()).count());
+        assert!(seen.windows(2).all(|w| w[0].1 <= w[1].1));
+        assert!(seen.iter().all(|&(v, dv)| d[v as usize] == dv));
+        let mut nodes: Vec<u32> = seen.iter().map(|s| s.0).collect(); nodes.sort_unstable(); nodes.dedup();
+        assert_eq!(nodes.len(), seen.len());
+        assert_eq!(sssp_run_baseline_cb(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d.as_mut_ptr(), p.as_mut_ptr(), &mut info, None, core::ptr::null_mut()), -3);
+    }
+
+    #[test]
+    fn multi_source_is_min_over_single_runs(){
+        let n = 100u32;
+        let g = crate::gen::random_graph_in(n, 3.0, 0x3C6EF372FE94F82B, 1.0, 11.0); let (off, tgt, wts) = (g.offsets, g.targets, g.weights);
+        let srcs = [4u32, 50, 4, 91]; let init = [2.0f32, 0.0, 1.0, 3.5];
+        let (mut dist, mut pred, mut info) = (vec![0f32; n as usize], vec![0i32; n as usize], empty_info());
+        assert_eq!(sssp_run_baseline_multi(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), srcs.as_ptr(), 4, init.as_ptr(), dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info), 0);
+        let mut want = vec![f32::INFINITY; n as usize];
+        let (mut d, mut p) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+        for (&s, &d0) in srcs.iter().zip(&init) {
+            crate::sssp_run_baseline(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), s, d.as_mut_ptr(), p.as_mut_ptr(), core::ptr::null_mut());
+            for v in 0..n as usize { want[v] = want[v].min(d0 + d[v]); }
+        }
+        for v in 0..n as usize { assert!((dist[v] - want[v]).abs() < 1e-4 || (dist[v].is_infinite() && want[v].is_infinite()), "v={}", v); }
+        // Duplicate 4 keeps its smaller seed (1.0) and is settled once.
+        assert_eq!((dist[4], pred[4], pred[50]), (1.0, -1, -1));
+        assert_eq!(info.settled as usize, dist.iter().filter(|x| x.is_finite()).count());
+    }
+
+    #[test]
+    fn time_window_closing_early_blocks_path(){
+        // 0 -(2)-> 1 -(1)-> 2, and 0 -(1)-> 3 whose edge 3->2 only opens at t=10.
+        let off = [0u32,2,3,3,4]; let tgt = [1u32,3,2,2]; let travel = [2.0f32,1.0,1.0,1.0];
+        let open = [0.0f32,0.0,0.0,10.0]; let mut close = [f32::INFINITY; 4];
+        let (mut arr, mut pred, mut info) = ([0f32;4], [0i32;4], empty_info());
+        let run = |close: &[f32], arr: &mut [f32;4], pred: &mut [i32;4], info: &mut SsspResultInfo|
+            sssp_run_time_windows(4, off.as_ptr(), tgt.as_ptr(), travel.as_ptr(), open.as_ptr(), close.as_ptr(), 0, 0.0, arr.as_mut_ptr(), pred.as_mut_ptr(), info);
+        assert_eq!(run(&close, &mut arr, &mut pred, &mut info), 0);
+        assert_eq!((arr[2], pred[2]), (3.0, 1));
+        // 1->2 closes at t=1 but the traveller reaches 1 at t=2: fall back to waiting at 3.
+        close[2] = 1.0;
+        assert_eq!(run(&close, &mut arr, &mut pred, &mut info), 0);
+        assert_eq!((arr[2], pred[2]), (11.0, 3));
+        // A window on 3->2 that shuts before it opens leaves 2 unreachable.
+        close[3] = 5.0;
+        assert_eq!(run(&close, &mut arr, &mut pred, &mut info), 0);
+        assert!(arr[2].is_infinite());
+        assert_eq!(arr[3], 1.0);
+    }
+
+    #[test]
+    fn baseline_to_stops_at_target_with_less_work(){
+        // Unit path 0->1->...->49: stopping at 10 settles 11 nodes.
+        let n = 50u32; let off: Vec<u32> = (0..=n).map(|i| i.min(n - 1)).collect();
+        let tgt: Vec<u32> = (1..n).collect(); let wts = vec![1.0f32; tgt.len()];
+        let (mut dist, mut pred, mut info) = (vec![0f32; n as usize], vec![0i32; n as usize], empty_info());
+        assert_eq!(sssp_run_baseline_to(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, 10, dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info), 0);
+        assert_eq!((dist[10], pred[10], info.error_code, info.settled), (10.0, 9, 1, 11));
+        assert!(info.relaxations < (n - 1) as u64);
+        assert!(dist[20].is_infinite());
+        // Unreachable target: full settle, flag 0.
+        assert_eq!(sssp_run_baseline_to(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 10, 3, dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info), 0);
+        assert_eq!((info.error_code, info.settled), (0, n - 10));
+        assert!(dist[3].is_infinite());
+        // A negative or NaN weight past the target is still rejected before the search.
+        for (w, want) in [(-1.0f32, -6), (f32::NAN, -7)] {
+            let mut bad = wts.clone(); bad[30] = w;
+            assert_eq!(sssp_run_baseline_to(n, off.as_ptr(), tgt.as_ptr(), bad.as_ptr(), 0, 10, dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info), want);
+            assert_eq!(info.error_code, want);
+        }
+    }
+
+    #[test]
+    fn radius_includes_cutoff_boundary_and_composes_with_target(){
+        // Unit path 0->1->...->49 plus a 0->30 shortcut of weight 5.
+        let n = 50u32; let mut off: Vec<u32> = (0..=n).map(|i| i.min(n - 1)).collect();
+        for o in off.iter_mut().skip(1) { *o += 1; }
+        let mut tgt: Vec<u32> = (1..n).collect(); let mut wts = vec![1.0f32; tgt.len()];
+        tgt.insert(1, 30); wts.insert(1, 5.0);
+        let (mut dist, mut pred, mut info) = (vec![0f32; n as usize], vec![0i32; n as usize], empty_info());
+        assert_eq!(sssp_run_baseline_radius(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, 6.0, dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info), 0);
+        // Within 6: 0..=6 on the path and 30, 31 via the shortcut; 6 and 31 sit exactly on the cutoff.
+        let inside: Vec<usize> = (0..n as usize).filter(|&v| dist[v].is_finite()).collect();
+        assert_eq!(inside, vec![0, 1, 2, 3, 4, 5, 6, 30, 31]);
+        assert_eq!((dist[6], dist[31], pred[31], info.settled, info.reachable), (6.0, 6.0, 30, 9, 9));
+        assert!(pred[7] == -1 && pred[32] == -1);
+        let full = info.relaxations;
+        assert_eq!(sssp_run_baseline_radius_to(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, 3, 6.0, dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info), 0);
+        assert_eq!((dist[3], info.error_code), (3.0, 1));
+        assert!(info.relaxations < full);
+        // A target outside the ball is not reached; the run still settles the whole ball.
+        assert_eq!(sssp_run_baseline_radius_to(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, 40, 6.0, dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info), 0);
+        assert_eq!((info.error_code, info.settled), (0, 9));
+        assert!(dist[40].is_infinite());
+        assert_eq!(sssp_run_baseline_radius(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, f32::NAN, dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info), -7);
+    }
+
+    #[test]
+    fn min_hops_prefers_fewer_edges_on_cost_ties(){
+        // 0->1->2->3 costs 1+1+1; the direct 0->3 edge ties at 3.
+        let off = [0u32,2,3,4,4]; let tgt = [1u32,3,2,3]; let wts = [1.0f32,3.0,1.0,1.0];
+        let (mut dist, mut hops, mut pred) = ([0f32;4], [0u32;4], [0i32;4]);
+        let mut info = empty_info();
+        assert_eq!(sssp_run_baseline_min_hops(4, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, dist.as_mut_ptr(), hops.as_mut_ptr(), pred.as_mut_ptr(), &mut info), 0);
+        assert_eq!((dist[3], hops[3], pred[3]), (3.0, 1, 0));
+        assert_eq!((hops[2], pred[2]), (2, 1));
+        // 0->1->3 (1+2) ties 0->2->4->3 (0.25+0.25+2.5); plain Dijkstra settles 4 before 1
+        // and keeps the first-found 3-hop route.
+        let off = [0u32,2,3,4,4,5]; let tgt = [1u32,2,3,4,3]; let wts = [1.0f32,0.25,2.0,0.25,2.5];
+        let (mut dist, mut hops, mut pred) = ([0f32;5], [0u32;5], [0i32;5]);
+        crate::sssp_run_baseline(5, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info);
+        assert_eq!((dist[3], pred[3]), (3.0, 4));
+        assert_eq!(sssp_run_baseline_min_hops(5, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, dist.as_mut_ptr(), hops.as_mut_ptr(), pred.as_mut_ptr(), &mut info), 0);
+        assert_eq!((dist[3], hops[3], pred[3]), (3.0, 2, 1));
+        let wts_neg = [1.0f32,-3.0,1.0,1.0,1.0];
+        assert_eq!(sssp_run_baseline_min_hops(4, off.as_ptr(), tgt.as_ptr(), wts_neg.as_ptr(), 0, dist.as_mut_ptr(), hops.as_mut_ptr(), pred.as_mut_ptr(), &mut info), -6);
+    }
+
+    #[test]
+    fn grid_uniform_cost_matches_manhattan_and_chebyshev(){
+        let (w, h) = (7u32, 5u32); let cost = vec![1.0f32; (w * h) as usize];
+        let (mut dist, mut pred) = (vec![0f32; (w * h) as usize], vec![0i32; (w * h) as usize]);
+        for &(conn, cheb) in &[(4, false), (8, true)] {
+            assert_eq!(sssp_run_grid(w, h, cost.as_ptr(), 2, 3, conn, dist.as_mut_ptr(), pred.as_mut_ptr()), 0);
+            for y in 0..h { for x in 0..w {
+                let (dx, dy) = ((x as i32 - 2).abs(), (y as i32 - 3).abs());
+                let want = if cheb { dx.max(dy) } else { dx + dy };
+                assert_eq!(dist[(y * w + x) as usize], want as f32);
+            } }
+            assert_eq!(pred[(3 * w + 2) as usize], -1);
+        }
+        // A wall column with one gap forces the detour through it.
+        let mut walled = cost.clone(); for y in 0..h - 1 { walled[(y * w + 4) as usize] = f32::INFINITY; }
+        assert_eq!(sssp_run_grid(w, h, walled.as_ptr(), 2, 0, 4, dist.as_mut_ptr(), pred.as_mut_ptr()), 0);
+        assert_eq!(dist[6], 12.0);
+        assert_eq!(sssp_run_grid(w, h, cost.as_ptr(), 0, 0, 6, dist.as_mut_ptr(), pred.as_mut_ptr()), -7);
+        assert_eq!(sssp_run_grid(w, h, cost.as_ptr(), w, 0, 4, dist.as_mut_ptr(), pred.as_mut_ptr()), -2);
+    }
+
+    #[test]
+    fn bidijkstra_matches_baseline_point_to_point(){
+        // s=0, a=1, c=2, d=3, t=4. The first node settled by both sides would be `a`
+        // (s->a->t = 4), but s->c->d->t = 3.9 is shorter.
+        let off = [0u32,2,3,4,5,5]; let tgt = [1u32,2,4,3,4]; let wts = [2.0f32,1.5,2.0,0.9,1.5];
+        let (r_off, r_tgt, r_wts) = crate::graph_ops::transpose_csr(&off, &tgt, &wts);
+        let mut dist = [0f32;5]; let mut info = empty_info();
+        assert_eq!(sssp_run_bidijkstra(5, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), r_off.as_ptr(), r_tgt.as_ptr(), r_wts.as_ptr(), 0, 4, dist.as_mut_ptr(), &mut info), 0);
+        assert!((dist[4] - 3.9).abs() < 1e-6);
+        assert!(info.settled == 2 || info.settled == 3);
+        let n = 300u32; let mut rng = crate::gen::XorShift64Star::new(0x510E527FADE682D1);
+        let mut next = || rng.next_u32();
+        let g = crate::gen::random_graph_in(n, 1.5, 0x510E527FADE682D1, 0.0, 12.5); let (off, tgt, wts) = (g.offsets, g.targets, g.weights);
+        let (r_off, r_tgt, r_wts) = crate::graph_ops::transpose_csr(&off, &tgt, &wts);
+        let (mut want, mut wp) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+        let mut dist = vec![0f32; n as usize];
+        for _ in 0..40 {
+            let (s, t) = (next() % n, next() % n);
+            crate::sssp_run_baseline(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), s, want.as_mut_ptr(), wp.as_mut_ptr(), core::ptr::null_mut());
+            assert_eq!(sssp_run_bidijkstra(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), r_off.as_ptr(), r_tgt.as_ptr(), r_wts.as_ptr(), s, t, dist.as_mut_ptr(), &mut info), 0);
+            // Sums can associate differently from the forward-only run; compare loosely.
+            if want[t as usize].is_finite() { assert!((dist[t as usize] - want[t as usize]).abs() <= 1e-4 * want[t as usize].max(1.0)); assert!(info.settled < n); }
+            else { assert!(dist[t as usize].is_infinite()); assert_eq!(info.settled, u32::MAX); }
+        }
+    }
+
+    #[test]
+    fn astar_exact_with_admissible_heuristics(){
+        // 8x8 unit grid (4-neighbour, both directions); Manhattan distance is consistent.
+        let w = 8u32; let n = w * w;
+        let mut off = vec![0u32]; let mut tgt = Vec::new();
+        for y in 0..w { for x in 0..w {
+            if x > 0 { tgt.push(y * w + x - 1); } if x + 1 < w { tgt.push(y * w + x + 1); }
+            if y > 0 { tgt.push((y - 1) * w + x); } if y + 1 < w { tgt.push((y + 1) * w + x); }
+            off.push(tgt.len() as u32);
+        } }
+        let wts = vec![1.0f32; tgt.len()];
+        let (source, target) = (0u32, n - 1);
+        let manhattan: Vec<f32> = (0..n).map(|v| ((w - 1 - v % w) + (w - 1 - v / w)) as f32).collect();
+        let (mut dist, mut pred) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+        let (mut zd, mut zp) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+        let (mut info, mut zinfo) = (empty_info(), empty_info());
+        assert_eq!(sssp_run_astar(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), source, target, manhattan.as_ptr(), dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info), 0);
+        let zero = vec![0f32; n as usize];
+        assert_eq!(sssp_run_astar(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), source, target, zero.as_ptr(), zd.as_mut_ptr(), zp.as_mut_ptr(), &mut zinfo), 0);
+        assert_eq!((dist[target as usize], info.error_code), (14.0, 0));
+        assert_eq!(zd[target as usize], 14.0);
+        assert!(info.settled < zinfo.settled);
+        let (mut hops, mut v) = (0, target as i32); while pred[v as usize] >= 0 { v = pred[v as usize]; hops += 1; }
+        assert_eq!((hops, v), (14, source as i32));
+        // s=0, a=1, b=2, c=3, t=4: s->a->c (2) beats s->b->c (4), then c->t (10).
+        // h(a) = 11 is admissible (a is 11 from t) but inconsistent (> w(a,c) + h(c)),
+        // so c is closed via b first and reopened once a is expanded.
+        let off = [0u32,2,3,4,5,5]; let tgt = [1u32,2,3,3,4]; let wts = [1.0f32,1.0,1.0,3.0,10.0];
+        let h = [0.0f32, 11.0, 0.0, 0.0, 0.0];
+        let (mut dist, mut pred) = ([0f32;5], [0i32;5]);
+        assert_eq!(sssp_run_astar(5, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, 4, h.as_ptr(), dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info), 0);
+        assert_eq!((dist[4], pred[3]), (12.0, 1));
+        assert_eq!(info.error_code & 1, 1);
+        assert_eq!(info.error_code & 2 != 0, cfg!(debug_assertions));
+        let neg = [0.0f32, -1.0, 0.0, 0.0, 0.0];
+        assert_eq!(sssp_run_astar(5, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, 4, neg.as_ptr(), dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info), -7);
+    }
+
+    #[test]
+    fn node_deletion_repairs_subtree(){
+        // Bridge: 0->1->{2->3}, 0->4, 4->5; node 1 is the only way into 2 and 3.
+        let off = [0u32,2,3,4,4,5,5]; let tgt = [1u32,4,2,3,5]; let wts = [1.0f32,2.0,1.0,1.0,1.0];
+        let (mut dist, mut pred) = ([0f32;6], [0i32;6]);
+        crate::sssp_run_baseline(6, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, dist.as_mut_ptr(), pred.as_mut_ptr(), core::ptr::null_mut());
+        assert_eq!(sssp_node_deleted(6, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, 1, dist.as_mut_ptr(), pred.as_mut_ptr()), 0);
+        assert!(dist[1].is_infinite() && dist[2].is_infinite() && dist[3].is_infinite());
+        assert_eq!((pred[2], pred[3]), (-1, -1));
+        assert_eq!((dist[4], dist[5]), (2.0, 3.0));
+        // Random graph: repair equals a full run with the deleted node's in-edges dropped.
+        let n = 150u32;
+        let g = crate::gen::random_graph_in(n, 3.0, 0x6A09E667F3BCC909, 1.0, 11.0); let (off, tgt, wts) = (g.offsets, g.targets, g.weights);
+        let (mut dist, mut pred) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+        crate::sssp_run_baseline(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, dist.as_mut_ptr(), pred.as_mut_ptr(), core::ptr::null_mut());
+        let deleted = (1..n).max_by_key(|&v| pred.iter().filter(|&&p| p == v as i32).count()).unwrap();
+        assert_eq!(sssp_node_deleted(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, deleted, dist.as_mut_ptr(), pred.as_mut_ptr()), 0);
+        let (mut coff, mut ctgt, mut cw) = (vec![0u32], Vec::new(), Vec::new());
+        for u in 0..n as usize { for e in off[u] as usize..off[u + 1] as usize { if tgt[e] != deleted { ctgt.push(tgt[e]); cw.push(wts[e]); } } coff.push(ctgt.len() as u32); }
+        let (mut want, mut wp) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+        crate::sssp_run_baseline(n, coff.as_ptr(), ctgt.as_ptr(), cw.as_ptr(), 0, want.as_mut_ptr(), wp.as_mut_ptr(), core::ptr::null_mut());
+        want[deleted as usize] = f32::INFINITY;
+        assert_eq!(dist, want);
+    }
+
+    #[test]
+    fn label_correcting_matches_baseline_with_different_relaxations(){
+        // Edge order from 0 is p(0.1), b(2), a(1): SLF scans b before a improves it,
+        // so b->e is relaxed twice (6 relaxations) where Dijkstra needs 5.
+        // Nodes: 0, p=1, b=2, a=3, e=4.
+        let off = [0u32,3,3,4,5,5]; let tgt = [1u32,2,3,4,2]; let wts = [0.1f32,2.0,1.0,1.0,0.5];
+        let (mut d1, mut p1, mut i1) = ([0f32;5], [0i32;5], empty_info());
+        let (mut d2, mut p2, mut i2) = ([0f32;5], [0i32;5], empty_info());
+        assert_eq!(sssp_run_label_correcting(5, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d1.as_mut_ptr(), p1.as_mut_ptr(), &mut i1), 0);
+        assert_eq!(crate::sssp_run_baseline(5, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d2.as_mut_ptr(), p2.as_mut_ptr(), &mut i2), 0);
+        assert_eq!(d1, d2);
+        assert_eq!(p1, p2);
+        assert_ne!(i1.relaxations, i2.relaxations);
+        assert_eq!(i1.settled, 5);
+    }
+
+    #[test]
+    fn baseline_u16_matches_scaled_f32_graph(){
+        // Weights multiples of 0.25 quantize exactly with scale 0.25.
+        let off = [0u32,2,4,5,6,6]; let tgt = [1u32,2,3,2,4,4];
+        let q = [3u16,10,2,1,7,40]; let scale = 0.25f32;
+        let wf: Vec<f32> = q.iter().map(|&w| w as f32 * scale).collect();
+        let (mut d1, mut p1, mut i1) = ([0f32;5], [0i32;5], empty_info());
+        let (mut d2, mut p2, mut i2) = ([0f32;5], [0i32;5], empty_info());
+        assert_eq!(sssp_run_baseline_u16(5, off.as_ptr(), tgt.as_ptr(), q.as_ptr(), scale, 0, d1.as_mut_ptr(), p1.as_mut_ptr(), &mut i1), 0);
+        assert_eq!(crate::sssp_run_baseline(5, off.as_ptr(), tgt.as_ptr(), wf.as_ptr(), 0, d2.as_mut_ptr(), p2.as_mut_ptr(), &mut i2), 0);
+        assert_eq!(d1, d2);
+        assert_eq!(p1, p2);
+        assert_eq!(i1.relaxations, i2.relaxations);
+    }
+
+    #[test]
+    fn ratio_path_matches_enumerated_paths(){
+        // DAG with competing routes: 0->1, 0->2, 1->2, 1->3, 2->3, 2->4, 3->4.
+        let off = [0u32,2,4,6,7,7]; let tgt = [1u32,2,2,3,3,4,4];
+        let cost = [4.0f32,9.0,1.0,6.0,2.0,10.0,1.0]; let time = [1.0f32,2.0,3.0,1.0,2.0,1.0,4.0];
+        let mut best = [f64::INFINITY;5]; best[0] = 0.0;
+        // Enumerate all simple paths from 0 with (cost, time) sums.
+        let mut st = vec![(0usize, 0.0f64, 0.0f64)];
+        while let Some((u, c, t)) = st.pop() {
+            if u != 0 { best[u] = best[u].min(c / t); }
+            for e in off[u] as usize..off[u+1] as usize { st.push((tgt[e] as usize, c + cost[e] as f64, t + time[e] as f64)); }
+        }
+        let mut ratio = [0f32;5]; let mut pred = [0i32;5]; let mut info = empty_info();
+        assert_eq!(sssp_run_ratio_path(5, off.as_ptr(), tgt.as_ptr(), cost.as_ptr(), time.as_ptr(), 0, ratio.as_mut_ptr(), pred.as_mut_ptr(), &mut info), 0);
+        for v in 0..5 { assert!((ratio[v] as f64 - best[v]).abs() < 1e-4, "v={} got={} want={}", v, ratio[v], best[v]); }
+        assert_eq!(info.settled, 5);
+        let bad_time = [1.0f32,0.0,1.0,1.0,1.0,1.0,1.0];
+        assert_eq!(sssp_run_ratio_path(5, off.as_ptr(), tgt.as_ptr(), cost.as_ptr(), bad_time.as_ptr(), 0, ratio.as_mut_ptr(), pred.as_mut_ptr(), &mut info), -7);
+    }
+
+    #[test]
+    fn sampled_degree_exact_at_full_degree_and_truncates_star(){
+        // Star: hub 0 -> leaves 1..=10 (weight = leaf id), then leaf 1 -> 2 (0.5).
+        let mut off = vec![0u32, 10]; let mut tgt: Vec<u32> = (1..=10).collect(); let mut wts: Vec<f32> = (1..=10).map(|x| x as f32).collect();
+        tgt.push(2); wts.push(0.5); off.push(11); for _ in 2..=10 { off.push(11); }
+        let n = 11u32;
+        let mut exact = vec![0f32; 11]; let mut pred = vec![0i32; 11];
+        crate::sssp_run_baseline(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, exact.as_mut_ptr(), pred.as_mut_ptr(), core::ptr::null_mut());
+        let mut dist = vec![0f32; 11];
+        for cap in [0u32, 10, 64] {
+            assert_eq!(sssp_run_baseline_sampled_degree(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, dist.as_mut_ptr(), pred.as_mut_ptr(), core::ptr::null_mut(), cap, 7), 0);
+            assert_eq!(dist, exact);
+        }
+        // Sampling 3 of the hub's 10 edges reaches only the sampled leaves (plus 2 via 1).
+        let mut info = empty_info();
+        assert_eq!(sssp_run_baseline_sampled_degree(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info, 3, 7), 0);
+        let reached = dist.iter().filter(|d| d.is_finite()).count();
+        assert!((4..=5).contains(&reached), "reached {}", reached);
+        for v in 0..11 { assert!(dist[v] >= exact[v]); }
+    }
+
+    #[test]
+    fn most_reliable_rejects_out_of_range(){
+        let off=[0u32,1,1]; let tgt=[1u32]; let mut prob=vec![0f32;2]; let mut pred=vec![-1i32;2];
+        for bad in [0.0f32, 1.5, -0.1, f32::NAN] {
+            let pr=[bad];
+            let rc = sssp_run_most_reliable(2, off.as_ptr(), tgt.as_ptr(), pr.as_ptr(), 0, prob.as_mut_ptr(), pred.as_mut_ptr(), core::ptr::null_mut());
+            assert_eq!(rc,-7);
+        }
+    }
+
+    #[test]
+    fn stoc_i32_exact_on_long_integer_paths(){
+        // Random integer graph checked against an i64 Dijkstra.
+        let n = 2000u32; let mut rng = crate::gen::XorShift64Star::new(0xA54FF53A5F1D36F1);
+        let mut next = || rng.next_u32();
+        let mut off = vec![0u32]; let mut tgt = Vec::new(); let mut wts: Vec<i32> = Vec::new();
+        for _ in 0..n { for _ in 0..3 { tgt.push(next() % n); wts.push((next() % 50_000) as i32); } off.push(tgt.len() as u32); }
+        let mut want = vec![i64::MAX; n as usize]; want[0] = 0;
+        let mut heap = BinaryHeap::new(); heap.push(Reverse((0i64, 0u32)));
+        while let Some(Reverse((d, u))) = heap.pop() {
+            if d > want[u as usize] { continue; }
+            for e in off[u as usize] as usize..off[u as usize + 1] as usize { let nd = d + wts[e] as i64; if nd < want[tgt[e] as usize] { want[tgt[e] as usize] = nd; heap.push(Reverse((nd, tgt[e]))); } }
+        }
+        let (mut d, mut p) = (vec![0i64; n as usize], vec![0i32; n as usize]);
+        let mut info = empty_info();
+        assert_eq!(sssp_run_stoc_i32(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d.as_mut_ptr(), p.as_mut_ptr(), &mut info), 0);
+        assert_eq!(d, want);
+        assert_eq!(info.settled as usize, want.iter().filter(|&&x| x != i64::MAX).count());
+        // A 600-edge chain of weight 99_999 sums past 2^24, where f32 distances round.
+        let len = 600u32; let c_off: Vec<u32> = (0..=len).map(|i| i.min(len - 1)).collect();
+        let c_tgt: Vec<u32> = (1..len).collect(); let c_wts = vec![99_999i32; len as usize - 1];
+        let (mut d, mut p) = (vec![0i64; len as usize], vec![0i32; len as usize]);
+        assert_eq!(sssp_run_stoc_i32(len, c_off.as_ptr(), c_tgt.as_ptr(), c_wts.as_ptr(), 0, d.as_mut_ptr(), p.as_mut_ptr(), core::ptr::null_mut()), 0);
+        assert_eq!(d[len as usize - 1], 99_999 * (len as i64 - 1));
+        let (n_off, n_tgt, n_wts) = ([0u32, 1, 2, 2], [1u32, 2], [5i32, -1]);
+        assert_eq!(sssp_run_stoc_i32(3, n_off.as_ptr(), n_tgt.as_ptr(), n_wts.as_ptr(), 0, d.as_mut_ptr(), p.as_mut_ptr(), &mut info), -6);
+        assert_eq!(info.error_code, -6);
+    }
+
+    #[test]
+    fn f64_runners_separate_paths_f32_would_tie(){
+        // 0->1 (2^24), 1->3 (1) vs 0->2 (2^24 + 2), 2->3 (0): in f32 both reach 3 at
+        // 2^24 (+1 rounds away), in f64 the route through 1 is shorter.
+        let off = [0u32, 2, 3, 4, 4]; let tgt = [1u32, 2, 3, 3]; let big = 16_777_216f64;
+        let wts = [big, big + 2.0, 1.0, 0.0];
+        let (mut d, mut p) = ([0f64; 4], [0i32; 4]);
+        let mut hs = BaselineHeapStats { pushes: 0, pops: 0, max_size: 0 };
+        assert_eq!(sssp_run_baseline_f64(4, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d.as_mut_ptr(), p.as_mut_ptr(), core::ptr::null_mut(), &mut hs), 0);
+        assert_eq!((d[3], p[3]), (big + 1.0, 1));
+        assert_eq!(hs.pushes, hs.pops);
+        assert_eq!(sssp_run_stoc_f64(4, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d.as_mut_ptr(), p.as_mut_ptr(), core::ptr::null_mut()), 0);
+        assert_eq!((d[3], p[3]), (big + 1.0, 1));
+        // Random graph: STOC f64 matches baseline f64 exactly.
+        let n = 3000u32; let mut rng = crate::gen::XorShift64Star::new(0x510E527FADE682D1);
+        let mut next = || rng.next_u32();
+        let mut off = vec![0u32]; let mut tgt = Vec::new(); let mut wts = Vec::new();
+        for _ in 0..n { for _ in 0..4 { tgt.push(next() % n); let r = next() % 100; wts.push(if r < 90 { 0.1 + r as f64 / 7.0 } else { r as f64 * 1000.0 }); } off.push(tgt.len() as u32); }
+        let (mut want, mut wp) = (vec![0f64; n as usize], vec![0i32; n as usize]);
+        let (mut got, mut gp) = (vec![0f64; n as usize], vec![0i32; n as usize]);
+        let mut info = empty_info();
+        assert_eq!(sssp_run_baseline_f64(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, want.as_mut_ptr(), wp.as_mut_ptr(), core::ptr::null_mut(), core::ptr::null_mut()), 0);
+        assert_eq!(sssp_run_stoc_f64(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, got.as_mut_ptr(), gp.as_mut_ptr(), &mut info), 0);
+        assert_eq!(got, want);
+        assert_eq!(info.settled as usize, want.iter().filter(|x| x.is_finite()).count());
+        assert!(info.light_relaxations > 0 && info.heavy_relaxations > 0);
+        let bad = [1.0f64, -1.0, 0.0, 0.0];
+        assert_eq!(sssp_run_stoc_f64(4, [0u32, 2, 3, 4, 4].as_ptr(), [1u32, 2, 3, 3].as_ptr(), bad.as_ptr(), 0, d.as_mut_ptr(), p.as_mut_ptr(), &mut info), -6);
+    }
+
+    #[test]
+    fn bellman_ford_handles_negative_edges_and_finds_cycles(){
+        // 0->1 (4), 0->2 (5), 2->1 (-3), 1->3 (2): best route to 1 goes through the negative edge.
+        let off = [0u32,2,3,4,4]; let tgt = [1u32,2,3,1]; let wts = [4.0f32,5.0,2.0,-3.0];
+        let (mut d, mut p) = ([0f32;4], [0i32;4]);
+        let mut info = empty_info();
+        assert_eq!(sssp_run_bellman_ford(4, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d.as_mut_ptr(), p.as_mut_ptr(), &mut info), 0);
+        assert_eq!(d, [0.0, 2.0, 5.0, 4.0]);
+        assert_eq!(p, [-1, 2, 0, 1]);
+        assert_eq!((info.reachable, info.error_code), (4, 0));
+        // Matches the baseline on a non-negative random graph.
+        let n = 300u32; let mut rng = crate::gen::XorShift64Star::new(0x9B05688C2B3E6C1F);
+        let mut next = || rng.next_u32();
+        let mut r_off = vec![0u32]; let mut r_tgt = Vec::new(); let mut r_wts = Vec::new();
+        for _ in 0..n { for _ in 0..3 { r_tgt.push(next() % n); r_wts.push((next() % 64) as f32 / 4.0); } r_off.push(r_tgt.len() as u32); }
+        let (mut want, mut wp) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+        let (mut got, mut gp) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+        crate::sssp_run_baseline(n, r_off.as_ptr(), r_tgt.as_ptr(), r_wts.as_ptr(), 0, want.as_mut_ptr(), wp.as_mut_ptr(), core::ptr::null_mut());
+        assert_eq!(sssp_run_bellman_ford(n, r_off.as_ptr(), r_tgt.as_ptr(), r_wts.as_ptr(), 0, got.as_mut_ptr(), gp.as_mut_ptr(), core::ptr::null_mut()), 0);
+        assert_eq!(got, want);
+        // 0->1 (1), cycle 1->2 (1), 2->3 (-1), 3->1 (-1) of weight -1, plus a tail 3->4.
+        let off = [0u32,1,2,3,5,5]; let tgt = [1u32,2,3,1,4]; let wts = [1.0f32,1.0,-1.0,-1.0,1.0];
+        let (mut d, mut p) = ([0f32;5], [0i32;5]);
+        assert_eq!(sssp_run_bellman_ford(5, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d.as_mut_ptr(), p.as_mut_ptr(), &mut info), -9);
+        assert_eq!(info.error_code, -9);
+        assert!((1..=3).contains(&info.settled), "cycle node {}", info.settled);
+    }
+
+    #[test]
+    fn spfa_matches_bellman_ford_and_counts_requeues(){
+        let n = 400u32; let mut rng = crate::gen::XorShift64Star::new(0x1F83D9ABFB41BD6B);
+        let mut next = || rng.next_u32();
+        // w + pot[u] - pot[v] over non-negative w: edges go negative, cycles cannot.
+        let pot: Vec<f32> = (0..n).map(|_| (next() % 32) as f32).collect();
+        let mut off = vec![0u32]; let mut tgt = Vec::new(); let mut wts = Vec::new();
+        for u in 0..n as usize { for _ in 0..3 { let v = next() % n; tgt.push(v); wts.push((next() % 16) as f32 + pot[u] - pot[v as usize]); } off.push(tgt.len() as u32); }
+        assert!(wts.iter().any(|&w| w < 0.0));
+        let (mut want, mut wp) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+        let (mut got, mut gp) = (vec![0f32; n as usize], vec![0i32; n as usize]);
+        assert_eq!(sssp_run_bellman_ford(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, want.as_mut_ptr(), wp.as_mut_ptr(), core::ptr::null_mut()), 0);
+        let mut info = empty_info(); let mut requeues = u64::MAX;
+        assert_eq!(sssp_run_spfa(n, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, got.as_mut_ptr(), gp.as_mut_ptr(), &mut info, &mut requeues), 0);
+        assert_eq!(got, want);
+        assert!(requeues < info.relaxations);
+        assert_eq!(info.reachable as usize, want.iter().filter(|d| d.is_finite()).count());
+        // Negative cycle 1->2->3->1 of weight -1.
+        let off = [0u32,1,2,3,5,5]; let tgt = [1u32,2,3,1,4]; let wts = [1.0f32,1.0,-1.0,-1.0,1.0];
+        let (mut d, mut p) = ([0f32;5], [0i32;5]);
+        assert_eq!(sssp_run_spfa(5, off.as_ptr(), tgt.as_ptr(), wts.as_ptr(), 0, d.as_mut_ptr(), p.as_mut_ptr(), &mut info, &mut requeues), -9);
+        assert_eq!(info.error_code, -9);
+        assert!(requeues > 0);
+    }
+}
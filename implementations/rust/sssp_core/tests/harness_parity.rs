@@ -1,26 +1,9 @@
 use sssp_core::{
-    sssp_run_baseline, sssp_run_spec_phase1, sssp_run_spec_phase2, sssp_run_spec_phase3, sssp_run_spec_boundary_chain,
-    SsspResultInfo,
+    sssp_run_baseline, sssp_run_stoc, sssp_run_spec_phase1, sssp_run_spec_phase2, sssp_run_spec_phase3, sssp_run_spec_boundary_chain,
+    sssp_run_spec_recursive, sssp_run_spec_recursive_ml, sssp_transpose_csr,
+    SsspResultInfo, CsrGraph,
 };
-
-// CSR graph representation helper
-struct CsrGraph { n:u32, offsets: Vec<u32>, targets: Vec<u32>, weights: Vec<f32> }
-
-fn path_graph(n:u32, w:f32) -> CsrGraph {
-    let mut offsets = Vec::with_capacity(n as usize + 1); offsets.push(0);
-    let mut targets = Vec::new(); let mut weights = Vec::new();
-    for u in 0..n { if u+1 < n { targets.push(u+1); weights.push(w); } offsets.push(targets.len() as u32); }
-    CsrGraph { n, offsets, targets, weights }
-}
-
-fn star_graph(k:u32, w:f32) -> CsrGraph { // center 0, leaves 1..k
-    let n = k+1; let mut offsets=Vec::with_capacity(n as usize +1); offsets.push(0); let mut targets=Vec::new(); let mut weights=Vec::new();
-    // center edges
-    for leaf in 1..n { targets.push(leaf); weights.push(w); }
-    offsets.push(targets.len() as u32);
-    for _ in 1..n { offsets.push(targets.len() as u32); }
-    CsrGraph { n, offsets, targets, weights }
-}
+use sssp_core::gen::{path_graph, star_graph, random_graph_in};
 
 fn complete_graph(n:u32, w:f32) -> CsrGraph {
     let mut offsets = Vec::with_capacity(n as usize +1); offsets.push(0); let mut targets=Vec::new(); let mut weights=Vec::new();
@@ -48,14 +31,17 @@ fn run_variant(
 ) -> (Vec<f32>, Vec<i32>, SsspResultInfo) {
     let mut dist = vec![0f32; g.n as usize];
     let mut pred = vec![-1i32; g.n as usize];
-    let mut info = SsspResultInfo { relaxations:0, light_relaxations:0, heavy_relaxations:0, settled:0, error_code:0 };
+    let mut info = SsspResultInfo { relaxations:0, light_relaxations:0, heavy_relaxations:0, settled:0, error_code:0, overflow:0, reachable:0, edges:0 };
     unsafe {
         let rc = match which {
             "baseline" => sssp_run_baseline(g.n, g.offsets.as_ptr(), g.targets.as_ptr(), g.weights.as_ptr(), source, dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info as *mut _),
+            "stoc" => sssp_run_stoc(g.n, g.offsets.as_ptr(), g.targets.as_ptr(), g.weights.as_ptr(), source, dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info as *mut _),
             "phase1" => sssp_run_spec_phase1(g.n, g.offsets.as_ptr(), g.targets.as_ptr(), g.weights.as_ptr(), source, dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info as *mut _),
             "phase2" => sssp_run_spec_phase2(g.n, g.offsets.as_ptr(), g.targets.as_ptr(), g.weights.as_ptr(), source, dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info as *mut _),
-            "phase3" => sssp_run_spec_phase3(g.n, g.offsets.as_ptr(), g.targets.as_ptr(), g.weights.as_ptr(), source, dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info as *mut _),
+            "phase3" => sssp_run_spec_phase3(g.n, g.offsets.as_ptr(), g.targets.as_ptr(), g.weights.as_ptr(), source, dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info as *mut _, f32::NAN),
             "chain"  => sssp_run_spec_boundary_chain(g.n, g.offsets.as_ptr(), g.targets.as_ptr(), g.weights.as_ptr(), source, dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info as *mut _),
+            "recursive" => sssp_run_spec_recursive(g.n, g.offsets.as_ptr(), g.targets.as_ptr(), g.weights.as_ptr(), source, dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info as *mut _),
+            "recursive_ml" => sssp_run_spec_recursive_ml(g.n, g.offsets.as_ptr(), g.targets.as_ptr(), g.weights.as_ptr(), source, dist.as_mut_ptr(), pred.as_mut_ptr(), &mut info as *mut _),
             _ => panic!("unknown variant")
         }; assert_eq!(rc,0, "variant {} returned rc {}", which, rc);
     }
@@ -77,32 +63,6 @@ fn assert_parity(base:&[f32], other:&[f32], tol:f32){
     }
 }
 
-// Simple deterministic pseudo-random directed graph generator
-fn pseudo_random_graph(n:u32, m:u32, seed:u64, w_min:f32, w_max:f32) -> CsrGraph {
-    assert!(n>=2);
-    let mut adj: Vec<Vec<(u32,f32)>> = vec![Vec::new(); n as usize];
-    let mut state = seed | 1; // ensure non-zero
-    let mut next_u32 = || { // xorshift64*
-        state ^= state >> 12; state ^= state << 25; state ^= state >> 27; state = state.wrapping_mul(2685821657736338717); (state >> 32) as u32
-    };
-    let span = w_max - w_min;
-    let mut edges = 0u32; let target_edges = m.min(n.saturating_mul(n-1));
-    let mut attempts = 0u32; let attempt_limit = target_edges * 10 + 1000;
-    while edges < target_edges && attempts < attempt_limit {
-        attempts += 1;
-        let u = next_u32() % n; let mut v = next_u32() % n; if u==v { continue; }
-        // avoid duplicate exact edge (linear scan small expected degree)
-        if adj[u as usize].iter().any(|(x,_)| *x==v) { continue; }
-        let w = w_min + span * ((next_u32() as f32) / (u32::MAX as f32));
-        adj[u as usize].push((v,w)); edges += 1;
-    }
-    for list in &mut adj { list.sort_by_key(|(v,_)| *v); }
-    let mut offsets = Vec::with_capacity(n as usize +1); offsets.push(0);
-    let mut targets = Vec::new(); let mut weights = Vec::new();
-    for u in 0..n as usize { for (v,w) in &adj[u] { targets.push(*v); weights.push(*w); } offsets.push(targets.len() as u32); }
-    CsrGraph { n, offsets, targets, weights }
-}
-
 #[test]
 fn parity_core_small_graphs(){
     let graphs = vec![
@@ -115,10 +75,12 @@ fn parity_core_small_graphs(){
     std::env::set_var("SSSP_SPEC_K","10000");
     std::env::set_var("SSSP_SPEC_PIVOT_MAX","10000");
     std::env::set_var("SSSP_SPEC_CHAIN_K","10000");
+    // Recursion computes distances itself at any frame size; keep it small so frames split.
+    std::env::set_var("SSSP_SPEC_RECURSION_K","8");
     for g in &graphs {
         let (bdist,_bpred,_binfo) = run_variant("baseline", g, 0);
         let bhash = hash_dist(&bdist);
-        for variant in ["phase1","phase2","phase3","chain"] { let (dist,_pred,_info) = run_variant(variant,g,0); assert_parity(&bdist,&dist,1e-5); let h = hash_dist(&dist); assert_eq!(bhash,h, "hash mismatch variant {}", variant); }
+        for variant in ["phase1","phase2","phase3","chain","recursive","recursive_ml"] { let (dist,_pred,_info) = run_variant(variant,g,0); assert_parity(&bdist,&dist,1e-5); let h = hash_dist(&dist); assert_eq!(bhash,h, "hash mismatch variant {}", variant); }
     }
 }
 
@@ -127,10 +89,36 @@ fn parity_random_graphs(){
     std::env::set_var("SSSP_SPEC_K","10000");
     std::env::set_var("SSSP_SPEC_PIVOT_MAX","10000");
     std::env::set_var("SSSP_SPEC_CHAIN_K","10000");
+    // Recursion computes distances itself at any frame size; keep it small so frames split.
+    std::env::set_var("SSSP_SPEC_RECURSION_K","8");
     for seed in 1..=5u64 { // moderate size to keep runtime reasonable
-        let g = pseudo_random_graph(40, 160, seed * 7919, 0.5, 3.5);
+        let g = random_graph_in(40, 4.0, seed * 7919, 0.5, 3.5);
         let (bdist,_bp,_bi) = run_variant("baseline", &g, 0);
         let bhash = hash_dist(&bdist);
-        for variant in ["phase1","phase2","phase3","chain"] { let (dist,_p,_i) = run_variant(variant,&g,0); assert_parity(&bdist,&dist,1e-4); let h = hash_dist(&dist); assert_eq!(bhash,h, "hash mismatch variant {} seed {}", variant, seed); }
+        for variant in ["phase1","phase2","phase3","chain","recursive","recursive_ml"] { let (dist,_p,_i) = run_variant(variant,&g,0); assert_parity(&bdist,&dist,1e-4); let h = hash_dist(&dist); assert_eq!(bhash,h, "hash mismatch variant {} seed {}", variant, seed); }
+    }
+}
+
+#[test]
+fn transpose_reverses_distances_on_bridge_cliques(){
+    // Edge weights are symmetric inside each clique; the only asymmetry is the
+    // one-way bridge, which the transpose turns around.
+    let g = bridge_cliques(4,5,1.5);
+    let m = g.targets.len();
+    let mut t = CsrGraph { n: g.n, offsets: vec![0; g.n as usize + 1], targets: vec![0; m], weights: vec![0.0; m] };
+    let rc = unsafe { sssp_transpose_csr(g.n, g.offsets.as_ptr(), g.targets.as_ptr(), g.weights.as_ptr(), t.offsets.as_mut_ptr(), t.targets.as_mut_ptr(), t.weights.as_mut_ptr()) };
+    assert_eq!(rc, 0);
+    assert_eq!(t.offsets[g.n as usize] as usize, m);
+    let far = g.n - 1;
+    let (back, _, _) = run_variant("baseline", &t, far);
+    for v in 0..g.n {
+        let (fwd, _, _) = run_variant("baseline", &g, v);
+        assert_eq!(back[v as usize], fwd[far as usize], "d_T({far},{v}) != d({v},{far})");
     }
+    // The bridge only points into the far clique, so nothing there reaches back.
+    let (fwd_far, _, _) = run_variant("baseline", &g, far);
+    assert!(fwd_far[0].is_infinite() && back[0].is_finite());
+    let mut bad = g.targets.clone(); bad[0] = g.n;
+    let rc = unsafe { sssp_transpose_csr(g.n, g.offsets.as_ptr(), bad.as_ptr(), g.weights.as_ptr(), t.offsets.as_mut_ptr(), t.targets.as_mut_ptr(), t.weights.as_mut_ptr()) };
+    assert_eq!(rc, -2);
 }
@@ -13,6 +13,9 @@ public static class RustSssp
         public ulong heavy_relaxations;
         public uint settled;
         public int error_code;
+        public uint overflow;
+        public uint reachable;
+        public ulong edges;
     }
 
     const string LIB = "sssp_core"; // ensure library on PATH / LD_LIBRARY_PATH / DYLD_LIBRARY_PATH
@@ -10,6 +10,9 @@ typedef struct SsspResultInfo {
   uint64_t heavy_relaxations;
   uint32_t settled;
   int32_t  error_code;
+  uint32_t overflow;
+  uint32_t reachable;
+  uint64_t edges;
 } SsspResultInfo;
 
 int32_t sssp_run_baseline(uint32_t n, const uint32_t* offsets, const uint32_t* targets,
@@ -40,6 +43,9 @@ type Stats struct {
 	HeavyRelaxations uint64
 	Settled          uint32
 	ErrorCode        int32
+	Overflow         uint32
+	Reachable        uint32
+	Edges            uint64
 	Version          uint32
 }
 
@@ -62,5 +68,5 @@ func Run(n uint32, offsets, targets []uint32, weights []float32, source uint32,
 	if rc != 0 {
 		return Result{}, nil
 	}
-	return Result{Dist: dist, Pred: pred, Stats: Stats{Relaxations: uint64(info.relaxations), LightRelaxations: uint64(info.light_relaxations), HeavyRelaxations: uint64(info.heavy_relaxations), Settled: uint32(info.settled), ErrorCode: int32(info.error_code), Version: uint32(C.sssp_version())}}, nil
+	return Result{Dist: dist, Pred: pred, Stats: Stats{Relaxations: uint64(info.relaxations), LightRelaxations: uint64(info.light_relaxations), HeavyRelaxations: uint64(info.heavy_relaxations), Settled: uint32(info.settled), ErrorCode: int32(info.error_code), Overflow: uint32(info.overflow), Reachable: uint32(info.reachable), Edges: uint64(info.edges), Version: uint32(C.sssp_version())}}, nil
 }